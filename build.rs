@@ -0,0 +1,53 @@
+// with the embed-assets feature enabled, generate a table of every asset file so they can be
+// compiled into the executable; otherwise the table is left empty
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const ASSET_DIRS: [&str; 4] = ["images", "sounds", "music", "data"];
+
+fn main() {
+    let mut entries = String::new();
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_some() {
+        let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+        for dir in ASSET_DIRS {
+            println!("cargo:rerun-if-changed={}", dir);
+            if !Path::new(&root).join(dir).is_dir() {
+                println!(
+                    "cargo:warning=asset directory {} not found, not embedding it",
+                    dir
+                );
+                continue;
+            }
+            embed_dir(&Path::new(&root).join(dir), dir, &mut entries);
+        }
+    }
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded.rs");
+    fs::write(
+        out,
+        format!(
+            "pub static EMBEDDED: &[(&str, &[u8])] = &[\n{}];\n",
+            entries
+        ),
+    )
+    .unwrap();
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+// add every file in a directory and its subdirectories, named by its path relative to the root
+fn embed_dir(path: &Path, name: &str, entries: &mut String) {
+    let mut paths: Vec<_> = fs::read_dir(path)
+        .unwrap()
+        .filter_map(|f| Some(f.ok()?.path()))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let name = format!("{}/{}", name, path.file_name().unwrap().to_string_lossy());
+        if path.is_dir() {
+            println!("cargo:rerun-if-changed={}", name);
+            embed_dir(&path, &name, entries);
+        } else {
+            entries.push_str(&format!("    ({:?}, include_bytes!({:?})),\n", name, path));
+        }
+    }
+}
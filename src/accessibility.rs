@@ -0,0 +1,174 @@
+use crate::i18n::Strings;
+use crate::{synth, GameEvent, Vector, HALF_WINDOW_WIDTH, HEIGHT, WIDTH};
+use macroquad::prelude::*;
+
+// how long an event caption stays on screen, in steps
+const CAPTION_FRAMES: u32 = 120;
+const LARGE_FONT_SIZE: f32 = 56.0;
+
+#[derive(Copy, Clone)]
+pub struct Accessibility {
+    // big, high-contrast score and captions describing what's happening
+    pub large_text: bool,
+    // a different sound for each team when they score or win the ball
+    pub audio_cues: bool,
+    // shapes to tell the teams apart without relying on their colours
+    pub team_markers: bool,
+}
+
+impl Accessibility {
+    pub fn new() -> Self {
+        Self {
+            large_text: false,
+            audio_cues: false,
+            team_markers: false,
+        }
+    }
+}
+
+// sound cues for each team, higher pitched for team 1 and lower for team 2
+pub fn cue_sounds() -> Vec<(String, Vec<u8>)> {
+    let mut cues = Vec::new();
+    for (team, pitch) in [(0, 2.0), (1, 1.0)] {
+        cues.push((
+            format!("cue_goal{}", team),
+            synth::tones(&[
+                (330.0 * pitch, 0.12),
+                (440.0 * pitch, 0.12),
+                (660.0 * pitch, 0.3),
+            ]),
+        ));
+        cues.push((
+            format!("cue_ball{}", team),
+            synth::tones(&[(495.0 * pitch, 0.08)]),
+        ));
+    }
+    cues
+}
+
+// name of the sound cue for an event, if it has one
+pub fn cue_for(event: &GameEvent) -> Option<String> {
+    match event {
+        GameEvent::Goal { team } => Some(format!("cue_goal{}", team)),
+        GameEvent::Possession { team } => Some(format!("cue_ball{}", team)),
+        _ => None,
+    }
+}
+
+// team 1 players stand on a ring and team 2 on a diamond
+pub fn draw_team_marker(team: usize, pos: Vector) {
+    let (x, y) = (pos.x, pos.y);
+    if team == 0 {
+        draw_circle_lines(x, y, 11.0, 3.0, BLACK);
+        draw_circle_lines(x, y, 11.0, 1.5, WHITE);
+    } else {
+        let points = [(x, y - 12.0), (x + 12.0, y), (x, y + 12.0), (x - 12.0, y)];
+        for i in 0..4 {
+            let (a, b) = (points[i], points[(i + 1) % 4]);
+            draw_line(a.0, a.1, b.0, b.1, 3.0, BLACK);
+            draw_line(a.0, a.1, b.0, b.1, 1.5, WHITE);
+        }
+    }
+}
+
+// marks the arrow over a team's active player: solid for team 1, striped for team 2
+pub fn draw_arrow_marker(team: usize, pos: Vector) {
+    let (x, y) = (pos.x, pos.y - 58.0);
+    draw_rectangle(x - 7.0, y - 7.0, 14.0, 14.0, BLACK);
+    if team == 0 {
+        draw_rectangle(x - 5.0, y - 5.0, 10.0, 10.0, WHITE);
+    } else {
+        for i in 0..3 {
+            draw_rectangle(x - 5.0, y - 5.0 + i as f32 * 4.0, 10.0, 2.0, WHITE);
+        }
+    }
+}
+
+// a line of text describing the latest goal or change of possession
+pub struct Captions {
+    event: Option<GameEvent>,
+    timer: u32,
+}
+
+impl Captions {
+    pub fn new() -> Self {
+        Self {
+            event: None,
+            timer: 0,
+        }
+    }
+
+    // called once per simulation step with the events from that step
+    pub fn update(&mut self, events: &[GameEvent]) {
+        self.timer = self.timer.saturating_sub(1);
+        for event in events {
+            match event {
+                GameEvent::Goal { .. } => (),
+                // don't let a change of possession (or a save, which comes with one) hide news of
+                // a goal
+                GameEvent::Possession { .. }
+                | GameEvent::NearMiss { .. }
+                | GameEvent::Save { .. } => {
+                    if self.timer > 0 && matches!(self.event, Some(GameEvent::Goal { .. })) {
+                        continue;
+                    }
+                }
+                _ => continue,
+            }
+            self.event = Some(*event);
+            self.timer = CAPTION_FRAMES;
+        }
+    }
+
+    pub fn draw(&self, strings: &Strings) {
+        if self.timer == 0 {
+            return;
+        }
+        let text = match self.event {
+            Some(GameEvent::Goal { team }) => {
+                strings.format("hud.goal_caption", &[&(team + 1).to_string()])
+            }
+            Some(GameEvent::Possession { team }) => {
+                strings.format("hud.possession_caption", &[&(team + 1).to_string()])
+            }
+            Some(GameEvent::NearMiss { team }) => {
+                strings.format("hud.near_miss_caption", &[&(team + 1).to_string()])
+            }
+            Some(GameEvent::Save { team }) => {
+                strings.format("hud.save_caption", &[&(team + 1).to_string()])
+            }
+            _ => return,
+        };
+        draw_boxed(&text, HEIGHT - 70.0, YELLOW);
+    }
+}
+
+// replaces the score bar; team 2 is on the left to match it
+pub fn draw_large_score(scores: [u8; 2], strings: &Strings) {
+    let text = strings.format(
+        "hud.large_score",
+        &[&scores[1].to_string(), &scores[0].to_string()],
+    );
+    draw_boxed(&text, 10.0, WHITE);
+}
+
+// white or yellow text on a solid black box, centred horizontally with its top edge at y
+fn draw_boxed(text: &str, y: f32, colour: Color) {
+    let size = measure_text(text, None, LARGE_FONT_SIZE as u16, 1.0);
+    let padding = 10.0;
+    let x = HALF_WINDOW_WIDTH - size.width / 2.0;
+    draw_rectangle(
+        (x - padding).max(0.0),
+        y,
+        (size.width + padding * 2.0).min(WIDTH),
+        size.height + padding * 2.0,
+        BLACK,
+    );
+    draw_text(
+        text,
+        x,
+        y + padding + size.offset_y,
+        LARGE_FONT_SIZE,
+        colour,
+    );
+}
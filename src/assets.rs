@@ -0,0 +1,81 @@
+// all asset files are read through here so that copies compiled into the executable (with the
+// embed-assets feature) can stand in for any that are missing on disk
+
+use macroquad::file::{load_file, FileError};
+use std::sync::OnceLock;
+
+include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
+
+// overrides where assets are found, as --assets does
+const ROOT_VAR: &str = "SOCCER_ASSETS";
+
+// the directory asset paths are relative to, if not the working directory
+static ROOT: OnceLock<String> = OnceLock::new();
+
+// decide where assets come from, which only takes effect before the first one is loaded: the
+// directory given on the command line if there is one, or else in SOCCER_ASSETS, or else the one
+// the executable is in so that it can be started from anywhere; if none of those has any assets,
+// as when running from target/ during development, the working directory
+pub fn find_root(dir: Option<&str>) {
+    let dir = dir
+        .map(str::to_owned)
+        .or_else(|| std::env::var(ROOT_VAR).ok())
+        .or_else(executable_dir);
+    if let Some(dir) = dir {
+        ROOT.set(dir.trim_end_matches('/').to_owned()).ok();
+    }
+}
+
+// the executable's directory if there are assets alongside it
+fn executable_dir() -> Option<String> {
+    let dir = std::env::current_exe().ok()?.parent()?.to_owned();
+    ["images", "sounds", "music", "data"]
+        .iter()
+        .any(|name| dir.join(name).is_dir())
+        .then(|| dir.to_string_lossy().into_owned())
+}
+
+// where an asset lives on disk
+pub fn on_disk(path: &str) -> String {
+    match ROOT.get() {
+        Some(root) => format!("{}/{}", root, path),
+        None => path.to_owned(),
+    }
+}
+
+pub async fn load(path: &str) -> Result<Vec<u8>, FileError> {
+    match load_file(&on_disk(path)).await {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => embedded(path).map(|bytes| bytes.to_vec()).ok_or(e),
+    }
+}
+
+fn embedded(path: &str) -> Option<&'static [u8]> {
+    EMBEDDED
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, bytes)| *bytes)
+}
+
+// names of the directories inside the given one, whether on disk or embedded
+pub fn subdirectories(dir: &str) -> Vec<String> {
+    let prefix = format!("{}/", dir);
+    let mut names: Vec<String> = EMBEDDED
+        .iter()
+        .filter_map(|(name, _)| {
+            let (subdir, _) = name.strip_prefix(&prefix)?.split_once('/')?;
+            Some(subdir.to_owned())
+        })
+        .collect();
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(entries) = std::fs::read_dir(on_disk(dir)) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
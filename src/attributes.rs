@@ -0,0 +1,128 @@
+// what sets one player apart from another: points in each of five attributes, where AVERAGE_POINTS
+// plays just as everyone did before players had attributes
+//
+// each team's squad is generated from the team's own seed, so it has the same character in every
+// match: a strength and a weakness shared by the whole squad, with a little variety from player to
+// player, and every player has the same number of points in total; a team's strength and weakness
+// are always among the first four attributes, the ones players had to begin with, so that each
+// team kept its character when strength came along
+
+use crate::lineup::SQUAD_SIZE;
+
+pub const MIN_POINTS: u8 = 1;
+pub const MAX_POINTS: u8 = 9;
+pub const AVERAGE_POINTS: u8 = 5;
+// how much each point above or below average changes a player's speed, reach and so on
+const POINT_EFFECT: f32 = 0.03;
+// the most any attribute can multiply something by
+pub const MAX_FACTOR: f32 = 1.0 + (MAX_POINTS - AVERAGE_POINTS) as f32 * POINT_EFFECT;
+// picked so that one team kicks hard but tackles weakly and the other the opposite way round, with
+// both about as quick as each other
+const TEAM_SEEDS: [u64; 2] = [5, 10];
+// points moved from a team's weakness to its strength for every player, and then moved about at
+// random for each one
+const TEAM_BIAS: u8 = 2;
+const PLAYER_SHUFFLES: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attributes {
+    // how fast they run
+    pub pace: u8,
+    // how hard they kick
+    pub kick_power: u8,
+    // how close they have to get to win the ball off someone
+    pub tackling: u8,
+    // how close the ball has to come for them to pick it up when it's loose
+    pub control: u8,
+    // how well they hold off an opponent going for the same loose ball
+    pub strength: u8,
+}
+
+fn factor(points: u8) -> f32 {
+    1.0 + (points as f32 - AVERAGE_POINTS as f32) * POINT_EFFECT
+}
+
+impl Attributes {
+    pub const AVERAGE: Self = Self {
+        pace: AVERAGE_POINTS,
+        kick_power: AVERAGE_POINTS,
+        tackling: AVERAGE_POINTS,
+        control: AVERAGE_POINTS,
+        strength: AVERAGE_POINTS,
+    };
+
+    pub fn pace_factor(&self) -> f32 {
+        factor(self.pace)
+    }
+
+    pub fn kick_factor(&self) -> f32 {
+        factor(self.kick_power)
+    }
+
+    pub fn tackling_factor(&self) -> f32 {
+        factor(self.tackling)
+    }
+
+    pub fn control_factor(&self) -> f32 {
+        factor(self.control)
+    }
+
+    pub fn strength_factor(&self) -> f32 {
+        factor(self.strength)
+    }
+
+    // the points in each attribute, numbered in the order they're declared
+    pub fn points(&self, n: usize) -> u8 {
+        let mut copy = *self;
+        *copy.points_mut(n)
+    }
+
+    pub fn points_mut(&mut self, n: usize) -> &mut u8 {
+        match n {
+            0 => &mut self.pace,
+            1 => &mut self.kick_power,
+            2 => &mut self.tackling,
+            3 => &mut self.control,
+            _ => &mut self.strength,
+        }
+    }
+
+    // move up to amount points from one attribute to another, as far as the limits allow
+    fn shift(&mut self, from: usize, to: usize, amount: u8) {
+        let amount = amount
+            .min(*self.points_mut(from) - MIN_POINTS)
+            .min(MAX_POINTS - *self.points_mut(to));
+        *self.points_mut(from) -= amount;
+        *self.points_mut(to) += amount;
+    }
+}
+
+// xorshift, so that generating squads doesn't disturb the match's own random numbers
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: u64) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n) as usize
+    }
+}
+
+// a team's squad, in the same order as SQUAD_NUMBERS
+pub fn generate(team: usize) -> [Attributes; SQUAD_SIZE] {
+    let mut rng = Rng(TEAM_SEEDS[team]);
+    let strength = rng.below(4);
+    let weakness = (strength + 1 + rng.below(3)) % 4;
+    std::array::from_fn(|_| {
+        let mut attributes = Attributes::AVERAGE;
+        attributes.shift(weakness, strength, TEAM_BIAS);
+        for _ in 0..PLAYER_SHUFFLES {
+            let (from, to) = (rng.below(5), rng.below(5));
+            if from != to {
+                attributes.shift(from, to, 1);
+            }
+        }
+        attributes
+    })
+}
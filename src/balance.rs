@@ -0,0 +1,113 @@
+// the numbers the AI and ball handling are tuned with, read from data/balance.toml when the game
+// starts so that they can be changed without recompiling; with the hot-reload feature, saving the
+// file while the game is running changes them in the match being played
+//
+// a key missing from the file keeps the value built into the game, which is the same as the file
+// that comes with it
+
+use crate::assets;
+use crate::data::{self, Document};
+use std::sync::RwLock;
+
+pub const PATH: &str = "data/balance.toml";
+
+// what was last read from the file, which every new match starts with
+static LOADED: RwLock<Option<Balance>> = RwLock::new(None);
+
+#[derive(Clone, PartialEq)]
+pub struct Balance {
+    // speeds for players in various situations; the computer's leads and its players on the ball
+    // go faster by the difficulty's speed boost (only for players on a computer-controlled team)
+    pub default_speed: f32,
+    pub cpu_with_ball_speed: f32,
+    pub intercept_speed: f32,
+    pub lead_speed: f32,
+    pub human_with_ball_speed: f32,
+    pub human_without_ball_speed: f32,
+    pub run_speed: f32,
+    // how far in front of whoever has the ball the first and second leads aim for
+    pub lead_distances: [f32; 2],
+    // how far in front of a player the ball they're dribbling is, across and up the screen, which
+    // is also how close they have to be to a loose ball to pick it up
+    pub dribble_x: f32,
+    pub dribble_y: f32,
+    // for each difficulty, in the order of DifficultyLevel: how much faster the computer's players
+    // are, and how many steps one has to have the ball before they can kick it
+    pub speed_boost: [f32; 3],
+    pub holdoff_timer: [i8; 3],
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        Self {
+            default_speed: 2.0,
+            cpu_with_ball_speed: 2.6,
+            intercept_speed: 2.75,
+            lead_speed: 2.9,
+            human_with_ball_speed: 3.0,
+            human_without_ball_speed: 3.3,
+            run_speed: 3.0,
+            lead_distances: [10.0, 50.0],
+            dribble_x: 18.0,
+            dribble_y: 16.0,
+            speed_boost: [0.0, 0.1, 0.2],
+            holdoff_timer: [120, 90, 60],
+        }
+    }
+}
+
+impl Balance {
+    fn from_document(doc: &Document) -> Self {
+        let mut balance = Self::default();
+        let number = |section, key, value: &mut f32| {
+            if let Some(n) = doc.get(section, key).and_then(|v| v.as_f32()) {
+                *value = n;
+            }
+        };
+        number("speeds", "default", &mut balance.default_speed);
+        number("speeds", "cpu_with_ball", &mut balance.cpu_with_ball_speed);
+        number("speeds", "intercept", &mut balance.intercept_speed);
+        number("speeds", "lead", &mut balance.lead_speed);
+        number(
+            "speeds",
+            "human_with_ball",
+            &mut balance.human_with_ball_speed,
+        );
+        number(
+            "speeds",
+            "human_without_ball",
+            &mut balance.human_without_ball_speed,
+        );
+        number("speeds", "run", &mut balance.run_speed);
+        number("leads", "first", &mut balance.lead_distances[0]);
+        number("leads", "second", &mut balance.lead_distances[1]);
+        number("dribble", "x", &mut balance.dribble_x);
+        number("dribble", "y", &mut balance.dribble_y);
+        for (i, level) in ["easy", "medium", "hard"].iter().enumerate() {
+            number(level, "speed_boost", &mut balance.speed_boost[i]);
+            let mut holdoff = balance.holdoff_timer[i] as f32;
+            number(level, "holdoff_timer", &mut holdoff);
+            balance.holdoff_timer[i] = holdoff.clamp(0.0, i8::MAX as f32) as i8;
+        }
+        balance
+    }
+}
+
+// read the file, or the copy built into the game if it isn't there, for every match from now on;
+// a mistake in the file is reported and the last good values kept
+pub fn load() {
+    #[cfg(not(target_arch = "wasm32"))]
+    let text = std::fs::read_to_string(assets::on_disk(PATH))
+        .unwrap_or_else(|_| include_str!("../data/balance.toml").to_owned());
+    #[cfg(target_arch = "wasm32")]
+    let text = include_str!("../data/balance.toml").to_owned();
+    match data::parse(&text) {
+        Ok(doc) => *LOADED.write().unwrap() = Some(Balance::from_document(&doc)),
+        Err(e) => eprintln!("{}: {}", assets::on_disk(PATH), e),
+    }
+}
+
+// what a new match plays with
+pub fn current() -> Balance {
+    LOADED.read().unwrap().clone().unwrap_or_default()
+}
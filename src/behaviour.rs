@@ -0,0 +1,70 @@
+// what a player is doing: each step, choose_behaviour picks one of these for every player from the
+// state of the match, the first that applies in the order they're listed, and follow_behaviour
+// turns it into a Target to head for
+//
+// adding a behaviour means adding a variant here, deciding where it goes in choose_behaviour, and
+// saying in follow_behaviour where it takes the player
+
+use crate::{Mark, Vector};
+use hecs::Entity;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Behaviour {
+    // before the player has been given anything to do
+    Wait,
+    // lining up for a kickoff, or waiting for the whistle
+    HoldKickoff,
+    // a training dummy, everyone when the AI is turned off, or a goalkeeper holding the ball
+    StandStill,
+    // keeping clear of the other team's goalkeeper while they hold the ball
+    BackOff(Vector),
+    // sliding in for the ball in the given direction, or on the ground after missing it
+    Slide(Option<Vector>),
+    // the player a human is controlling
+    Controlled,
+    // a human's teammate running to where they've been asked for a pass
+    RunForPass(Vector),
+    // closing down whoever has the ball when a human asks
+    Press(Entity),
+    // a computer player dribbling the ball
+    CarryBall,
+    // running into space planned by plan_support_runs when a teammate has the ball
+    SupportAttack(Vector),
+    // heading in front of the player with the ball, the given distance ahead of them
+    Lead(Entity, f32),
+    // standing between the ball and a player or the goal
+    Mark(Mark),
+    // a human team's players just run at the ball instead of marking
+    Chase,
+    // running to where a loose ball can be reached
+    InterceptLoose,
+    // going back to their position, when there's nothing else to do
+    GoHome,
+    // the scorer running a lap of honour
+    Celebrate,
+}
+
+impl Behaviour {
+    // for the inspector
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Wait => "wait",
+            Self::HoldKickoff => "wait for kickoff",
+            Self::StandStill => "stand still",
+            Self::BackOff(_) => "back off from keeper",
+            Self::Slide(Some(_)) => "slide tackle",
+            Self::Slide(None) => "get up",
+            Self::Controlled => "human control",
+            Self::RunForPass(_) => "run for a pass",
+            Self::Press(_) => "press",
+            Self::CarryBall => "dribble",
+            Self::SupportAttack(_) => "support",
+            Self::Lead(..) => "lead",
+            Self::Mark(_) => "mark",
+            Self::Chase => "chase",
+            Self::InterceptLoose => "intercept",
+            Self::GoHome => "go home",
+            Self::Celebrate => "celebrate",
+        }
+    }
+}
@@ -0,0 +1,316 @@
+// for playing the game with code rather than a keyboard: a Bot is shown a read-only view of the
+// match every step and answers with its team's input, just as a player at the keyboard would, and
+// a tournament plays bots against each other without opening a window
+//
+//   --tournament chaser,sweeper,cpu   play each bot against each other one, home and away, and
+//                                     print a league table; cpu is the game's own AI, and the
+//                                     name of a personality (defensive, possession, long-ball,
+//                                     pressing) is the game's AI playing that way; with
+//                                     --broadcast, the matches can be watched as they're
+//                                     played (see broadcast.rs)
+//
+// a bot's team plays by the same rules as a human team: the bot moves whichever player is active,
+// and the game switches to another player for it when it doesn't ask to switch itself
+
+use crate::broadcast::Broadcast;
+use crate::input::{FrameInput, TeamInput};
+use crate::launch::LaunchOptions;
+use crate::personality::{self, Personality};
+use crate::{
+    get_difficulty, DifficultyLevel, Game, Position, Team, Vector, HALF_LEVEL_W, LEVEL_H,
+    MAX_SPEED, TEAM_CONTROLS,
+};
+use euclid::vec2;
+
+// a player as a bot sees them
+#[derive(Copy, Clone)]
+pub struct BotPlayer {
+    pub pos: Vector,
+    pub team: u8,
+    // the one on a team that its input moves
+    pub active: bool,
+}
+
+// everything a bot is told about the match, from its own team's point of view
+pub struct MatchView {
+    pub team: u8,
+    // the middle of the goal the team is shooting at, and of the one it's defending
+    pub goal: Vector,
+    pub own_goal: Vector,
+    pub ball: Vector,
+    // zero while someone is dribbling it
+    pub ball_velocity: Vector,
+    // the team of whoever has the ball
+    pub ball_owner: Option<u8>,
+    pub players: Vec<BotPlayer>,
+    // indexed by team
+    pub score: [u8; 2],
+}
+
+impl MatchView {
+    fn new(game: &Game, team: usize) -> Self {
+        let ball = game.balls[0];
+        let ball_team = |id| game.world.get::<Team>(id).ok().map(|t| t.0);
+        let goal_y = |t: usize| if t == 0 { 0.0 } else { LEVEL_H };
+        Self {
+            team: team as u8,
+            goal: vec2(HALF_LEVEL_W, goal_y(team)),
+            own_goal: vec2(HALF_LEVEL_W, goal_y(1 - team)),
+            ball: game.ball_pos(ball).unwrap_or_else(|_| Vector::zero()),
+            ball_velocity: game
+                .world
+                .get::<Vector>(ball.entity)
+                .map_or_else(|_| Vector::zero(), |v| *v),
+            ball_owner: ball.owner.and_then(ball_team),
+            players: game
+                .world
+                .query::<(&Position, &Team)>()
+                .iter()
+                .map(|(id, (pos, t))| BotPlayer {
+                    pos: pos.0,
+                    team: t.0,
+                    active: game.teams[t.0 as usize].active_player == Some(id),
+                })
+                .collect(),
+            score: [game.teams[0].score, game.teams[1].score],
+        }
+    }
+
+    // the player on our team that we're moving
+    pub fn active(&self) -> Option<BotPlayer> {
+        self.players
+            .iter()
+            .find(|p| p.team == self.team && p.active)
+            .copied()
+    }
+
+    pub fn have_ball(&self) -> bool {
+        self.ball_owner == Some(self.team)
+    }
+}
+
+pub trait Bot {
+    fn think(&mut self, view: &MatchView) -> FrameInput;
+}
+
+// lets a bot drive a team anywhere a keyboard could
+pub struct BotInput(pub Box<dyn Bot>);
+
+impl TeamInput for BotInput {
+    fn read(&mut self, game: &Game, team: usize) -> FrameInput {
+        self.0.think(&MatchView::new(game, team))
+    }
+}
+
+// full speed from one point towards another
+fn towards(from: Vector, to: Vector) -> Vector {
+    let d = to - from;
+    if d.length() < 1.0 {
+        Vector::zero()
+    } else {
+        d.normalize() * MAX_SPEED
+    }
+}
+
+// run with the ball at the goal and shoot from this close
+fn attack(view: &MatchView, pos: Vector, range: f32) -> FrameInput {
+    FrameInput {
+        movement: towards(pos, view.goal),
+        shoot: (view.goal - pos).length() < range,
+        ..FrameInput::none()
+    }
+}
+
+// always after the ball, and shoots once it's near enough to goal
+struct Chaser;
+
+impl Bot for Chaser {
+    fn think(&mut self, view: &MatchView) -> FrameInput {
+        let pos = match view.active() {
+            Some(player) => player.pos,
+            None => return FrameInput::none(),
+        };
+        if view.have_ball() {
+            attack(view, pos, 300.0)
+        } else {
+            FrameInput {
+                movement: towards(pos, view.ball),
+                ..FrameInput::none()
+            }
+        }
+    }
+}
+
+// stays between the ball and its own goal, deeper when it's ahead, until the ball comes close, and
+// shoots from range
+struct Sweeper;
+
+impl Bot for Sweeper {
+    fn think(&mut self, view: &MatchView) -> FrameInput {
+        let pos = match view.active() {
+            Some(player) => player.pos,
+            None => return FrameInput::none(),
+        };
+        if view.have_ball() {
+            return attack(view, pos, 450.0);
+        }
+        // head for where the ball is going rather than where it is
+        let ball = view.ball + view.ball_velocity * 10.0;
+        let ahead = view.score[view.team as usize] > view.score[1 - view.team as usize];
+        let dest = if (ball - pos).length() < 200.0 {
+            ball
+        } else {
+            view.own_goal + (ball - view.own_goal) * if ahead { 0.25 } else { 0.4 }
+        };
+        FrameInput {
+            movement: towards(pos, dest),
+            ..FrameInput::none()
+        }
+    }
+}
+
+// the bots that can be asked for by name; "cpu" and the personalities aren't here as they're the
+// game's AI rather than bots
+type NewBot = fn() -> Box<dyn Bot>;
+
+const BOTS: [(&str, NewBot); 2] = [
+    ("chaser", || Box::new(Chaser)),
+    ("sweeper", || Box::new(Sweeper)),
+];
+
+fn new_bot(name: &str) -> Option<Box<dyn Bot>> {
+    BOTS.iter()
+        .find(|(bot_name, _)| *bot_name == name)
+        .map(|(_, new)| new())
+}
+
+// how the game's AI plays for an entrant that isn't a bot
+fn cpu_personality(name: &str) -> Option<Personality> {
+    if name == "cpu" {
+        Some(Personality::Balanced)
+    } else {
+        Personality::from_name(name)
+    }
+}
+
+#[derive(Default)]
+struct Standing {
+    played: u32,
+    won: u32,
+    drawn: u32,
+    lost: u32,
+    scored: u32,
+    conceded: u32,
+}
+
+impl Standing {
+    fn points(&self) -> u32 {
+        self.won * 3 + self.drawn
+    }
+
+    fn add(&mut self, scored: u8, conceded: u8) {
+        self.played += 1;
+        self.scored += scored as u32;
+        self.conceded += conceded as u32;
+        match scored.cmp(&conceded) {
+            std::cmp::Ordering::Greater => self.won += 1,
+            std::cmp::Ordering::Equal => self.drawn += 1,
+            std::cmp::Ordering::Less => self.lost += 1,
+        }
+    }
+}
+
+// play a match between two entrants to the end, or for --frames steps, and give the score
+fn play_match(
+    names: [&str; 2],
+    options: &LaunchOptions,
+    broadcast: &mut Option<Broadcast>,
+) -> [u8; 2] {
+    if let Some(broadcast) = broadcast {
+        broadcast.set_title(format!("{} v {}", names[0], names[1]));
+    }
+    let level = options.difficulty.unwrap_or(DifficultyLevel::Medium);
+    let mut game = Game::new(get_difficulty(level));
+    let mut bots = names.map(|name| new_bot(name).map(BotInput));
+    for (t, bot) in bots.iter().enumerate() {
+        game.teams[t].controls = bot.as_ref().map(|_| TEAM_CONTROLS[t]);
+        if let Some(personality) = cpu_personality(names[t]) {
+            game.teams[t].personality = personality;
+        }
+    }
+    let mut steps = 0;
+    while options.frames.is_none_or(|frames| steps < frames) {
+        let game_over = game.teams[0].score.max(game.teams[1].score) == game.goals_to_win
+            && game.score_timer == 1;
+        for (t, bot) in bots.iter_mut().enumerate() {
+            if let Some(bot) = bot {
+                let input = bot.read(&game, t);
+                game.apply_input(t, input);
+            }
+        }
+        game.update();
+        steps += 1;
+        if let Some(broadcast) = broadcast {
+            broadcast.update(&game);
+            broadcast.keep_time();
+        }
+        if game_over {
+            break;
+        }
+    }
+    [game.teams[0].score, game.teams[1].score]
+}
+
+pub fn run_tournament(entrants: &str, options: &LaunchOptions) {
+    let names: Vec<&str> = entrants.split(',').collect();
+    if let Some(name) = names
+        .iter()
+        .find(|name| cpu_personality(name).is_none() && new_bot(name).is_none())
+    {
+        let known: Vec<&str> = BOTS
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(personality::ALL.iter().map(|p| p.name()))
+            .collect();
+        eprintln!(
+            "--tournament: no bot called {}; try {} or cpu",
+            name,
+            known.join(", ")
+        );
+        std::process::exit(1);
+    }
+    let mut table: Vec<Standing> = names.iter().map(|_| Standing::default()).collect();
+    let mut broadcast = Broadcast::from_args();
+    for home in 0..names.len() {
+        for away in (0..names.len()).filter(|away| *away != home) {
+            let score = play_match([names[home], names[away]], options, &mut broadcast);
+            println!("{} {}-{} {}", names[home], score[0], score[1], names[away]);
+            table[home].add(score[0], score[1]);
+            table[away].add(score[1], score[0]);
+        }
+    }
+    let mut order: Vec<usize> = (0..names.len()).collect();
+    order.sort_by_key(|i| {
+        let s = &table[*i];
+        std::cmp::Reverse((s.points(), s.scored as i32 - s.conceded as i32, s.scored))
+    });
+    println!();
+    println!(
+        "{:<12}{:>4}{:>4}{:>4}{:>4}{:>5}{:>5}{:>5}",
+        "", "P", "W", "D", "L", "F", "A", "Pts"
+    );
+    for i in order {
+        let s = &table[i];
+        println!(
+            "{:<12}{:>4}{:>4}{:>4}{:>4}{:>5}{:>5}{:>5}",
+            names[i],
+            s.played,
+            s.won,
+            s.drawn,
+            s.lost,
+            s.scored,
+            s.conceded,
+            s.points()
+        );
+    }
+}
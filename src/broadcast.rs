@@ -0,0 +1,432 @@
+// broadcasting a match for spectators to watch live: with --broadcast, whatever's being played (a
+// match in the window, a headless match or a bot tournament, see bots.rs) is sent after every step
+// to anyone who connects to BROADCAST_PORT, as just what's changed in what the renderer draws (see
+// render.rs) since the step before; spectators only watch, so they can come and go without holding
+// up the match, and one that can't keep up is dropped
+//
+//   --broadcast       broadcast the match; a headless one is played in real time rather than as
+//                     fast as possible, so there's something to watch
+//   --spectate ADDR   watch the match broadcast from ADDR (an address, with or without the port)
+//
+// the broadcast is lines of text, each a record naming what it describes followed by its value, and
+// a record is only sent when its value changes; a spectator is sent every record when they arrive.
+// "step N" ends each step:
+//   title TEXT                     what's being played, such as who's playing who in a tournament
+//   players N                      how many players there are
+//   pI X Y DIR DISTANCE INVOLVED   where the Ith player is, which way they're facing, how far
+//                                  they've run and whether they have the ball or are being played
+//   nI TEAM NUMBER NAME            who the Ith player is, with - for a player without a number
+//   balls SCALE X Y ...            where each ball is, and how big they are
+//   arrowT X Y                     where team T's active player is, or - if it's not human
+//   ripple X Y STRENGTH STEPS      the net shaking after a goal, or - once it's stopped
+//   lighting NAME                  day, evening or night
+//   camera X Y ZOOM                what the match camera is looking at
+//   score A B                      both teams' scores
+//
+// spectating isn't possible in the browser, which can't open sockets
+
+use crate::camera::Camera;
+use crate::i18n::Strings;
+use crate::lighting::Lighting;
+use crate::net::Lines;
+use crate::render::{Frame, PlayerView, Renderer};
+use crate::scaler::Scaler;
+use crate::textures::Textures;
+use crate::{
+    accessibility, draw_text_centred, Game, NetRipple, RenderOptions, HALF_WINDOW_WIDTH, HEIGHT,
+    TIMESTEP, WIDTH,
+};
+use euclid::vec2;
+use macroquad::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+pub const BROADCAST_PORT: u16 = 45019;
+// a spectator with this much of the broadcast still waiting to go to them has fallen too far behind
+const MAX_BACKLOG: usize = 1 << 20;
+// more players than any match has, so a broadcast saying there are more makes no sense
+const MAX_PLAYERS: usize = 64;
+const LIGHTINGS: [Lighting; 3] = [Lighting::Day, Lighting::Evening, Lighting::Night];
+
+pub struct Broadcast {
+    listener: TcpListener,
+    spectators: Vec<Lines>,
+    frame: Frame,
+    // every record as it was last sent, by what it describes
+    sent: BTreeMap<String, String>,
+    title: String,
+    step: u32,
+    // when the first step was broadcast, for keeping a headless match to real time
+    started: Option<Instant>,
+}
+
+impl Broadcast {
+    // None unless --broadcast was given
+    pub fn from_args() -> Option<Self> {
+        if !std::env::args().any(|arg| arg == "--broadcast") {
+            return None;
+        }
+        let listener = TcpListener::bind(("0.0.0.0", BROADCAST_PORT))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener));
+        match listener {
+            Ok(listener) => Some(Self {
+                listener,
+                spectators: Vec::new(),
+                frame: Frame::new(),
+                sent: BTreeMap::new(),
+                title: String::new(),
+                step: 0,
+                started: None,
+            }),
+            Err(e) => {
+                warn!("couldn't broadcast: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    // call after each step
+    pub fn update(&mut self, game: &Game) {
+        while let Ok((stream, address)) = self.listener.accept() {
+            match Lines::new(stream) {
+                Ok(mut spectator) => {
+                    let mut everything: String = self.sent.values().cloned().collect();
+                    everything.push_str(&format!("step {}\n", self.step));
+                    spectator.send(&everything);
+                    self.spectators.push(spectator);
+                }
+                Err(e) => warn!("couldn't broadcast to {}: {}", address, e),
+            }
+        }
+        let mut changes = String::new();
+        for line in self.records(game) {
+            let key = line.split(' ').next().unwrap_or_default().to_owned();
+            if self.sent.get(&key) != Some(&line) {
+                changes.push_str(&line);
+                self.sent.insert(key, line);
+            }
+        }
+        // a player who's gone has to be sent in full if there's ever one at that place again
+        let players = self.frame.players.len();
+        self.sent
+            .retain(|key, _| match key.strip_prefix(['p', 'n']) {
+                Some(index) => index.parse().map_or(true, |i: usize| i < players),
+                None => true,
+            });
+        self.step += 1;
+        changes.push_str(&format!("step {}\n", self.step));
+        // spectators aren't listened to, and anything they send is ignored, but receiving is how a
+        // connection that's gone shows up; one that's gone, or fallen too far behind, is dropped
+        self.spectators.retain_mut(|spectator| {
+            spectator.send(&changes);
+            spectator.receive().is_ok() && spectator.backlog() < MAX_BACKLOG
+        });
+    }
+
+    // the whole match as records, each a line
+    fn records(&mut self, game: &Game) -> Vec<String> {
+        let frame = &mut self.frame;
+        frame.capture(game);
+        let mut records = vec![
+            format!("title {}\n", self.title),
+            format!("players {}\n", frame.players.len()),
+        ];
+        for (i, player) in frame.players.iter().enumerate() {
+            records.push(format!(
+                "p{} {:.0} {:.0} {} {:.0} {}\n",
+                i, player.pos.x, player.pos.y, player.dir, player.distance, player.involved as u8
+            ));
+            let number = player.number.map_or("-".to_owned(), |n| n.to_string());
+            records.push(format!(
+                "n{} {} {} {}\n",
+                i, player.team, number, player.name
+            ));
+        }
+        let mut balls = format!("balls {}", frame.ball_scale);
+        for ball in &frame.balls {
+            write!(balls, " {:.0} {:.0}", ball.x, ball.y).unwrap();
+        }
+        records.push(balls + "\n");
+        for (t, arrow) in frame.arrows.iter().enumerate() {
+            records.push(match arrow {
+                Some(pos) => format!("arrow{} {:.0} {:.0}\n", t, pos.x, pos.y),
+                None => format!("arrow{} -\n", t),
+            });
+        }
+        records.push(match frame.net_ripple {
+            Some(ripple) => format!(
+                "ripple {:.0} {:.0} {:.2} {}\n",
+                ripple.pos.x, ripple.pos.y, ripple.strength, ripple.steps_left
+            ),
+            None => "ripple -\n".to_owned(),
+        });
+        records.push(format!("lighting {}\n", frame.lighting.key()));
+        let camera = &game.camera;
+        records.push(format!(
+            "camera {:.0} {:.0} {:.3}\n",
+            camera.focus.x, camera.focus.y, camera.zoom
+        ));
+        records.push(format!(
+            "score {} {}\n",
+            game.teams[0].score, game.teams[1].score
+        ));
+        records
+    }
+
+    // for a match played without a window, which would otherwise be over before anyone could watch
+    // it: wait until it's time for the next step
+    pub fn keep_time(&mut self) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let due = started + Duration::from_secs_f32(TIMESTEP * self.step as f32);
+        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+// what a spectator knows about the match
+#[derive(Clone)]
+struct View {
+    frame: Frame,
+    camera: Camera,
+    score: [u8; 2],
+    title: String,
+}
+
+impl View {
+    fn new() -> Self {
+        Self {
+            frame: Frame::new(),
+            camera: Camera::new(vec2(WIDTH / 2.0, HEIGHT / 2.0), vec2(WIDTH, HEIGHT)),
+            score: [0, 0],
+            title: String::new(),
+        }
+    }
+}
+
+pub struct Spectator {
+    lines: Lines,
+    // as the records for the step arriving say, and as it was at the end of the last step, which is
+    // what's shown
+    next: View,
+    shown: View,
+    // why the broadcast stopped, if it has
+    ended: Option<String>,
+}
+
+impl Spectator {
+    pub fn connect(address: &str) -> Result<Self, String> {
+        let view = View::new();
+        Ok(Self {
+            lines: Lines::connect(address, BROADCAST_PORT)?,
+            next: view.clone(),
+            shown: view,
+            ended: None,
+        })
+    }
+
+    // call once a frame
+    pub fn update(&mut self) {
+        if self.ended.is_some() {
+            return;
+        }
+        match self.lines.receive() {
+            Ok(lines) => {
+                for line in lines {
+                    if line.starts_with("step ") {
+                        self.shown.clone_from(&self.next);
+                    } else if read_record(&mut self.next, &line).is_none() {
+                        self.ended = Some(format!("didn't understand \"{}\"", line));
+                        return;
+                    }
+                }
+            }
+            Err(e) => self.ended = Some(e),
+        }
+    }
+
+    pub fn draw_world(
+        &self,
+        renderer: &mut Renderer,
+        textures: &Textures,
+        scaler: &Scaler,
+        options: &RenderOptions,
+    ) {
+        renderer.draw_recorded(
+            &self.shown.frame,
+            textures,
+            &self.shown.camera,
+            scaler.target(),
+            options,
+        );
+        set_camera(&scaler.screen_camera());
+    }
+
+    pub fn draw_hud(&self, strings: &Strings) {
+        accessibility::draw_large_score(self.shown.score, strings);
+        let status = match &self.ended {
+            Some(reason) => strings.format("hud.broadcast_ended", &[reason]),
+            None => strings.format("hud.spectating", &[&self.shown.title]),
+        };
+        draw_text_centred(&status, HALF_WINDOW_WIDTH, HEIGHT - 10.0, 16.0);
+    }
+}
+
+// change the view as a record says, or None if it makes no sense
+fn read_record(view: &mut View, line: &str) -> Option<()> {
+    let (key, value) = line.split_once(' ')?;
+    let fields: Vec<&str> = value.split(' ').collect();
+    let number = |i: usize| -> Option<f32> { fields.get(i)?.parse().ok() };
+    let below =
+        |i: usize, limit: u8| -> Option<u8> { fields.get(i)?.parse().ok().filter(|n| *n < limit) };
+    let frame = &mut view.frame;
+    match key {
+        "title" => view.title = value.to_owned(),
+        "players" => {
+            let count: usize = value.parse().ok().filter(|n| *n <= MAX_PLAYERS)?;
+            frame.players.resize_with(count, empty_player);
+        }
+        "balls" => {
+            frame.ball_scale = number(0)?;
+            frame.balls = (1..fields.len())
+                .step_by(2)
+                .map(|i| Some(vec2(number(i)?, number(i + 1)?)))
+                .collect::<Option<_>>()?;
+        }
+        "ripple" => {
+            frame.net_ripple = match value {
+                "-" => None,
+                _ => Some(NetRipple {
+                    pos: vec2(number(0)?, number(1)?),
+                    strength: number(2)?,
+                    steps_left: number(3)? as u32,
+                }),
+            }
+        }
+        "lighting" => {
+            frame.lighting = *LIGHTINGS.iter().find(|l| l.key() == value)?;
+        }
+        "camera" => {
+            view.camera.focus = vec2(number(0)?, number(1)?);
+            view.camera.zoom = number(2)?;
+        }
+        "score" => view.score = [number(0)? as u8, number(1)? as u8],
+        _ => {
+            if let Some(t) = key.strip_prefix("arrow") {
+                let arrow = frame.arrows.get_mut(t.parse::<usize>().ok()?)?;
+                *arrow = match value {
+                    "-" => None,
+                    _ => Some(vec2(number(0)?, number(1)?)),
+                };
+                return Some(());
+            }
+            let (kind, index) = (key.get(..1)?, key.get(1..)?);
+            let index: usize = index.parse().ok().filter(|i| *i < MAX_PLAYERS)?;
+            if index >= frame.players.len() {
+                frame.players.resize_with(index + 1, empty_player);
+            }
+            let player = &mut frame.players[index];
+            match kind {
+                "p" => {
+                    player.pos = vec2(number(0)?, number(1)?);
+                    player.dir = below(2, 8)?;
+                    player.distance = number(3)?;
+                    player.involved = fields.get(4) == Some(&"1");
+                }
+                "n" => {
+                    player.team = below(0, 2)?;
+                    player.number = number(1).map(|n| n as u8);
+                    player.name = value.splitn(3, ' ').nth(2).unwrap_or_default().to_owned();
+                }
+                _ => return None,
+            }
+        }
+    }
+    Some(())
+}
+
+fn empty_player() -> PlayerView {
+    PlayerView {
+        pos: vec2(0.0, 0.0),
+        team: 0,
+        dir: 0,
+        distance: 0.0,
+        number: None,
+        name: String::new(),
+        involved: false,
+        target: None,
+        peer: None,
+        lead: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn players_are_read_in_full() {
+        let mut view = View::new();
+        assert!(read_record(&mut view, "players 2").is_some());
+        assert!(read_record(&mut view, "p1 100 200 3 4.5 1").is_some());
+        assert!(read_record(&mut view, "n1 1 9 GRACE HOPPER").is_some());
+        assert!(read_record(&mut view, "n0 0 - KEEPER").is_some());
+        let players = &view.frame.players;
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[1].pos, vec2(100.0, 200.0));
+        assert_eq!((players[1].dir, players[1].distance), (3, 4.5));
+        assert!(players[1].involved);
+        assert_eq!((players[1].team, players[1].number), (1, Some(9)));
+        assert_eq!(players[1].name, "GRACE HOPPER");
+        assert_eq!(
+            (players[0].number, players[0].name.as_str()),
+            (None, "KEEPER")
+        );
+    }
+
+    #[test]
+    fn records_with_missing_fields() {
+        for line in [
+            "p0 100 200",
+            "n0",
+            "camera 1 2",
+            "score 1",
+            "balls 1 10",
+            "ripple 1 2 0.5",
+            "arrow0 5",
+            "lighting dusk",
+            "players",
+            "p",
+            "step",
+        ] {
+            assert!(
+                read_record(&mut View::new(), line).is_none(),
+                "read {:?}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_records() {
+        for line in [
+            "players 65",
+            "players 4000000000",
+            "p64 0 0 0 0 0",
+            "p4000000000 0 0 0 0 0",
+            "p0 0 0 8 0 0",
+            "n0 2 9 NOBODY",
+            "arrow2 0 0",
+        ] {
+            let mut view = View::new();
+            assert!(read_record(&mut view, line).is_none(), "read {:?}", line);
+            assert!(view.frame.players.len() <= MAX_PLAYERS);
+        }
+    }
+}
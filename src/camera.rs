@@ -0,0 +1,176 @@
+use crate::{GameEvent, Vector, KICK_STRENGTH, LEVEL_H, LEVEL_W};
+use euclid::vec2;
+use macroquad::prelude::{get_frame_time, is_key_down, Camera2D, KeyCode, Rect};
+
+const MAX_PAN_SPEED: f32 = 8.0;
+const DEFAULT_DEADZONE: (f32, f32) = (40.0, 30.0);
+const DEFAULT_LOOKAHEAD: f32 = 60.0;
+
+// the camera starts zooming out once the ball is travelling faster than this, and reaches MIN_ZOOM
+// for a full-strength kick
+const ZOOM_OUT_BALL_SPEED: f32 = 6.0;
+const MIN_ZOOM: f32 = 0.8;
+const ZOOM_RATE: f32 = 0.01;
+
+// shake when the ball hits the net, and a brief freeze when a shot is struck
+const GOAL_SHAKE_FRAMES: u32 = 20;
+const SHAKE_MAGNITUDE: f32 = 6.0;
+const SHOT_HIT_STOP_FRAMES: u32 = 3;
+
+// a camera flown by hand moves this many level units a second, and zooms by this much a second
+const FLY_SPEED: f32 = 600.0;
+const FLY_ZOOM_RATE: f32 = 1.0;
+// zoomed out any further, the view would be wider than the level
+const FLY_ZOOM_RANGE: (f32, f32) = (0.8, 2.0);
+
+#[derive(Clone)]
+pub struct Camera {
+    pub focus: Vector,
+    // size of the area of the screen this camera draws to
+    pub viewport: Vector,
+    pub zoom: f32,
+    // the zoom level to settle at when the ball isn't flying about
+    pub base_zoom: f32,
+    // half-size of the box around the focus within which the tracked point can move freely
+    pub deadzone: Vector,
+    // how far ahead of the ball to look towards the goal being attacked
+    pub lookahead: f32,
+    pub dynamic_zoom: bool,
+    // whether shake and hit-stop are enabled
+    pub effects: bool,
+    shake_frames: u32,
+    hit_stop_frames: u32,
+}
+
+impl Camera {
+    pub fn new(focus: Vector, viewport: Vector) -> Self {
+        Self {
+            focus,
+            viewport,
+            zoom: 1.0,
+            base_zoom: 1.0,
+            deadzone: vec2(DEFAULT_DEADZONE.0, DEFAULT_DEADZONE.1),
+            lookahead: DEFAULT_LOOKAHEAD,
+            dynamic_zoom: true,
+            effects: true,
+            shake_frames: 0,
+            hit_stop_frames: 0,
+        }
+    }
+
+    // start shaking or freezing in response to what happened in the last step
+    pub fn react(&mut self, events: &[GameEvent]) {
+        if !self.effects {
+            return;
+        }
+        for event in events {
+            match event {
+                GameEvent::Goal { .. } => self.shake_frames = GOAL_SHAKE_FRAMES,
+                GameEvent::Kick { shot: true, .. } => self.hit_stop_frames = SHOT_HIT_STOP_FRAMES,
+                _ => (),
+            }
+        }
+    }
+
+    // returns true if the game should be frozen for this step, counting down the hit-stop
+    pub fn hold(&mut self) -> bool {
+        if self.hit_stop_frames > 0 {
+            self.hit_stop_frames -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // attack_dir is the y direction of the goal being attacked (-1 or 1), or 0 if nobody has the ball
+    pub fn update(&mut self, ball_pos: Vector, ball_vel: Vector, attack_dir: f32) {
+        let tracked = ball_pos + vec2(0.0, attack_dir * self.lookahead);
+        let target_zoom = if self.dynamic_zoom {
+            let t =
+                (ball_vel.length() - ZOOM_OUT_BALL_SPEED) / (KICK_STRENGTH - ZOOM_OUT_BALL_SPEED);
+            self.base_zoom * (1.0 - (1.0 - MIN_ZOOM) * t.clamp(0.0, 1.0))
+        } else {
+            self.base_zoom
+        };
+        self.track(tracked, target_zoom);
+    }
+
+    // follow a point, moving no more than necessary, while easing towards the given zoom level
+    pub fn track(&mut self, tracked: Vector, target_zoom: f32) {
+        let diff = tracked - self.focus;
+        // only move far enough to bring the tracked point back to the edge of the deadzone
+        let excess = vec2(
+            outside_deadzone(diff.x, self.deadzone.x),
+            outside_deadzone(diff.y, self.deadzone.y),
+        );
+        self.focus += excess.with_max_length(MAX_PAN_SPEED);
+        self.zoom += (target_zoom - self.zoom).clamp(-ZOOM_RATE, ZOOM_RATE);
+        self.shake_frames = self.shake_frames.saturating_sub(1);
+    }
+
+    // for a camera that's been set free of the match, such as for watching a replay or taking a
+    // photo: WASD moves it, and Q and E zoom out and in; call once a frame
+    pub fn fly(&mut self) {
+        let key = |code| is_key_down(code) as i32 as f32;
+        let dir: Vector = vec2(
+            key(KeyCode::D) - key(KeyCode::A),
+            key(KeyCode::S) - key(KeyCode::W),
+        );
+        let dt = get_frame_time();
+        // and doesn't shake, which would otherwise never wear off
+        self.shake_frames = 0;
+        self.focus += dir * FLY_SPEED * dt / self.zoom;
+        self.focus = vec2(
+            self.focus.x.clamp(0.0, LEVEL_W),
+            self.focus.y.clamp(0.0, LEVEL_H),
+        );
+        let zoom = key(KeyCode::E) - key(KeyCode::Q);
+        self.zoom = (self.zoom * (1.0 + zoom * FLY_ZOOM_RATE * dt))
+            .clamp(FLY_ZOOM_RANGE.0, FLY_ZOOM_RANGE.1);
+    }
+
+    // jolts the view in a different direction each frame, dying away as the shake wears off
+    fn shake_offset(&self) -> Vector {
+        if self.shake_frames == 0 {
+            return Vector::zero();
+        }
+        let strength = SHAKE_MAGNITUDE * self.shake_frames as f32 / GOAL_SHAKE_FRAMES as f32;
+        let angle = self.shake_frames as f32 * 2.4;
+        vec2(angle.cos(), angle.sin()) * strength
+    }
+
+    pub fn view_size(&self) -> Vector {
+        self.viewport / self.zoom
+    }
+
+    // top left corner of the view in level coordinates, kept within the level
+    pub fn offset(&self) -> Vector {
+        let size = self.view_size();
+        vec2(
+            (self.focus.x - size.x / 2.).clamp(0.0, LEVEL_W - size.x),
+            (self.focus.y - size.y / 2.).clamp(0.0, LEVEL_H - size.y),
+        )
+    }
+
+    // convert a point on this camera's viewport into level coordinates
+    pub fn screen_to_world(&self, pos: Vector) -> Vector {
+        self.offset() + pos / self.zoom
+    }
+
+    // macroquad camera for drawing in level coordinates
+    pub fn world_camera(&self) -> Camera2D {
+        let offs = self.offset() + self.shake_offset();
+        let size = self.view_size();
+        Camera2D::from_display_rect(Rect::new(offs.x, offs.y, size.x, size.y))
+    }
+}
+
+fn outside_deadzone(d: f32, half_size: f32) -> f32 {
+    if d > half_size {
+        d - half_size
+    } else if d < -half_size {
+        d + half_size
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,93 @@
+// quick chat in network play (see net.rs): the number keys 1 to 6 each send the other player one of
+// a few set phrases, and whatever either player has said is shown in the bottom corner of the screen
+// for a few seconds. only where the phrase is in PHRASES crosses the network, so each player reads
+// it in their own language
+
+use crate::i18n::Strings;
+use crate::net::{Heard, NetMatch};
+use crate::{Sounds, HEIGHT};
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+// the key that says each phrase, and the phrase's key in the language files
+pub const PHRASES: [(KeyCode, &str); 6] = [
+    (KeyCode::Key1, "chat.good_game"),
+    (KeyCode::Key2, "chat.nice_goal"),
+    (KeyCode::Key3, "chat.what_a_save"),
+    (KeyCode::Key4, "chat.unlucky"),
+    (KeyCode::Key5, "chat.sorry"),
+    (KeyCode::Key6, "chat.laugh"),
+];
+// how long something that's been said stays on screen
+const SHOW_SECONDS: f64 = 5.0;
+// the most that's shown at once; older ones go first
+const MAX_SHOWN: usize = 4;
+// a player can't say anything else this soon after the last thing, so mashing the keys can't bury
+// the other player's screen
+const QUIET_SECONDS: f64 = 1.0;
+
+struct Said {
+    team: usize,
+    phrase: usize,
+    at: f64,
+}
+
+pub struct Chat {
+    said: VecDeque<Said>,
+    last_said: f64,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self {
+            said: VecDeque::new(),
+            last_said: f64::NEG_INFINITY,
+        }
+    }
+
+    // call once a frame in a network match, with what's been heard from the other player since the
+    // frame before
+    pub fn update(&mut self, net: &mut NetMatch, heard: &[Heard], sounds: &Sounds) {
+        let now = get_time();
+        for said in heard {
+            if let Heard::Chat(phrase) = *said {
+                self.add(1 - net.local_team, phrase, now);
+                sounds.play("move");
+            }
+        }
+        if now - self.last_said >= QUIET_SECONDS {
+            if let Some(phrase) = PHRASES.iter().position(|(key, _)| is_key_pressed(*key)) {
+                net.say(phrase);
+                self.add(net.local_team, phrase, now);
+                self.last_said = now;
+            }
+        }
+        self.said.retain(|said| now - said.at < SHOW_SECONDS);
+    }
+
+    fn add(&mut self, team: usize, phrase: usize, at: f64) {
+        self.said.push_back(Said { team, phrase, at });
+        if self.said.len() > MAX_SHOWN {
+            self.said.pop_front();
+        }
+    }
+
+    // newest at the bottom, with what this player said in yellow
+    pub fn draw(&self, local_team: usize, strings: &Strings) {
+        for (i, said) in self.said.iter().rev().enumerate() {
+            let text = strings.format(
+                "chat.said",
+                &[
+                    &(said.team + 1).to_string(),
+                    strings.get(PHRASES[said.phrase].1),
+                ],
+            );
+            let colour = if said.team == local_team {
+                YELLOW
+            } else {
+                WHITE
+            };
+            draw_text(&text, 20., HEIGHT - 34. - 24. * i as f32, 24., colour);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// for tracking down desyncs: hashes the simulation state every so often and either writes the
+// hashes to a file or checks them against a file written by an earlier run, so two runs that should
+// match (two peers, or a replay and the live game) can be compared step by step
+//
+//   --checksum-log PATH       write "step hash" lines to PATH
+//   --checksum-compare PATH   compare against PATH and report the first step that differs
+//   --checksum-every N        hash every N steps rather than CHECK_FRAMES
+//
+// runs will only match if they start from the same --seed and get the same input
+
+use crate::{Game, Momentum, Position, Timer, Vector};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const CHECK_FRAMES: u64 = 60;
+
+enum Mode {
+    // hashes written so far, saved on every check so nothing is lost if the game is closed
+    Log(String, String),
+    // the reference hashes by step
+    Compare(HashMap<u64, u64>),
+}
+
+pub struct Checksums {
+    mode: Mode,
+    every: u64,
+    step: u64,
+    // the first step at which the hashes differed
+    pub desync: Option<u64>,
+}
+
+impl Checksums {
+    // None unless one of the modes was asked for on the command line
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |name: &str| {
+            args.iter()
+                .position(|arg| arg == name)
+                .and_then(|i| args.get(i + 1))
+        };
+        let every = value("--checksum-every")
+            .and_then(|n| n.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(CHECK_FRAMES);
+        let mode = if let Some(path) = value("--checksum-log") {
+            Mode::Log(path.clone(), String::new())
+        } else if let Some(path) = value("--checksum-compare") {
+            let text = match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("couldn't read checksums from {}: {}", path, e);
+                    return None;
+                }
+            };
+            let reference = text
+                .lines()
+                .filter_map(|line| {
+                    let (step, hash) = line.split_once(' ')?;
+                    Some((step.parse().ok()?, u64::from_str_radix(hash, 16).ok()?))
+                })
+                .collect();
+            Mode::Compare(reference)
+        } else {
+            return None;
+        };
+        Some(Self {
+            mode,
+            every,
+            step: 0,
+            desync: None,
+        })
+    }
+
+    // called after every simulation step
+    pub fn update(&mut self, game: &Game) {
+        self.step += 1;
+        if !self.step.is_multiple_of(self.every) {
+            return;
+        }
+        let hash = hash_state(game);
+        match &mut self.mode {
+            Mode::Log(path, text) => {
+                writeln!(text, "{} {:016x}", self.step, hash).unwrap();
+                if let Err(e) = std::fs::write(&*path, &*text) {
+                    warn!("couldn't write checksums to {}: {}", path, e);
+                }
+            }
+            Mode::Compare(reference) => {
+                let expected = reference.get(&self.step).copied();
+                if self.desync.is_none() && expected.is_some_and(|e| e != hash) {
+                    error!(
+                        "desync at step {}: expected {:016x}, got {:016x}",
+                        self.step,
+                        expected.unwrap(),
+                        hash
+                    );
+                    self.desync = Some(self.step);
+                }
+            }
+        }
+    }
+}
+
+// FNV-1a over everything that affects how the match plays out from here
+fn hash_state(game: &Game) -> u64 {
+    let mut hash = Fnv(0xcbf29ce484222325);
+    for (_, (pos, vel, timer, momentum)) in &mut game.world.query::<(
+        &Position,
+        Option<&Vector>,
+        Option<&Timer>,
+        Option<&Momentum>,
+    )>() {
+        hash.vector(pos.0);
+        hash.vector(vel.copied().unwrap_or_else(Vector::zero));
+        hash.bytes(&[timer.map_or(0, |t| t.0) as u8]);
+        hash.vector(momentum.map_or_else(Vector::zero, |m| m.0));
+    }
+    for team in &game.teams {
+        hash.bytes(&[team.score]);
+    }
+    hash.bytes(&game.score_timer.to_le_bytes());
+    hash.0
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn vector(&mut self, v: Vector) {
+        self.bytes(&v.x.to_bits().to_le_bytes());
+        self.bytes(&v.y.to_bits().to_le_bytes());
+    }
+}
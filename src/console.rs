@@ -0,0 +1,260 @@
+// a drop-down console for setting up awkward situations while testing, opened and closed with the
+// backtick key during a match; the match is paused while it's open
+
+use crate::error::WorldExt;
+use crate::geometry::on_pitch;
+use crate::{
+    get_difficulty, Game, Position, Restart, Squad, Team, Vector, DIFFICULTY_OPTIONS, WIDTH,
+};
+use euclid::vec2;
+use macroquad::prelude::*;
+
+// how many lines of earlier commands and their output to keep on screen
+const MAX_LINES: usize = 8;
+const LINE_HEIGHT: f32 = 20.0;
+
+struct Command {
+    name: &'static str,
+    // the arguments it takes, for help
+    usage: &'static str,
+    // what the command did, or why it couldn't, a line at a time
+    run: fn(&mut Game, &[&str]) -> Result<String, String>,
+}
+
+const COMMANDS: [Command; 7] = [
+    Command {
+        name: "help",
+        usage: "",
+        run: help,
+    },
+    Command {
+        name: "score",
+        usage: "TEAM1 TEAM2",
+        run: score,
+    },
+    Command {
+        name: "give_ball",
+        usage: "SHIRT [TEAM]",
+        run: give_ball,
+    },
+    Command {
+        name: "restart",
+        usage: "kickoff|goal_kick TEAM",
+        run: restart,
+    },
+    Command {
+        name: "set_difficulty",
+        usage: "easy|medium|hard",
+        run: set_difficulty,
+    },
+    Command {
+        name: "teleport_ball",
+        usage: "X Y",
+        run: teleport_ball,
+    },
+    Command {
+        name: "toggle",
+        usage: "ai",
+        run: toggle,
+    },
+];
+
+fn parse<T: std::str::FromStr>(arg: Option<&&str>) -> Result<T, String> {
+    let arg = arg.ok_or("not enough arguments")?;
+    arg.parse().map_err(|_| format!("don't understand {}", arg))
+}
+
+fn help(_game: &mut Game, _args: &[&str]) -> Result<String, String> {
+    Ok(COMMANDS
+        .iter()
+        .map(|c| format!("{} {}", c.name, c.usage).trim_end().to_owned())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn score(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let scores: [u8; 2] = [parse(args.first())?, parse(args.get(1))?];
+    if scores.iter().any(|s| *s >= game.goals_to_win) {
+        return Err(format!("scores must be below {}", game.goals_to_win));
+    }
+    for (team, score) in game.teams.iter_mut().zip(scores) {
+        team.score = score;
+    }
+    Ok(format!("score is now {}-{}", scores[0], scores[1]))
+}
+
+// teams are numbered from 1 as on the scoreboard
+fn give_ball(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let shirt: u8 = parse(args.first())?;
+    let team: u8 = if args.len() > 1 {
+        parse(args.get(1))?
+    } else {
+        1
+    };
+    if !(1..=2).contains(&team) {
+        return Err("team must be 1 or 2".to_owned());
+    }
+    let t = team - 1;
+    let player = game
+        .world
+        .query::<(&Team, &Squad)>()
+        .iter()
+        .find(|(_, (pt, squad))| pt.0 == t && squad.number == shirt)
+        .map(|(id, _)| id)
+        .ok_or(format!("nobody on team {} wears {}", team, shirt))?;
+    if game.has_ball(player) {
+        return Err("they already have a ball".to_owned());
+    }
+    let pos = game
+        .world
+        .component::<Position>(player)
+        .map_err(|e| e.to_string())?
+        .0;
+    let ball = &mut game.balls[0];
+    // only a loose ball has a velocity
+    game.world.remove_one::<Vector>(ball.entity).ok();
+    game.world
+        .component_mut::<Position>(ball.entity)
+        .map_err(|e| e.to_string())?
+        .0 = pos;
+    ball.owner = Some(player);
+    game.kickoff_player = None;
+    game.last_touch = Some(player);
+    game.assister = None;
+    game.teams[t as usize].active_player = Some(player);
+    Ok(format!(
+        "gave the ball to number {} on team {}",
+        shirt, team
+    ))
+}
+
+// start play again straight away, the way a match would after a goal or a ball into an empty net
+fn restart(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let team: usize = parse(args.get(1))?;
+    if !(1..=2).contains(&team) {
+        return Err("team must be 1 or 2".to_owned());
+    }
+    game.restart = match args.first() {
+        Some(&"kickoff") => Restart::Kickoff { team: team - 1 },
+        Some(&"goal_kick") => Restart::GoalKick { team: team - 1 },
+        Some(name) => return Err(format!("no restart called {}", name)),
+        None => return Err("not enough arguments".to_owned()),
+    };
+    game.score_timer = 0;
+    game.reset();
+    Ok(format!("{} to team {}", args[0].replace('_', " "), team))
+}
+
+fn set_difficulty(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let name = args.first().ok_or("not enough arguments")?;
+    let level = DIFFICULTY_OPTIONS
+        .iter()
+        .find(|level| level.key() == *name)
+        .ok_or(format!("no difficulty called {}", name))?;
+    game.difficulty = get_difficulty(*level);
+    Ok(format!("difficulty is now {}", name))
+}
+
+fn teleport_ball(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    let (x, y): (f32, f32) = (parse(args.first())?, parse(args.get(1))?);
+    if !on_pitch(x, y) {
+        return Err(format!("{}, {} isn't on the pitch", x, y));
+    }
+    game.drop_ball(vec2(x, y)).map_err(|e| e.to_string())?;
+    Ok(format!("ball is at {}, {}", x, y))
+}
+
+fn toggle(game: &mut Game, args: &[&str]) -> Result<String, String> {
+    match args.first() {
+        Some(&"ai") => {
+            game.ai_enabled = !game.ai_enabled;
+            Ok(format!(
+                "computer players are {}",
+                if game.ai_enabled { "on" } else { "off" }
+            ))
+        }
+        Some(name) => Err(format!("can't toggle {}", name)),
+        None => Err("not enough arguments".to_owned()),
+    }
+}
+
+pub struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        // throw away whatever was typed while playing
+        while get_char_pressed().is_some() {}
+    }
+
+    // called every frame while the console is open, instead of updating the match
+    pub fn update(&mut self, game: &mut Game) {
+        while let Some(c) = get_char_pressed() {
+            if c != '`' && !c.is_control() {
+                self.input.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        }
+        if is_key_pressed(KeyCode::Enter) && !self.input.trim().is_empty() {
+            let input = std::mem::take(&mut self.input);
+            let output = self.run(game, &input);
+            self.lines.push(format!("> {}", input));
+            self.lines.extend(output.lines().map(str::to_owned));
+            let excess = self.lines.len().saturating_sub(MAX_LINES);
+            self.lines.drain(..excess);
+        }
+    }
+
+    fn run(&self, game: &mut Game, input: &str) -> String {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        match COMMANDS.iter().find(|c| c.name == words[0]) {
+            Some(command) => match (command.run)(game, &words[1..]) {
+                Ok(message) => message,
+                Err(message) => format!("{}: {}", command.name, message),
+            },
+            None => format!("no command called {}; try help", words[0]),
+        }
+    }
+
+    // draw in screen coordinates
+    pub fn draw(&self) {
+        let height = (MAX_LINES + 1) as f32 * LINE_HEIGHT + 10.0;
+        draw_rectangle(0.0, 0.0, WIDTH, height, Color::new(0.0, 0.0, 0.0, 0.8));
+        for (i, line) in self.lines.iter().enumerate() {
+            draw_text(line, 10.0, (i + 1) as f32 * LINE_HEIGHT, 18.0, LIGHTGRAY);
+        }
+        let cursor = if (get_time() * 2.0) as i64 % 2 == 0 {
+            "_"
+        } else {
+            ""
+        };
+        draw_text(
+            &format!("> {}{}", self.input, cursor),
+            10.0,
+            height - 10.0,
+            18.0,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{GameEvent, GOAL_BOUNDS_X, LEVEL_H, LEVEL_W, PITCH_BOUNDS_X, PITCH_BOUNDS_Y};
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+// gap between the touchline and the front row of the crowd
+const CROWD_MARGIN: f32 = 12.0;
+const SPECTATOR_SPACING: f32 = 14.0;
+// how quickly the crowd calms down after something exciting, per step
+const CHEER_DECAY: f32 = 0.992;
+// how excited a team's fans get at a near miss or a save, where a goal is 1
+const NEAR_MISS_CHEER: f32 = 0.5;
+const JUMP_HEIGHT: f32 = 6.0;
+
+const SHIRTS: [Color; 6] = [
+    Color::new(0.9, 0.2, 0.2, 1.0),
+    Color::new(0.95, 0.95, 0.95, 1.0),
+    Color::new(0.2, 0.4, 0.95, 1.0),
+    Color::new(0.95, 0.8, 0.2, 1.0),
+    Color::new(0.3, 0.3, 0.35, 1.0),
+    Color::new(0.2, 0.7, 0.3, 1.0),
+];
+const SKIN: [Color; 3] = [
+    Color::new(0.96, 0.8, 0.69, 1.0),
+    Color::new(0.78, 0.57, 0.43, 1.0),
+    Color::new(0.45, 0.3, 0.2, 1.0),
+];
+
+struct Spectator {
+    pos: Vec2,
+    shirt: Color,
+    skin: Color,
+    // team this spectator supports
+    team: usize,
+    phase: f32,
+}
+
+// the crowd around the edges of the level, which sways gently and jumps up when their team scores
+pub struct Crowd {
+    spectators: Vec<Spectator>,
+    cheer: [f32; 2],
+    time: f32,
+}
+
+impl Crowd {
+    pub fn new() -> Self {
+        let mut spectators = Vec::new();
+        let mut add = |x: f32, y: f32| {
+            // each team's fans sit in the half of the ground they're attacking
+            let team = if y < LEVEL_H / 2.0 { 0 } else { 1 };
+            spectators.push(Spectator {
+                pos: vec2(x, y),
+                shirt: SHIRTS[gen_range(0, SHIRTS.len())],
+                skin: SKIN[gen_range(0, SKIN.len())],
+                team,
+                phase: gen_range(0.0, std::f32::consts::TAU),
+            });
+        };
+        // down each side of the pitch
+        let mut y = PITCH_BOUNDS_Y.0;
+        while y < PITCH_BOUNDS_Y.1 {
+            let mut x = PITCH_BOUNDS_X.0 - CROWD_MARGIN;
+            while x > 0.0 {
+                add(x, y);
+                add(LEVEL_W - x, y);
+                x -= SPECTATOR_SPACING;
+            }
+            y += SPECTATOR_SPACING;
+        }
+        // behind each goal line, leaving room for the goals themselves
+        let mut y = PITCH_BOUNDS_Y.0 - CROWD_MARGIN * 2.0;
+        while y > 0.0 {
+            let mut x = PITCH_BOUNDS_X.0;
+            while x < PITCH_BOUNDS_X.1 {
+                if x < GOAL_BOUNDS_X.0 - CROWD_MARGIN || x > GOAL_BOUNDS_X.1 + CROWD_MARGIN {
+                    add(x, y);
+                    add(x, LEVEL_H - y);
+                }
+                x += SPECTATOR_SPACING;
+            }
+            y -= SPECTATOR_SPACING;
+        }
+        // draw from the back rows forwards
+        spectators.sort_by(|a, b| a.pos.y.partial_cmp(&b.pos.y).unwrap());
+        Self {
+            spectators,
+            cheer: [0.0, 0.0],
+            time: 0.0,
+        }
+    }
+
+    // called once per simulation step with the events from that step
+    pub fn update(&mut self, events: &[GameEvent]) {
+        self.time += crate::TIMESTEP;
+        for cheer in &mut self.cheer {
+            *cheer *= CHEER_DECAY;
+        }
+        for event in events {
+            match *event {
+                GameEvent::Goal { team } => self.cheer[team] = 1.0,
+                // a chance or a save gets the fans on their feet, but not as much as a goal
+                GameEvent::NearMiss { team } | GameEvent::Save { team } => {
+                    self.cheer[team] = self.cheer[team].max(NEAR_MISS_CHEER)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // draw in level coordinates, underneath the players
+    pub fn draw(&self) {
+        for s in &self.spectators {
+            let cheer = self.cheer[s.team];
+            // excited fans bounce up and down, everyone else just sways from side to side
+            let bounce = (self.time * 12.0 + s.phase).sin().abs() * JUMP_HEIGHT * cheer;
+            let sway = (self.time * 1.5 + s.phase).sin() * 1.5 * (1.0 - cheer);
+            let (x, y) = (s.pos.x + sway, s.pos.y - bounce);
+            draw_rectangle(x - 5.0, y - 2.0, 10.0, 8.0, s.shirt);
+            draw_circle(x, y - 5.0, 3.5, s.skin);
+            if cheer > 0.3 {
+                // arms in the air
+                draw_line(x - 5.0, y - 1.0, x - 7.0, y - 10.0, 2.0, s.shirt);
+                draw_line(x + 5.0, y - 1.0, x + 7.0, y - 10.0, 2.0, s.shirt);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// how loud the crowd is during a match: it gets louder as the ball nears either goal, roars when
+// the home side scores and goes quiet when the away side does, and groans or gasps when a chance
+// goes begging
+//
+// team 0 is the home side, as it's the one a single player plays as
+
+use crate::{Game, GameEvent, HALF_LEVEL_H, HALF_PITCH_H};
+
+const HOME_TEAM: usize = 0;
+// the crowd's volume with the ball in midfield, and how much louder it gets as the ball reaches the
+// goal line, starting from this far out
+const CALM_VOLUME: f32 = 0.4;
+const SWELL_VOLUME: f32 = 0.35;
+const SWELL_DISTANCE: f32 = 400.0;
+// added to the volume after a goal, and how quickly that wears off, per step
+const HOME_GOAL_EXCITEMENT: f32 = 0.6;
+const AWAY_GOAL_EXCITEMENT: f32 = -0.3;
+const EXCITEMENT_DECAY: f32 = 0.99;
+// how much of the way the volume moves towards where it should be each step, so it swells rather
+// than jumping about
+const VOLUME_EASING: f32 = 0.05;
+
+pub struct CrowdNoise {
+    excitement: f32,
+    volume: f32,
+}
+
+impl CrowdNoise {
+    pub fn new() -> Self {
+        Self {
+            excitement: 0.0,
+            volume: CALM_VOLUME,
+        }
+    }
+
+    // called once per simulation step; gives the name of a sound for the crowd to make, if
+    // something that happened calls for one
+    pub fn update(&mut self, game: &Game) -> Option<&'static str> {
+        self.excitement *= EXCITEMENT_DECAY;
+        let mut reaction = None;
+        for event in &game.events {
+            match *event {
+                GameEvent::Goal { team } => {
+                    self.excitement = if team == HOME_TEAM {
+                        HOME_GOAL_EXCITEMENT
+                    } else {
+                        AWAY_GOAL_EXCITEMENT
+                    };
+                    // the roar or the silence is immediate
+                    self.volume = (CALM_VOLUME + self.excitement).clamp(0.0, 1.0);
+                }
+                // a home chance going wide, or an away save, is a letdown; an away chance going
+                // wide is a near thing
+                GameEvent::NearMiss { team } if team == HOME_TEAM => reaction = Some("groan"),
+                GameEvent::Save { team } if team != HOME_TEAM => reaction = Some("groan"),
+                GameEvent::NearMiss { .. } | GameEvent::Save { .. } => reaction = Some("oooh"),
+                _ => (),
+            }
+        }
+        // the ball nearest to a goal line is the one the crowd is watching
+        let nearest = game
+            .ball_positions()
+            .unwrap_or_default()
+            .iter()
+            .map(|pos| HALF_PITCH_H - (pos.y - HALF_LEVEL_H).abs())
+            .fold(f32::MAX, f32::min);
+        let closeness = (1.0 - nearest / SWELL_DISTANCE).clamp(0.0, 1.0);
+        let wanted = (CALM_VOLUME + SWELL_VOLUME * closeness + self.excitement).clamp(0.0, 1.0);
+        self.volume += (wanted - self.volume) * VOLUME_EASING;
+        reaction
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+}
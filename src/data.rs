@@ -0,0 +1,199 @@
+// reader for the small subset of TOML used by the game's data files: [section] headers, and
+// key = value pairs where a value is a number, a "string", true/false or a [list, of, values]
+
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_pair(&self) -> Option<(f32, f32)> {
+        match self {
+            Self::List(v) if v.len() == 2 => Some((v[0].as_f32()?, v[1].as_f32()?)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Document {
+    // keys before the first section header live in the section named ""
+    pub sections: HashMap<String, HashMap<String, Value>>,
+}
+
+impl Document {
+    pub fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.sections.get(section)?.get(key)
+    }
+}
+
+pub fn parse(text: &str) -> Result<Document, String> {
+    let mut doc = Document::default();
+    let mut section = String::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fail = |msg: &str| format!("line {}: {}", n + 1, msg);
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| fail("unclosed section"))?;
+            section = name.trim().to_owned();
+            doc.sections.entry(section.clone()).or_default();
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| fail("expected key = value"))?;
+        let value = parse_value(value.trim()).ok_or_else(|| fail("bad value"))?;
+        doc.sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key.trim().trim_matches('"').to_owned(), value);
+    }
+    Ok(doc)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if let Some(inner) = s.strip_prefix('[') {
+        let inner = inner.strip_suffix(']')?.trim();
+        if inner.is_empty() {
+            return Some(Value::List(Vec::new()));
+        }
+        return split_list(inner)
+            .iter()
+            .map(|item| parse_value(item.trim()))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::List);
+    }
+    if let Some(inner) = s.strip_prefix('"') {
+        return Some(Value::Str(inner.strip_suffix('"')?.replace("\\n", "\n")));
+    }
+    match s {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => s.parse().ok().map(Value::Number),
+    }
+}
+
+// split on top-level commas, ignoring any inside strings or nested lists
+fn split_list(s: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut depth, mut in_string, mut start) = (0, false, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                items.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        items.push(&s[start..]);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_before_a_section_go_in_the_unnamed_one() {
+        let doc = parse("speed = 2.5\n[ball]\nbounce = true\n").unwrap();
+        assert_eq!(doc.get("", "speed"), Some(&Value::Number(2.5)));
+        assert_eq!(doc.get("ball", "bounce"), Some(&Value::Bool(true)));
+        assert_eq!(doc.get("ball", "speed"), None);
+    }
+
+    #[test]
+    fn comments_inside_strings_are_kept() {
+        let doc = parse("name = \"number # 9\" # the striker\n# a whole line\n").unwrap();
+        assert_eq!(
+            doc.get("", "name"),
+            Some(&Value::Str("number # 9".to_owned()))
+        );
+        assert_eq!(doc.sections[""].len(), 1);
+    }
+
+    #[test]
+    fn nested_lists() {
+        let doc = parse("pairs = [[1, 2], [\"a, b\", [3]], []]\n").unwrap();
+        let expected = Value::List(vec![
+            Value::List(vec![Value::Number(1.0), Value::Number(2.0)]),
+            Value::List(vec![
+                Value::Str("a, b".to_owned()),
+                Value::List(vec![Value::Number(3.0)]),
+            ]),
+            Value::List(Vec::new()),
+        ]);
+        assert_eq!(doc.get("", "pairs"), Some(&expected));
+        assert_eq!(
+            doc.get("", "pairs").and_then(|v| match v {
+                Value::List(items) => items[0].as_pair(),
+                _ => None,
+            }),
+            Some((1.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn unclosed_section() {
+        let e = parse("a = 1\n[ball\nbounce = true\n").err().unwrap();
+        assert_eq!(e, "line 2: unclosed section");
+    }
+
+    #[test]
+    fn bad_values() {
+        for text in [
+            "speed = fast",
+            "name = \"unfinished",
+            "list = [1, 2",
+            "list = [1, fast]",
+            "speed",
+        ] {
+            assert!(parse(text).is_err(), "parsed {:?}", text);
+        }
+        assert_eq!(
+            parse("\n\nspeed = fast\n").err().unwrap(),
+            "line 3: bad value"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+// something the simulation relies on that turned out not to hold, most likely an entity that was
+// despawned while something else still referred to it; rather than crash mid-game, the step that
+// found it gives up and the match starts again from kickoff (see Game::recover)
+
+use hecs::{Component, ComponentError, Entity, Ref, RefMut, World};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum GameError {
+    NoSuchEntity(Entity),
+    MissingComponent(Entity, &'static str),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSuchEntity(id) => write!(f, "entity {:?} no longer exists", id),
+            Self::MissingComponent(id, name) => write!(f, "entity {:?} has no {}", id, name),
+        }
+    }
+}
+
+fn component_error<T: Component>(id: Entity, e: ComponentError) -> GameError {
+    match e {
+        ComponentError::NoSuchEntity => GameError::NoSuchEntity(id),
+        ComponentError::MissingComponent(_) => {
+            GameError::MissingComponent(id, std::any::type_name::<T>())
+        }
+    }
+}
+
+// versions of World's accessors whose errors say which entity and component they were about, so
+// they can be passed up with ?
+pub trait WorldExt {
+    fn component<T: Component>(&self, id: Entity) -> Result<Ref<'_, T>, GameError>;
+    fn component_mut<T: Component>(&self, id: Entity) -> Result<RefMut<'_, T>, GameError>;
+    fn add_component(&mut self, id: Entity, component: impl Component) -> Result<(), GameError>;
+    fn take_component<T: Component>(&mut self, id: Entity) -> Result<T, GameError>;
+}
+
+impl WorldExt for World {
+    fn component<T: Component>(&self, id: Entity) -> Result<Ref<'_, T>, GameError> {
+        self.get::<T>(id).map_err(|e| component_error::<T>(id, e))
+    }
+
+    fn component_mut<T: Component>(&self, id: Entity) -> Result<RefMut<'_, T>, GameError> {
+        self.get_mut::<T>(id)
+            .map_err(|e| component_error::<T>(id, e))
+    }
+
+    fn add_component(&mut self, id: Entity, component: impl Component) -> Result<(), GameError> {
+        self.insert_one(id, component)
+            .map_err(|_| GameError::NoSuchEntity(id))
+    }
+
+    fn take_component<T: Component>(&mut self, id: Entity) -> Result<T, GameError> {
+        self.remove_one::<T>(id)
+            .map_err(|e| component_error::<T>(id, e))
+    }
+}
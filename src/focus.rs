@@ -0,0 +1,55 @@
+// noticing when the game goes into the background, so that a match can pause instead of carrying
+// on with nobody watching; in the browser the page reports whether it has focus (web/focus.js),
+// but miniquad doesn't say on the desktop, so there the sign is a long gap between frames, which is
+// what happens while the window is minimised or isn't being drawn
+
+use macroquad::prelude::*;
+
+// a frame taking longer than this, in seconds, means the game wasn't running in between
+const STALL_SECONDS: f32 = 0.5;
+
+#[derive(PartialEq)]
+pub enum FocusChange {
+    None,
+    Lost,
+    Regained,
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn soccer_has_focus() -> i32;
+}
+
+#[cfg(target_arch = "wasm32")]
+fn has_focus() -> bool {
+    unsafe { soccer_has_focus() != 0 }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn has_focus() -> bool {
+    true
+}
+
+pub struct FocusWatch {
+    focused: bool,
+}
+
+impl FocusWatch {
+    pub fn new() -> Self {
+        Self { focused: true }
+    }
+
+    // call once a frame
+    pub fn update(&mut self) -> FocusChange {
+        let focused = has_focus();
+        let change = if (self.focused && !focused) || get_frame_time() > STALL_SECONDS {
+            FocusChange::Lost
+        } else if !self.focused && focused {
+            FocusChange::Regained
+        } else {
+            FocusChange::None
+        };
+        self.focused = focused;
+        change
+    }
+}
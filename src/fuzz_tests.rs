@@ -0,0 +1,154 @@
+// stress test: plays match after match headless with random settings and random button mashing
+// on the human teams, to shake out the unwraps that would fire if the world ever got into a state
+// the rest of the code doesn't expect
+//
+// by default it only runs for long enough to be worth doing on every test run; for a proper
+// soak, set FUZZ_STEPS, e.g.
+//
+//   FUZZ_STEPS=5000000 cargo test --release fuzz
+//
+// a failure names the seed of the match that went wrong, and FUZZ_SEED=n replays just that match
+
+use crate::golden_tests::RANDOM_LOCK;
+use crate::mutators::SPEEDS;
+use crate::tactics::{self, Tactics};
+use crate::test_rng::Rng;
+use crate::training::TrainingSetup;
+use crate::{
+    get_difficulty, Assist, Game, Handicap, Position, Settings, SwitchMode, DIFFICULTY_OPTIONS,
+    HANDICAP_SPEEDS, MATCH_LENGTHS, MAX_HEAD_START, MAX_SPEED, MOUSE_CONTROLS, TEAM_CONTROLS,
+};
+use euclid::vec2;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const DEFAULT_STEPS: u64 = 30000;
+// a match that hasn't finished by now is abandoned for a new one
+const MAX_MATCH_STEPS: u32 = 20000;
+
+fn random_settings(rng: &mut Rng) -> Settings {
+    let mut settings = Settings::new();
+    for _ in 0..rng.below(3) {
+        settings.weather = settings.weather.next();
+    }
+    if rng.chance(2) {
+        settings.switch_mode = SwitchMode::Cycle;
+    }
+    settings.auto_switch = rng.chance(2);
+    if rng.chance(2) {
+        settings.assist = Assist::Pressure;
+    }
+    settings.goals_to_win = MATCH_LENGTHS[rng.below(MATCH_LENGTHS.len())];
+    settings.mutators.multi_ball = rng.chance(3);
+    settings.mutators.giant_ball = rng.chance(3);
+    settings.mutators.power_ups = rng.chance(2);
+    settings.mutators.speed = SPEEDS[rng.below(SPEEDS.len())];
+    for handicap in &mut settings.handicaps {
+        *handicap = Handicap {
+            head_start: rng.below(MAX_HEAD_START as usize + 1) as u8,
+            speed: HANDICAP_SPEEDS[rng.below(HANDICAP_SPEEDS.len())],
+        };
+    }
+    for t in &mut settings.tactics {
+        *t = Tactics {
+            pressing: rng.below(tactics::LEVELS as usize) as u8,
+            line: rng.below(tactics::LEVELS as usize) as u8,
+        };
+    }
+    settings
+}
+
+// play a match from start to finish, with everything about it chosen from the seed
+fn play(seed: u64, max_steps: u32) -> u32 {
+    macroquad::rand::srand(seed);
+    let mut rng = Rng(seed | 1);
+    let settings = random_settings(&mut rng);
+    let mut game = Game::new(get_difficulty(DIFFICULTY_OPTIONS[rng.below(3)]));
+    for (team, controls) in game.teams.iter_mut().zip(TEAM_CONTROLS) {
+        team.controls = match rng.below(3) {
+            0 => None,
+            1 => Some(controls),
+            _ => Some(MOUSE_CONTROLS),
+        };
+    }
+    game.apply_settings(&settings);
+    if rng.chance(8) {
+        game.teams[1].controls = None;
+        game.start_training(TrainingSetup {
+            dummies: rng.chance(2),
+            targets: rng.chance(2),
+        });
+    }
+    for step in 1..=max_steps {
+        for t in 0..=1 {
+            if !game.teams[t].human() {
+                continue;
+            }
+            if game.sticks[t].is_none() || rng.chance(20) {
+                let dir = vec2(rng.direction(), rng.direction());
+                game.sticks[t] = Some(dir * MAX_SPEED);
+            }
+            game.shoot_pressed[t] = rng.chance(15);
+            game.switch_pressed[t] = rng.chance(40);
+            game.call_pressed[t] = rng.chance(40);
+            game.tackle_pressed[t] = rng.chance(40);
+        }
+        if rng.chance(30) {
+            game.cursor = vec2(rng.below(1000) as f32, rng.below(1400) as f32);
+        }
+        let game_over = game.teams[0].score.max(game.teams[1].score) >= game.goals_to_win
+            && game.score_timer == 1;
+        // go through step rather than update so that an error fails the test rather than
+        // quietly restarting the match
+        if let Err(e) = game.step() {
+            panic!("{} at step {}", e, step);
+        }
+        for (_, pos) in &mut game.world.query::<&Position>() {
+            assert!(
+                pos.0.x.is_finite() && pos.0.y.is_finite(),
+                "position {:?} at step {}",
+                pos.0,
+                step
+            );
+        }
+        // sharing out the choice of targets between threads mustn't change what's chosen
+        if step % 100 == 0 {
+            let players = game.player_states();
+            assert!(
+                game.choose_targets(&players).ok()
+                    == game.choose_targets_in_parallel(&players).ok(),
+                "parallel targets differ at step {}",
+                step
+            );
+        }
+        if game_over {
+            return step;
+        }
+    }
+    max_steps
+}
+
+#[test]
+fn fuzz_game_update() {
+    let _lock = RANDOM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(seed) = std::env::var("FUZZ_SEED").ok().and_then(|s| s.parse().ok()) {
+        play(seed, MAX_MATCH_STEPS);
+        return;
+    }
+    let total = std::env::var("FUZZ_STEPS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_STEPS);
+    let mut seeds = Rng(0x5eed);
+    let mut played = 0;
+    while played < total {
+        let seed = seeds.next();
+        let max_steps = MAX_MATCH_STEPS.min((total - played) as u32);
+        match catch_unwind(AssertUnwindSafe(|| play(seed, max_steps))) {
+            Ok(steps) => played += steps as u64,
+            Err(_) => panic!(
+                "match with seed {} panicked (rerun it with FUZZ_SEED)",
+                seed
+            ),
+        }
+    }
+}
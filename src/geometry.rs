@@ -0,0 +1,308 @@
+// the shape of the level: where the pitch and goals are, where players may walk, how the ball
+// bounces off the edges, and the eight directions players face
+
+use crate::{
+    Vector, CENTRE_CIRCLE_RADIUS, GOAL_BOUNDS_X, GOAL_BOUNDS_Y, HALF_GOAL_W, HALF_LEVEL_H,
+    HALF_LEVEL_W, HALF_PITCH_H, PITCH_BOUNDS_X, PITCH_BOUNDS_Y,
+};
+use euclid::vec2;
+use std::f32::consts::PI;
+
+pub fn on_pitch(x: f32, y: f32) -> bool {
+    (x >= PITCH_BOUNDS_X.0 && x < PITCH_BOUNDS_X.1 && y >= PITCH_BOUNDS_Y.0 && y < PITCH_BOUNDS_Y.1)
+        || (x >= GOAL_BOUNDS_X.0
+            && x < GOAL_BOUNDS_X.1
+            && y >= GOAL_BOUNDS_Y.0
+            && y < GOAL_BOUNDS_Y.1)
+}
+
+pub fn allow_movement(x: f32, y: f32) -> bool {
+    if (x - HALF_LEVEL_W).abs() > HALF_LEVEL_W {
+        // Trying to walk off the left or right side of the level
+        false
+    } else if (x - HALF_LEVEL_W).abs() < HALF_GOAL_W + 20.0 {
+        // Player is within the bounds of the goals on the X axis, don't let them walk into, through or behind the goal
+        // +20 takes with of player sprite into account
+        (y - HALF_LEVEL_H).abs() < HALF_PITCH_H
+    } else {
+        // Player is outside the bounds of the goals on the X axis, so they can walk off the pitch and to the edge
+        // of the level
+        (y - HALF_LEVEL_H).abs() < HALF_LEVEL_H
+    }
+}
+
+pub fn in_centre_circle(pos: Vector) -> bool {
+    (pos - vec2(HALF_LEVEL_W, HALF_LEVEL_H)).length() < CENTRE_CIRCLE_RADIUS
+}
+
+// the nearest point to pos on or outside the centre circle; from the very centre, that's straight
+// back into the given team's half (team 0 defends the bottom goal)
+pub fn outside_centre_circle(pos: Vector, team: u8) -> Vector {
+    let centre = vec2(HALF_LEVEL_W, HALF_LEVEL_H);
+    let offset = pos - centre;
+    if offset.length() >= CENTRE_CIRCLE_RADIUS {
+        pos
+    } else if offset.length() == 0.0 {
+        centre + vec2(0.0, if team == 0 { 1.0 } else { -1.0 }) * CENTRE_CIRCLE_RADIUS
+    } else {
+        centre + offset.normalize() * CENTRE_CIRCLE_RADIUS
+    }
+}
+
+// where a ball moving by vel from pos crosses either goal line, if it does so this step (whether or
+// not it's between the posts)
+pub fn goal_line_crossing(pos: Vector, vel: Vector) -> Option<Vector> {
+    let line = if vel.y < 0.0 {
+        PITCH_BOUNDS_Y.0
+    } else {
+        PITCH_BOUNDS_Y.1
+    };
+    let t = (line - pos.y) / vel.y;
+    (0.0..1.0)
+        .contains(&t)
+        .then(|| vec2(pos.x + vel.x * t, line))
+}
+
+pub fn ball_physics(pos: f32, vel: f32, bounds: (f32, f32), drag: f32) -> (f32, f32) {
+    let mut pos = pos;
+    let mut vel = vel;
+    pos += vel;
+    if pos < bounds.0 || pos > bounds.1 {
+        pos -= vel;
+        vel = -vel;
+    }
+    (pos, vel * drag)
+}
+
+// new velocity for a loose ball at ball moving at vel, if it's run into a player standing at body
+// with the given radius, keeping bounce of its speed
+pub fn deflect(
+    ball: Vector,
+    vel: Vector,
+    body: Vector,
+    radius: f32,
+    bounce: f32,
+) -> Option<Vector> {
+    let offset = ball - body;
+    // only bounce off the front of the player, so the ball can't get stuck inside them
+    if offset.length() > radius || offset.length() <= 0.0 || offset.dot(vel) >= 0.0 {
+        return None;
+    }
+    let normal = offset.normalize();
+    Some((vel - normal * 2.0 * vel.dot(normal)) * bounce)
+}
+
+// how close the middle of the ball gets to the middle of a goalpost when it hits it, and how much of
+// its speed it keeps bouncing off
+const POST_RADIUS: f32 = 9.0;
+const POST_BOUNCE: f32 = 0.7;
+
+// how a loose ball moving by vel from pos bounces if it would hit one of the goalposts this step;
+// it stays where it is for the step, against the post
+pub fn hit_post(pos: Vector, vel: Vector) -> Option<Vector> {
+    let speed = vel.square_length();
+    if speed == 0.0 {
+        return None;
+    }
+    [GOAL_BOUNDS_X.0, GOAL_BOUNDS_X.1]
+        .iter()
+        .flat_map(|x| [PITCH_BOUNDS_Y.0, PITCH_BOUNDS_Y.1].map(|y| vec2(*x, y)))
+        .filter(|post| (pos - *post).dot(vel) < 0.0)
+        .filter_map(|post| {
+            // the nearest the ball comes to the post on its way this step
+            let t = ((post - pos).dot(vel) / speed).clamp(0.0, 1.0);
+            let offset = pos + vel * t - post;
+            (offset.length() < POST_RADIUS).then_some((t, offset))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, offset)| {
+            let normal = offset.try_normalize().unwrap_or_else(|| -vel.normalize());
+            (vel - normal * 2.0 * vel.dot(normal)) * POST_BOUNCE
+        })
+}
+
+#[derive(Copy, Clone)]
+pub struct Angle(pub i32);
+
+impl Angle {
+    pub fn sin(&self) -> f32 {
+        (self.0 as f32 * PI / 4.0).sin()
+    }
+
+    pub fn cos(&self) -> f32 {
+        (self.0 as f32 * PI / 4.0).cos()
+    }
+
+    pub fn from_vec(v: Vector) -> Self {
+        Angle((((4.0 / PI * v.x.atan2(-v.y)) + 8.5) as i32) % 8)
+    }
+
+    pub fn to_vec(a: Self) -> Vector {
+        vec2(a.sin(), -a.cos())
+    }
+}
+
+// property-style tests: each property is checked against a few thousand pseudo-random inputs from a
+// fixed seed, so a failure always reproduces
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::{LEVEL_H, LEVEL_W};
+
+    const CASES: usize = 10000;
+
+    #[test]
+    fn allow_movement_stays_in_level() {
+        let mut rng = Rng(1);
+        for _ in 0..CASES {
+            let x = rng.range(-200.0, LEVEL_W + 200.0);
+            let y = rng.range(-200.0, LEVEL_H + 200.0);
+            if allow_movement(x, y) {
+                assert!((0.0..=LEVEL_W).contains(&x), "allowed x {} at y {}", x, y);
+                assert!((0.0..=LEVEL_H).contains(&y), "allowed y {} at x {}", y, x);
+            }
+        }
+    }
+
+    #[test]
+    fn allow_movement_keeps_out_of_goals() {
+        let mut rng = Rng(2);
+        for _ in 0..CASES {
+            let x = rng.range(GOAL_BOUNDS_X.0, GOAL_BOUNDS_X.1);
+            let y = rng.range(0.0, LEVEL_H);
+            if allow_movement(x, y) {
+                assert!(
+                    y > PITCH_BOUNDS_Y.0 && y < PITCH_BOUNDS_Y.1,
+                    "allowed into a goal at {}, {}",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn outside_centre_circle_is_outside() {
+        let mut rng = Rng(4);
+        for _ in 0..CASES {
+            let pos = vec2(
+                rng.range(HALF_LEVEL_W - 200.0, HALF_LEVEL_W + 200.0),
+                rng.range(HALF_LEVEL_H - 200.0, HALF_LEVEL_H + 200.0),
+            );
+            let moved = outside_centre_circle(pos, (rng.next() % 2) as u8);
+            let dist = (moved - vec2(HALF_LEVEL_W, HALF_LEVEL_H)).length();
+            assert!(
+                dist > CENTRE_CIRCLE_RADIUS - 0.01,
+                "{:?} moved to {:?}",
+                pos,
+                moved
+            );
+            if !in_centre_circle(pos) {
+                assert_eq!(moved, pos);
+            }
+        }
+    }
+
+    #[test]
+    fn on_pitch_is_inside_level() {
+        let mut rng = Rng(3);
+        for _ in 0..CASES {
+            let x = rng.range(-200.0, LEVEL_W + 200.0);
+            let y = rng.range(-200.0, LEVEL_H + 200.0);
+            if on_pitch(x, y) {
+                assert!((0.0..LEVEL_W).contains(&x) && (0.0..LEVEL_H).contains(&y));
+            }
+        }
+    }
+
+    #[test]
+    fn ball_physics_stays_in_bounds() {
+        let mut rng = Rng(4);
+        for _ in 0..CASES {
+            let bounds = PITCH_BOUNDS_X;
+            let pos = rng.range(bounds.0, bounds.1);
+            let vel = rng.range(-30.0, 30.0);
+            let drag = rng.range(0.9, 1.0);
+            let (new_pos, new_vel) = ball_physics(pos, vel, bounds, drag);
+            // allowing for rounding when a bounce puts the ball back where it was
+            assert!(
+                new_pos >= bounds.0 - 0.001 && new_pos <= bounds.1 + 0.001,
+                "ball at {} moving {} ended up at {}",
+                pos,
+                vel,
+                new_pos
+            );
+            assert!((new_vel.abs() - vel.abs() * drag).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn deflect_sends_ball_away() {
+        let mut rng = Rng(6);
+        for _ in 0..CASES {
+            let body: Vector = vec2(rng.range(0.0, 100.0), rng.range(0.0, 100.0));
+            let ball = body + vec2(rng.range(-20.0, 20.0), rng.range(-20.0, 20.0));
+            let vel: Vector = vec2(rng.range(-12.0, 12.0), rng.range(-12.0, 12.0));
+            if let Some(new_vel) = deflect(ball, vel, body, 15.0, 0.5) {
+                assert!(
+                    (ball - body).dot(new_vel) >= -0.001,
+                    "ball at {:?} moving {:?} deflected into the player at {:?}",
+                    ball,
+                    vel,
+                    body
+                );
+                assert!((new_vel.length() - vel.length() * 0.5).abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn hit_post_keeps_speed() {
+        let mut rng = Rng(7);
+        let post = vec2(GOAL_BOUNDS_X.0, PITCH_BOUNDS_Y.0);
+        for _ in 0..CASES {
+            let pos = post + vec2(rng.range(-30.0, 30.0), rng.range(-30.0, 30.0));
+            let vel: Vector = vec2(rng.range(-12.0, 12.0), rng.range(-12.0, 12.0));
+            if let Some(new_vel) = hit_post(pos, vel) {
+                assert!(
+                    (new_vel.length() - vel.length() * POST_BOUNCE).abs() < 0.001,
+                    "ball at {:?} moving {:?} came off the post at {:?}",
+                    pos,
+                    vel,
+                    new_vel
+                );
+            }
+        }
+        // straight at the post it comes straight back, and well wide of it nothing happens
+        let back = hit_post(post + vec2(0.0, 10.0), vec2(0.0, -8.0)).unwrap();
+        assert!(back.y > 0.0 && back.x.abs() < 0.001);
+        assert!(hit_post(post + vec2(30.0, 10.0), vec2(0.0, -8.0)).is_none());
+        assert!(hit_post(post + vec2(0.0, 10.0), vec2(0.0, 8.0)).is_none());
+    }
+
+    #[test]
+    fn angle_round_trips() {
+        for a in 0..8 {
+            assert_eq!(Angle::from_vec(Angle::to_vec(Angle(a))).0, a);
+        }
+        let mut rng = Rng(5);
+        for _ in 0..CASES {
+            let v: Vector = vec2(rng.range(-100.0, 100.0), rng.range(-100.0, 100.0));
+            if v.length() < 0.001 {
+                continue;
+            }
+            let angle = Angle::from_vec(v);
+            assert!((0..8).contains(&angle.0));
+            // the nearest of the eight directions is never more than half an octant away
+            let dir = Angle::to_vec(angle);
+            let between = (v.normalize().dot(dir)).clamp(-1.0, 1.0).acos();
+            assert!(
+                between <= PI / 8.0 + 0.001,
+                "{:?} became direction {}",
+                v,
+                angle.0
+            );
+        }
+    }
+}
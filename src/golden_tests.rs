@@ -0,0 +1,224 @@
+// golden-master tests: each case plays a match headless from a fixed seed, with team 1 following
+// an input script if there is one, and compares the scores and everyone's final positions with a
+// fixture in tests/golden; run with UPDATE_GOLDEN=1 to rewrite the fixtures after a deliberate
+// change to the AI or physics
+//
+// scripts have a line per input: how many steps to hold it for, the x and y direction to move in
+// (-1, 0 or 1), and any of s (shoot), w (switch), c (call) and t (tackle) pressed on its first
+// step, or - for none; blank lines and anything after # are ignored
+
+use crate::profiles::Profiles;
+use crate::replays::{MatchRecord, Playback};
+use crate::{
+    get_difficulty, new_match, DifficultyLevel, Game, Position, Settings, Squad, Team, Vector,
+    MAX_SPEED, TEAM_CONTROLS,
+};
+use euclid::vec2;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// name of each case and how many steps to run it for
+const CASES: [(&str, u32); 2] = [("cpu_vs_cpu", 3000), ("attack", 3000)];
+const SEED: u64 = 12345;
+
+// held by any test that uses macroquad's random number generator, since it's shared between the
+// test threads and the cases here have to get the same numbers every time
+pub static RANDOM_LOCK: Mutex<()> = Mutex::new(());
+
+struct Input {
+    steps: u32,
+    dir: Vector,
+    presses: String,
+}
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+}
+
+fn parse_script(text: &str) -> Vec<Input> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(n, line)| {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                return None;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| -> f32 {
+                fields
+                    .get(i)
+                    .and_then(|f| f.parse().ok())
+                    .unwrap_or_else(|| panic!("script line {}: bad field {}", n + 1, i + 1))
+            };
+            Some(Input {
+                steps: number(0) as u32,
+                dir: vec2(number(1), number(2)),
+                presses: fields.get(3).copied().unwrap_or("-").to_owned(),
+            })
+        })
+        .collect()
+}
+
+// a script being followed, and the input it's part way through
+struct Scripted {
+    inputs: std::vec::IntoIter<Input>,
+    current: Option<Input>,
+}
+
+impl Scripted {
+    fn load(name: &str) -> Option<Self> {
+        let text = std::fs::read_to_string(fixture(&format!("{}.script", name))).ok()?;
+        Some(Self {
+            inputs: parse_script(&text).into_iter(),
+            current: None,
+        })
+    }
+
+    // give team 1 its input for the next step
+    fn apply(&mut self, game: &mut Game) {
+        if self.current.as_ref().is_none_or(|input| input.steps == 0) {
+            self.current = self.inputs.next();
+            if let Some(input) = &self.current {
+                game.shoot_pressed[0] = input.presses.contains('s');
+                game.switch_pressed[0] = input.presses.contains('w');
+                game.call_pressed[0] = input.presses.contains('c');
+                game.tackle_pressed[0] = input.presses.contains('t');
+            }
+        }
+        // stand still once the script runs out
+        let dir = self
+            .current
+            .as_ref()
+            .map_or(vec2(0.0, 0.0), |input| input.dir);
+        game.sticks[0] = Some(dir * MAX_SPEED);
+        if let Some(input) = &mut self.current {
+            input.steps -= 1;
+        }
+    }
+}
+
+// play a case and describe how it ended up
+fn run(name: &str, steps: u32) -> String {
+    macroquad::rand::srand(SEED);
+    let mut game = Game::new(get_difficulty(DifficultyLevel::Medium));
+    let mut script = Scripted::load(name);
+    if script.is_some() {
+        game.teams[0].controls = Some(TEAM_CONTROLS[0]);
+    }
+    for _ in 0..steps {
+        if let Some(script) = &mut script {
+            script.apply(&mut game);
+        }
+        game.update();
+    }
+    snapshot(&game)
+}
+
+fn snapshot(game: &Game) -> String {
+    let mut text = String::new();
+    writeln!(
+        text,
+        "score {} {}",
+        game.teams[0].score, game.teams[1].score
+    )
+    .unwrap();
+    for ball in &game.balls {
+        let pos = game.ball_pos(*ball).unwrap();
+        writeln!(text, "ball {:.1} {:.1}", pos.x, pos.y).unwrap();
+    }
+    let mut players: Vec<(u8, u8, Vector)> = game
+        .world
+        .query::<(&Team, &Squad, &Position)>()
+        .iter()
+        .map(|(_, (team, squad, pos))| (team.0, squad.number, pos.0))
+        .collect();
+    players.sort_by_key(|(team, number, _)| (*team, *number));
+    for (team, number, pos) in players {
+        writeln!(text, "player {} {} {:.1} {:.1}", team, number, pos.x, pos.y).unwrap();
+    }
+    text
+}
+
+// the cases share macroquad's random number generator, so they run one after another in a single
+// test rather than in parallel
+#[test]
+fn golden_master() {
+    let _lock = RANDOM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    for (name, steps) in CASES {
+        let actual = run(name, steps);
+        let path = fixture(&format!("{}.expected", name));
+        if update {
+            std::fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1)", path.display(), e));
+        assert_eq!(
+            actual, expected,
+            "{} no longer plays out the same; if that's intended, rerun with UPDATE_GOLDEN=1",
+            name
+        );
+    }
+}
+
+// a match played from the attack script, with a substitution and a change of tactics from the
+// pause menu along the way, and its replay
+fn recorded_match() -> (Game, String) {
+    let mut settings = Settings::new();
+    settings.lighting = None;
+    settings.mutators.power_ups = true;
+    macroquad::rand::srand(SEED);
+    let mut game = new_match(&settings, &Profiles::new());
+    let mut record = MatchRecord::new(SEED, &settings, &game);
+    let mut script = Scripted::load("attack").unwrap();
+    for step in 0..3000 {
+        match step {
+            500 => game.substitute(0, 2, 1).unwrap(),
+            800 => game.teams[0].tactics.pressing = 4,
+            _ => (),
+        }
+        script.apply(&mut game);
+        record.record(&game);
+        game.update();
+    }
+    let text = record.to_text(&game.result(), 1);
+    (game, text)
+}
+
+// the match plays out the same again from its replay
+#[test]
+fn replay_plays_back_the_same() {
+    let _lock = RANDOM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (game, text) = recorded_match();
+    let mut playback = Playback::from_text(&text).unwrap();
+    let mut replayed = playback.new_game();
+    for _ in 0..3000 {
+        playback.apply(&mut replayed);
+        replayed.update();
+    }
+    assert_eq!(snapshot(&replayed), snapshot(&game));
+}
+
+// and seeking back to a step that's already been played, through the keyframe before it, finds
+// the match as it was there
+#[test]
+fn replay_seeks_back_to_the_same() {
+    let _lock = RANDOM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let (_, text) = recorded_match();
+    let mut playback = Playback::from_text(&text).unwrap();
+    let mut replayed = playback.new_game();
+    let mut expected = String::new();
+    for step in 0..2900 {
+        if step == 2000 {
+            expected = snapshot(&replayed);
+        }
+        playback.apply(&mut replayed);
+        replayed.update();
+    }
+    playback.seek(&mut replayed, 2000);
+    assert_eq!(snapshot(&replayed), expected);
+}
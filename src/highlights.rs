@@ -0,0 +1,158 @@
+// highlights: the lead-up to each goal and the first moments of the celebration are kept, from the
+// rewind history (see rewind.rs) as the match goes on, and G saves the last of them as a numbered
+// sequence of PNG images, in a directory of its own under highlights in the config directory (see
+// storage.rs), ready to be turned into an animation for sharing; the images are drawn off-screen
+// one a frame, so the match carries on while they're saved
+//
+// browsers don't let a page write files, so there are no highlights there
+
+use crate::i18n::Strings;
+use crate::render::Renderer;
+use crate::rewind::Rewind;
+use crate::textures::Textures;
+use crate::{
+    draw_score_bar, draw_text_centred, Game, GameEvent, RenderOptions, HALF_WINDOW_WIDTH, HEIGHT,
+    WIDTH,
+};
+use macroquad::prelude::*;
+use std::path::PathBuf;
+
+// four seconds before the goal and a second and a half after
+const LEAD_UP_STEPS: usize = 240;
+const FOLLOW_STEPS: usize = 90;
+// images are saved at 30 a second
+const STEPS_PER_IMAGE: usize = 2;
+
+// the highlight being saved
+struct Export {
+    dir: PathBuf,
+    steps: Vec<Game>,
+    // the step to draw next; the one before it was drawn last frame and is still to be written
+    next: usize,
+    target: RenderTarget,
+}
+
+pub struct Highlights {
+    // the last goal a step at a time, and how many steps after it are still to be added
+    clip: Vec<Game>,
+    following: usize,
+    export: Option<Export>,
+}
+
+impl Highlights {
+    pub fn new() -> Self {
+        Self {
+            clip: Vec::new(),
+            following: 0,
+            export: None,
+        }
+    }
+
+    // call after each step of a match, with the steps before it still in rewind
+    pub fn update(&mut self, game: &Game, rewind: &Rewind) {
+        if game
+            .events
+            .iter()
+            .any(|event| matches!(event, GameEvent::Goal { .. }))
+        {
+            self.clip = rewind.recent(LEAD_UP_STEPS).map(Game::snapshot).collect();
+            self.following = FOLLOW_STEPS;
+        }
+        if self.following > 0 {
+            self.clip.push(game.snapshot());
+            self.following -= 1;
+        }
+    }
+
+    // forget the last goal, for when a match is over and done with
+    pub fn clear(&mut self) {
+        self.clip.clear();
+        self.following = 0;
+    }
+
+    // start saving the last goal, unless there isn't one or it's already being saved
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&mut self) {
+        if self.clip.is_empty() || self.export.is_some() {
+            return;
+        }
+        let name = format!("highlights/goal-{}", crate::history::today());
+        match crate::storage::new_dir(&name) {
+            Ok(dir) => {
+                self.export = Some(Export {
+                    dir,
+                    steps: self
+                        .clip
+                        .iter()
+                        .step_by(STEPS_PER_IMAGE)
+                        .map(Game::snapshot)
+                        .collect(),
+                    next: 0,
+                    target: render_target(WIDTH as u32, HEIGHT as u32),
+                })
+            }
+            Err(e) => warn!("couldn't save highlight: {}", e),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&mut self) {
+        warn!("highlights can't be saved in the browser");
+    }
+
+    // write the image drawn last frame, once macroquad has actually drawn it, and draw the next;
+    // this leaves the camera pointing at the render target, so call it before drawing anything else
+    pub fn export(
+        &mut self,
+        renderer: &mut Renderer,
+        textures: &Textures,
+        options: &RenderOptions,
+    ) {
+        let export = match &mut self.export {
+            Some(export) => export,
+            None => return,
+        };
+        if export.next > 0 {
+            let path = export.dir.join(format!("{:04}.png", export.next));
+            export
+                .target
+                .texture
+                .get_texture_data()
+                .export_png(&path.to_string_lossy());
+        }
+        let game = match export.steps.get(export.next) {
+            Some(game) => game,
+            None => {
+                info!("saved highlight to {}", export.dir.display());
+                export.target.delete();
+                self.export = None;
+                return;
+            }
+        };
+        // what the debug overlays show about the match as it is now would be wrong for the goal
+        let options = RenderOptions {
+            debug_draw: false,
+            ..*options
+        };
+        renderer.draw(game, textures, &game.camera, export.target, &options);
+        set_camera(&Camera2D {
+            render_target: Some(export.target),
+            ..Camera2D::from_display_rect(Rect::new(0.0, 0.0, WIDTH, HEIGHT))
+        });
+        draw_score_bar(game, textures, HALF_WINDOW_WIDTH);
+        export.next += 1;
+    }
+
+    // how far through saving a highlight it's got, while it's at it
+    pub fn draw(&self, strings: &Strings) {
+        if let Some(export) = &self.export {
+            let percent = 100 * export.next / export.steps.len();
+            draw_text_centred(
+                &strings.format("hud.saving_highlight", &[&percent.to_string()]),
+                HALF_WINDOW_WIDTH,
+                HEIGHT - 60.0,
+                16.0,
+            );
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::i18n::Strings;
+use crate::{data, storage};
+use macroquad::prelude::*;
+
+const FILE_NAME: &str = "history.toml";
+// how many of the latest results the history page lists
+const RECENT_RESULTS: usize = 8;
+// the kinds of match the record is kept for: a single player game at each difficulty, and two
+// players against each other; each names itself in the language files under title
+pub const MODES: [&str; 4] = ["easy", "medium", "hard", "two_players"];
+
+pub struct MatchResult {
+    // yyyy-mm-dd
+    pub date: String,
+    // one of MODES
+    pub mode: &'static str,
+    // goals for team 1 and team 2; in single player games the player is team 1
+    pub score: (u8, u8),
+    pub seconds: u32,
+    // passes completed and intercepted by team 1 and by team 2; matches recorded before passes were
+    // counted have none
+    pub passes: [(u32, u32); 2],
+}
+
+// every completed match, oldest first, kept in the config directory with each match as a numbered
+// section of a data file
+pub struct History {
+    results: Vec<MatchResult>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        let text = match storage::read(FILE_NAME) {
+            Some(text) => text,
+            None => return Self::new(),
+        };
+        let doc = match data::parse(&text) {
+            Ok(doc) => doc,
+            Err(e) => {
+                warn!("{}: {}", FILE_NAME, e);
+                return Self::new();
+            }
+        };
+        let mut numbered: Vec<(u32, MatchResult)> = doc
+            .sections
+            .iter()
+            .filter_map(|(name, values)| {
+                let mode = values.get("mode")?.as_str()?;
+                let score = values.get("score")?.as_pair()?;
+                let result = MatchResult {
+                    date: values.get("date")?.as_str()?.to_owned(),
+                    mode: MODES.iter().find(|m| **m == mode)?,
+                    score: (score.0 as u8, score.1 as u8),
+                    seconds: values.get("seconds")?.as_f32()? as u32,
+                    passes: ["passes1", "passes2"].map(|key| {
+                        let pair = values.get(key).and_then(|v| v.as_pair());
+                        pair.map_or((0, 0), |(c, i)| (c as u32, i as u32))
+                    }),
+                };
+                Some((name.parse().ok()?, result))
+            })
+            .collect();
+        numbered.sort_by_key(|(n, _)| *n);
+        Self {
+            results: numbered.into_iter().map(|(_, result)| result).collect(),
+        }
+    }
+
+    fn new() -> Self {
+        Self {
+            results: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, result: MatchResult) {
+        let text = format!(
+            "\n[{}]\ndate = \"{}\"\nmode = \"{}\"\nscore = [{}, {}]\nseconds = {}\n\
+             passes1 = [{}, {}]\npasses2 = [{}, {}]\n",
+            self.results.len() + 1,
+            result.date,
+            result.mode,
+            result.score.0,
+            result.score.1,
+            result.seconds,
+            result.passes[0].0,
+            result.passes[0].1,
+            result.passes[1].0,
+            result.passes[1].1
+        );
+        if let Err(e) = storage::append(FILE_NAME, &text) {
+            warn!("couldn't save match history: {}", e);
+        }
+        self.results.push(result);
+    }
+
+    // matches won by team 1 and by team 2 in a mode
+    pub fn record_for(&self, mode: &str) -> (usize, usize) {
+        let results = self.results.iter().filter(|r| r.mode == mode);
+        let won = results.clone().filter(|r| r.score.0 > r.score.1).count();
+        (won, results.count() - won)
+    }
+
+    // draw in screen coordinates, the most recent first, starting with the baseline at top
+    pub fn draw_recent(&self, strings: &Strings, top: f32) {
+        if self.results.is_empty() {
+            draw_text(strings.get("history.empty"), 120.0, top, 20.0, WHITE);
+        }
+        for (i, result) in self.results.iter().rev().take(RECENT_RESULTS).enumerate() {
+            let text = strings.format(
+                "history.result",
+                &[
+                    &result.date,
+                    strings.get(&format!("title.{}", result.mode)),
+                    &result.score.0.to_string(),
+                    &result.score.1.to_string(),
+                    &format!("{}:{:02}", result.seconds / 60, result.seconds % 60),
+                ],
+            );
+            draw_text(&text, 120.0, top + i as f32 * 22.0, 20.0, WHITE);
+        }
+    }
+}
+
+// today's date in UTC as yyyy-mm-dd
+pub fn today() -> String {
+    let days = (macroquad::miniquad::date::now() / 86400.0).floor() as i64;
+    // convert days since 1970-01-01 to a date in the proleptic Gregorian calendar, counting years
+    // from March so the leap day comes at the end
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,81 @@
+// development aid, enabled with the hot-reload feature: polls the images directory and swaps in
+// any sprite image that has changed on disk, so art can be tweaked without restarting the game, and
+// rereads the balance file when it changes
+
+use crate::assets;
+use crate::balance;
+use crate::textures::{Sprite, Textures};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::time::SystemTime;
+
+const POLL_INTERVAL: f64 = 0.5;
+
+// every png file finishes with this chunk, so its absence means the file is still being written
+const PNG_END: [u8; 8] = [0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82];
+
+pub struct Watcher {
+    modified: HashMap<Sprite, SystemTime>,
+    balance_modified: Option<SystemTime>,
+    last_poll: f64,
+}
+
+impl Watcher {
+    pub fn new(textures: &Textures) -> Self {
+        let modified = textures
+            .sprites()
+            .into_iter()
+            .filter_map(|sprite| Some((sprite, modified_time(&textures.image_path(sprite))?)))
+            .collect();
+        Self {
+            modified,
+            balance_modified: modified_time(&assets::on_disk(balance::PATH)),
+            last_poll: get_time(),
+        }
+    }
+
+    // true if the balance file was reread, for the match being played to take up
+    pub fn update(&mut self, textures: &mut Textures, filter: FilterMode) -> bool {
+        if get_time() - self.last_poll < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = get_time();
+        for sprite in textures.sprites() {
+            let path = textures.image_path(sprite);
+            let time = match modified_time(&path) {
+                Some(time) => time,
+                None => continue,
+            };
+            if self.modified.get(&sprite) == Some(&time) {
+                continue;
+            }
+            match fs::read(&path) {
+                Ok(bytes) if bytes.ends_with(&PNG_END) => {
+                    let image = Image::from_file_with_format(&bytes, None);
+                    textures.replace(sprite, &image, filter);
+                    self.modified.insert(sprite, time);
+                    info!("reloaded {}", path);
+                }
+                // try again next time
+                _ => (),
+            }
+        }
+        self.reload_balance()
+    }
+
+    fn reload_balance(&mut self) -> bool {
+        let time = modified_time(&assets::on_disk(balance::PATH));
+        if time.is_none() || time == self.balance_modified {
+            return false;
+        }
+        self.balance_modified = time;
+        balance::load();
+        info!("reloaded {}", balance::PATH);
+        true
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
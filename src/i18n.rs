@@ -0,0 +1,86 @@
+use crate::{assets, data};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+// each language's code, which names its file in data/lang, and its name for itself
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "ENGLISH"), ("es", "ESPANOL")];
+// the language of the text drawn into the menu and banner images
+const IMAGE_LANGUAGE: usize = 0;
+
+// every piece of text shown to the player, in the chosen language
+pub struct Strings {
+    language: usize,
+    table: HashMap<String, String>,
+    // the English text, for anything a translation leaves out
+    fallback: HashMap<String, String>,
+}
+
+impl Strings {
+    pub async fn load(language: usize) -> Self {
+        let fallback = read(
+            include_str!("../data/lang/en.toml"),
+            "built-in English text",
+        );
+        let path = format!("data/lang/{}.toml", LANGUAGES[language].0);
+        let table = match assets::load(&path).await {
+            Ok(bytes) => read(&String::from_utf8_lossy(&bytes), &path),
+            Err(_) => {
+                warn!("couldn't load {}", path);
+                HashMap::new()
+            }
+        };
+        Self {
+            language,
+            table,
+            fallback,
+        }
+    }
+
+    pub fn language(&self) -> usize {
+        self.language
+    }
+
+    // true if text baked into the images is in this language, so they can be used as they are
+    pub fn matches_images(&self) -> bool {
+        self.language == IMAGE_LANGUAGE
+    }
+
+    // keys are "section.name"; a missing key is shown as is so it's easy to spot
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.table
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    // the text for key with each {} replaced by the next of args
+    pub fn format(&self, key: &str, args: &[&str]) -> String {
+        let mut pieces = self.get(key).split("{}");
+        let mut text = pieces.next().unwrap_or_default().to_owned();
+        for (i, piece) in pieces.enumerate() {
+            text.push_str(args.get(i).copied().unwrap_or_default());
+            text.push_str(piece);
+        }
+        text
+    }
+}
+
+// flatten a language file into "section.name" keys
+fn read(text: &str, path: &str) -> HashMap<String, String> {
+    let doc = match data::parse(text) {
+        Ok(doc) => doc,
+        Err(e) => {
+            warn!("{}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+    let mut table = HashMap::new();
+    for (section, values) in doc.sections {
+        for (name, value) in values {
+            if let Some(s) = value.as_str() {
+                table.insert(format!("{}.{}", section, name), s.to_owned());
+            }
+        }
+    }
+    table
+}
@@ -0,0 +1,192 @@
+// where a human team's instructions come from: the match itself never reads the keyboard, it's
+// handed a FrameInput for each human team and holds on to it until its next step, so anything that
+// can produce FrameInputs can play
+//
+//   --record-inputs PATH   write what each team asked for at every step of the match to PATH
+//   --play-inputs PATH     in a headless match, play the human teams from a file written by
+//                          --record-inputs instead of leaving them standing still
+//
+// a recording only plays out the same way if the match starts from the same --seed
+//
+// there's no gamepad source because macroquad 0.3 can't read gamepads. network play (see net.rs)
+// isn't a source either: both copies of the match have to act on exactly the same input at the
+// same step, so once each team's input has been applied as usual, NetMatch overwrites both with
+// Game::replace_input, the local one delayed to match the remote one
+
+use crate::{Controls, Game, Vector, MAX_SPEED};
+use euclid::vec2;
+use macroquad::prelude::*;
+use std::fmt::Write;
+
+// how often a recording is saved, in steps
+const SAVE_FRAMES: usize = 60;
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct FrameInput {
+    pub movement: Vector,
+    pub shoot: bool,
+    pub switch: bool,
+    pub call: bool,
+    pub tackle: bool,
+}
+
+impl FrameInput {
+    pub fn none() -> Self {
+        Self {
+            movement: Vector::zero(),
+            shoot: false,
+            switch: false,
+            call: false,
+            tackle: false,
+        }
+    }
+}
+
+pub trait TeamInput {
+    // called once a frame for a human team, or once a step when there's no window
+    fn read(&mut self, game: &Game, team: usize) -> FrameInput;
+}
+
+// the keys a team was given, and the mouse buttons if they're aiming with the mouse
+pub struct Keyboard(pub Controls);
+
+impl TeamInput for Keyboard {
+    fn read(&mut self, _game: &Game, _team: usize) -> FrameInput {
+        let controls = &self.0;
+        let axis = |minus, plus| {
+            if is_key_down(minus) {
+                -1.
+            } else if is_key_down(plus) {
+                1.
+            } else {
+                0.
+            }
+        };
+        let mut input = FrameInput {
+            movement: vec2(
+                axis(controls.left, controls.right),
+                axis(controls.up, controls.down),
+            ) * MAX_SPEED,
+            shoot: is_key_pressed(controls.shoot),
+            switch: is_key_pressed(controls.switch),
+            call: is_key_pressed(controls.call),
+            tackle: is_key_pressed(controls.tackle),
+        };
+        if controls.mouse_aim {
+            input.shoot |= is_mouse_button_pressed(MouseButton::Left);
+            input.switch |= is_mouse_button_pressed(MouseButton::Right);
+        }
+        input
+    }
+}
+
+// a team's half of a recording, a step at a time; once it runs out the team stands still
+pub struct Replay {
+    steps: std::vec::IntoIter<FrameInput>,
+}
+
+impl Replay {
+    // both teams' halves of the recording at path
+    pub fn load(path: &str) -> Option<[Replay; 2]> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("couldn't read inputs from {}: {}", path, e);
+                return None;
+            }
+        };
+        let mut teams = [Vec::new(), Vec::new()];
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            for (t, steps) in teams.iter_mut().enumerate() {
+                let input = fields.get(t * 3..t * 3 + 3).and_then(parse_input);
+                steps.push(input.unwrap_or_else(FrameInput::none));
+            }
+        }
+        Some(teams.map(|steps| Replay {
+            steps: steps.into_iter(),
+        }))
+    }
+}
+
+impl TeamInput for Replay {
+    fn read(&mut self, _game: &Game, _team: usize) -> FrameInput {
+        self.steps.next().unwrap_or_else(FrameInput::none)
+    }
+}
+
+// "x y presses", where presses has s, w, c and t for shoot, switch, call and tackle, or is - for
+// none
+pub fn parse_input(fields: &[&str]) -> Option<FrameInput> {
+    let presses = fields[2];
+    Some(FrameInput {
+        movement: vec2(fields[0].parse().ok()?, fields[1].parse().ok()?),
+        shoot: presses.contains('s'),
+        switch: presses.contains('w'),
+        call: presses.contains('c'),
+        tackle: presses.contains('t'),
+    })
+}
+
+pub fn write_input(text: &mut String, input: FrameInput) {
+    let presses: String = [
+        (input.shoot, 's'),
+        (input.switch, 'w'),
+        (input.call, 'c'),
+        (input.tackle, 't'),
+    ]
+    .iter()
+    .filter_map(|(pressed, c)| pressed.then_some(*c))
+    .collect();
+    let presses = if presses.is_empty() { "-" } else { &presses };
+    write!(
+        text,
+        "{} {} {}",
+        input.movement.x, input.movement.y, presses
+    )
+    .unwrap();
+}
+
+// a line for each step with both teams' input, in the format Replay reads
+pub struct Recording {
+    path: String,
+    text: String,
+    steps: usize,
+}
+
+impl Recording {
+    // None unless --record-inputs was given
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let path = args
+            .iter()
+            .position(|arg| arg == "--record-inputs")
+            .and_then(|i| args.get(i + 1))?;
+        Some(Self {
+            path: path.clone(),
+            text: String::new(),
+            steps: 0,
+        })
+    }
+
+    // call just before each step, once the input for it has been applied
+    pub fn record(&mut self, game: &Game) {
+        for t in 0..=1 {
+            if t > 0 {
+                self.text.push(' ');
+            }
+            write_input(&mut self.text, game.input(t));
+        }
+        self.text.push('\n');
+        self.steps += 1;
+        if self.steps.is_multiple_of(SAVE_FRAMES) {
+            self.save();
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = std::fs::write(&self.path, &self.text) {
+            warn!("couldn't write inputs to {}: {}", self.path, e);
+        }
+    }
+}
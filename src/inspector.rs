@@ -0,0 +1,122 @@
+// part of debug mode: click on a player to see what the AI is thinking for them, kept up to date
+// as the match goes on
+
+use crate::{Attributes, Game, Lead, Mark, Position, Squad, Target, Team, Timer};
+use hecs::Entity;
+use macroquad::prelude::*;
+
+// how close to a player a click has to be to pick them
+const PICK_RADIUS: f32 = 30.0;
+const PANEL_X: f32 = 560.0;
+const PANEL_Y: f32 = 40.0;
+const PANEL_WIDTH: f32 = 230.0;
+const LINE_HEIGHT: f32 = 18.0;
+
+pub struct Inspector {
+    selected: Option<Entity>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self { selected: None }
+    }
+
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    // call while debug mode is on, after the game has been told where the cursor is
+    pub fn update(&mut self, game: &Game) {
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        // clicking away from everyone clears the selection
+        self.selected = game
+            .world
+            .query::<(&Position, &Team)>()
+            .iter()
+            .map(|(id, (pos, _))| (id, (pos.0 - game.cursor).length()))
+            .filter(|(_, dist)| *dist <= PICK_RADIUS)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(id, _)| id);
+    }
+
+    // a line for each thing worth knowing about the selected player, or None if there isn't one
+    fn describe(&self, game: &Game) -> Option<Vec<String>> {
+        let id = self.selected?;
+        let world = &game.world;
+        let team = world.get::<Team>(id).ok()?;
+        let squad = world.get::<Squad>(id).ok()?;
+        let timer = world.get::<Timer>(id).ok()?;
+        let target = world.get::<Target>(id).ok()?;
+        let lead = world.get::<Lead>(id).ok()?;
+        let mark = world.get::<Mark>(id).ok()?;
+        let attributes = world.get::<Attributes>(id).ok()?;
+        let mut lines = vec![
+            format!("entity {:?}", id),
+            format!("#{} {}, team {}", squad.number, squad.name, team.0),
+            format!(
+                "pace {} kick {} tackle {} control {} strength {}",
+                attributes.pace,
+                attributes.kick_power,
+                attributes.tackling,
+                attributes.control,
+                attributes.strength
+            ),
+            format!("behaviour: {}", target.behaviour.name()),
+            format!(
+                "target: {:.0}, {:.0} at {:.2}",
+                target.pos.x, target.pos.y, target.speed
+            ),
+            format!("timer: {}", timer.0),
+            match *lead {
+                Lead(Some(dist), Some(n)) => format!("lead {} at {:.0}", n + 1, dist),
+                Lead(None, Some(n)) => format!("lead {}, holding back", n + 1),
+                _ => "not a lead".to_owned(),
+            },
+            match *mark {
+                Mark::Goal(_) => "marking the goal".to_owned(),
+                Mark::Player(other) => format!("marking {}", game.player_name(other)),
+            },
+        ];
+        if game.has_ball(id) {
+            lines.push("has the ball".to_owned());
+        }
+        Some(lines)
+    }
+
+    // draw in level coordinates, a ring around the selected player
+    pub fn draw_marker(&self, game: &Game) {
+        let pos = self
+            .selected
+            .and_then(|id| game.world.get::<Position>(id).ok().map(|p| p.0));
+        if let Some(pos) = pos {
+            draw_circle_lines(pos.x, pos.y, PICK_RADIUS, 2.0, YELLOW);
+        }
+    }
+
+    // draw in screen coordinates
+    pub fn draw_panel(&self, game: &Game) {
+        let lines = match self.describe(game) {
+            Some(lines) => lines,
+            None => return,
+        };
+        let height = lines.len() as f32 * LINE_HEIGHT + 10.0;
+        draw_rectangle(
+            PANEL_X,
+            PANEL_Y,
+            PANEL_WIDTH,
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                PANEL_X + 8.0,
+                PANEL_Y + (i + 1) as f32 * LINE_HEIGHT,
+                16.0,
+                WHITE,
+            );
+        }
+    }
+}
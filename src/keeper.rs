@@ -0,0 +1,167 @@
+// goalkeepers, on difficulties that have them: a computer team's keeper is whichever of its
+// players is nearest its own goal, and when they gather the ball near the goal they hold on to it
+// for a moment, with nobody allowed to take it off them and the other team backing off, and then
+// distribute it: a short throw to a defender with nobody near them, or a long kick upfield if the
+// other team is pressing or no defender is free
+
+use crate::error::{GameError, WorldExt};
+use crate::{Game, Position, Team, Vector, HALF_LEVEL_H, HALF_LEVEL_W, LEVEL_H, PASS_RANGE};
+use euclid::vec2;
+use hecs::Entity;
+
+// how close to the middle of their goal a keeper has to be to gather the ball rather than just
+// pick it up
+const KEEPER_AREA: f32 = 180.0;
+// steps the keeper holds the ball for before distributing it
+const HOLD_FRAMES: u32 = 45;
+// opponents keep this far away from a keeper holding the ball, so that they can get rid of it
+const CLEARANCE: f32 = 150.0;
+// a defender counts as free with no opponent this close to them
+const UNMARKED_DISTANCE: f32 = 80.0;
+// the other team is pressing if this many of them are within PRESSING_RADIUS of the keeper
+const PRESSING_PLAYERS: usize = 3;
+const PRESSING_RADIUS: f32 = 200.0;
+// how hard a throw goes, compared with a kick
+const THROW_STRENGTH: f32 = 0.6;
+
+// a keeper holding the ball
+#[derive(Copy, Clone)]
+pub struct Distribution {
+    keeper: Entity,
+    steps_left: u32,
+}
+
+fn own_goal(team: u8) -> Vector {
+    vec2(HALF_LEVEL_W, (1 - team) as f32 * LEVEL_H)
+}
+
+impl Game {
+    // the player keeping goal for a team, when the difficulty has goalkeepers
+    pub fn goalie(&self, team: u8) -> Option<Entity> {
+        if !self.difficulty.goalie_enabled {
+            return None;
+        }
+        self.grid
+            .nearest(own_goal(team), |p| p.team == team)
+            .map(|p| p.id)
+    }
+
+    // true while a keeper is holding on to the ball
+    pub fn holding(&self, id: Entity) -> bool {
+        self.distribution.is_some_and(|d| d.keeper == id)
+    }
+
+    // where a computer player backs off to when they're too close to the other team's keeper
+    // while they're holding the ball
+    pub fn back_off(&self, team: u8, pos: Vector) -> Result<Option<Vector>, GameError> {
+        let keeper = match self.distribution {
+            Some(d) => d.keeper,
+            None => return Ok(None),
+        };
+        if self.world.component::<Team>(keeper)?.0 == team {
+            return Ok(None);
+        }
+        let keeper_pos = self.world.component::<Position>(keeper)?.0;
+        let offset = pos - keeper_pos;
+        if offset.length() >= CLEARANCE {
+            return Ok(None);
+        }
+        // straight away from them, or upfield if standing right on top of them
+        let away = offset
+            .try_normalize()
+            .unwrap_or_else(|| vec2(0.0, own_goal(team).y - keeper_pos.y).normalize());
+        Ok(Some(keeper_pos + away * CLEARANCE))
+    }
+
+    // called when someone picks up a loose ball; if it's a computer team's keeper near their goal,
+    // they gather it and start holding on to it, and this is true
+    pub fn gather(&mut self, id: Entity) -> Result<bool, GameError> {
+        let team = self.world.component::<Team>(id)?.0;
+        let pos = self.world.component::<Position>(id)?.0;
+        if self.teams[team as usize].human()
+            || self.goalie(team) != Some(id)
+            || (pos - own_goal(team)).length() > KEEPER_AREA
+        {
+            return Ok(false);
+        }
+        self.distribution = Some(Distribution {
+            keeper: id,
+            steps_left: HOLD_FRAMES,
+        });
+        Ok(true)
+    }
+
+    // counts down the keeper's hold on the ball; true once it's time to let go of it
+    pub fn ready_to_distribute(&mut self) -> bool {
+        match &mut self.distribution {
+            Some(distribution) if distribution.steps_left > 0 => {
+                distribution.steps_left -= 1;
+                false
+            }
+            _ => {
+                self.distribution = None;
+                true
+            }
+        }
+    }
+
+    // who a keeper should give the ball to, and how hard: a throw to a free defender, or failing
+    // that a kick to whoever's furthest forward
+    pub fn distribution_target(&self, keeper: Entity) -> Result<Option<(Entity, f32)>, GameError> {
+        let team = self.world.component::<Team>(keeper)?.0;
+        let pos = self.world.component::<Position>(keeper)?.0;
+        Ok(match self.free_defender(keeper, team, pos) {
+            Some(defender) => Some((defender, THROW_STRENGTH)),
+            None => self
+                .furthest_forward(keeper, team)
+                .map(|forward| (forward, 1.0)),
+        })
+    }
+
+    // the teammate in the keeper's own half with the most room, if the other team isn't pressing
+    // and there's one with nobody near them who a throw would reach safely
+    fn free_defender(&self, keeper: Entity, team: u8, pos: Vector) -> Option<Entity> {
+        let opponents: Vec<Vector> = self
+            .world
+            .query::<(&Team, &Position)>()
+            .iter()
+            .filter(|(_, (t, _))| t.0 != team)
+            .map(|(_, (_, p))| p.0)
+            .collect();
+        let pressing = opponents
+            .iter()
+            .filter(|p| (**p - pos).length() < PRESSING_RADIUS)
+            .count();
+        if pressing >= PRESSING_PLAYERS {
+            return None;
+        }
+        let room = |p: Vector| {
+            opponents
+                .iter()
+                .map(|o| (*o - p).length())
+                .fold(f32::MAX, f32::min)
+        };
+        let own_half = |p: Vector| (p.y - HALF_LEVEL_H) * (own_goal(team).y - HALF_LEVEL_H) > 0.0;
+        self.world
+            .query::<(&Team, &Position)>()
+            .iter()
+            .filter(|(id, (t, p))| {
+                *id != keeper
+                    && t.0 == team
+                    && own_half(p.0)
+                    && (p.0 - pos).length() < PASS_RANGE * THROW_STRENGTH
+                    && room(p.0) > UNMARKED_DISTANCE
+                    && !self.pass_cut_out(pos, p.0, team)
+            })
+            .max_by(|a, b| room((a.1).1 .0).total_cmp(&room((b.1).1 .0)))
+            .map(|(id, _)| id)
+    }
+
+    // the teammate nearest the other team's goal, to launch a long kick towards
+    fn furthest_forward(&self, keeper: Entity, team: u8) -> Option<Entity> {
+        let goal = own_goal(1 - team);
+        self.grid
+            .nearest(goal, |p| p.team == team && p.id != keeper)
+            .map(|p| p.id)
+    }
+}
@@ -0,0 +1,82 @@
+// knocking the ball on: a human player on the ball who double-taps a direction pushes it a few
+// yards ahead of them that way and sprints after it, going faster than they could dribble but
+// leaving it loose for anyone to nip in and take; for a moment after it leaves their foot they
+// can't touch it, so that it gets ahead of them, and then they're sure to bring it under control
+// if they get to it before KNOCK_ON_FRAMES are up
+
+use crate::geometry::Angle;
+use crate::{Game, Vector};
+use hecs::Entity;
+
+// how soon after letting go of a direction pushing it again counts as a double tap
+const DOUBLE_TAP_FRAMES: u8 = 15;
+// how fast the ball leaves the player's foot, which is no faster than they can always control
+pub const KNOCK_ON_SPEED: f32 = 6.0;
+// steps the player can't touch the ball for, and then how long they have to catch up with it
+pub const KNOCK_ON_DELAY: i8 = 8;
+const KNOCK_ON_FRAMES: u32 = 60;
+// how much faster than usual the player runs while they're chasing the ball
+pub const KNOCK_ON_BOOST: f32 = 1.15;
+
+// a human team's stick over the last few steps, for spotting a double tap
+#[derive(Copy, Clone, Default)]
+pub struct Taps {
+    // the direction it's being pushed now
+    held: Option<i32>,
+    // the direction it was last let go of, and the steps left in which pushing it that way again
+    // is a double tap
+    released: Option<(i32, u8)>,
+    // whether it was double-tapped this step
+    pub double_tapped: bool,
+}
+
+impl Taps {
+    // call once a step with the way the team wants to move
+    pub fn update(&mut self, stick: Vector) {
+        let dir = (stick.length() > 0.0).then(|| Angle::from_vec(stick).0);
+        self.double_tapped = false;
+        match (self.held, dir) {
+            (Some(old), None) => self.released = Some((old, DOUBLE_TAP_FRAMES)),
+            (None, Some(new)) => {
+                self.double_tapped = self.released.is_some_and(|(old, _)| old == new);
+                self.released = None;
+            }
+            _ => (),
+        }
+        self.released = self
+            .released
+            .and_then(|(old, steps)| steps.checked_sub(1).map(|steps| (old, steps)));
+        self.held = dir;
+    }
+}
+
+// a player chasing the ball they've knocked on
+#[derive(Copy, Clone)]
+pub struct KnockOn {
+    pub player: Entity,
+    steps_left: u32,
+}
+
+impl KnockOn {
+    pub fn new(player: Entity) -> Self {
+        Self {
+            player,
+            steps_left: KNOCK_ON_FRAMES,
+        }
+    }
+
+    // counts down the time they have to get to the ball; false once it's up
+    pub fn count_down(&mut self) -> bool {
+        self.steps_left = self.steps_left.saturating_sub(1);
+        self.steps_left > 0
+    }
+}
+
+impl Game {
+    // true while a player is chasing the ball they've knocked on
+    pub fn knocking_on(&self, id: Entity) -> bool {
+        self.teams
+            .iter()
+            .any(|team| team.knock_on.is_some_and(|k| k.player == id))
+    }
+}
@@ -0,0 +1,262 @@
+// options for starting the game from a script or a tester's shortcut rather than through the menus:
+//
+//   --players 0|1|2              go straight into a match with this many human teams (0 leaves
+//                                both to the computer)
+//   --difficulty easy|medium|hard  how good the computer team is in that match
+//   --seed N                     make the players' starting positions the same every run
+//   --headless                   play the match without opening a window and print the result
+//   --frames N                   stop a headless match after N steps if it hasn't finished
+//   --assets PATH                load images, sounds and data from PATH (see assets::find_root)
+//   --no-assets                  draw everything with placeholder graphics
+//
+// see also input.rs, replays.rs, checksum.rs and bots.rs for options for recording and checking
+// matches, sim_log.rs for logging what the AI decides, net.rs for playing one over the network and
+// broadcast.rs for watching one
+
+use crate::broadcast::Broadcast;
+use crate::checksum::Checksums;
+use crate::input::{Recording, Replay, TeamInput};
+use crate::net::{self, Connection, Host, Message, NetMatch, Rejoin};
+use crate::profiles::Profiles;
+use crate::replays::{self, MatchRecord};
+use crate::roster::Roster;
+use crate::sim_log::SimLog;
+use crate::{
+    assets, get_difficulty, new_match, new_match_with_squads, seed_match, DifficultyLevel, Game,
+    NumPlayers, Settings, DIFFICULTY_OPTIONS,
+};
+use std::time::Duration;
+
+// how long a headless match waits between looking for news from the other copy of the game
+const NET_WAIT: Duration = Duration::from_millis(1);
+
+pub struct LaunchOptions {
+    pub players: Option<u8>,
+    pub difficulty: Option<DifficultyLevel>,
+    pub seed: Option<u64>,
+    pub headless: bool,
+    pub frames: Option<u32>,
+    pub use_images: bool,
+    pub play_inputs: Option<String>,
+    pub replay: Option<String>,
+    pub tournament: Option<String>,
+    pub host: bool,
+    pub join: Option<String>,
+    pub spectate: Option<String>,
+    pub sim_log: SimLog,
+}
+
+impl LaunchOptions {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |name: &str| {
+            args.iter()
+                .position(|arg| arg == name)
+                .and_then(|i| args.get(i + 1))
+                .map(String::as_str)
+        };
+        let players = value("--players").and_then(|n| match n.parse() {
+            Ok(n) if n <= 2 => Some(n),
+            _ => {
+                eprintln!("--players: expected 0, 1 or 2, not {}", n);
+                None
+            }
+        });
+        let difficulty = value("--difficulty").and_then(|name| {
+            let level = DIFFICULTY_OPTIONS
+                .iter()
+                .find(|level| level.key() == name)
+                .copied();
+            if level.is_none() {
+                eprintln!("--difficulty: expected easy, medium or hard, not {}", name);
+            }
+            level
+        });
+        assets::find_root(value("--assets"));
+        Self {
+            players,
+            difficulty,
+            seed: value("--seed").and_then(|s| s.parse().ok()),
+            headless: args.iter().any(|arg| arg == "--headless"),
+            frames: value("--frames").and_then(|n| n.parse().ok()),
+            use_images: !args.iter().any(|arg| arg == "--no-assets"),
+            play_inputs: value("--play-inputs").map(str::to_owned),
+            replay: value("--replay").map(str::to_owned),
+            tournament: value("--tournament").map(str::to_owned),
+            host: args.iter().any(|arg| arg == "--host"),
+            join: value("--join").map(str::to_owned),
+            spectate: value("--spectate").map(str::to_owned),
+            // with no levels given, everything is logged at debug
+            sim_log: if args.iter().any(|arg| arg == "--sim-log") {
+                SimLog::parse(value("--sim-log").unwrap_or("debug"))
+            } else {
+                SimLog::off()
+            },
+        }
+    }
+
+    // the match asked for on the command line, if any
+    pub fn new_game(&self) -> Option<Game> {
+        if self.players.is_none() && self.difficulty.is_none() {
+            return None;
+        }
+        let mut settings = Settings::new();
+        if self.players == Some(2) {
+            settings.num_players = NumPlayers::Two;
+        }
+        if let Some(level) = self.difficulty {
+            settings.difficulty_level = level;
+        }
+        // nobody playing from the command line has a profile
+        let mut game = new_match(&settings, &Profiles::new());
+        if self.players == Some(0) {
+            game.teams[0].controls = None;
+        }
+        Some(game)
+    }
+
+    // with --host or --join, the match agreed with another copy of the game, and the connection to
+    // it; this waits for the other copy, and gives up on an error
+    fn network_match(&self) -> Option<(Game, NetMatch)> {
+        let fail = |e: String| -> ! {
+            eprintln!("network match: {}", e);
+            std::process::exit(1)
+        };
+        if self.host {
+            let mut host = Host::new("HEADLESS").unwrap_or_else(|e| fail(e));
+            let mut connection = loop {
+                match host.accept() {
+                    Ok(Some(connection)) => break connection,
+                    Ok(None) => std::thread::sleep(NET_WAIT),
+                    Err(e) => fail(e),
+                }
+            };
+            let mut settings = Settings::new();
+            settings.num_players = NumPlayers::Two;
+            let seed = seed_match();
+            let game = new_match_with_squads(&settings, [0, 1].map(Roster::default_for));
+            connection.send(&Message::Start(net::start_text(seed, &settings, &game)));
+            let record = MatchRecord::new(seed, &settings, &game);
+            let rejoin = Rejoin::Host("HEADLESS".to_owned());
+            Some((game, NetMatch::new(connection, 0, rejoin, record)))
+        } else if let Some(address) = &self.join {
+            let mut connection = Connection::connect(address).unwrap_or_else(|e| fail(e));
+            loop {
+                for message in connection.receive().unwrap_or_else(|e| fail(e)) {
+                    match message {
+                        Message::Start(text) => {
+                            let (seed, settings, rosters) =
+                                net::read_start(&text).unwrap_or_else(|e| fail(e));
+                            macroquad::rand::srand(seed);
+                            let game = new_match_with_squads(&settings, rosters);
+                            let record = MatchRecord::new(seed, &settings, &game);
+                            let rejoin = Rejoin::Join(address.clone());
+                            return Some((game, NetMatch::new(connection, 1, rejoin, record)));
+                        }
+                        // a match already under way, which this is rejoining
+                        Message::Resume(text) => {
+                            return Some(
+                                NetMatch::rejoined(
+                                    connection,
+                                    &text,
+                                    Rejoin::Join(address.clone()),
+                                )
+                                .unwrap_or_else(|e| fail(e)),
+                            );
+                        }
+                        _ => (),
+                    }
+                }
+                std::thread::sleep(NET_WAIT);
+            }
+        } else {
+            None
+        }
+    }
+}
+
+// play a match to the end, or for --frames steps, as fast as possible; the exit code is 1 if
+// --checksum-compare found a desync
+pub fn run_headless(options: &LaunchOptions) {
+    let mut playback = None;
+    let mut net = None;
+    let mut game = match options.replay.as_deref().and_then(replays::open_file) {
+        Some((game, replay)) => {
+            playback = Some(replay);
+            game
+        }
+        None => match options.network_match() {
+            Some((game, net_match)) => {
+                net = Some(net_match);
+                game
+            }
+            None => options.new_game().unwrap_or_else(|| {
+                // nobody can press anything, so there's no point having human teams unless asked
+                // for
+                let mut game = Game::new(get_difficulty(DifficultyLevel::Medium));
+                game.teams[0].controls = None;
+                game
+            }),
+        },
+    };
+    let mut checksums = Checksums::from_args();
+    let mut recording = Recording::from_args();
+    let mut broadcast = Broadcast::from_args();
+    let mut inputs: Option<[Replay; 2]> = options.play_inputs.as_deref().and_then(Replay::load);
+    let mut steps = 0;
+    while options.frames.is_none_or(|frames| steps < frames) {
+        let game_over = game.teams[0].score.max(game.teams[1].score) == game.goals_to_win
+            && game.score_timer == 1;
+        if let Some(playback) = &mut playback {
+            playback.apply(&mut game);
+        }
+        if let Some(inputs) = &mut inputs {
+            for (t, source) in inputs.iter_mut().enumerate() {
+                // in a network match, the other team is the other copy's to play
+                let other = net.as_ref().is_some_and(|net| net.local_team != t);
+                if game.teams[t].human() && !other {
+                    let input = source.read(&game, t);
+                    game.apply_input(t, input);
+                }
+            }
+        }
+        if let Some(net) = &mut net {
+            while !net.ready() {
+                if let Err(e) = net.receive(&mut game) {
+                    eprintln!("network match: {}", e);
+                    std::process::exit(1);
+                }
+                std::thread::sleep(NET_WAIT);
+            }
+            net.apply(&mut game);
+        }
+        if let Some(recording) = &mut recording {
+            recording.record(&game);
+        }
+        game.update();
+        steps += 1;
+        if let Some(broadcast) = &mut broadcast {
+            broadcast.update(&game);
+            broadcast.keep_time();
+        }
+        if let Some(checksums) = &mut checksums {
+            checksums.update(&game);
+        }
+        if game_over {
+            break;
+        }
+    }
+    if let Some(recording) = &recording {
+        recording.save();
+    }
+    if let Some(net) = net {
+        net.close();
+    }
+    println!(
+        "{}-{} after {} steps",
+        game.teams[0].score, game.teams[1].score, steps
+    );
+    if checksums.is_some_and(|c| c.desync.is_some()) {
+        std::process::exit(1);
+    }
+}
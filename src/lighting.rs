@@ -0,0 +1,119 @@
+use crate::{LEVEL_H, LEVEL_W, PITCH_BOUNDS_X, PITCH_BOUNDS_Y};
+use macroquad::prelude::*;
+
+// the light map covers the whole level at a low resolution, relying on filtering to smooth it out
+const LIGHT_MAP_SCALE: f32 = 10.0;
+// floodlights are just outside each corner of the pitch
+const FLOODLIGHT_OFFSET: f32 = 40.0;
+const FLOODLIGHT_RANGE: f32 = 900.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Lighting {
+    Day,
+    Evening,
+    Night,
+}
+
+impl Lighting {
+    pub fn random() -> Self {
+        match rand::gen_range(0, 3) {
+            0 => Self::Day,
+            1 => Self::Evening,
+            _ => Self::Night,
+        }
+    }
+
+    // names the lighting in the language files
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Evening => "evening",
+            Self::Night => "night",
+        }
+    }
+
+    // colour of the sky's tint, with alpha giving its strength away from the floodlights
+    fn ambient(self) -> Color {
+        match self {
+            Self::Day => BLANK,
+            Self::Evening => Color::new(0.35, 0.15, 0.1, 0.35),
+            Self::Night => Color::new(0.02, 0.03, 0.12, 0.7),
+        }
+    }
+}
+
+// a tint layer for each lighting condition, drawn over the level to darken it except where the
+// floodlights reach
+pub struct LightMaps {
+    evening: Texture2D,
+    night: Texture2D,
+}
+
+impl LightMaps {
+    pub fn new() -> Self {
+        Self {
+            evening: build_light_map(Lighting::Evening),
+            night: build_light_map(Lighting::Night),
+        }
+    }
+
+    // draw in level coordinates, after the sprites
+    pub fn draw(&self, lighting: Lighting) {
+        let texture = match lighting {
+            Lighting::Day => return,
+            Lighting::Evening => self.evening,
+            Lighting::Night => self.night,
+        };
+        draw_texture_ex(
+            texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(LEVEL_W, LEVEL_H)),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+fn build_light_map(lighting: Lighting) -> Texture2D {
+    let ambient = lighting.ambient();
+    let (w, h) = (
+        (LEVEL_W / LIGHT_MAP_SCALE) as u16,
+        (LEVEL_H / LIGHT_MAP_SCALE) as u16,
+    );
+    let floodlights = [
+        vec2(PITCH_BOUNDS_X.0, PITCH_BOUNDS_Y.0),
+        vec2(PITCH_BOUNDS_X.1, PITCH_BOUNDS_Y.0),
+        vec2(PITCH_BOUNDS_X.0, PITCH_BOUNDS_Y.1),
+        vec2(PITCH_BOUNDS_X.1, PITCH_BOUNDS_Y.1),
+    ]
+    .map(|corner| {
+        let outwards = (corner - vec2(LEVEL_W, LEVEL_H) / 2.0).normalize();
+        corner + outwards * FLOODLIGHT_OFFSET
+    });
+    let mut image = Image::gen_image_color(w, h, BLANK);
+    for y in 0..h {
+        for x in 0..w {
+            let pos = (vec2(x as f32, y as f32) + vec2(0.5, 0.5)) * LIGHT_MAP_SCALE;
+            // each floodlight falls off smoothly, and overlapping lights add up
+            let light: f32 = floodlights
+                .iter()
+                .map(|f| (1.0 - pos.distance(*f) / FLOODLIGHT_RANGE).max(0.0).powi(2))
+                .sum();
+            let alpha = ambient.a * (1.0 - light.min(1.0) * 0.8);
+            image.set_pixel(
+                x as u32,
+                y as u32,
+                Color {
+                    a: alpha,
+                    ..ambient
+                },
+            );
+        }
+    }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Linear);
+    texture
+}
@@ -0,0 +1,81 @@
+// who's playing for a team: which squad member is in each position, who's still on the bench, and
+// who's been injured or sent off; players are respawned at every kickoff from this, so it's kept on
+// the game rather than on the entities
+//
+// squad members are numbered as in SQUAD_NUMBERS, and positions as in PLAYER_START_POS
+
+use crate::{PLAYER_START_POS, SQUAD_NUMBERS};
+
+pub const SQUAD_SIZE: usize = SQUAD_NUMBERS.len();
+const POSITIONS: usize = PLAYER_START_POS.len();
+// substitutes a team can bring on over a match
+pub const MAX_SUBSTITUTIONS: u8 = 3;
+
+#[derive(Clone)]
+pub struct Lineup {
+    positions: [usize; POSITIONS],
+    // squad members who haven't played yet, in the order they're listed
+    bench: Vec<usize>,
+    injured: [bool; SQUAD_SIZE],
+    sent_off: [bool; SQUAD_SIZE],
+    substitutions: u8,
+}
+
+impl Lineup {
+    // the first seven squad members start, and the rest are substitutes
+    pub fn new() -> Self {
+        Self {
+            positions: std::array::from_fn(|n| n),
+            bench: (POSITIONS..SQUAD_SIZE).collect(),
+            injured: [false; SQUAD_SIZE],
+            sent_off: [false; SQUAD_SIZE],
+            substitutions: 0,
+        }
+    }
+
+    // the squad member playing in a position
+    pub fn member(&self, position: usize) -> usize {
+        self.positions[position]
+    }
+
+    pub fn bench(&self) -> &[usize] {
+        &self.bench
+    }
+
+    pub fn injured(&self, member: usize) -> bool {
+        self.injured[member]
+    }
+
+    pub fn injure(&mut self, member: usize) {
+        self.injured[member] = true;
+    }
+
+    // a position left empty because whoever was playing there was sent off
+    pub fn empty(&self, position: usize) -> bool {
+        self.sent_off[self.positions[position]]
+    }
+
+    pub fn send_off(&mut self, member: usize) {
+        self.sent_off[member] = true;
+    }
+
+    pub fn can_substitute(&self) -> bool {
+        self.substitutions < MAX_SUBSTITUTIONS && !self.bench.is_empty()
+    }
+
+    // bring on the substitute at bench_index for whoever's in position, and say who came off
+    pub fn substitute(&mut self, position: usize, bench_index: usize) -> usize {
+        let off = self.positions[position];
+        self.positions[position] = self.bench.remove(bench_index);
+        self.substitutions += 1;
+        off
+    }
+
+    // the first injured player a computer team would take off, if it has anyone to bring on
+    pub fn injured_position(&self) -> Option<usize> {
+        if !self.can_substitute() {
+            return None;
+        }
+        (0..POSITIONS).find(|n| !self.empty(*n) && self.injured[self.positions[*n]])
+    }
+}
@@ -0,0 +1,157 @@
+use crate::textures::{TextureLoader, Textures};
+use crate::{accessibility, synth, Sounds};
+use macroquad::prelude::*;
+
+// how long to spend loading each frame before redrawing the progress bar
+const FRAME_BUDGET: f64 = 1.0 / 30.0;
+const MAX_LISTED_FILES: usize = 12;
+
+// load every texture and sound while showing a progress bar, then if anything was missing, list
+// the files and wait for the player to acknowledge it before carrying on without them
+pub async fn load_assets(use_images: bool, theme: Option<String>) -> (Textures, Sounds) {
+    let mut loader = TextureLoader::new(theme).await;
+    let sprites = if use_images {
+        loader.sprites()
+    } else {
+        Vec::new()
+    };
+    let mut sound_files = vec![
+        ("theme".to_owned(), true),
+        ("sudden_death".to_owned(), true),
+        ("crowd".to_owned(), false),
+        ("move".to_owned(), false),
+        ("goal0".to_owned(), false),
+        ("goal1".to_owned(), false),
+    ];
+    for k in 0..=3 {
+        sound_files.push((format!("kick{}", k), false));
+    }
+
+    let mut progress = Progress {
+        done: 0,
+        total: sprites.len() + sound_files.len(),
+        frame_start: get_time(),
+    };
+    let mut missing = Vec::new();
+    let mut sounds = Sounds::new();
+    for sprite in sprites {
+        if let Err(path) = loader.load(sprite).await {
+            missing.push(path);
+        }
+        progress.step().await;
+    }
+    for (key, is_music) in sound_files {
+        if let Err(path) = sounds.preload(key, is_music).await {
+            missing.push(path);
+        }
+        progress.step().await;
+    }
+    for (key, bytes) in accessibility::cue_sounds() {
+        sounds.generate(key, &bytes).await;
+    }
+    // a referee's whistle for the start of play, and three blasts of it, the last one long, for
+    // the end of the match; a zero frequency is a pause
+    let trill = |notes: usize| (0..notes).map(|i| (2100.0 + 200.0 * (i % 2) as f32, 0.05));
+    let whistle: Vec<(f32, f32)> = trill(8).collect();
+    sounds.generate("whistle", &synth::tones(&whistle)).await;
+    let pause = [(0.0, 0.15)];
+    let full_time: Vec<(f32, f32)> = trill(6)
+        .chain(pause)
+        .chain(trill(6))
+        .chain(pause)
+        .chain(trill(16))
+        .collect();
+    sounds
+        .generate("full_time", &synth::tones(&full_time))
+        .await;
+    // the crowd's "oooh" at a chance that didn't quite go in
+    let oooh = synth::tones(&[(233.0, 0.25), (220.0, 0.3), (196.0, 0.45)]);
+    sounds.generate("oooh", &oooh).await;
+    // and its groan when the home side's chance goes begging
+    let groan = synth::tones(&[(175.0, 0.3), (165.0, 0.3), (147.0, 0.3), (131.0, 0.5)]);
+    sounds.generate("groan", &groan).await;
+    let textures = loader.finish();
+
+    if !missing.is_empty() {
+        for path in &missing {
+            warn!("couldn't load {}", path);
+        }
+        loop {
+            draw_missing(&missing);
+            next_frame().await;
+            if is_key_pressed(KeyCode::Space) {
+                break;
+            }
+        }
+    }
+    (textures, sounds)
+}
+
+// reload just the textures, e.g. after changing theme; anything missing has already been reported
+pub async fn load_textures(theme: Option<String>) -> Textures {
+    let mut loader = TextureLoader::new(theme).await;
+    let sprites = loader.sprites();
+    let mut progress = Progress {
+        done: 0,
+        total: sprites.len(),
+        frame_start: get_time(),
+    };
+    for sprite in sprites {
+        let _ = loader.load(sprite).await;
+        progress.step().await;
+    }
+    loader.finish()
+}
+
+struct Progress {
+    done: usize,
+    total: usize,
+    frame_start: f64,
+}
+
+impl Progress {
+    // count one more file as loaded, and give up the rest of the frame if the budget has run out
+    async fn step(&mut self) {
+        self.done += 1;
+        if get_time() - self.frame_start > FRAME_BUDGET {
+            draw_progress(self.done as f32 / self.total as f32);
+            next_frame().await;
+            self.frame_start = get_time();
+        }
+    }
+}
+
+fn draw_progress(fraction: f32) {
+    clear_background(BLACK);
+    let (w, h) = (screen_width() * 0.6, 20.0);
+    let (x, y) = ((screen_width() - w) / 2.0, (screen_height() - h) / 2.0);
+    draw_text("LOADING", x, y - 10.0, 24.0, WHITE);
+    draw_rectangle_lines(x, y, w, h, 2.0, WHITE);
+    draw_rectangle(x + 4.0, y + 4.0, (w - 8.0) * fraction, h - 8.0, WHITE);
+}
+
+fn draw_missing(missing: &[String]) {
+    clear_background(BLACK);
+    let mut y = 40.0;
+    let mut line = |text: &str, colour: Color| {
+        draw_text(text, 20.0, y, 20.0, colour);
+        y += 22.0;
+    };
+    line("Some game files couldn't be loaded:", YELLOW);
+    for path in missing.iter().take(MAX_LISTED_FILES) {
+        line(&format!("  {}", path), WHITE);
+    }
+    if missing.len() > MAX_LISTED_FILES {
+        line(
+            &format!("  ...and {} more", missing.len() - MAX_LISTED_FILES),
+            WHITE,
+        );
+    }
+    line("", WHITE);
+    line(
+        "Missing images will be replaced with placeholder graphics",
+        YELLOW,
+    );
+    line("and missing sounds will be silent.", YELLOW);
+    line("Press SPACE to continue.", WHITE);
+}
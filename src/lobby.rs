@@ -0,0 +1,411 @@
+// the lobby for network play (see net.rs), reached from the network page of the options menu: one
+// player hosts, with the match settings they've chosen in the options menu, and waits for someone to
+// join; the other finds the match in a list of those hosted on the local network and joins it; then
+// both see who's playing and the settings, and once both have pressed Space to say they're ready,
+// the host kicks off. the host plays as team 1 and whoever joined as team 2, both with the one
+// player keys, and both teams with their own squads rather than anyone's profile
+//
+// there's no rendezvous server, so matches can only be found on the local network; --join plays one
+// hosted anywhere else that can be reached
+//
+// while a match is waiting for someone who lost their connection, the host lists it again, and
+// whoever joined can rejoin it from here if their game closed in the meantime (see net.rs)
+
+use crate::data;
+use crate::i18n::Strings;
+use crate::lighting::Lighting;
+use crate::menu::{self, MenuAction, Pointer};
+use crate::net::{self, Connecting, Connection, Finder, Host, Message, NetMatch, Rejoin};
+use crate::replays::{self, MatchRecord};
+use crate::roster::Roster;
+use crate::{
+    draw_text_centred, new_match_with_squads, seed_match, Game, NumPlayers, Settings, Sounds,
+    HALF_WINDOW_WIDTH, HEIGHT, WIDTH,
+};
+use macroquad::prelude::*;
+
+enum Stage {
+    Finding(Finder),
+    // the settings the match will be played with
+    Hosting(Host, Settings),
+    // joining the match chosen from those found
+    Connecting(Connecting),
+    Joined(Joined),
+    // what went wrong
+    Failed(String),
+}
+
+struct Joined {
+    connection: Connection,
+    team: usize,
+    // empty until the other player has said who they are
+    names: [String; 2],
+    ready: [bool; 2],
+    // the host's, which whoever joined doesn't have until the host has sent them
+    settings: Option<Settings>,
+    rejoin: Rejoin,
+}
+
+// how a match begins once it's been agreed on
+enum Begin {
+    // at kickoff, from a seed
+    New(u64, Settings, Box<Game>),
+    // part way through, as the host sent it to someone rejoining
+    Resume(String),
+}
+
+// a match that's been agreed on, with everything needed to play it
+pub struct Kickoff {
+    pub game: Game,
+    pub net: NetMatch,
+}
+
+// what the lobby wants to happen after this frame
+pub enum LobbyExit {
+    Stay,
+    Leave,
+    Start(Box<Kickoff>),
+}
+
+pub struct Lobby {
+    stage: Stage,
+    name: String,
+    // the highlighted match in the list of those found
+    selected: usize,
+}
+
+impl Lobby {
+    // host a match with the settings chosen in the options menu
+    pub fn host(name: String, settings: &Settings) -> Self {
+        let stage = match Host::new(&name) {
+            Ok(host) => Stage::Hosting(host, network_settings(settings)),
+            Err(e) => Stage::Failed(e),
+        };
+        Self {
+            stage,
+            name,
+            selected: 0,
+        }
+    }
+
+    pub fn find(name: String) -> Self {
+        let stage = match Finder::new() {
+            Ok(finder) => Stage::Finding(finder),
+            Err(e) => Stage::Failed(e),
+        };
+        Self {
+            stage,
+            name,
+            selected: 0,
+        }
+    }
+
+    // for when a match has to be abandoned, saying why
+    pub fn failed(message: String) -> Self {
+        Self {
+            stage: Stage::Failed(message),
+            name: String::new(),
+            selected: 0,
+        }
+    }
+
+    // called once a frame
+    pub fn update(&mut self, pointer: &Pointer, sounds: &Sounds) -> LobbyExit {
+        let leave = is_key_pressed(KeyCode::Escape) || pointer.clicked_back();
+        let stage = std::mem::replace(&mut self.stage, Stage::Failed(String::new()));
+        let (stage, exit) = match stage {
+            Stage::Finding(mut finder) => {
+                finder.update();
+                let count = finder.found.len();
+                self.selected = self.selected.min(count.saturating_sub(1));
+                let action = match count {
+                    0 if leave => MenuAction::Back,
+                    0 => MenuAction::None,
+                    _ => menu::navigate(&mut self.selected, count, menu::LIST_LAYOUT, pointer),
+                };
+                if action != MenuAction::None {
+                    sounds.play("move");
+                }
+                match action {
+                    MenuAction::Back => (Stage::Finding(finder), LobbyExit::Leave),
+                    MenuAction::Confirm => {
+                        let address = finder.found[self.selected].address.to_string();
+                        (
+                            Stage::Connecting(Connecting::new(&address)),
+                            LobbyExit::Stay,
+                        )
+                    }
+                    _ => (Stage::Finding(finder), LobbyExit::Stay),
+                }
+            }
+            Stage::Hosting(_, _) if leave => (Stage::Failed(String::new()), LobbyExit::Leave),
+            Stage::Hosting(mut host, settings) => match host.accept() {
+                Ok(Some(mut connection)) => {
+                    connection.send(&Message::Name {
+                        team: 0,
+                        name: self.name.clone(),
+                    });
+                    connection.send(&Message::Settings(replays::settings_text(&settings)));
+                    let joined = Joined {
+                        connection,
+                        team: 0,
+                        names: [self.name.clone(), String::new()],
+                        ready: [false, false],
+                        settings: Some(settings),
+                        rejoin: Rejoin::Host(self.name.clone()),
+                    };
+                    (Stage::Joined(joined), LobbyExit::Stay)
+                }
+                Ok(None) => (Stage::Hosting(host, settings), LobbyExit::Stay),
+                Err(e) => (Stage::Failed(e), LobbyExit::Stay),
+            },
+            Stage::Connecting(_) if leave => (Stage::Failed(String::new()), LobbyExit::Leave),
+            Stage::Connecting(connecting) => match connecting.poll() {
+                Some(Ok(connection)) => {
+                    (self.join(connection, connecting.address), LobbyExit::Stay)
+                }
+                Some(Err(e)) => (Stage::Failed(e), LobbyExit::Stay),
+                None => (Stage::Connecting(connecting), LobbyExit::Stay),
+            },
+            Stage::Joined(joined) if leave => {
+                // say goodbye, rather than just vanishing
+                joined.connection.close();
+                (Stage::Failed(String::new()), LobbyExit::Leave)
+            }
+            Stage::Joined(mut joined) => match joined.update(sounds) {
+                Ok(None) => (Stage::Joined(joined), LobbyExit::Stay),
+                Ok(Some(begin)) => {
+                    let kickoff = match begin {
+                        Begin::New(seed, settings, game) => {
+                            let record = MatchRecord::new(seed, &settings, &game);
+                            Ok(Kickoff {
+                                net: NetMatch::new(
+                                    joined.connection,
+                                    joined.team,
+                                    joined.rejoin,
+                                    record,
+                                ),
+                                game: *game,
+                            })
+                        }
+                        Begin::Resume(text) => {
+                            NetMatch::rejoined(joined.connection, &text, joined.rejoin)
+                                .map(|(game, net)| Kickoff { game, net })
+                        }
+                    };
+                    match kickoff {
+                        Ok(kickoff) => (
+                            Stage::Failed(String::new()),
+                            LobbyExit::Start(Box::new(kickoff)),
+                        ),
+                        Err(e) => (Stage::Failed(e), LobbyExit::Stay),
+                    }
+                }
+                Err(e) => (Stage::Failed(e), LobbyExit::Stay),
+            },
+            Stage::Failed(message) => (
+                Stage::Failed(message),
+                if leave {
+                    LobbyExit::Leave
+                } else {
+                    LobbyExit::Stay
+                },
+            ),
+        };
+        self.stage = stage;
+        exit
+    }
+
+    // join the match hosted at address, now there's a connection to it
+    fn join(&self, mut connection: Connection, address: String) -> Stage {
+        connection.send(&Message::Name {
+            team: 1,
+            name: self.name.clone(),
+        });
+        Stage::Joined(Joined {
+            connection,
+            team: 1,
+            names: [String::new(), self.name.clone()],
+            ready: [false, false],
+            settings: None,
+            rejoin: Rejoin::Join(address),
+        })
+    }
+
+    pub fn draw(&self, strings: &Strings) {
+        draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_text(strings.get("lobby.title"), 100., 100., 48., WHITE);
+        let keys = match &self.stage {
+            Stage::Finding(finder) => {
+                if finder.found.is_empty() {
+                    draw_text(strings.get("lobby.finding"), 120., 140., 24., WHITE);
+                }
+                for (i, found) in finder.found.iter().enumerate() {
+                    let label = strings.format(
+                        "lobby.found",
+                        &[&found.name, &found.address.ip().to_string()],
+                    );
+                    let colour = if i == self.selected { YELLOW } else { WHITE };
+                    draw_text(&label, 120., menu::LIST_LAYOUT.baseline(i), 24., colour);
+                }
+                "lobby.find_keys"
+            }
+            Stage::Hosting(_, _) => {
+                let port = net::GAME_PORT.to_string();
+                draw_text(
+                    &strings.format("lobby.hosting", &[&port]),
+                    120.,
+                    140.,
+                    24.,
+                    WHITE,
+                );
+                "options.back"
+            }
+            Stage::Connecting(connecting) => {
+                draw_text(
+                    &strings.format("lobby.connecting", &[&connecting.address]),
+                    120.,
+                    140.,
+                    24.,
+                    WHITE,
+                );
+                "options.back"
+            }
+            Stage::Joined(joined) => {
+                joined.draw(strings);
+                "lobby.lobby_keys"
+            }
+            Stage::Failed(message) => {
+                draw_text(
+                    &strings.format("lobby.failed", &[message]),
+                    120.,
+                    140.,
+                    24.,
+                    WHITE,
+                );
+                "options.back"
+            }
+        };
+        draw_text_centred(strings.get(keys), HALF_WINDOW_WIDTH, HEIGHT - 10.0, 16.0);
+    }
+}
+
+impl Joined {
+    // how the match begins once it's time to; an error if the other player has gone, or sent
+    // something that makes no sense
+    fn update(&mut self, sounds: &Sounds) -> Result<Option<Begin>, String> {
+        for message in self.connection.receive()? {
+            match message {
+                Message::Name { team, name } => self.names[team] = name,
+                Message::Ready { team, ready } => {
+                    self.ready[team] = ready;
+                    sounds.play("move");
+                }
+                Message::Settings(text) => {
+                    let settings = data::parse(&text).ok().and_then(|doc| {
+                        replays::settings_from(doc.sections.get("")?).map(|s| network_settings(&s))
+                    });
+                    self.settings = Some(settings.ok_or("settings not recognised")?);
+                }
+                Message::Start(text) => {
+                    let (seed, settings, rosters) = net::read_start(&text)?;
+                    macroquad::rand::srand(seed);
+                    let game = new_match_with_squads(&settings, rosters);
+                    return Ok(Some(Begin::New(seed, settings, Box::new(game))));
+                }
+                Message::Resume(text) => return Ok(Some(Begin::Resume(text))),
+                Message::Bye => return Err("the other player left".to_owned()),
+                Message::Input { .. } | Message::Chat { .. } => (),
+            }
+        }
+        if is_key_pressed(KeyCode::Space) && self.settings.is_some() {
+            let ready = !self.ready[self.team];
+            self.ready[self.team] = ready;
+            self.connection.send(&Message::Ready {
+                team: self.team,
+                ready,
+            });
+            sounds.play("move");
+        }
+        // the host kicks off once everyone's ready
+        if self.team == 0 && self.ready == [true, true] {
+            if let Some(settings) = self.settings.take() {
+                let seed = seed_match();
+                let game = new_match_with_squads(&settings, [0, 1].map(Roster::default_for));
+                self.connection
+                    .send(&Message::Start(net::start_text(seed, &settings, &game)));
+                return Ok(Some(Begin::New(seed, settings, Box::new(game))));
+            }
+        }
+        Ok(None)
+    }
+
+    fn draw(&self, strings: &Strings) {
+        for team in 0..=1 {
+            let name = match self.names[team].as_str() {
+                "" => strings.get("lobby.joining"),
+                name => name,
+            };
+            let ready = strings.get(if self.ready[team] {
+                "lobby.ready"
+            } else {
+                "lobby.not_ready"
+            });
+            let label = strings.format("lobby.player", &[&(team + 1).to_string(), name, ready]);
+            let colour = if team == self.team { YELLOW } else { WHITE };
+            draw_text(&label, 120., menu::LIST_LAYOUT.baseline(team), 24., colour);
+        }
+        if let Some(settings) = &self.settings {
+            for (i, label) in settings_labels(settings, strings).iter().enumerate() {
+                draw_text(label, 120., menu::LIST_LAYOUT.baseline(i + 3), 24., WHITE);
+            }
+        }
+    }
+}
+
+// the host's settings as a network match is played with them: two human teams, on the keys, each
+// with their own screen
+fn network_settings(settings: &Settings) -> Settings {
+    let mut network = Settings::new();
+    network.weather = settings.weather;
+    network.lighting = settings.lighting;
+    network.switch_mode = settings.switch_mode;
+    network.auto_switch = settings.auto_switch;
+    network.assist = settings.assist;
+    network.goals_to_win = settings.goals_to_win;
+    network.mutators = settings.mutators;
+    network.handicaps = settings.handicaps;
+    network.tactics = settings.tactics;
+    network.num_players = NumPlayers::Two;
+    network
+}
+
+// the settings that make a difference to the match, as the options menu labels them
+fn settings_labels(settings: &Settings, strings: &Strings) -> Vec<String> {
+    let on_off = |on| strings.get(if on { "options.on" } else { "options.off" });
+    let choice = |key: &str, value: &str| {
+        strings.format(
+            &format!("options.{}", key),
+            &[strings.get(&format!("options.{}_{}", key, value))],
+        )
+    };
+    let mutators = &settings.mutators;
+    vec![
+        strings.format(
+            "options.match_length",
+            &[&settings.goals_to_win.to_string()],
+        ),
+        choice("weather", settings.weather.key()),
+        choice(
+            "lighting",
+            settings.lighting.map_or("random", Lighting::key),
+        ),
+        strings.format("options.multi_ball", &[on_off(mutators.multi_ball)]),
+        strings.format("options.giant_ball", &[on_off(mutators.giant_ball)]),
+        strings.format(
+            "options.game_speed",
+            &[&(mutators.speed * 100.0).round().to_string()],
+        ),
+        strings.format("options.power_ups", &[on_off(mutators.power_ups)]),
+    ]
+}
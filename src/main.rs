@@ -1,35 +1,192 @@
+mod accessibility;
+mod assets;
+mod attributes;
+mod balance;
+mod behaviour;
+mod bots;
+mod broadcast;
+mod camera;
+mod chat;
+mod checksum;
+mod console;
+mod crowd;
+mod crowd_noise;
+mod data;
+mod error;
+mod focus;
+#[cfg(test)]
+mod fuzz_tests;
+mod geometry;
+#[cfg(test)]
+mod golden_tests;
+mod highlights;
+mod history;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod i18n;
+mod input;
+mod inspector;
+mod keeper;
+mod knock_on;
+mod launch;
+mod lighting;
+mod lineup;
+mod loading;
+mod lobby;
+mod menu;
+mod movement;
+mod mutators;
+mod net;
+mod pause_menu;
+mod penalties;
+mod perf;
+mod personality;
+mod photo;
+mod placeholder;
+mod powerups;
+mod profiles;
+mod render;
+mod replay_controls;
+mod replays;
+mod rewind;
+mod roster;
+mod roster_editor;
+mod scaler;
+mod schedule;
+mod screenshot;
+#[macro_use]
+mod sim_log;
+mod spatial;
+mod stats;
+mod storage;
+mod support;
+mod synth;
+mod tackle;
+mod tactics;
+#[cfg(test)]
+mod test_rng;
+mod textures;
+mod touch;
+mod training;
+mod tutorial;
+mod weather;
+
+use accessibility::{Accessibility, Captions};
+use attributes::Attributes;
+use balance::Balance;
+use behaviour::Behaviour;
+use broadcast::{Broadcast, Spectator};
+use camera::Camera;
+use chat::Chat;
+use console::Console;
+use crowd_noise::CrowdNoise;
+use error::{GameError, WorldExt};
 use euclid::{vec2, Vector2D};
+use focus::{FocusChange, FocusWatch};
+use geometry::{
+    allow_movement, ball_physics, deflect, goal_line_crossing, hit_post, in_centre_circle,
+    on_pitch, outside_centre_circle, Angle,
+};
 use hecs::{Entity, EntityBuilder, World};
+use highlights::Highlights;
+use history::{History, MatchResult};
+use i18n::Strings;
+use input::{FrameInput, Keyboard, Recording, TeamInput};
+use inspector::Inspector;
+use keeper::Distribution;
+use knock_on::{KnockOn, Taps, KNOCK_ON_BOOST, KNOCK_ON_DELAY, KNOCK_ON_SPEED};
+use launch::LaunchOptions;
+use lighting::Lighting;
+use lineup::Lineup;
+use lobby::{Kickoff, Lobby, LobbyExit};
 use macroquad::audio::{
-    load_sound, play_sound, play_sound_once, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+    load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
 };
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
+use menu::{MenuAction, Pointer};
+use mutators::Mutators;
+use net::{Heard, NetMatch};
+use pause_menu::PauseMenu;
+use penalties::Penalties;
+use perf::PerfHud;
+use personality::Personality;
+use photo::PhotoMode;
+use powerups::PowerUps;
+use profiles::Profiles;
+use render::Renderer;
+use replay_controls::ReplayControls;
+use replays::{MatchRecord, Playback, Replays};
+use rewind::Rewind;
+use roster::Roster;
+use roster_editor::RosterEditor;
+use scaler::{ScaleMode, Scaler};
+use schedule::{BallStep, BALL_SYSTEMS, SYSTEMS};
+use screenshot::Screenshots;
+use sim_log::SimLog;
+use spatial::PlayerGrid;
+use stats::MatchStats;
 use std::collections::HashMap;
 use std::f32::consts::PI;
+use support::SupportRun;
+use tackle::{Slide, SLIDE_SPEED};
+use tactics::Tactics;
+use textures::{Sprite, Textures};
+use touch::TouchControls;
+use training::{Training, TrainingSetup};
+use tutorial::Tutorial;
+use weather::{Precipitation, Weather};
 
 enum PixelUnit {}
 
 type Vector = Vector2D<f32, PixelUnit>;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Position(Vector);
+#[derive(Clone)]
 struct Home(Vector);
+#[derive(Clone)]
 struct Team(u8);
+#[derive(Clone)]
 struct Ball();
+#[derive(Clone)]
 struct Peer(Entity);
 
+#[derive(Clone)]
+struct Squad {
+    number: u8,
+    name: String,
+}
+
+impl Squad {
+    // where they are in SQUAD_NUMBERS
+    fn member(&self) -> usize {
+        SQUAD_NUMBERS
+            .iter()
+            .position(|number| *number == self.number)
+            .unwrap()
+    }
+}
+
+#[derive(Clone, PartialEq)]
 struct Target {
     pos: Vector,
     speed: f32,
+    // which of the AI's behaviours chose this target, for the inspector
+    behaviour: Behaviour,
 }
 
 impl Target {
     fn new(pos: Vector) -> Self {
-        Self { pos, speed: 2.0 }
+        Self {
+            pos,
+            speed: 2.0,
+            behaviour: Behaviour::Wait,
+        }
     }
 }
 
+#[derive(Clone)]
 struct Animation {
     dir: Angle,
     frame: f32,
@@ -44,8 +201,17 @@ impl Animation {
     }
 }
 
+#[derive(Clone)]
 struct Timer(i8);
 
+// a player who's been hurt in a tackle, and runs slower until they're substituted
+#[derive(Clone)]
+struct Injured;
+
+// a player's velocity, which only lags behind where they want to go on a slippery pitch
+#[derive(Clone)]
+struct Momentum(Vector);
+
 const HEIGHT: f32 = 480.0;
 const WIDTH: f32 = 800.0;
 
@@ -72,21 +238,29 @@ const GOAL_BOUNDS_Y: (f32, f32) = (
     HALF_LEVEL_H + HALF_PITCH_H + GOAL_DEPTH,
 );
 
-fn on_pitch(x: f32, y: f32) -> bool {
-    (x >= PITCH_BOUNDS_X.0 && x < PITCH_BOUNDS_X.1 && y >= PITCH_BOUNDS_Y.0 && y < PITCH_BOUNDS_Y.1)
-        || (x >= GOAL_BOUNDS_X.0
-            && x < GOAL_BOUNDS_X.1
-            && y >= GOAL_BOUNDS_Y.0
-            && y < GOAL_BOUNDS_Y.1)
-}
-
 const AI_MIN_X: f32 = 78.0;
 const AI_MAX_X: f32 = LEVEL_W - 78.0;
 const AI_MIN_Y: f32 = 98.0;
 const AI_MAX_Y: f32 = LEVEL_H - 98.0;
 
 const KICK_STRENGTH: f32 = 11.5;
-const DRAG: f32 = 0.98;
+// a pass is weighted to reach its target still going at PASS_ARRIVAL_SPEED, slow enough to control,
+// but never softer than MIN_PASS_STRENGTH times a normal kick or harder than one; a shot is hit to
+// reach the goal at SHOT_ARRIVAL_SPEED, so long ones are hit harder, up to MAX_SHOT_STRENGTH
+const PASS_ARRIVAL_SPEED: f32 = 4.0;
+const MIN_PASS_STRENGTH: f32 = 0.5;
+const SHOT_ARRIVAL_SPEED: f32 = 8.0;
+const MAX_SHOT_STRENGTH: f32 = 1.25;
+// a ball going slower than this has as good as stopped
+const REST_SPEED: f32 = 0.25;
+
+// with at least this many players, their targets are chosen in parallel; below it, starting the
+// threads takes longer than the work
+const PARALLEL_PLAYERS: usize = 64;
+// how far ahead the debug overlay follows a loose ball's path
+const MAX_PREDICTED_STEPS: usize = 600;
+// the furthest away a teammate can be to be passed to
+const PASS_RANGE: f32 = 300.0;
 
 const PLAYER_START_POS: [(f32, f32); 7] = [
     (350., 550.),
@@ -98,24 +272,130 @@ const PLAYER_START_POS: [(f32, f32); 7] = [
     (650., 1150.),
 ];
 
-const LEAD_DISTANCE_1: f32 = 10.0;
-const LEAD_DISTANCE_2: f32 = 50.0;
-
-const DRIBBLE_DIST_X: f32 = 18.0;
-const DRIBBLE_DIST_Y: f32 = 16.0;
+// how much closer than usual a marker gets to the most dangerous mark, on difficulties with
+// lane_marking (see lane_fraction)
+const LANE_THREAT_WEIGHT: f32 = 0.7;
 
-// Speeds for players in various situations. Speeds including 'BASE' can be boosted by the speed_boost difficulty
-// setting (only for players on a computer-controlled team)
-const PLAYER_DEFAULT_SPEED: f32 = 2.0;
-const CPU_PLAYER_WITH_BALL_BASE_SPEED: f32 = 2.6;
-const PLAYER_INTERCEPT_BALL_SPEED: f32 = 2.75;
-const LEAD_PLAYER_BASE_SPEED: f32 = 2.9;
-const HUMAN_PLAYER_WITH_BALL_SPEED: f32 = 3.0;
-const HUMAN_PLAYER_WITHOUT_BALL_SPEED: f32 = 3.3;
+// the other speeds players run at are in the balance file (see balance.rs)
 const MAX_SPEED: f32 = 10.0;
 
+// shirt numbers and names for each team; the first seven start, in the same order as
+// PLAYER_START_POS, and the rest are substitutes
+const SQUAD_NUMBERS: [u8; 10] = [9, 10, 7, 8, 11, 5, 6, 12, 14, 15];
+const SQUAD_NAMES: [[&str; 10]; 2] = [
+    [
+        "Hughes",
+        "Barker",
+        "Okafor",
+        "Lindqvist",
+        "Moreno",
+        "Carter",
+        "Doyle",
+        "Pryce",
+        "Adeyemi",
+        "Walsh",
+    ],
+    [
+        "Novak", "Tanaka", "Brennan", "Silva", "Fischer", "Ward", "Kowalski", "Ruiz", "Haddad",
+        "Berg",
+    ],
+];
+
+// the simulation always advances in steps of this length, however fast frames are drawn
+const TIMESTEP: f32 = 1.0 / 60.0;
+// after a long stall, give up catching up rather than running many steps in one frame
+const MAX_STEPS_PER_FRAME: u32 = 5;
+
 const GOALS_TO_WIN: u8 = 9;
-const GOAL_FRAMES: i32 = 60;
+
+// how many steps early the shoot button can be pressed and still kick the ball on gaining it
+const SHOOT_BUFFER_FRAMES: u8 = 6;
+const GOAL_FRAMES: i32 = 120;
+// nobody moves for this many steps after the players line up for a kickoff, while the screen counts
+// down 3, 2, 1, and then the whistle goes
+const KICKOFF_COUNT_FRAMES: u32 = 40;
+const KICKOFF_COUNTDOWN_FRAMES: u32 = KICKOFF_COUNT_FRAMES * 3;
+// the team that isn't kicking off lines up outside this circle
+const CENTRE_CIRCLE_RADIUS: f32 = 90.0;
+// goal kicks are taken from this far in front of the goal line, by the player who starts deepest
+const GOAL_KICK_DISTANCE: f32 = 80.0;
+const GOAL_KICK_TAKER: usize = 5;
+const KICK_WINDOW_FRAMES: u32 = 120;
+// a shot that goes past a post by less than this is a near miss
+const NEAR_MISS_DISTANCE: f32 = 80.0;
+// a tackle on someone with the ball from behind them (further round than this, as the cosine of the
+// angle from where they're facing) is a foul, and doesn't win the ball
+const FOUL_ANGLE_COS: f32 = -0.7;
+// every third foul is a booking, as is one made running faster than this; a second booking is a
+// sending off, unless it would leave the team with fewer than MIN_PLAYERS
+const FOULS_PER_BOOKING: u32 = 3;
+const HARD_TACKLE_SPEED: f32 = 3.1;
+const MIN_PLAYERS: usize = 4;
+// how long a card is shown for
+const BOOKING_FRAMES: u32 = 180;
+// the chance of a tackle made faster than HARD_TACKLE_SPEED injuring the player with the ball, and
+// how much slower than usual an injured player runs
+const INJURY_CHANCE: f32 = 0.15;
+const INJURED_SPEED: f32 = 0.7;
+// how close a loose ball has to come to a player who can't take it to bounce off them, how much of
+// its speed it keeps and how long after that they still can't take it
+const BODY_RADIUS: f32 = 12.0;
+const PLAYER_BOUNCE: f32 = 0.5;
+const DEFLECTION_FRAMES: i8 = 10;
+// a loose ball slower than FIRST_TOUCH_SPEED is always controlled; one at full kick strength gets
+// away from a player with average control MISCONTROL_CHANCE of the time, running on at
+// MISCONTROL_BOUNCE of its speed in a direction up to MISCONTROL_SPREAD radians either side of the
+// way it was going, and they need MISCONTROL_FRAMES to go after it again
+const FIRST_TOUCH_SPEED: f32 = 6.0;
+const MISCONTROL_CHANCE: f32 = 0.4;
+const MISCONTROL_BOUNCE: f32 = 0.3;
+const MISCONTROL_SPREAD: f32 = 0.8;
+const MISCONTROL_FRAMES: i8 = 12;
+// a ball in the net keeps this much of its speed each step, and the net ripples for RIPPLE_FRAMES
+// after it goes in
+const NET_DRAG: f32 = 0.7;
+const RIPPLE_FRAMES: u32 = 40;
+// players on both teams within DUEL_REACH times their usual reach of a loose ball go shoulder to
+// shoulder for it, where facing it and being nearer it count as well as strength, and some of it
+// is luck; the loser can't take the ball for DUEL_FRAMES, while the two of them are drawn leaning
+// JOSTLE_DISTANCE into each other
+const DUEL_REACH: f32 = 1.5;
+const DUEL_FACING: f32 = 0.1;
+const DUEL_NEARNESS: f32 = 0.1;
+const DUEL_LUCK: f32 = 0.1;
+const DUEL_FRAMES: i8 = 20;
+const JOSTLE_DISTANCE: f32 = 4.0;
+// how long a substitution is shown for
+const SUBSTITUTION_FRAMES: u32 = 180;
+// the seconds added on for each kind of stoppage; there's no clock to add them to, but the fourth
+// official's board goes up for ADDED_TIME_FRAMES each time they make another minute, and the
+// minutes stay next to the score bar
+const FOUL_STOPPAGE: u32 = 10;
+const BOOKING_STOPPAGE: u32 = 30;
+const INJURY_STOPPAGE: u32 = 45;
+const SUBSTITUTION_STOPPAGE: u32 = 30;
+const ADDED_TIME_FRAMES: u32 = 180;
+
+// after scoring, the scorer runs laps of a circle of this radius while the camera zooms in on them
+const CELEBRATION_RADIUS: f32 = 50.0;
+const CELEBRATION_LAP_FRAMES: f32 = 90.0;
+const CELEBRATION_SPEED: f32 = 4.0;
+const CELEBRATION_ZOOM: f32 = 1.25;
+// the camera stays this much closer in while the next goal wins, and the scoreboard flashes this
+// many times a second
+const SUDDEN_DEATH_ZOOM: f32 = 1.15;
+const SUDDEN_DEATH_FLASH_RATE: f64 = 2.0;
+// calling for a pass sends the nearest teammate within range on a run upfield, for at most
+// RUN_FRAMES steps
+const RUN_CALL_RANGE: f32 = 350.0;
+const RUN_DISTANCE: f32 = 300.0;
+const RUN_FRAMES: u8 = 90;
+const SHORTCUT_FONT_SIZE: f32 = 16.0;
+const VOLUME_STEP: f32 = 0.1;
+// how long after switching player another press of the switch key carries on cycling, in steps
+const SWITCH_CYCLE_FRAMES: u8 = 45;
+// number of frames the goal banner takes to slide on and off the screen
+const BANNER_SLIDE_FRAMES: i32 = 15;
 
 #[derive(Copy, Clone)]
 struct Controls {
@@ -124,26 +404,11 @@ struct Controls {
     left: KeyCode,
     right: KeyCode,
     shoot: KeyCode,
-}
-
-impl Controls {
-    fn movement(&self) -> Vector {
-        let dy = if is_key_down(self.up) {
-            -1.
-        } else if is_key_down(self.down) {
-            1.
-        } else {
-            0.
-        };
-        let dx = if is_key_down(self.left) {
-            -1.
-        } else if is_key_down(self.right) {
-            1.
-        } else {
-            0.
-        };
-        return vec2(dx, dy) * MAX_SPEED;
-    }
+    switch: KeyCode,
+    call: KeyCode,
+    tackle: KeyCode,
+    // kick towards the mouse cursor, which can also be clicked to shoot or switch player
+    mouse_aim: bool,
 }
 
 const TEAM_CONTROLS: [Controls; 2] = [
@@ -153,6 +418,10 @@ const TEAM_CONTROLS: [Controls; 2] = [
         left: KeyCode::Left,
         right: KeyCode::Right,
         shoot: KeyCode::Space,
+        switch: KeyCode::RightShift,
+        call: KeyCode::RightControl,
+        tackle: KeyCode::RightAlt,
+        mouse_aim: false,
     },
     Controls {
         up: KeyCode::W,
@@ -160,9 +429,26 @@ const TEAM_CONTROLS: [Controls; 2] = [
         left: KeyCode::A,
         right: KeyCode::D,
         shoot: KeyCode::LeftShift,
+        switch: KeyCode::Q,
+        call: KeyCode::E,
+        tackle: KeyCode::F,
+        mouse_aim: false,
     },
 ];
 
+// for a single player who'd rather aim with the mouse, leaving their other hand on WASD
+const MOUSE_CONTROLS: Controls = Controls {
+    up: KeyCode::W,
+    down: KeyCode::S,
+    left: KeyCode::A,
+    right: KeyCode::D,
+    shoot: KeyCode::Space,
+    switch: KeyCode::Q,
+    call: KeyCode::E,
+    tackle: KeyCode::F,
+    mouse_aim: true,
+};
+
 #[derive(Copy, Clone)]
 enum DifficultyLevel {
     Easy = 0,
@@ -170,77 +456,362 @@ enum DifficultyLevel {
     Hard = 2,
 }
 
+impl DifficultyLevel {
+    // names the level in the language files and the match history
+    fn key(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+const DIFFICULTY_OPTIONS: [DifficultyLevel; 3] = [
+    DifficultyLevel::Easy,
+    DifficultyLevel::Medium,
+    DifficultyLevel::Hard,
+];
+
+#[derive(Clone)]
 struct Difficulty {
+    level: DifficultyLevel,
     goalie_enabled: bool,
     second_lead_enabled: bool,
-    speed_boost: f32,
-    holdoff_timer: i8,
+    // markers stand in the passing lane to their mark, tighter the more dangerous the mark is
+    lane_marking: bool,
+    // the computer's shots go up to this many radians either side of where they're aimed, and it
+    // only shoots when the goal is this much lower cost than where the player is
+    aim_noise: f32,
+    shot_caution: f32,
 }
 
 fn get_difficulty(level: DifficultyLevel) -> Difficulty {
     match level {
         DifficultyLevel::Easy => Difficulty {
+            level,
             goalie_enabled: false,
             second_lead_enabled: false,
-            speed_boost: 0.0,
-            holdoff_timer: 120,
+            lane_marking: false,
+            aim_noise: 0.15,
+            shot_caution: 40.0,
         },
         DifficultyLevel::Medium => Difficulty {
+            level,
             goalie_enabled: false,
             second_lead_enabled: true,
-            speed_boost: 0.1,
-            holdoff_timer: 90,
+            lane_marking: false,
+            aim_noise: 0.08,
+            shot_caution: 15.0,
         },
         DifficultyLevel::Hard => Difficulty {
+            level,
             goalie_enabled: true,
             second_lead_enabled: true,
-            speed_boost: 0.2,
-            holdoff_timer: 60,
+            lane_marking: true,
+            aim_noise: 0.03,
+            shot_caution: 0.0,
         },
     }
 }
 
-#[derive(Copy, Clone)]
-struct Angle(i32);
-
-impl Angle {
-    fn sin(&self) -> f32 {
-        (self.0 as f32 * PI / 4.0).sin()
-    }
-
-    fn cos(&self) -> f32 {
-        (self.0 as f32 * PI / 4.0).cos()
-    }
-
-    fn from_vec(v: Vector) -> Self {
-        Angle((((4.0 / PI * v.x.atan2(-v.y)) + 8.5) as i32) % 8)
-    }
-
-    fn to_vec(a: Self) -> Vector {
-        vec2(a.sin(), -a.cos())
-    }
-}
-
 const ANGLE_DIFFS: [i32; 8] = [0, 1, 1, 1, 1, 7, 7, 7];
 
 enum State {
     Menu(MenuState, Settings),
     Play,
     GameOver,
+    Penalties(Penalties),
+    // watching a match broadcast from somewhere else; boxed, as it holds a whole frame twice
+    Spectate(Box<Spectator>),
 }
 
 impl State {
     fn is_menu(&self) -> bool {
-        match self {
-            Self::Menu(_, _) => true,
-            _ => false,
-        }
+        matches!(self, Self::Menu(_, _))
     }
 }
 
 enum MenuState {
     NumPlayers,
     Difficulty,
+    // a page of OPTIONS_PAGES, and the highlighted item on it
+    Options(usize, usize),
+    // boxed, as a whole squad is much bigger than the other states
+    RosterEditor(Box<RosterEditor>),
+    // boxed for the same reason, and because it holds the network connections
+    Lobby(Box<Lobby>),
+}
+
+#[derive(Copy, Clone)]
+enum OptionItem {
+    // leads to another page of OPTIONS_PAGES
+    Page(usize),
+    Language,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    Scaling,
+    Theme,
+    CameraEffects,
+    BackgroundPause,
+    MatchLength,
+    Weather,
+    Lighting,
+    Switching,
+    AutoSwitch,
+    Assist,
+    MouseAim,
+    LargeText,
+    AudioCues,
+    TeamMarkers,
+    MultiBall,
+    GiantBall,
+    GameSpeed,
+    PowerUps,
+    // the handicap for a team
+    HeadStart(usize),
+    TeamSpeed(usize),
+    // a team's tactics
+    Pressing(usize),
+    DefensiveLine(usize),
+    // the win/loss record for one of history::MODES
+    HistoryRecord(usize),
+    // the nth most recently saved replay
+    Replay(usize),
+    // the profile a team is playing as
+    Profile(usize),
+    NewProfile,
+    // the squad of the profile a team is playing as
+    EditRoster(usize),
+    TrainingDummies,
+    TrainingTargets,
+    StartTraining,
+    StartTutorial,
+    StartPenalties,
+    HostMatch,
+    FindMatch,
+}
+
+struct OptionsPage {
+    // key for the page's name in the language files
+    title: &'static str,
+    items: &'static [OptionItem],
+}
+
+const OPTIONS_PAGE: usize = 0;
+const ACCESSIBILITY_PAGE: usize = 4;
+// reached from the title screen rather than the options page
+const TRAINING_PAGE: usize = 5;
+const MUTATORS_PAGE: usize = 6;
+const HANDICAP_PAGE: usize = 7;
+// also lists recent results below the items
+const HISTORY_PAGE: usize = 8;
+// also shows the chosen profiles' stats below the items
+const PROFILES_PAGE: usize = 9;
+const TACTICS_PAGE: usize = 10;
+const REPLAYS_PAGE: usize = 11;
+const NETWORK_PAGE: usize = 12;
+const OPTIONS_PAGES: [OptionsPage; 13] = [
+    OptionsPage {
+        title: "options.page_options",
+        items: &[
+            OptionItem::Page(1),
+            OptionItem::Page(2),
+            OptionItem::Page(3),
+            OptionItem::Page(ACCESSIBILITY_PAGE),
+            OptionItem::Page(MUTATORS_PAGE),
+            OptionItem::Page(HANDICAP_PAGE),
+            OptionItem::Page(TACTICS_PAGE),
+            OptionItem::Page(HISTORY_PAGE),
+            OptionItem::Page(REPLAYS_PAGE),
+            OptionItem::Page(PROFILES_PAGE),
+            OptionItem::Page(NETWORK_PAGE),
+            OptionItem::Language,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_audio",
+        items: &[OptionItem::MusicVolume, OptionItem::SfxVolume],
+    },
+    OptionsPage {
+        title: "options.page_video",
+        items: &[
+            OptionItem::Fullscreen,
+            OptionItem::Scaling,
+            OptionItem::Theme,
+            OptionItem::CameraEffects,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_gameplay",
+        items: &[
+            OptionItem::MatchLength,
+            OptionItem::Weather,
+            OptionItem::Lighting,
+            OptionItem::Switching,
+            OptionItem::AutoSwitch,
+            OptionItem::Assist,
+            OptionItem::MouseAim,
+            OptionItem::BackgroundPause,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_accessibility",
+        items: &[
+            OptionItem::LargeText,
+            OptionItem::AudioCues,
+            OptionItem::TeamMarkers,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_training",
+        items: &[
+            OptionItem::TrainingDummies,
+            OptionItem::TrainingTargets,
+            OptionItem::StartTraining,
+            OptionItem::StartTutorial,
+            OptionItem::StartPenalties,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_mutators",
+        items: &[
+            OptionItem::MultiBall,
+            OptionItem::GiantBall,
+            OptionItem::GameSpeed,
+            OptionItem::PowerUps,
+        ],
+    },
+    OptionsPage {
+        title: "options.page_handicap",
+        items: &[
+            OptionItem::HeadStart(0),
+            OptionItem::TeamSpeed(0),
+            OptionItem::HeadStart(1),
+            OptionItem::TeamSpeed(1),
+        ],
+    },
+    OptionsPage {
+        title: "options.page_history",
+        items: &[
+            OptionItem::HistoryRecord(0),
+            OptionItem::HistoryRecord(1),
+            OptionItem::HistoryRecord(2),
+            OptionItem::HistoryRecord(3),
+        ],
+    },
+    OptionsPage {
+        title: "options.page_profiles",
+        items: &[
+            OptionItem::Profile(0),
+            OptionItem::Profile(1),
+            OptionItem::NewProfile,
+            OptionItem::EditRoster(0),
+            OptionItem::EditRoster(1),
+        ],
+    },
+    OptionsPage {
+        title: "options.page_tactics",
+        items: &[
+            OptionItem::Pressing(0),
+            OptionItem::DefensiveLine(0),
+            OptionItem::Pressing(1),
+            OptionItem::DefensiveLine(1),
+        ],
+    },
+    OptionsPage {
+        title: "options.page_replays",
+        items: &[
+            OptionItem::Replay(0),
+            OptionItem::Replay(1),
+            OptionItem::Replay(2),
+            OptionItem::Replay(3),
+            OptionItem::Replay(4),
+            OptionItem::Replay(5),
+            OptionItem::Replay(6),
+            OptionItem::Replay(7),
+        ],
+    },
+    OptionsPage {
+        title: "options.page_network",
+        items: &[OptionItem::HostMatch, OptionItem::FindMatch],
+    },
+];
+
+// what the options menu wants to happen after this frame
+enum OptionsExit {
+    Stay,
+    Menu(MenuState),
+    StartTraining,
+    StartTutorial,
+    StartPenalties,
+    // the nth most recently saved replay
+    PlayReplay(usize),
+}
+
+// match lengths to choose from, as the number of goals needed to win
+const MATCH_LENGTHS: [u8; 3] = [3, 5, GOALS_TO_WIN];
+// the most goals a team can be given before kickoff
+const MAX_HEAD_START: u8 = 3;
+// how fast a handicapped team's players run, as multiples of normal
+const HANDICAP_SPEEDS: [f32; 5] = [0.8, 0.9, 1.0, 1.1, 1.2];
+
+// everything the options pages can change
+struct OptionsContext<'a> {
+    settings: &'a mut Settings,
+    scaler: &'a mut Scaler,
+    sounds: &'a mut Sounds,
+    theme: &'a mut Option<String>,
+    themes: &'a [String],
+    camera_effects: &'a mut bool,
+    // pause a match when the window goes into the background
+    background_pause: &'a mut bool,
+    accessibility: &'a mut Accessibility,
+    // index into i18n::LANGUAGES, and the text for the language currently loaded
+    language: &'a mut usize,
+    strings: &'a Strings,
+    history: &'a History,
+    replays: &'a Replays,
+    profiles: &'a mut Profiles,
+}
+
+// what the switch key does when pressed several times in quick succession
+#[derive(Copy, Clone, PartialEq)]
+enum SwitchMode {
+    // always go to the nearest other player to the ball
+    Nearest,
+    // work outwards through the team, from nearest the ball to furthest away
+    Cycle,
+}
+
+impl SwitchMode {
+    // names the mode in the language files
+    fn key(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Cycle => "cycle",
+        }
+    }
+}
+
+// how much help human teams get from the players they aren't controlling
+#[derive(Copy, Clone, PartialEq)]
+enum Assist {
+    // teammates take up sensible positions but leave the ball carrier to you
+    AutoPosition,
+    // the nearest teammate also closes down whoever has the ball
+    Pressure,
+}
+
+impl Assist {
+    // names the assist level in the language files
+    fn key(self) -> &'static str {
+        match self {
+            Self::AutoPosition => "auto_position",
+            Self::Pressure => "pressure",
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -249,9 +820,48 @@ enum NumPlayers {
     Two = 2,
 }
 
+const NUM_PLAYERS_OPTIONS: [NumPlayers; 2] = [NumPlayers::One, NumPlayers::Two];
+
+// evens up a match between players of different abilities
+#[derive(Copy, Clone)]
+struct Handicap {
+    // goals the team starts the match with
+    head_start: u8,
+    // one of HANDICAP_SPEEDS
+    speed: f32,
+}
+
+impl Handicap {
+    fn new() -> Self {
+        Self {
+            head_start: 0,
+            speed: 1.0,
+        }
+    }
+}
+
 struct Settings {
     num_players: NumPlayers,
     difficulty_level: DifficultyLevel,
+    split_screen: bool,
+    weather: Weather,
+    // None means pick at random for each match
+    lighting: Option<Lighting>,
+    switch_mode: SwitchMode,
+    // give human teams control of the player nearest the ball as soon as they lose it
+    auto_switch: bool,
+    assist: Assist,
+    // use MOUSE_CONTROLS in single player games
+    mouse_aim: bool,
+    goals_to_win: u8,
+    training: TrainingSetup,
+    mutators: Mutators,
+    handicaps: [Handicap; 2],
+    tactics: [Tactics; 2],
+    // index into the profiles each team is playing as
+    profiles: [Option<usize>; 2],
+    // being typed in on the profiles page
+    new_profile_name: String,
 }
 
 impl Settings {
@@ -259,15 +869,93 @@ impl Settings {
         Self {
             num_players: NumPlayers::One,
             difficulty_level: DifficultyLevel::Medium,
+            split_screen: false,
+            weather: Weather::Clear,
+            lighting: Some(Lighting::Day),
+            switch_mode: SwitchMode::Nearest,
+            auto_switch: false,
+            assist: Assist::AutoPosition,
+            mouse_aim: false,
+            goals_to_win: GOALS_TO_WIN,
+            training: TrainingSetup::new(),
+            mutators: Mutators::new(),
+            handicaps: [Handicap::new(), Handicap::new()],
+            tactics: [Tactics::new(), Tactics::new()],
+            profiles: [None, None],
+            new_profile_name: String::new(),
+        }
+    }
+
+    // controls for the human team in a single player game or training
+    fn one_player_controls(&self) -> Controls {
+        if self.mouse_aim {
+            MOUSE_CONTROLS
+        } else {
+            TEAM_CONTROLS[0]
         }
     }
 }
 
-#[derive(PartialEq)]
-enum MenuChange {
-    Up,
-    Down,
-    NoChange,
+#[derive(Copy, Clone, PartialEq)]
+enum PlayerLabels {
+    Off,
+    Active,
+    All,
+}
+
+impl PlayerLabels {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Active,
+            Self::Active => Self::All,
+            Self::All => Self::Off,
+        }
+    }
+}
+
+// the parts of debug mode that can be turned on and off separately with F2 onwards
+#[derive(Copy, Clone)]
+enum Overlay {
+    Targets,
+    ShootTarget,
+    Peers,
+    Leads,
+    Costs,
+    CostHeatmap,
+    PositionHeatmap,
+    Trails,
+    BallPath,
+}
+
+const OVERLAYS: [(Overlay, KeyCode, &str, bool); 9] = [
+    (Overlay::Targets, KeyCode::F2, "targets", true),
+    (Overlay::ShootTarget, KeyCode::F3, "shoot target", true),
+    (Overlay::Peers, KeyCode::F4, "peers", true),
+    (Overlay::Leads, KeyCode::F5, "leads", true),
+    (Overlay::Costs, KeyCode::F6, "costs", true),
+    (Overlay::CostHeatmap, KeyCode::F7, "cost heatmap", false),
+    (
+        Overlay::PositionHeatmap,
+        KeyCode::H,
+        "position heatmap",
+        false,
+    ),
+    (Overlay::Trails, KeyCode::M, "movement trails", false),
+    (Overlay::BallPath, KeyCode::B, "ball path", true),
+];
+
+struct RenderOptions {
+    debug_draw: bool,
+    // which overlays debug mode shows, indexed by Overlay
+    overlays: [bool; 9],
+    player_labels: PlayerLabels,
+    accessibility: Accessibility,
+}
+
+impl RenderOptions {
+    fn shows(&self, overlay: Overlay) -> bool {
+        self.debug_draw && self.overlays[overlay as usize]
+    }
 }
 
 #[derive(PartialEq)]
@@ -277,6 +965,29 @@ enum SoundState {
     Play(f32),
 }
 
+// things that happen during a simulation step which other parts of the game (sound, the crowd,
+// etc.) might want to react to; they're cleared at the start of each step
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum GameEvent {
+    Goal { team: usize },
+    // shot is true if the ball was aimed at the goal
+    Kick { team: usize, shot: bool },
+    // a team has won the ball from the other (or picked it up when nobody had it)
+    Possession { team: usize },
+    // a human team used the switch key to control a different player
+    Switch { team: usize },
+    // the kickoff countdown has finished and the kickoff player can go
+    Whistle,
+    // a shot went just wide
+    NearMiss { team: usize },
+    // a player on the team was booked, or sent off if red
+    Card { team: usize, red: bool },
+    // a team got to a shot at their goal before it went in
+    Save { team: usize },
+    // a team brought on a substitute
+    Substitution { team: usize },
+}
+
 #[derive(Debug)]
 enum ShootTarget {
     Goal(Position),
@@ -292,18 +1003,141 @@ impl ShootTarget {
     }
 }
 
+// a kick that nobody has reached yet; whoever gets to it first decides whether a pass was completed
+// or intercepted, or whether a shot was saved, unless it's still loose after KICK_WINDOW_FRAMES
+#[derive(Copy, Clone)]
+struct KickInFlight {
+    from: Entity,
+    team: u8,
+    // at the goal rather than to a teammate
+    shot: bool,
+    steps_left: u32,
+}
+
+// something a loose ball ran into as it moved
+#[derive(Copy, Clone, PartialEq)]
+enum Contact {
+    Post,
+    Net,
+}
+
+// two players who went shoulder to shoulder for a loose ball, the winner first
+#[derive(Copy, Clone)]
+struct Duel {
+    players: [Entity; 2],
+    steps_left: i8,
+}
+
+// the net shaking where the ball went in, harder the faster it was going
 #[derive(Copy, Clone)]
+struct NetRipple {
+    pos: Vector,
+    strength: f32,
+    steps_left: u32,
+}
+
+// who the last goal goes down to, for showing under the goal banner
+#[derive(Clone)]
+struct Scorer {
+    team: u8,
+    squad: Squad,
+    // the ball went in off the other team
+    own_goal: bool,
+}
+
+// the last card the referee showed, for the HUD
+#[derive(Clone)]
+struct Booking {
+    team: u8,
+    squad: Squad,
+    red: bool,
+    steps_left: u32,
+}
+
+// the last substitution made, for the HUD
+#[derive(Clone)]
+struct Substitution {
+    team: u8,
+    on: Squad,
+    off: Squad,
+    steps_left: u32,
+}
+
+// how play starts again once the players have been put back in their places
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Restart {
+    // from the centre spot, after a goal or at the start of the match
+    Kickoff { team: usize },
+    // from in front of the team's own goal, when the ball went in without anyone scoring
+    GoalKick { team: usize },
+}
+
+impl Restart {
+    fn team(&self) -> usize {
+        match self {
+            Self::Kickoff { team } | Self::GoalKick { team } => *team,
+        }
+    }
+
+    // where the ball is put
+    fn spot(&self) -> Vector {
+        match self {
+            Self::Kickoff { .. } => vec2(HALF_LEVEL_W, HALF_LEVEL_H),
+            Self::GoalKick { team: 0 } => vec2(HALF_LEVEL_W, PITCH_BOUNDS_Y.1 - GOAL_KICK_DISTANCE),
+            Self::GoalKick { .. } => vec2(HALF_LEVEL_W, PITCH_BOUNDS_Y.0 + GOAL_KICK_DISTANCE),
+        }
+    }
+
+    // where the player taking it stands, and which one of their team it is (in the same order as
+    // PLAYER_START_POS)
+    fn taker(&self) -> (Vector, usize) {
+        match *self {
+            Self::Kickoff { team } => (self.spot() + vec2(team as f32 * 60.0 - 30.0, 0.0), 0),
+            Self::GoalKick { team } => {
+                let behind = if team == 0 { 30.0 } else { -30.0 };
+                (self.spot() + vec2(0.0, behind), GOAL_KICK_TAKER)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
 enum Mark {
     Goal(Position),
     Player(Entity),
 }
 
+#[derive(Clone)]
 struct Lead(Option<f32>, Option<u8>);
 
+#[derive(Clone)]
 struct TeamInfo {
     controls: Option<Controls>,
     score: u8,
+    // multiplies how fast the team's players run, from its handicap
+    speed: f32,
     active_player: Option<Entity>,
+    // steps for which a recent shoot press will still kick the ball if we gain possession
+    shoot_buffer: u8,
+    // position in the nearest-to-the-ball order reached by cycling, and steps left in which
+    // another press will carry on from there
+    switch_cycle: usize,
+    switch_cycle_timer: u8,
+    // a teammate making a run after we called for the ball
+    run: Option<Run>,
+    // the stick, for knocking the ball on with a double tap, and the player chasing it if they have
+    taps: Taps,
+    knock_on: Option<KnockOn>,
+    // how the computer plays for the team
+    personality: Personality,
+    tactics: Tactics,
+}
+
+#[derive(Copy, Clone)]
+struct Run {
+    player: Entity,
+    dest: Vector,
+    timer: u8,
 }
 
 impl TeamInfo {
@@ -311,7 +1145,16 @@ impl TeamInfo {
         Self {
             controls,
             score: 0,
+            speed: 1.0,
             active_player: None,
+            shoot_buffer: 0,
+            switch_cycle: 0,
+            switch_cycle_timer: 0,
+            run: None,
+            taps: Taps::default(),
+            knock_on: None,
+            personality: Personality::Balanced,
+            tactics: Tactics::new(),
         }
     }
 
@@ -320,18 +1163,107 @@ impl TeamInfo {
     }
 }
 
+// what choosing a player's target needs to know about them, copied out of the world so that
+// targets can be chosen on several threads at once
+struct PlayerState {
+    id: Entity,
+    pos: Vector,
+    team: u8,
+    home: Vector,
+    lead: Option<f32>,
+    mark: Mark,
+    // where they're running to support the attack, if they are
+    support: Option<Vector>,
+    dir: Angle,
+    attributes: Attributes,
+    // the target from last step, which some behaviours leave partly as it was
+    target: Target,
+}
+
+// a ball and the player dribbling it, if anyone is
+#[derive(Copy, Clone)]
+struct BallInPlay {
+    entity: Entity,
+    owner: Option<Entity>,
+}
+
 struct Game {
     difficulty: Difficulty,
-    camera_focus: Vector,
+    // tuning from the balance file, as it was when the match started
+    balance: Balance,
+    camera: Camera,
+    split_screen: bool,
+    team_cameras: [Camera; 2],
+    weather: Weather,
+    lighting: Lighting,
+    switch_mode: SwitchMode,
+    auto_switch: bool,
+    assist: Assist,
+    // player on a human team chasing down the ball carrier, when the assist calls for it
+    presser: Option<Entity>,
+    mutators: Mutators,
+    power_ups: PowerUps,
     world: World,
-    ball: Entity,
-    ball_owner: Option<Entity>,
+    // where the players are, for finding who's nearest to somewhere
+    grid: PlayerGrid,
+    // every ball in play; there's only more than one with the multi-ball mutator
+    balls: Vec<BallInPlay>,
     kickoff_player: Option<Entity>,
+    // steps left before the kickoff player can move
+    kickoff_countdown: u32,
+    // how the next reset starts play again
+    restart: Restart,
     teams: [TeamInfo; 2],
+    // the first team to score this many wins
+    goals_to_win: u8,
     scoring_team: usize,
     score_timer: i32,
-    debug_shoot_target: Option<Vector>,
+    // the profiles of the human teams, to be updated with the result
+    profiles: [Option<usize>; 2],
+    // simulation steps since kickoff, not counting hit-stop
+    steps_played: u32,
+    // the last player to have had the ball, and the teammate whose pass they got it from
+    last_touch: Option<Entity>,
+    assister: Option<Entity>,
+    kick: Option<KickInFlight>,
+    // whoever the last goal went down to, until play starts again
+    scorer: Option<Scorer>,
+    // who's playing in each position for each team, and who's on the bench
+    lineups: [Lineup; 2],
+    // everyone's names and attributes, by team
+    rosters: [Roster; 2],
+    booking: Option<Booking>,
+    substitution: Option<Substitution>,
+    // the time added on for stoppages, and how much longer the board showing it is up for
+    added_seconds: u32,
+    added_board: u32,
+    // a computer team's goalkeeper holding the ball before giving it to someone
+    distribution: Option<Distribution>,
+    net_ripple: Option<NetRipple>,
+    duel: Option<Duel>,
+    stats: MatchStats,
+    // player celebrating a goal, and the centre of the loop they're running around
+    celebration: Option<(Entity, Vector)>,
+    // where a player with the ball is and where they'd kick it
+    debug_shoot_target: Option<(Vector, Vector)>,
     shoot_now: [bool; 2],
+    // shoot presses since the last update, so they aren't missed when a frame doesn't run one
+    shoot_pressed: [bool; 2],
+    switch_pressed: [bool; 2],
+    call_pressed: [bool; 2],
+    tackle_pressed: [bool; 2],
+    // where the mouse is pointing in level coordinates
+    cursor: Vector,
+    // movement that steers a team instead of the keyboard: the on-screen joystick while it's held
+    // for the first team, or an input script in the tests
+    sticks: [Option<Vector>; 2],
+    // set when this is a training session rather than a match
+    training: Option<Training>,
+    events: Vec<GameEvent>,
+    // the AI's decisions, when asked for on the command line
+    log: SimLog,
+    // computer players stand still when this is turned off from the console
+    ai_enabled: bool,
 }
 
 impl Game {
@@ -342,285 +1274,1442 @@ impl Game {
         let ball = world.spawn(eb.build());
         let mut me = Self {
             difficulty,
-            camera_focus: vec2(HALF_LEVEL_W as f32, HALF_LEVEL_H as f32),
+            balance: balance::current(),
+            camera: Camera::new(vec2(HALF_LEVEL_W, HALF_LEVEL_H), vec2(WIDTH, HEIGHT)),
+            split_screen: false,
+            team_cameras: [
+                Camera::new(vec2(HALF_LEVEL_W, HALF_LEVEL_H), vec2(WIDTH / 2.0, HEIGHT)),
+                Camera::new(vec2(HALF_LEVEL_W, HALF_LEVEL_H), vec2(WIDTH / 2.0, HEIGHT)),
+            ],
+            weather: Weather::Clear,
+            lighting: Lighting::Day,
+            switch_mode: SwitchMode::Nearest,
+            auto_switch: false,
+            assist: Assist::AutoPosition,
+            presser: None,
+            mutators: Mutators::new(),
+            power_ups: PowerUps::new(),
             world,
-            ball,
-            ball_owner: None,
+            grid: PlayerGrid::new(),
+            balls: vec![BallInPlay {
+                entity: ball,
+                owner: None,
+            }],
             kickoff_player: None,
+            kickoff_countdown: 0,
+            restart: Restart::Kickoff { team: 0 },
             teams: [TeamInfo::new(None), TeamInfo::new(None)],
+            goals_to_win: GOALS_TO_WIN,
             scoring_team: 1,
             score_timer: 0,
+            profiles: [None, None],
+            steps_played: 0,
+            last_touch: None,
+            assister: None,
+            kick: None,
+            scorer: None,
+            lineups: [Lineup::new(), Lineup::new()],
+            rosters: [Roster::default_for(0), Roster::default_for(1)],
+            booking: None,
+            substitution: None,
+            added_seconds: 0,
+            added_board: 0,
+            distribution: None,
+            net_ripple: None,
+            duel: None,
+            stats: MatchStats::new(),
+            celebration: None,
             debug_shoot_target: None,
             shoot_now: [false, false],
+            shoot_pressed: [false, false],
+            switch_pressed: [false, false],
+            call_pressed: [false, false],
+            tackle_pressed: [false, false],
+            cursor: vec2(HALF_LEVEL_W, HALF_LEVEL_H),
+            sticks: [None, None],
+            training: None,
+            events: Vec::new(),
+            log: SimLog::current(),
+            ai_enabled: true,
         };
         me.add_players();
         me
     }
 
-    fn reset(&mut self) {
-        self.world.clear();
-        let mut eb = EntityBuilder::new();
-        build_ball(&mut eb);
-        self.ball = self.world.spawn(eb.build());
-        self.add_players();
-        self.ball_owner = None;
-        self.camera_focus = vec2(HALF_LEVEL_W as f32, HALF_LEVEL_H as f32);
+    fn set_camera_effects(&mut self, enabled: bool) {
+        self.camera.effects = enabled;
+        for camera in &mut self.team_cameras {
+            camera.effects = enabled;
+        }
     }
 
-    fn check_goals(&mut self) {
-        let ball_y = self.world.get_mut::<Position>(self.ball).unwrap().0.y;
-        self.score_timer -= 1;
-        if self.score_timer == 0 {
-            self.reset();
-        } else if self.score_timer < 0 && (ball_y - HALF_LEVEL_H).abs() > HALF_PITCH_H {
-            self.scoring_team = if ball_y < HALF_LEVEL_H { 0 } else { 1 };
-            self.teams[self.scoring_team].score += 1;
-            self.score_timer = GOAL_FRAMES;
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.weather = settings.weather;
+        self.lighting = settings.lighting.unwrap_or_else(Lighting::random);
+        self.switch_mode = settings.switch_mode;
+        self.auto_switch = settings.auto_switch;
+        self.assist = settings.assist;
+        self.goals_to_win = settings.goals_to_win;
+        for t in 0..=1 {
+            self.profiles[t] = settings.profiles[t].filter(|_| self.teams[t].human());
+        }
+        for (team, handicap) in self.teams.iter_mut().zip(settings.handicaps) {
+            // a head start can't win the match outright
+            team.score = handicap.head_start.min(self.goals_to_win - 1);
+            team.speed = handicap.speed;
+        }
+        for (team, tactics) in self.teams.iter_mut().zip(settings.tactics) {
+            team.tactics = tactics;
         }
+        // the number of balls may have changed
+        self.mutators = settings.mutators;
+        self.reset();
     }
 
-    fn add_players(&mut self) {
-        let mut ids = Vec::new();
-        let mut eb = EntityBuilder::new();
-        for (x, y) in PLAYER_START_POS {
-            build_player(&mut eb, x, y, 550., 0);
-            ids.push(self.world.spawn(eb.build()));
-            build_player(&mut eb, LEVEL_W - x, LEVEL_H - y, 150., 1);
-            ids.push(self.world.spawn(eb.build()));
+    // turn this into a training session for team 0, starting from kickoff
+    fn start_training(&mut self, setup: TrainingSetup) {
+        self.training = Some(Training::new(setup));
+        self.score_timer = 0;
+        self.restart = Restart::Kickoff { team: 0 };
+        self.reset();
+    }
+
+    // leave the first ball lying still at pos with nobody on it
+    fn drop_ball(&mut self, pos: Vector) -> Result<(), GameError> {
+        let ball = self.balls[0].entity;
+        if let Some(owner) = self.balls[0].owner.take() {
+            self.world.component_mut::<Timer>(owner)?.0 = 60;
         }
-        for ii in 0..14 {
-            self.world.insert_one(ids[ii], Peer(ids[13 - ii])).unwrap();
-            self.world
-                .insert_one(ids[ii], Mark::Player(ids[13 - ii]))
-                .unwrap();
+        self.kickoff_player = None;
+        self.world.component_mut::<Position>(ball)?.0 = pos;
+        self.world.add_component(ball, Vector::zero())
+    }
+
+    fn ball_pos(&self, ball: BallInPlay) -> Result<Vector, GameError> {
+        Ok(self.world.component::<Position>(ball.entity)?.0)
+    }
+
+    // where each of the balls is, in the same order as balls
+    fn ball_positions(&self) -> Result<Vec<Vector>, GameError> {
+        self.balls.iter().map(|b| self.ball_pos(*b)).collect()
+    }
+
+    fn nearest_ball(&self, pos: Vector) -> Result<BallInPlay, GameError> {
+        let positions = self.ball_positions()?;
+        let nearest = (0..self.balls.len())
+            .min_by(|a, b| cmp_dist(positions[*a], positions[*b], pos))
+            .unwrap();
+        Ok(self.balls[nearest])
+    }
+
+    // how the simulation log refers to a player
+    fn player_name(&self, id: Entity) -> String {
+        match (self.world.get::<Team>(id), self.world.get::<Squad>(id)) {
+            (Ok(team), Ok(squad)) => format!("team {} #{}", team.0, squad.number),
+            _ => format!("{:?}", id),
         }
-        self.teams[0].active_player = Some(ids[0]);
-        self.teams[1].active_player = Some(ids[1]);
-        let kickoff_team = 1 - self.scoring_team;
-        let kp = ids[kickoff_team];
-        self.kickoff_player = Some(kp);
-        self.world.get_mut::<Position>(kp).unwrap().0 = vec2(
-            HALF_LEVEL_W - 30.0 + kickoff_team as f32 * 60.0,
-            HALF_LEVEL_H,
-        );
     }
 
-    fn update(&mut self) {
-        for (_, t) in &mut self.world.query::<&mut Timer>() {
-            if t.0 > 0 {
-                t.0 -= 1
+    fn target_name(&self, target: &ShootTarget) -> String {
+        match target {
+            ShootTarget::Goal(_) => "the goal".to_owned(),
+            ShootTarget::Player(_, id) => self.player_name(*id),
+        }
+    }
+
+    // true if the player is dribbling any of the balls
+    fn has_ball(&self, player: Entity) -> bool {
+        self.balls.iter().any(|b| b.owner == Some(player))
+    }
+
+    // the ball a player at pos should be concerned with: the one they have, or else the nearest
+    fn ball_for(&self, player: Entity, pos: Vector) -> Result<BallInPlay, GameError> {
+        match self.balls.iter().find(|b| b.owner == Some(player)) {
+            Some(ball) => Ok(*ball),
+            None => self.nearest_ball(pos),
+        }
+    }
+
+    fn team_has_ball(&self, team: usize) -> Result<bool, GameError> {
+        for id in self.balls.iter().filter_map(|b| b.owner) {
+            if self.world.component::<Team>(id)?.0 as usize == team {
+                return Ok(true);
             }
         }
-        self.check_goals();
-        self.set_behaviours();
-        self.set_player_targets();
-        update_players(&mut self.world, self.ball);
-        self.update_ball();
-        self.switch_players();
+        Ok(false)
     }
 
-    fn set_behaviours(&mut self) {
-        for (_, (peer, mark, lead)) in self.world.query_mut::<(&Peer, &mut Mark, &mut Lead)>() {
-            *mark = Mark::Player(peer.0);
-            *lead = Lead(None, None);
+    fn reset(&mut self) {
+        self.world.clear();
+        let mut eb = EntityBuilder::new();
+        self.balls.clear();
+        for _ in 0..self.mutators.ball_count() {
+            build_ball(&mut eb);
+            let entity = self.world.spawn(eb.build());
+            self.balls.push(BallInPlay {
+                entity,
+                owner: None,
+            });
         }
-        match self.ball_owner {
-            None => {}
-            Some(owner_id) => {
-                let ball_owner_pos = self.world.get::<Position>(owner_id).unwrap().0;
-                let defending_team = 1 - self.world.get::<Team>(owner_id).unwrap().0;
-                let goal = vec2(HALF_LEVEL_W, (1 - defending_team) as f32 * LEVEL_H);
-                if self.difficulty.goalie_enabled {
-                    // find the nearest player to the goal on each team and make them mark it
-                    let goalie = self
-                        .world
-                        .query::<(&Team, &Position)>()
-                        .iter()
-                        .filter(|(_, (t, _))| t.0 == defending_team)
-                        .min_by(|(_, (_, p1)), (_, (_, p2))| cmp_dist(p1.0, p2.0, goal))
-                        .unwrap()
-                        .0;
-                    let ball_owner_peer = self.world.get::<Peer>(owner_id).unwrap().0;
-                    let goalie_mark = *self.world.get_mut::<Mark>(goalie).unwrap();
-                    *self.world.get_mut::<Mark>(ball_owner_peer).unwrap() = goalie_mark;
-                    *self.world.get_mut::<Mark>(goalie).unwrap() = Mark::Goal(Position(goal));
-                }
-                // find the two leads
-                let mut players = self
-                    .world
-                    .query::<(&Team, &Timer, &Mark, &Position)>()
-                    .iter()
-                    .filter(|(id, (team, timer, mark, _))| {
-                        let dt = &self.teams[defending_team as usize];
-                        team.0 == defending_team
-                            && timer.0 <= 0
-                            && (!dt.human()
-                                || dt.active_player.is_none()
-                                || dt.active_player.unwrap() != *id)
-                            && match *mark {
-                                Mark::Player(_) => true,
-                                Mark::Goal(_) => false,
-                            }
-                    })
-                    .map(|(id, (_, _, _, pos))| (id, pos.0))
-                    .collect::<Vec<_>>();
-                players.sort_by(|a, b| cmp_dist(a.1, b.1, ball_owner_pos));
-                let (upfield, downfield): (Vec<(Entity, Vector)>, Vec<(Entity, Vector)>) =
-                    players.iter().partition(|(_, pos)| {
-                        if defending_team == 1 {
-                            pos.y > ball_owner_pos.y
-                        } else {
-                            pos.y < ball_owner_pos.y
-                        }
-                    });
-                let mut upfield: Vec<_> = upfield.into_iter().map(Some).collect();
-                upfield.extend([None, None]);
-                let mut downfield: Vec<_> = downfield.into_iter().map(Some).collect();
-                downfield.extend([None, None]);
-                use std::iter::once;
-                let alternating: Vec<(Entity, Vector)> = upfield
-                    .into_iter()
-                    .zip(downfield.into_iter())
-                    .flat_map(|tup| once(tup.0).chain(once(tup.1)))
-                    .filter_map(|x| x)
-                    .collect();
-                for (n, (id, _)) in alternating.iter().enumerate() {
-                    let mut lead = self.world.get_mut::<Lead>(*id).unwrap();
-                    lead.1 = Some(n as u8);
-                    if n == 0 {
-                        lead.0 = Some(LEAD_DISTANCE_1);
-                    }
-                    if n == 1 && self.difficulty.second_lead_enabled {
-                        lead.0 = Some(LEAD_DISTANCE_2);
-                    }
+        for t in 0..=1 {
+            if self.training.is_none() && !self.teams[t].human() {
+                // a computer team takes off anyone who's injured while play is stopped
+                while let Some(position) = self.lineups[t].injured_position() {
+                    // nobody's on the pitch to update, so this can't fail
+                    self.substitute(t, position, 0).unwrap();
                 }
             }
         }
+        self.add_players();
+        self.last_touch = None;
+        self.assister = None;
+        self.kick = None;
+        self.scorer = None;
+        self.celebration = None;
+        self.presser = None;
+        self.distribution = None;
+        for team in &mut self.teams {
+            team.run = None;
+            team.knock_on = None;
+        }
+        self.camera.focus = self.restart.spot();
+        for camera in &mut self.team_cameras {
+            camera.focus = vec2(HALF_LEVEL_W, HALF_LEVEL_H);
+        }
     }
 
-    fn set_player_targets(&mut self) {
-        for (id, (pos, team, home, lead, mark, anim, target)) in &mut self.world.query::<(
-            &Position,
-            &Team,
-            &Home,
-            &Lead,
-            &Mark,
-            &Animation,
-            &mut Target,
-        )>() {
-            // if we're pre-kickoff and not the kickoff player, just stand and wait
-            if self.kickoff_player.is_some() && self.kickoff_player.unwrap() != id {
-                target.pos = pos.0;
-                continue;
-            }
-            let my_team = &self.teams[team.0 as usize];
-            let i_am_active_player = match my_team.active_player {
-                None => false,
-                Some(aid) => aid == id,
-            };
-            let ball_pos = self.world.get::<Position>(self.ball).unwrap().0;
-            let active = (ball_pos.y - pos.0.y).abs() < 400.0;
-            // choose one of the following behaviours
-            // if we're the currently-controlled player on a human team, respond to controls
-            if my_team.human() && i_am_active_player {
-                if self.ball_owner == Some(id) {
-                    target.speed = HUMAN_PLAYER_WITH_BALL_SPEED;
+    fn check_goals(&mut self) -> Result<(), GameError> {
+        // with more than one ball, whichever goes in first counts
+        let in_goal = self.ball_positions()?.into_iter().find(|pos| in_net(*pos));
+        self.score_timer -= 1;
+        if self.score_timer == 0 {
+            self.reset();
+        } else if let Some(ball_pos) = in_goal.filter(|_| self.score_timer < 0) {
+            let ball_y = ball_pos.y;
+            if let Some(training) = &mut self.training {
+                // in training the ball just comes back for another go, and the score is left
+                // alone so team 0 always kicks off and the match never ends
+                if ball_y < HALF_LEVEL_H {
+                    training.goal(ball_pos.x);
+                    self.events.push(GameEvent::Goal { team: 0 });
+                    self.score_timer = GOAL_FRAMES;
+                    self.restart = Restart::Kickoff { team: 0 };
                 } else {
-                    target.speed = HUMAN_PLAYER_WITHOUT_BALL_SPEED;
+                    self.restart = Restart::GoalKick { team: 0 };
+                    self.reset();
                 }
-                target.pos = pos.0 + my_team.controls.unwrap().movement();
-                continue;
+                return Ok(());
             }
-            // set the default behaviour
-            target.pos = home.0;
-            target.speed = PLAYER_DEFAULT_SPEED;
-            match self.ball_owner {
-                Some(owner_id) if owner_id == id => {
-                    // if we're computer-controlled and have the ball, do the cost function thing
-                    let best_dest = (-2..=2)
-                        .map(|d| {
-                            let dest = pos.0 + Angle::to_vec(Angle(anim.dir.0 + d)) * 3.0;
-                            let cost = self.cost(dest, team.0, d.abs() as f32);
-                            (cost, dest)
-                        })
-                        .min_by(|a, b| (a.0).partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
-                        .unwrap()
-                        .1;
-                    target.pos = best_dest;
-                    target.speed = CPU_PLAYER_WITH_BALL_BASE_SPEED + self.difficulty.speed_boost;
-                }
-                Some(owner_id) => {
-                    if team.0 == self.world.get::<Team>(owner_id).unwrap().0 {
-                        // if my team has the ball and I'm active, go somewhere useful
-                        if active {
-                            let direction = if team.0 == 0 { -1. } else { 1. };
-                            target.pos = (home.0 + (ball_pos + vec2(0.0, 400.0 * direction))) / 2.0;
-                        }
+            self.scoring_team = if ball_y < HALF_LEVEL_H { 0 } else { 1 };
+            self.teams[self.scoring_team].score += 1;
+            self.restart = Restart::Kickoff {
+                team: 1 - self.scoring_team,
+            };
+            self.score_timer = GOAL_FRAMES;
+            self.events.push(GameEvent::Goal {
+                team: self.scoring_team,
+            });
+            // the goal goes down to whoever touched the ball last, and if that was someone on the
+            // other team it's an own goal, with no assist
+            self.scorer = match self.last_touch {
+                Some(id) => {
+                    let team = self.world.component::<Team>(id)?.0;
+                    let own_goal = team as usize != self.scoring_team;
+                    if own_goal {
+                        self.stats_for(id)?.own_goals += 1;
                     } else {
-                        match lead.0 {
-                            Some(lead_dist) => {
-                                // if other team has the ball and I'm a lead, try to intercept
-                                let ball_owner_ref = self.world.entity(owner_id).unwrap();
-                                let ball_owner_pos = ball_owner_ref.get::<Position>().unwrap().0;
-                                let ball_owner_dir = ball_owner_ref.get::<Animation>().unwrap().dir;
-                                let mut targ =
-                                    ball_owner_pos + Angle::to_vec(ball_owner_dir) * lead_dist;
-                                targ.x = targ.x.max(AI_MIN_X).min(AI_MAX_X);
-                                targ.y = targ.y.max(AI_MIN_Y).min(AI_MAX_Y);
-                                let other_team = &self.teams[1 - team.0 as usize];
-                                target.pos = targ;
-                                target.speed = LEAD_PLAYER_BASE_SPEED
-                                    + if other_team.human() {
-                                        self.difficulty.speed_boost
-                                    } else {
-                                        0.
-                                    };
-                            }
-                            None => {
-                                let mark_pos = match mark {
-                                    Mark::Goal(goal_pos) => goal_pos.0,
-                                    Mark::Player(mark_id) => {
-                                        self.world.get::<Position>(*mark_id).unwrap().0
-                                    }
-                                };
-                                // if our mark is inactive, do nothing (run towards home)
-                                // if active, human team players just run towards the ball, computer
-                                // players mark the designated target
-                                if (mark_pos.y - ball_pos.y).abs() < 400. {
-                                    if my_team.human() {
-                                        target.pos = ball_pos;
-                                    } else {
-                                        let mark_to_ball_vec = ball_pos - mark_pos;
-                                        let dist_from_mark = match mark {
-                                            Mark::Goal(_) => mark_to_ball_vec.length().min(150.0),
-                                            Mark::Player(_) => mark_to_ball_vec.length() / 2.0,
-                                        };
-                                        target.pos = mark_pos
-                                            + mark_to_ball_vec.normalize() * dist_from_mark;
-                                    }
-                                }
-                            }
+                        self.stats_for(id)?.goals += 1;
+                        if let Some(assister) = self.assister {
+                            self.stats_for(assister)?.assists += 1;
                         }
                     }
+                    let squad = (*self.world.component::<Squad>(id)?).clone();
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Info,
+                        "{}{}",
+                        self.player_name(id),
+                        if own_goal {
+                            " scores an own goal"
+                        } else {
+                            " scores"
+                        }
+                    );
+                    Some(Scorer {
+                        team,
+                        squad,
+                        own_goal,
+                    })
                 }
-                None => {
-                    // if no-one has the ball and I'm active, try to intercept the ball
-                    let mut sim_ball_pos = ball_pos;
-                    let mut sim_ball_vel = *self.world.get::<Vector>(self.ball).unwrap();
-                    let mut frame = 0.0;
-                    while (sim_ball_pos - pos.0).length()
-                        > PLAYER_INTERCEPT_BALL_SPEED * frame + DRIBBLE_DIST_X
-                        && sim_ball_vel.length() > 0.5
-                    {
-                        sim_ball_pos += sim_ball_vel;
-                        sim_ball_vel *= DRAG;
-                        frame += 1.0;
-                    }
-                    target.pos = sim_ball_pos;
-                    target.speed = PLAYER_INTERCEPT_BALL_SPEED;
+                None => None,
+            };
+            // nobody celebrates an own goal
+            self.celebration = self.last_touch.and_then(|id| {
+                let team = self.world.get::<Team>(id).ok()?.0;
+                let pos = self.world.get::<Position>(id).ok()?.0;
+                let centre = vec2(
+                    pos.x
+                        .clamp(AI_MIN_X + CELEBRATION_RADIUS, AI_MAX_X - CELEBRATION_RADIUS),
+                    pos.y
+                        .clamp(AI_MIN_Y + CELEBRATION_RADIUS, AI_MAX_Y - CELEBRATION_RADIUS),
+                );
+                (team as usize == self.scoring_team).then_some((id, centre))
+            });
+        }
+        Ok(())
+    }
+
+    fn stats_for(&mut self, player: Entity) -> Result<&mut stats::PlayerStats, GameError> {
+        let team = self.world.component::<Team>(player)?.0;
+        let squad = self.world.component::<Squad>(player)?;
+        Ok(self.stats.get_mut(team, &squad))
+    }
+
+    // whether a tackle from pos on the player with the ball comes from behind them
+    fn tackled_from_behind(&self, owner: Entity, pos: Vector) -> Result<bool, GameError> {
+        let owner_pos = self.world.component::<Position>(owner)?.0;
+        let facing = Angle::to_vec(self.world.component::<Animation>(owner)?.dir);
+        let towards = pos - owner_pos;
+        Ok(towards.length() > 0.0 && facing.dot(towards.normalize()) < FOUL_ANGLE_COS)
+    }
+
+    fn hard_tackle(&self, id: Entity) -> Result<bool, GameError> {
+        Ok(self.world.component::<Momentum>(id)?.0.length() > HARD_TACKLE_SPEED)
+    }
+
+    // the tackler has to back off like someone who's just lost the ball, and may be booked
+    fn foul(&mut self, id: Entity) -> Result<(), GameError> {
+        self.world.component_mut::<Timer>(id)?.0 = 60;
+        self.add_stoppage(FOUL_STOPPAGE);
+        let hard = self.hard_tackle(id)?;
+        let stats = self.stats_for(id)?;
+        stats.fouls += 1;
+        if !hard && !stats.fouls.is_multiple_of(FOULS_PER_BOOKING) {
+            sim_log!(self.log, Ball, Info, "{} fouls", self.player_name(id));
+            return Ok(());
+        }
+        stats.bookings += 1;
+        let second_booking = stats.bookings >= 2;
+        self.add_stoppage(BOOKING_STOPPAGE);
+        let team = self.world.component::<Team>(id)?.0;
+        let players = self
+            .grid
+            .players()
+            .iter()
+            .filter(|p| p.team == team)
+            .count();
+        let red = second_booking && players > MIN_PLAYERS;
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "{} is {}",
+            self.player_name(id),
+            if red { "sent off" } else { "booked" }
+        );
+        self.booking = Some(Booking {
+            team,
+            squad: (*self.world.component::<Squad>(id)?).clone(),
+            red,
+            steps_left: BOOKING_FRAMES,
+        });
+        self.events.push(GameEvent::Card {
+            team: team as usize,
+            red,
+        });
+        if red {
+            let member = self.world.component::<Squad>(id)?.member();
+            self.lineups[team as usize].send_off(member);
+            self.remove_player(id)?;
+        }
+        Ok(())
+    }
+
+    // a hard tackle might hurt the player with the ball, who runs slower from then on
+    fn injure(&mut self, id: Entity) -> Result<(), GameError> {
+        if self.world.get::<Injured>(id).is_ok() {
+            return Ok(());
+        }
+        let team = self.world.component::<Team>(id)?.0;
+        let member = self.world.component::<Squad>(id)?.member();
+        self.lineups[team as usize].injure(member);
+        self.world.add_component(id, Injured)?;
+        self.add_stoppage(INJURY_STOPPAGE);
+        sim_log!(self.log, Ball, Info, "{} is injured", self.player_name(id));
+        Ok(())
+    }
+
+    fn add_stoppage(&mut self, seconds: u32) {
+        let minutes = self.added_minutes();
+        self.added_seconds += seconds;
+        if self.added_minutes() > minutes {
+            self.added_board = ADDED_TIME_FRAMES;
+        }
+    }
+
+    // the added time as the fourth official shows it, in whole minutes rounded up
+    fn added_minutes(&self) -> u32 {
+        self.added_seconds.div_ceil(60)
+    }
+
+    // bring on a substitute for whoever's playing in a position; they take over right where the
+    // player coming off is, or are there at the next kickoff if that player isn't on the pitch
+    fn substitute(
+        &mut self,
+        team: usize,
+        position: usize,
+        bench_index: usize,
+    ) -> Result<(), GameError> {
+        let lineup = &mut self.lineups[team];
+        let on_member = lineup.bench()[bench_index];
+        let on = self.rosters[team].squad(on_member);
+        let off = self.rosters[team].squad(lineup.substitute(position, bench_index));
+        let player = self
+            .world
+            .query::<(&Team, &Squad)>()
+            .iter()
+            .find(|(_, (t, squad))| t.0 as usize == team && squad.number == off.number)
+            .map(|(id, _)| id);
+        if let Some(id) = player {
+            *self.world.component_mut::<Squad>(id)? = on.clone();
+            *self.world.component_mut::<Attributes>(id)? = self.rosters[team].attributes(on_member);
+            if self.world.get::<Injured>(id).is_ok() {
+                self.world.take_component::<Injured>(id)?;
+            }
+        }
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "team {} brings on #{} for #{}",
+            team,
+            on.number,
+            off.number
+        );
+        self.add_stoppage(SUBSTITUTION_STOPPAGE);
+        self.substitution = Some(Substitution {
+            team: team as u8,
+            on,
+            off,
+            steps_left: SUBSTITUTION_FRAMES,
+        });
+        self.events.push(GameEvent::Substitution { team });
+        Ok(())
+    }
+
+    // take a player off the pitch: the teammate whose position is nearest theirs moves across to
+    // cover some of the space they've left, and whoever was marking them drops back to guard the
+    // goal
+    fn remove_player(&mut self, id: Entity) -> Result<(), GameError> {
+        let team = self.world.component::<Team>(id)?.0;
+        let home = self.world.component::<Home>(id)?.0;
+        let cover = self
+            .world
+            .query::<(&Team, &Home)>()
+            .iter()
+            .filter(|(other, (t, _))| *other != id && t.0 == team)
+            .min_by(|(_, (_, h1)), (_, (_, h2))| cmp_dist(h1.0, h2.0, home))
+            .map(|(other, _)| other);
+        if let Some(cover) = cover {
+            let mut cover_home = self.world.component_mut::<Home>(cover)?;
+            cover_home.0 = (cover_home.0 + home) / 2.0;
+        }
+        if let Ok(peer) = self.world.get::<Peer>(id).map(|p| p.0) {
+            // the goal the peer's team defends
+            let goal = vec2(HALF_LEVEL_W, team as f32 * LEVEL_H);
+            self.world.take_component::<Peer>(peer)?;
+            *self.world.component_mut::<Mark>(peer)? = Mark::Goal(Position(goal));
+        }
+        for b in 0..self.balls.len() {
+            if self.balls[b].owner == Some(id) {
+                self.balls[b].owner = None;
+                self.world
+                    .add_component(self.balls[b].entity, Vector::zero())?;
+            }
+        }
+        let forget = |player: &mut Option<Entity>| {
+            if *player == Some(id) {
+                *player = None;
+            }
+        };
+        forget(&mut self.kickoff_player);
+        forget(&mut self.last_touch);
+        forget(&mut self.assister);
+        forget(&mut self.presser);
+        self.kick = self.kick.filter(|kick| kick.from != id);
+        self.celebration = self.celebration.filter(|(player, _)| *player != id);
+        for t in &mut self.teams {
+            t.run = t.run.take().filter(|run| run.player != id);
+        }
+        self.world
+            .despawn(id)
+            .map_err(|_| GameError::NoSuchEntity(id))?;
+        self.grid.rebuild(&self.world);
+        let t = team as usize;
+        if self.teams[t].active_player == Some(id) {
+            self.teams[t].active_player = None;
+            self.teams[t].active_player = self.players_nearest_ball(t)?.first().copied();
+        }
+        Ok(())
+    }
+
+    // for the match history, once the match is over
+    fn result(&self) -> MatchResult {
+        MatchResult {
+            date: history::today(),
+            mode: if self.teams.iter().all(TeamInfo::human) {
+                "two_players"
+            } else {
+                self.difficulty.level.key()
+            },
+            score: (self.teams[0].score, self.teams[1].score),
+            seconds: (self.steps_played as f32 * TIMESTEP) as u32,
+            passes: [0, 1].map(|team| self.stats.passes(team)),
+        }
+    }
+
+    // true once both teams need just one more goal to win
+    fn sudden_death(&self) -> bool {
+        self.training.is_none() && self.teams.iter().all(|t| t.score + 1 == self.goals_to_win)
+    }
+
+    fn celebrate(&mut self) -> Result<(), GameError> {
+        if let Some((id, centre)) = self.celebration {
+            let angle = (GOAL_FRAMES - self.score_timer) as f32 / CELEBRATION_LAP_FRAMES * 2.0 * PI;
+            let mut target = self.world.component_mut::<Target>(id)?;
+            target.pos = centre + vec2(angle.cos(), angle.sin()) * CELEBRATION_RADIUS;
+            target.speed = CELEBRATION_SPEED;
+            target.behaviour = Behaviour::Celebrate;
+        }
+        Ok(())
+    }
+
+    fn add_players(&mut self) {
+        let mut ids = Vec::new();
+        let mut eb = EntityBuilder::new();
+        for (n, (x, y)) in PLAYER_START_POS.iter().copied().enumerate() {
+            for (t, lineup) in self.lineups.iter().enumerate() {
+                let (x, y) = if t == 0 {
+                    (x, y)
+                } else {
+                    (LEVEL_W - x, LEVEL_H - y)
+                };
+                build_player(&mut eb, x, y, t as u8);
+                let member = lineup.member(n);
+                eb.add(self.rosters[t].squad(member));
+                eb.add(self.rosters[t].attributes(member));
+                if lineup.injured(member) {
+                    eb.add(Injured);
                 }
+                ids.push(self.world.spawn(eb.build()));
+            }
+        }
+        for ii in 0..14 {
+            self.world.insert_one(ids[ii], Peer(ids[13 - ii])).unwrap();
+            self.world
+                .insert_one(ids[ii], Mark::Player(ids[13 - ii]))
+                .unwrap();
+        }
+        self.teams[0].active_player = Some(ids[0]);
+        self.teams[1].active_player = Some(ids[1]);
+        if self.training.as_ref().is_some_and(|t| !t.setup.dummies) {
+            // training without dummies takes the other team off altogether
+            for ii in (0..14).step_by(2) {
+                self.world.despawn(ids[ii + 1]).unwrap();
+                self.world.remove_one::<Peer>(ids[ii]).unwrap();
+            }
+            self.teams[1].active_player = None;
+        }
+        for (ii, id) in ids.iter().enumerate() {
+            if self.lineups[ii % 2].empty(ii / 2) {
+                self.remove_player(*id).unwrap();
+            }
+        }
+        let kickoff_team = self.restart.team();
+        let (taker_pos, taker) = self.restart.taker();
+        // if the usual taker has been sent off, someone else steps up
+        let kp = std::iter::once(taker)
+            .chain(0..7)
+            .map(|n| ids[n * 2 + kickoff_team])
+            .find(|id| self.world.contains(*id))
+            .unwrap();
+        self.kickoff_player = Some(kp);
+        self.kickoff_countdown = KICKOFF_COUNTDOWN_FRAMES;
+        self.world.get_mut::<Position>(kp).unwrap().0 = taker_pos;
+        self.world
+            .get_mut::<Position>(self.balls[0].entity)
+            .unwrap()
+            .0 = self.restart.spot();
+        // at a kickoff the other team has to stand back, so move anyone in the centre circle out to
+        // its edge
+        if let Some(kept_out) = self.kept_out_of_centre() {
+            for (_, (pos, team)) in self.world.query_mut::<(&mut Position, &Team)>() {
+                if team.0 == kept_out {
+                    pos.0 = outside_centre_circle(pos.0, team.0);
+                }
+            }
+        }
+        // a second ball starts in the half of the team that isn't kicking off
+        if let Some(ball) = self.balls.get(1) {
+            self.world.get_mut::<Position>(ball.entity).unwrap().0 = vec2(
+                HALF_LEVEL_W,
+                HALF_LEVEL_H + 100.0 * (2.0 * kickoff_team as f32 - 1.0),
+            );
+        }
+    }
+
+    fn update(&mut self) {
+        if let Err(e) = self.step() {
+            self.recover(e);
+        }
+    }
+
+    // something the simulation relies on turned out not to hold: rather than crash mid-game, log
+    // it and start again from kickoff with the score as it was
+    fn recover(&mut self, e: GameError) {
+        error!("{}; restarting from kickoff", e);
+        self.score_timer = 0;
+        self.shoot_pressed = [false, false];
+        self.switch_pressed = [false, false];
+        self.call_pressed = [false, false];
+        self.tackle_pressed = [false, false];
+        self.restart = Restart::Kickoff {
+            team: self.restart.team(),
+        };
+        self.reset();
+    }
+
+    fn step(&mut self) -> Result<(), GameError> {
+        self.events.clear();
+        if self.camera.hold() {
+            return Ok(());
+        }
+        self.steps_played += 1;
+        self.log.step = self.steps_played;
+        for (name, system) in SYSTEMS {
+            system(self).inspect_err(|_| error!("{} system failed", name))?;
+        }
+        Ok(())
+    }
+
+    fn count_down_timers(&mut self) -> Result<(), GameError> {
+        for (_, t) in &mut self.world.query::<&mut Timer>() {
+            if t.0 > 0 {
+                t.0 -= 1
+            }
+        }
+        if let Some(kick) = &mut self.kick {
+            kick.steps_left -= 1;
+            if kick.steps_left == 0 {
+                self.kick = None;
+            }
+        }
+        if let Some(booking) = &mut self.booking {
+            booking.steps_left -= 1;
+            if booking.steps_left == 0 {
+                self.booking = None;
+            }
+        }
+        if let Some(substitution) = &mut self.substitution {
+            substitution.steps_left -= 1;
+            if substitution.steps_left == 0 {
+                self.substitution = None;
+            }
+        }
+        self.added_board = self.added_board.saturating_sub(1);
+        for team in &mut self.teams {
+            if team.knock_on.as_mut().is_some_and(|k| !k.count_down()) {
+                team.knock_on = None;
+            }
+        }
+        if let Some(duel) = &mut self.duel {
+            duel.steps_left -= 1;
+            if duel.steps_left == 0 {
+                self.duel = None;
+            }
+        }
+        if let Some(ripple) = &mut self.net_ripple {
+            ripple.steps_left -= 1;
+            if ripple.steps_left == 0 {
+                self.net_ripple = None;
+            }
+        }
+        if self.kickoff_player.is_some() && self.kickoff_countdown > 0 {
+            self.kickoff_countdown -= 1;
+            if self.kickoff_countdown == 0 {
+                self.events.push(GameEvent::Whistle);
+            }
+        }
+        Ok(())
+    }
+
+    // the number to show while counting down to a kickoff
+    fn kickoff_count(&self) -> Option<u32> {
+        (self.kickoff_player.is_some() && self.kickoff_countdown > 0)
+            .then(|| self.kickoff_countdown.div_ceil(KICKOFF_COUNT_FRAMES))
+    }
+
+    fn rebuild_grid(&mut self) -> Result<(), GameError> {
+        self.grid.rebuild(&self.world);
+        Ok(())
+    }
+
+    fn update_power_ups(&mut self) -> Result<(), GameError> {
+        if self.mutators.power_ups && self.training.is_none() {
+            self.power_ups.update(&mut self.world);
+        }
+        Ok(())
+    }
+
+    fn move_players(&mut self) -> Result<(), GameError> {
+        let ball_positions = self.ball_positions()?;
+        let speeds = [0, 1].map(|t| self.mutators.speed * self.teams[t].speed);
+        let kept_out = self.kept_out_of_centre();
+        update_players(
+            &mut self.world,
+            &ball_positions,
+            self.weather.player_grip(),
+            speeds,
+            kept_out,
+            &mut self.stats,
+        );
+        Ok(())
+    }
+
+    fn update_balls(&mut self) -> Result<(), GameError> {
+        self.shoot_now = [false, false];
+        self.debug_shoot_target = None;
+        for b in 0..self.balls.len() {
+            let mut ball = BallStep {
+                b,
+                owner: self.balls[b].owner,
+                owner_team: None,
+            };
+            for (name, system) in BALL_SYSTEMS {
+                system(self, &mut ball).inspect_err(|_| error!("ball {} failed", name))?;
             }
+            self.balls[b].owner = ball.owner;
+        }
+        Ok(())
+    }
+
+    fn count_training_shots(&mut self) -> Result<(), GameError> {
+        if let Some(training) = &mut self.training {
+            training.count_shots(&self.events);
+        }
+        Ok(())
+    }
+
+    fn move_cameras(&mut self) -> Result<(), GameError> {
+        self.update_camera()?;
+        self.camera.react(&self.events);
+        for camera in &mut self.team_cameras {
+            camera.react(&self.events);
         }
+        Ok(())
+    }
+
+    fn finish_input(&mut self) -> Result<(), GameError> {
+        for t in 0..=1 {
+            let team = &mut self.teams[t];
+            // remember a press that didn't kick the ball this step
+            team.shoot_buffer = if self.shoot_pressed[t] && !self.shoot_now[t] {
+                SHOOT_BUFFER_FRAMES
+            } else {
+                team.shoot_buffer.saturating_sub(1)
+            };
+            team.switch_cycle_timer = team.switch_cycle_timer.saturating_sub(1);
+        }
+        self.shoot_pressed = [false, false];
+        self.switch_pressed = [false, false];
+        self.call_pressed = [false, false];
+        self.tackle_pressed = [false, false];
+        Ok(())
+    }
+
+    // hold on to a human team's input until the next step; presses last until a step sees them,
+    // so one isn't lost on a frame that doesn't run a step
+    fn apply_input(&mut self, team: usize, input: FrameInput) {
+        self.sticks[team] = Some(input.movement);
+        self.shoot_pressed[team] |= input.shoot;
+        self.switch_pressed[team] |= input.switch;
+        self.call_pressed[team] |= input.call;
+        self.tackle_pressed[team] |= input.tackle;
+    }
+
+    // make the next step act on exactly this input, whatever was applied before; for network play,
+    // where both copies of the match have to act on the same input at the same step
+    fn replace_input(&mut self, team: usize, input: FrameInput) {
+        self.sticks[team] = Some(input.movement);
+        self.shoot_pressed[team] = input.shoot;
+        self.switch_pressed[team] = input.switch;
+        self.call_pressed[team] = input.call;
+        self.tackle_pressed[team] = input.tackle;
+    }
+
+    // the input the next step will act on
+    fn input(&self, team: usize) -> FrameInput {
+        FrameInput {
+            movement: self.movement(team),
+            shoot: self.shoot_pressed[team],
+            switch: self.switch_pressed[team],
+            call: self.call_pressed[team],
+            tackle: self.tackle_pressed[team],
+        }
+    }
+
+    // the on-screen stick, while it's being used, takes over from the keys
+    fn apply_touch(&mut self, touch: &TouchControls) {
+        if let Some(stick) = touch.stick() {
+            self.sticks[0] = Some(vec2(stick.x, stick.y) * MAX_SPEED);
+        }
+        if self.teams[0].human() {
+            self.shoot_pressed[0] |= touch.kicked();
+        }
+    }
+
+    // how much faster than usual the computer's players run on this difficulty
+    fn speed_boost(&self) -> f32 {
+        self.balance.speed_boost[self.difficulty.level as usize]
+    }
+
+    // how a human team wants its active player to move
+    fn movement(&self, team: usize) -> Vector {
+        self.sticks[team].unwrap_or_else(Vector::zero)
+    }
+
+    // cursor is the mouse position in game screen coordinates
+    fn aim_at(&mut self, cursor: Vec2) {
+        self.cursor = self.camera.screen_to_world(vec2(cursor.x, cursor.y));
+    }
+
+    // direction a team's player would kick the ball in from pos: towards the cursor if they're
+    // aiming with the mouse, otherwise the way they're facing
+    fn aim_dir(&self, team: usize, pos: Vector, facing: Angle) -> Vector {
+        let to_cursor = self.cursor - pos;
+        match self.teams[team].controls {
+            Some(controls) if controls.mouse_aim && to_cursor.length() > 0.0 => {
+                to_cursor.normalize()
+            }
+            _ => Angle::to_vec(facing),
+        }
+    }
+
+    fn update_camera(&mut self) -> Result<(), GameError> {
+        // with more than one ball, the camera sticks with the first
+        let ball = self.balls[0];
+        let ball_pos = self.ball_pos(ball)?;
+        let ball_vel = self
+            .world
+            .get::<Vector>(ball.entity)
+            .map(|v| *v)
+            .unwrap_or_else(|_| Vector::zero());
+        // look ahead towards the goal the team in possession is attacking
+        let attack_dir = match ball.owner {
+            None => 0.0,
+            Some(owner_id) => 2.0 * self.world.component::<Team>(owner_id)?.0 as f32 - 1.0,
+        };
+        let celebrating_pos = self
+            .celebration
+            .and_then(|(id, _)| self.world.get::<Position>(id).ok().map(|p| p.0));
+        let base_zoom = if self.sudden_death() {
+            SUDDEN_DEATH_ZOOM
+        } else {
+            1.0
+        };
+        self.camera.base_zoom = base_zoom;
+        match celebrating_pos {
+            Some(pos) => self.camera.track(pos, CELEBRATION_ZOOM),
+            None => self.camera.update(ball_pos, ball_vel, attack_dir),
+        }
+        if self.split_screen {
+            // in split screen each team's camera follows their own active player
+            for t in 0..=1 {
+                let active_pos = self.teams[t]
+                    .active_player
+                    .and_then(|id| self.world.get::<Position>(id).ok().map(|p| p.0));
+                self.team_cameras[t].base_zoom = base_zoom;
+                if let Some(pos) = active_pos {
+                    self.team_cameras[t].update(pos, ball_vel, 2.0 * t as f32 - 1.0);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // a loose ball bounces off anyone who can't take it just now, having lost it or only just
+    // kicked it, and then they have to wait a moment longer before they can; in training, dummies
+    // never go for the ball but it bounces off them from as far away as a player would pick it up
+    fn deflect_off_players(&mut self) -> Result<(), GameError> {
+        let dummies = self.training.as_ref().is_some_and(|t| t.setup.dummies);
+        for b in 0..self.balls.len() {
+            let ball = self.balls[b].entity;
+            let vel = match self.world.get::<Vector>(ball) {
+                Ok(vel) => *vel,
+                Err(_) => continue,
+            };
+            let ball_pos = self.ball_pos(self.balls[b])?;
+            let deflection = self
+                .world
+                .query::<(&Team, &Position, &Timer)>()
+                .iter()
+                .find_map(|(id, (team, pos, timer))| {
+                    if dummies && team.0 == 1 {
+                        let bounce = training::DUMMY_BOUNCE;
+                        deflect(ball_pos, vel, pos.0, self.balance.dribble_x, bounce)
+                            .map(|v| (None, v))
+                    } else if timer.0 > 0 || powerups::frozen(&self.world, id) {
+                        deflect(ball_pos, vel, pos.0, BODY_RADIUS, PLAYER_BOUNCE)
+                            .map(|v| (Some(id), v))
+                    } else {
+                        None
+                    }
+                });
+            match deflection {
+                Some((player, new_vel)) => {
+                    *self.world.component_mut::<Vector>(ball)? = new_vel;
+                    if let Some(id) = player {
+                        sim_log!(
+                            self.log,
+                            Ball,
+                            Info,
+                            "the ball deflects off {}",
+                            self.player_name(id)
+                        );
+                        let timer = &mut self.world.component_mut::<Timer>(id)?.0;
+                        *timer = (*timer).max(DEFLECTION_FRAMES);
+                        self.last_touch = Some(id);
+                    }
+                }
+                None => continue,
+            }
+        }
+        Ok(())
+    }
+
+    fn set_behaviours(&mut self) -> Result<(), GameError> {
+        for (_, (peer, mark, lead)) in self.world.query_mut::<(&Peer, &mut Mark, &mut Lead)>() {
+            *mark = Mark::Player(peer.0);
+            *lead = Lead(None, None);
+        }
+        self.presser = None;
+        // nobody defends in training
+        if self.training.is_some() {
+            return Ok(());
+        }
+        // with more than one ball, whoever has each of them gets closed down
+        let owners: Vec<Entity> = self.balls.iter().filter_map(|b| b.owner).collect();
+        for owner_id in owners {
+            let ball_owner_pos = self.world.component::<Position>(owner_id)?.0;
+            let defending_team = 1 - self.world.component::<Team>(owner_id)?.0;
+            let goal = vec2(HALF_LEVEL_W, (1 - defending_team) as f32 * LEVEL_H);
+            // the nearest player to the goal on each team marks it
+            if let Some(goalie) = self.goalie(defending_team) {
+                let goalie_mark = *self.world.component_mut::<Mark>(goalie)?;
+                // the player with the ball has nobody marking them if their peer was sent off
+                if let Ok(peer) = self.world.get::<Peer>(owner_id).map(|p| p.0) {
+                    *self.world.component_mut::<Mark>(peer)? = goalie_mark;
+                    sim_log!(
+                        self.log,
+                        Behaviours,
+                        Debug,
+                        "{} marks the goal, {} takes over their mark",
+                        self.player_name(goalie),
+                        self.player_name(peer)
+                    );
+                }
+                *self.world.component_mut::<Mark>(goalie)? = Mark::Goal(Position(goal));
+            }
+            let dt = &self.teams[defending_team as usize];
+            if dt.human() && self.assist == Assist::Pressure {
+                self.presser = self
+                    .grid
+                    .nearest(ball_owner_pos, |p| {
+                        p.team == defending_team
+                            && self.world.get::<Timer>(p.id).is_ok_and(|t| t.0 <= 0)
+                            && dt.active_player != Some(p.id)
+                    })
+                    .map(|p| p.id);
+                if let Some(presser) = self.presser {
+                    sim_log!(
+                        self.log,
+                        Behaviours,
+                        Debug,
+                        "{} presses {}",
+                        self.player_name(presser),
+                        self.player_name(owner_id)
+                    );
+                }
+            }
+            // find the two leads
+            let mut players = self
+                .world
+                .query::<(&Team, &Timer, &Mark, &Position)>()
+                .iter()
+                .filter(|(id, (team, timer, mark, _))| {
+                    let dt = &self.teams[defending_team as usize];
+                    team.0 == defending_team
+                        && timer.0 <= 0
+                        && self.presser != Some(*id)
+                        && (!dt.human()
+                            || dt.active_player.is_none()
+                            || dt.active_player.unwrap() != *id)
+                        && match *mark {
+                            Mark::Player(_) => true,
+                            Mark::Goal(_) => false,
+                        }
+                })
+                .map(|(id, (_, _, _, pos))| (id, pos.0))
+                .collect::<Vec<_>>();
+            players.sort_by(|a, b| cmp_dist(a.1, b.1, ball_owner_pos));
+            let (upfield, downfield): (Vec<_>, Vec<_>) = players.iter().partition(|(_, pos)| {
+                if defending_team == 1 {
+                    pos.y > ball_owner_pos.y
+                } else {
+                    pos.y < ball_owner_pos.y
+                }
+            });
+            let mut upfield: Vec<_> = upfield.into_iter().map(Some).collect();
+            upfield.extend([None, None]);
+            let mut downfield: Vec<_> = downfield.into_iter().map(Some).collect();
+            downfield.extend([None, None]);
+            use std::iter::once;
+            let alternating: Vec<(Entity, Vector)> = upfield
+                .into_iter()
+                .zip(downfield)
+                .flat_map(|tup| once(tup.0).chain(once(tup.1)))
+                .flatten()
+                .collect();
+            let lead_scale = dt.personality.style().lead_scale;
+            for (n, (id, _)) in alternating.iter().enumerate() {
+                let mut lead = self.world.component_mut::<Lead>(*id)?;
+                lead.1 = Some(n as u8);
+                if n == 0 {
+                    lead.0 = Some(self.balance.lead_distances[0] * lead_scale[0]);
+                }
+                if n == 1 && self.difficulty.second_lead_enabled {
+                    lead.0 = Some(self.balance.lead_distances[1] * lead_scale[1]);
+                }
+                if lead.0.is_some() {
+                    sim_log!(
+                        self.log,
+                        Behaviours,
+                        Debug,
+                        "{} is lead {} against {}",
+                        self.player_name(*id),
+                        n + 1,
+                        self.player_name(owner_id)
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_player_targets(&mut self) -> Result<(), GameError> {
+        let players = self.player_states();
+        // the log has to come out in order, so only share the work out when it's quiet
+        let targets = if players.len() >= PARALLEL_PLAYERS
+            && !self
+                .log
+                .enabled(sim_log::Area::Targets, sim_log::Level::Trace)
+        {
+            self.choose_targets_in_parallel(&players)?
+        } else {
+            self.choose_targets(&players)?
+        };
+        let kept_out = self.kept_out_of_centre();
+        for (player, mut target) in players.iter().zip(targets) {
+            if kept_out == Some(player.team) {
+                target.pos = outside_centre_circle(target.pos, player.team);
+            }
+            *self.world.component_mut::<Target>(player.id)? = target;
+        }
+        Ok(())
+    }
+
+    // until the kickoff has been taken, the team that isn't taking it has to stay out of the centre
+    // circle
+    fn kept_out_of_centre(&self) -> Option<u8> {
+        if !matches!(self.restart, Restart::Kickoff { .. }) {
+            return None;
+        }
+        let kicker = self.kickoff_player?;
+        Some(1 - self.world.get::<Team>(kicker).ok()?.0)
+    }
+
+    fn player_states(&self) -> Vec<PlayerState> {
+        self.world
+            .query::<(
+                &Position,
+                &Team,
+                &Home,
+                &Lead,
+                &Mark,
+                &SupportRun,
+                &Animation,
+                &Attributes,
+                &Target,
+            )>()
+            .iter()
+            .map(
+                |(id, (pos, team, home, lead, mark, support, anim, attributes, target))| {
+                    PlayerState {
+                        id,
+                        pos: pos.0,
+                        team: team.0,
+                        home: home.0,
+                        lead: lead.0,
+                        mark: *mark,
+                        support: support.0,
+                        dir: anim.dir,
+                        attributes: *attributes,
+                        target: target.clone(),
+                    }
+                },
+            )
+            .collect()
+    }
+
+    fn choose_targets(&self, players: &[PlayerState]) -> Result<Vec<Target>, GameError> {
+        players.iter().map(|p| self.choose_target(p)).collect()
+    }
+
+    // split the players between a thread per core
+    #[cfg(not(target_arch = "wasm32"))]
+    fn choose_targets_in_parallel(
+        &self,
+        players: &[PlayerState],
+    ) -> Result<Vec<Target>, GameError> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let per_thread = players.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = players
+                .chunks(per_thread)
+                .map(|chunk| scope.spawn(move || self.choose_targets(chunk)))
+                .collect();
+            let mut targets = Vec::with_capacity(players.len());
+            for handle in handles {
+                targets.extend(handle.join().unwrap()?);
+            }
+            Ok(targets)
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn choose_targets_in_parallel(
+        &self,
+        players: &[PlayerState],
+    ) -> Result<Vec<Target>, GameError> {
+        self.choose_targets(players)
+    }
+
+    // where a player should head for this step, which only reads the game so that it can be done
+    // for many players at once
+    fn choose_target(&self, player: &PlayerState) -> Result<Target, GameError> {
+        let ball = self.ball_for(player.id, player.pos)?;
+        let ball_pos = self.ball_pos(ball)?;
+        let behaviour = self.choose_behaviour(player, ball, ball_pos)?;
+        self.follow_behaviour(behaviour, player, ball, ball_pos)
+    }
+
+    // the first of the behaviours that applies to a player, in order
+    fn choose_behaviour(
+        &self,
+        player: &PlayerState,
+        ball: BallInPlay,
+        ball_pos: Vector,
+    ) -> Result<Behaviour, GameError> {
+        let PlayerState {
+            id,
+            pos,
+            team,
+            lead,
+            mark,
+            support,
+            ..
+        } = *player;
+        // if we're pre-kickoff and not the kickoff player, or the whistle hasn't gone yet, just stand
+        // and wait
+        if self
+            .kickoff_player
+            .is_some_and(|kp| kp != id || self.kickoff_countdown > 0)
+        {
+            return Ok(Behaviour::HoldKickoff);
+        }
+        // training dummies never move, and nor does anyone else when the AI is turned off, or a
+        // goalkeeper while they're holding the ball
+        if self.holding(id)
+            || (self.training.is_some() && team == 1)
+            || (!self.ai_enabled && !self.teams[team as usize].human())
+        {
+            return Ok(Behaviour::StandStill);
+        }
+        // a slide tackle carries on until it's over, and then the player's on the ground for a bit
+        if let Ok(slide) = self.world.get::<Slide>(id) {
+            return Ok(Behaviour::Slide(slide.sliding().then_some(slide.dir)));
+        }
+        let my_team = &self.teams[team as usize];
+        // if we're the currently-controlled player on a human team, respond to controls
+        if my_team.human() && my_team.active_player == Some(id) {
+            return Ok(Behaviour::Controlled);
+        }
+        if let Some(dest) = self.back_off(team, pos)? {
+            return Ok(Behaviour::BackOff(dest));
+        }
+        // teammates of human players might be running for a pass or closing someone down
+        if let Some(run) = my_team.run.filter(|run| run.player == id) {
+            return Ok(Behaviour::RunForPass(run.dest));
+        }
+        if let (Some(presser), Some(owner_id)) = (self.presser, ball.owner) {
+            if presser == id {
+                return Ok(Behaviour::Press(owner_id));
+            }
+        }
+        let active_range = my_team.tactics.active_range();
+        Ok(match ball.owner {
+            Some(owner_id) if owner_id == id => Behaviour::CarryBall,
+            Some(owner_id) if team == self.world.component::<Team>(owner_id)?.0 => {
+                // if my team has the ball and I'm active, make the run planned for me
+                match support.filter(|_| (ball_pos.y - pos.y).abs() < active_range) {
+                    Some(dest) => Behaviour::SupportAttack(dest),
+                    None => Behaviour::GoHome,
+                }
+            }
+            // if other team has the ball and I'm a lead, try to intercept
+            Some(owner_id) => match lead {
+                Some(lead_dist) => Behaviour::Lead(owner_id, lead_dist),
+                None => {
+                    let mark_pos = match mark {
+                        Mark::Goal(goal_pos) => goal_pos.0,
+                        Mark::Player(mark_id) => self.world.component::<Position>(mark_id)?.0,
+                    };
+                    // if our mark is inactive, go home; if active, human team players just run
+                    // towards the ball, computer players mark the designated target
+                    if (mark_pos.y - ball_pos.y).abs() >= active_range {
+                        Behaviour::GoHome
+                    } else if my_team.human() {
+                        Behaviour::Chase
+                    } else {
+                        Behaviour::Mark(mark)
+                    }
+                }
+            },
+            None => Behaviour::InterceptLoose,
+        })
+    }
+
+    // where a behaviour takes a player, and how fast
+    fn follow_behaviour(
+        &self,
+        behaviour: Behaviour,
+        player: &PlayerState,
+        ball: BallInPlay,
+        ball_pos: Vector,
+    ) -> Result<Target, GameError> {
+        let PlayerState {
+            id,
+            pos,
+            team,
+            home,
+            dir,
+            attributes,
+            ..
+        } = *player;
+        let my_team = &self.teams[team as usize];
+        let mut target = player.target.clone();
+        target.behaviour = behaviour;
+        match behaviour {
+            Behaviour::HoldKickoff => {
+                target.pos = pos;
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} waits for kickoff",
+                    self.player_name(id)
+                );
+            }
+            Behaviour::StandStill => target.pos = pos,
+            Behaviour::BackOff(dest) => target.pos = dest,
+            Behaviour::Slide(dir) => {
+                target.pos = pos + dir.unwrap_or_else(Vector::zero) * SLIDE_SPEED;
+                target.speed = SLIDE_SPEED;
+            }
+            Behaviour::Controlled => {
+                if ball.owner == Some(id) {
+                    target.speed = self.balance.human_with_ball_speed;
+                } else if self.knocking_on(id) {
+                    target.speed = self.balance.human_without_ball_speed * KNOCK_ON_BOOST;
+                } else {
+                    target.speed = self.balance.human_without_ball_speed;
+                }
+                target.pos = pos + self.movement(team as usize);
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} is under control",
+                    self.player_name(id)
+                );
+            }
+            Behaviour::RunForPass(dest) => {
+                target.pos = dest;
+                target.speed = self.balance.run_speed;
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} runs for a pass to {:?}",
+                    self.player_name(id),
+                    dest
+                );
+            }
+            Behaviour::Press(owner_id) => {
+                target.pos = self.world.component::<Position>(owner_id)?.0;
+                target.speed = self.balance.lead_speed;
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} closes down {}",
+                    self.player_name(id),
+                    self.player_name(owner_id)
+                );
+            }
+            Behaviour::CarryBall => {
+                // computer-controlled with the ball, so do the cost function thing
+                let best_dest = (-2..=2)
+                    .map(|d| {
+                        let dest = pos + Angle::to_vec(Angle(dir.0 + d)) * 3.0;
+                        let cost = self.cost(dest, team, d.abs() as f32);
+                        (cost, dest)
+                    })
+                    .min_by(|a, b| (a.0).partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+                    .unwrap()
+                    .1;
+                target.pos = best_dest;
+                target.speed = self.balance.cpu_with_ball_speed + self.speed_boost();
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} dribbles to {:?}",
+                    self.player_name(id),
+                    best_dest
+                );
+            }
+            Behaviour::SupportAttack(dest) => {
+                target.pos = dest;
+                target.speed = self.balance.default_speed;
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} supports the attack at {:?}",
+                    self.player_name(id),
+                    dest
+                );
+            }
+            Behaviour::Lead(owner_id, lead_dist) => {
+                let ball_owner_pos = self.world.component::<Position>(owner_id)?.0;
+                let ball_owner_dir = self.world.component::<Animation>(owner_id)?.dir;
+                let mut targ = ball_owner_pos + Angle::to_vec(ball_owner_dir) * lead_dist;
+                targ.x = targ.x.clamp(AI_MIN_X, AI_MAX_X);
+                targ.y = targ.y.clamp(AI_MIN_Y, AI_MAX_Y);
+                let other_team = &self.teams[1 - team as usize];
+                target.pos = targ;
+                target.speed = self.balance.lead_speed
+                    + if other_team.human() {
+                        self.speed_boost()
+                    } else {
+                        0.
+                    };
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} leads at {:?}, {} ahead of {}",
+                    self.player_name(id),
+                    targ,
+                    lead_dist,
+                    self.player_name(owner_id)
+                );
+            }
+            Behaviour::Mark(mark) => {
+                let mark_pos = match mark {
+                    Mark::Goal(goal_pos) => goal_pos.0,
+                    Mark::Player(mark_id) => self.world.component::<Position>(mark_id)?.0,
+                };
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} marks {}",
+                    self.player_name(id),
+                    match mark {
+                        Mark::Goal(_) => "the goal".to_owned(),
+                        Mark::Player(mark_id) => self.player_name(mark_id),
+                    }
+                );
+                let mark_to_ball_vec = ball_pos - mark_pos;
+                let dist_from_mark = match mark {
+                    Mark::Goal(_) => mark_to_ball_vec.length().min(150.0),
+                    Mark::Player(_) => {
+                        let marking = my_team.personality.style().marking;
+                        mark_to_ball_vec.length()
+                            * if self.difficulty.lane_marking {
+                                lane_fraction(marking, mark_pos, team)
+                            } else {
+                                marking
+                            }
+                    }
+                };
+                target.pos = mark_pos;
+                // the ball can be right on top of the mark just as they win it, with no direction
+                // to it
+                if mark_to_ball_vec.length() > 0.0 {
+                    target.pos += mark_to_ball_vec.normalize() * dist_from_mark;
+                }
+                target.speed = self.balance.default_speed;
+            }
+            Behaviour::Chase => {
+                target.pos = ball_pos;
+                target.speed = self.balance.default_speed;
+            }
+            Behaviour::InterceptLoose => {
+                // try to intercept the ball, going by how fast we'll actually run there
+                let mut sim_ball_pos = ball_pos;
+                let mut sim_ball_vel = *self.world.component::<Vector>(ball.entity)?;
+                let mut frame = 0.0;
+                let speed = self.balance.intercept_speed * attributes.pace_factor();
+                while (sim_ball_pos - pos).length() > speed * frame + self.balance.dribble_x
+                    && sim_ball_vel.length() > 0.5
+                {
+                    sim_ball_pos += sim_ball_vel;
+                    sim_ball_vel *= self.weather.ball_drag();
+                    frame += 1.0;
+                }
+                target.pos = sim_ball_pos;
+                target.speed = self.balance.intercept_speed;
+                sim_log!(
+                    self.log,
+                    Targets,
+                    Trace,
+                    "{} goes for the loose ball at {:?}",
+                    self.player_name(id),
+                    sim_ball_pos
+                );
+            }
+            Behaviour::GoHome => {
+                target.pos = my_team.tactics.home(home, team);
+                target.speed = self.balance.default_speed;
+            }
+            // never chosen by choose_behaviour
+            Behaviour::Wait | Behaviour::Celebrate => (),
+        }
+        Ok(target)
     }
 
     fn cost(&self, pos: Vector, team: u8, handicap: f32) -> f32 {
@@ -643,110 +2732,429 @@ impl Game {
             + handicap
     }
 
-    fn update_ball(&mut self) {
+    // whether a pass from one point to another would be cut out: the ball is followed step by step
+    // as it slows down (see steps), and if anyone on the other team could run to where it is by
+    // then and reach it, it would be
+    fn pass_cut_out(&self, from: Vector, to: Vector, team: u8) -> bool {
+        let drag = self.weather.ball_drag();
+        let length = (to - from).length();
+        let dir = (to - from) / length;
+        let speed = KICK_STRENGTH * pass_strength(length, drag);
+        let reach = self.balance.dribble_x * self.mutators.ball_reach();
+        let defenders: Vec<(Vector, Attributes)> = self
+            .world
+            .query::<(&Position, &Team, &Attributes)>()
+            .iter()
+            .filter(|(_, (_, t, _))| t.0 != team)
+            .map(|(_, (pos, _, attributes))| (pos.0, *attributes))
+            .collect();
+        (1..=steps(length, speed, drag)).any(|frame| {
+            let travelled = (speed * (1.0 - drag.powi(frame)) / (1.0 - drag)).min(length);
+            let ball = from + dir * travelled;
+            defenders.iter().any(|(pos, attributes)| {
+                let run = self.balance.intercept_speed * attributes.pace_factor() * frame as f32;
+                (ball - *pos).length() < run + reach * attributes.control_factor()
+            })
+        })
+    }
+
+    // a shot about to go over the line just wide of the goal it was aimed at is a near miss
+    fn check_near_miss(&mut self, step: &mut BallStep) -> Result<(), GameError> {
+        let kick = match self.kick.filter(|kick| kick.shot && step.owner.is_none()) {
+            Some(kick) => kick,
+            None => return Ok(()),
+        };
+        let ball = self.balls[step.b].entity;
+        let pos = self.world.component::<Position>(ball)?.0;
+        let vel = *self.world.component::<Vector>(ball)?;
+        let aimed_at = if kick.team == 0 {
+            PITCH_BOUNDS_Y.0
+        } else {
+            PITCH_BOUNDS_Y.1
+        };
+        if let Some(crossing) = goal_line_crossing(pos, vel).filter(|c| c.y == aimed_at) {
+            let wide = (crossing.x - HALF_LEVEL_W).abs() - HALF_GOAL_W;
+            if (0.0..NEAR_MISS_DISTANCE).contains(&wide) {
+                sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{}'s shot goes just wide",
+                    self.player_name(kick.from)
+                );
+                self.kick = None;
+                self.stats_for(kick.from)?.near_misses += 1;
+                self.events.push(GameEvent::NearMiss {
+                    team: kick.team as usize,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // a loose ball rolls and bounces, an owned one is dribbled
+    fn move_ball(&mut self, step: &mut BallStep) -> Result<(), GameError> {
+        let ball = self.balls[step.b].entity;
         let mut new_ball_vector = None;
-        let mut ball_pos = self.world.get_mut::<Position>(self.ball).unwrap();
-        let mut old_owner = None;
-        let owner_team: Option<u8>;
-        match self.ball_owner {
+        let mut ball_pos = self.world.component_mut::<Position>(ball)?;
+        match step.owner {
             None => {
-                let bounds_x = if (ball_pos.0.y - HALF_LEVEL_H).abs() > HALF_PITCH_H {
-                    GOAL_BOUNDS_X
-                } else {
-                    PITCH_BOUNDS_X
-                };
-                let bounds_y = if (ball_pos.0.x - HALF_LEVEL_W).abs() < HALF_GOAL_W {
-                    GOAL_BOUNDS_Y
-                } else {
-                    PITCH_BOUNDS_Y
-                };
-                let vel = *self.world.get::<Vector>(self.ball).unwrap();
-                let (px, vx) = ball_physics(ball_pos.0.x, vel.x, bounds_x);
-                let (py, vy) = ball_physics(ball_pos.0.y, vel.y, bounds_y);
-                ball_pos.0 = vec2(px, py);
-                *self.world.get_mut::<Vector>(self.ball).unwrap() = vec2(vx, vy);
-                owner_team = None;
+                let vel = *self.world.component::<Vector>(ball)?;
+                let (pos, new_vel, contact) = roll_ball(ball_pos.0, vel, self.weather.ball_drag());
+                ball_pos.0 = pos;
+                *self.world.component_mut::<Vector>(ball)? = new_vel;
+                step.owner_team = None;
+                match contact {
+                    Some(Contact::Post) => {
+                        sim_log!(self.log, Ball, Info, "the ball hits the post");
+                    }
+                    Some(Contact::Net) => {
+                        self.net_ripple = Some(NetRipple {
+                            pos,
+                            strength: (vel.length() / KICK_STRENGTH).min(1.0),
+                            steps_left: RIPPLE_FRAMES,
+                        });
+                    }
+                    None => {}
+                }
             }
             Some(owner_id) => {
                 self.kickoff_player = None;
                 // calculate new position based on dribbling
-                let owner_pos = &*self.world.get::<Position>(owner_id).unwrap();
-                let owner_anim = &*self.world.get::<Animation>(owner_id).unwrap();
+                let owner_pos = &*self.world.component::<Position>(owner_id)?;
+                let owner_anim = &*self.world.component::<Animation>(owner_id)?;
                 let new_x = avg(
                     ball_pos.0.x,
-                    owner_pos.0.x + DRIBBLE_DIST_X * owner_anim.dir.sin(),
+                    owner_pos.0.x + self.balance.dribble_x * owner_anim.dir.sin(),
                 );
                 let new_y = avg(
                     ball_pos.0.y,
-                    owner_pos.0.y - DRIBBLE_DIST_Y * owner_anim.dir.cos(),
+                    owner_pos.0.y - self.balance.dribble_y * owner_anim.dir.cos(),
                 );
                 // check ball doesn't go off pitch
                 if on_pitch(new_x, new_y) {
                     ball_pos.0 = vec2(new_x, new_y);
                 } else {
                     // player dribbled off the pitch so they lose the ball
-                    self.ball_owner = None;
-                    self.world.get_mut::<Timer>(owner_id).unwrap().0 = 60;
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Info,
+                        "{} dribbles off the pitch",
+                        self.player_name(owner_id)
+                    );
+                    step.owner = None;
+                    self.world.component_mut::<Timer>(owner_id)?.0 = 60;
                     new_ball_vector = Some(Angle::to_vec(owner_anim.dir) * 3.0);
                 }
-                owner_team = Some(self.world.get::<Team>(owner_id).unwrap().0);
+                step.owner_team = Some(self.world.component::<Team>(owner_id)?.0);
+            }
+        }
+        drop(ball_pos);
+        // this is an awkward consequence of choosing to add and remove the Vector component
+        if let Some(nbv) = new_ball_vector {
+            self.world.add_component(ball, nbv)?;
+        }
+        Ok(())
+    }
+
+    // where a loose ball will go if nobody touches it, a step at a time until it stops, for the
+    // debug overlay
+    fn predict_ball_path(&self, ball: BallInPlay) -> Option<Vec<Vector>> {
+        if ball.owner.is_some() {
+            return None;
+        }
+        let mut pos = self.world.get::<Position>(ball.entity).ok()?.0;
+        let mut vel = *self.world.get::<Vector>(ball.entity).ok()?;
+        let drag = self.weather.ball_drag();
+        let mut path = Vec::new();
+        while vel.length() > 0.5 && path.len() < MAX_PREDICTED_STEPS {
+            (pos, vel, _) = roll_ball(pos, vel, drag);
+            path.push(pos);
+        }
+        Some(path)
+    }
+
+    // a loose ball coming at a player faster than FIRST_TOUCH_SPEED may get away from them, more
+    // often the faster it's going and the worse their control; if it does, it runs on a little way
+    // past them and this is true
+    fn miscontrols(&mut self, id: Entity, ball: Entity) -> Result<bool, GameError> {
+        let vel = *self.world.component::<Vector>(ball)?;
+        let control = self.world.component::<Attributes>(id)?.control_factor();
+        let pace = (vel.length() - FIRST_TOUCH_SPEED) / (KICK_STRENGTH - FIRST_TOUCH_SPEED);
+        if pace <= 0.0 || gen_range(0.0, 1.0) >= pace.min(1.0) * MISCONTROL_CHANCE / control {
+            return Ok(false);
+        }
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "{} miscontrols the ball",
+            self.player_name(id)
+        );
+        let angle = gen_range(-MISCONTROL_SPREAD, MISCONTROL_SPREAD);
+        let (sin, cos) = angle.sin_cos();
+        *self.world.component_mut::<Vector>(ball)? =
+            vec2(vel.x * cos - vel.y * sin, vel.x * sin + vel.y * cos) * MISCONTROL_BOUNCE;
+        self.world.component_mut::<Timer>(id)?.0 = MISCONTROL_FRAMES;
+        self.last_touch = Some(id);
+        Ok(true)
+    }
+
+    // when players on both teams are closing in on a loose ball together, the nearest of each go
+    // shoulder to shoulder for it: the stronger one usually wins, helped by facing the ball and
+    // being nearer it, and the other can't take it for a moment; the winner, if there's a contest
+    fn contest(&mut self, ball_pos: Vector, reach: f32) -> Result<Option<Entity>, GameError> {
+        let mut nearest: [Option<(Entity, f32)>; 2] = [None, None];
+        let reach = reach * DUEL_REACH;
+        for player in self.grid.within(ball_pos, reach * attributes::MAX_FACTOR) {
+            let id = player.id;
+            let distance = (player.pos - ball_pos).length();
+            if distance > reach * self.world.component::<Attributes>(id)?.control_factor()
+                || self.world.component::<Timer>(id)?.0 > 0
+                || self.has_ball(id)
+                || powerups::frozen(&self.world, id)
+                || (self.training.is_some() && player.team == 1)
+            {
+                continue;
+            }
+            let best = &mut nearest[player.team as usize];
+            if best.is_none_or(|(_, d)| distance < d) {
+                *best = Some((id, distance));
             }
         }
-        // update camera while we still have the ball position uniquely borrowed
-        self.camera_focus += (ball_pos.0 - self.camera_focus).with_max_length(8.0);
-        drop(ball_pos);
-        // this is an awkward consequence of choosing to add and remove the Vector component
-        if let Some(nbv) = new_ball_vector {
-            self.world.insert_one(self.ball, nbv).unwrap();
-        }
-        let ball_pos = self.world.get::<Position>(self.ball).unwrap().0;
+        let (a, b) = match nearest {
+            [Some((a, _)), Some((b, _))] => (a, b),
+            _ => return Ok(None),
+        };
+        let score = |id: Entity| -> Result<f32, GameError> {
+            let pos = self.world.component::<Position>(id)?.0;
+            let facing = Angle::to_vec(self.world.component::<Animation>(id)?.dir);
+            let to_ball = (ball_pos - pos).try_normalize().unwrap_or(facing);
+            let nearness = 1.0 - (ball_pos - pos).length() / (reach * attributes::MAX_FACTOR);
+            Ok(self.world.component::<Attributes>(id)?.strength_factor()
+                + DUEL_FACING * facing.dot(to_ball)
+                + DUEL_NEARNESS * nearness
+                + gen_range(0.0, DUEL_LUCK))
+        };
+        let (winner, loser) = if score(a)? >= score(b)? {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "{} holds off {} for the ball",
+            self.player_name(winner),
+            self.player_name(loser)
+        );
+        self.world.component_mut::<Timer>(loser)?.0 = DUEL_FRAMES;
+        self.duel = Some(Duel {
+            players: [winner, loser],
+            steps_left: DUEL_FRAMES,
+        });
+        Ok(Some(winner))
+    }
+
+    // how far a player is drawn from where they really are, leaning into whoever they're going
+    // shoulder to shoulder with for the ball
+    fn jostle(&self, id: Entity) -> Vector {
+        let duel = match self.duel.filter(|d| d.players.contains(&id)) {
+            Some(duel) => duel,
+            None => return Vector::zero(),
+        };
+        let other = duel.players[(duel.players[0] == id) as usize];
+        let (pos, other_pos) = match (
+            self.world.get::<Position>(id),
+            self.world.get::<Position>(other),
+        ) {
+            (Ok(pos), Ok(other_pos)) => (pos.0, other_pos.0),
+            _ => return Vector::zero(),
+        };
+        // two bumps, dying away
+        let progress = 1.0 - duel.steps_left as f32 / DUEL_FRAMES as f32;
+        let lean = (progress * 2.0 * PI).sin().abs() * (1.0 - progress);
+        (other_pos - pos)
+            .try_normalize()
+            .unwrap_or_else(Vector::zero)
+            * JOSTLE_DISTANCE
+            * lean
+    }
+
+    // a player who gets to the ball takes it, unless their team already has it
+    fn take_possession(&mut self, step: &mut BallStep) -> Result<(), GameError> {
+        let ball = self.balls[step.b].entity;
+        let mut old_owner = None;
+        let ball_pos = self.world.component::<Position>(ball)?.0;
         // search for a player that can acquire the ball
+        let previous_touch = self.last_touch;
+        let previous_team = self
+            .last_touch
+            .and_then(|id| self.world.get::<Team>(id).ok().map(|t| t.0));
         let mut ball_was_acquired = false;
-        for (id, (player_pos, team, timer)) in &mut self.world.query::<(&Position, &Team, &Timer)>()
-        {
-            if (owner_team.is_none() || owner_team.unwrap() != team.0)
-                && (ball_pos - player_pos.0).length() <= DRIBBLE_DIST_X
-                && timer.0 == 0
+        let reach = self.balance.dribble_x * self.mutators.ball_reach();
+        let winner = match step.owner {
+            None => self.contest(ball_pos, reach)?,
+            Some(_) => None,
+        };
+        for player in self.grid.within(ball_pos, reach * attributes::MAX_FACTOR) {
+            let id = player.id;
+            if winner.is_some_and(|winner| winner != id) {
+                continue;
+            }
+            // winning the ball off someone is down to tackling, and picking it up when it's loose
+            // is down to control
+            let attributes = *self.world.component::<Attributes>(id)?;
+            let skill = if step.owner_team.is_some() {
+                attributes.tackling_factor()
+            } else {
+                attributes.control_factor()
+            };
+            if (player.pos - ball_pos).length() > reach * skill {
+                continue;
+            }
+            if (step.owner_team.is_none() || step.owner_team.unwrap() != player.team)
+                && !step.owner.is_some_and(|owner| self.holding(owner))
+                && self.world.component::<Timer>(id)?.0 == 0
+                && !self.has_ball(id)
+                && !powerups::frozen(&self.world, id)
+                && !(self.training.is_some() && player.team == 1)
             {
-                old_owner = self.ball_owner;
+                if let Some(owner) = step.owner.filter(|_| step.owner_team.is_some()) {
+                    if self.hard_tackle(id)? && gen_range(0.0, 1.0) < INJURY_CHANCE {
+                        self.injure(owner)?;
+                    }
+                    if self.tackled_from_behind(owner, player.pos)? {
+                        self.foul(id)?;
+                        if !self.world.contains(id) {
+                            // sent off
+                            break;
+                        }
+                        continue;
+                    }
+                }
+                // the player who knocked the ball on always brings it back under control
+                if step.owner.is_none() && !self.knocking_on(id) && self.miscontrols(id, ball)? {
+                    continue;
+                }
+                old_owner = step.owner;
                 // acquire the ball
-                self.ball_owner = Some(id);
-                self.teams[team.0 as usize].active_player = Some(id);
+                step.owner = Some(id);
+                self.last_touch = Some(id);
+                self.teams[player.team as usize].active_player = Some(id);
                 ball_was_acquired = true;
             }
         }
         if ball_was_acquired {
+            let new_owner = step.owner.unwrap();
+            for team in &mut self.teams {
+                team.knock_on = None;
+            }
+            let team = self.world.component::<Team>(new_owner)?.0;
+            match old_owner {
+                Some(old_owner) => sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{} takes the ball off {}",
+                    self.player_name(new_owner),
+                    self.player_name(old_owner)
+                ),
+                None => sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{} picks up the ball",
+                    self.player_name(new_owner)
+                ),
+            }
+            if previous_team != Some(team) {
+                self.events.push(GameEvent::Possession {
+                    team: team as usize,
+                });
+                if previous_team.is_some() {
+                    self.stats_for(new_owner)?.interceptions += 1;
+                }
+            }
+            // a pass counts once someone else gets to it, and whoever made it gets an assist if
+            // the player they found goes on to score; a shot the other team gets to is a save
+            if previous_touch != Some(new_owner) {
+                self.assister = None;
+            }
+            if let Some(kick) = self.kick.take().filter(|kick| kick.from != new_owner) {
+                if kick.shot {
+                    if kick.team != team {
+                        sim_log!(
+                            self.log,
+                            Ball,
+                            Info,
+                            "{} saves {}'s shot",
+                            self.player_name(new_owner),
+                            self.player_name(kick.from)
+                        );
+                        self.stats_for(new_owner)?.saves += 1;
+                        self.events.push(GameEvent::Save {
+                            team: team as usize,
+                        });
+                    }
+                } else {
+                    let passer = self.stats_for(kick.from)?;
+                    if kick.team == team {
+                        passer.passes_completed += 1;
+                        self.assister = Some(kick.from);
+                    } else {
+                        passer.passes_intercepted += 1;
+                    }
+                }
+            }
             if old_owner.is_none() {
-                self.world.remove_one::<Vector>(self.ball).unwrap();
+                self.world.take_component::<Vector>(ball)?;
+                if self.gather(new_owner)? {
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Info,
+                        "{} gathers the ball",
+                        self.player_name(new_owner)
+                    );
+                }
             }
             // set ball's timer so the computer can't shoot immediately
-            let mut ball_timer = self.world.get_mut::<Timer>(self.ball).unwrap();
-            ball_timer.0 = self.difficulty.holdoff_timer;
+            let mut ball_timer = self.world.component_mut::<Timer>(ball)?;
+            ball_timer.0 = self.balance.holdoff_timer[self.difficulty.level as usize];
         }
         // if someone lost the ball, set their timer so they can't reacquire it
-        old_owner.map(|owner| {
-            let mut owner_timer = self.world.get_mut::<Timer>(owner).unwrap();
+        if let Some(owner) = old_owner {
+            let mut owner_timer = self.world.component_mut::<Timer>(owner)?;
             owner_timer.0 = 60;
-        });
-        // if the ball has an owner, maybe kick it
-        self.shoot_now = [false, false];
-        self.debug_shoot_target = None;
-        match self.ball_owner {
+        }
+        Ok(())
+    }
+
+    // whoever has the ball may kick it
+    fn kick_ball(&mut self, step: &mut BallStep) -> Result<(), GameError> {
+        let ball = self.balls[step.b].entity;
+        match step.owner {
             None => (),
+            Some(owner_id) if self.holding(owner_id) => self.distribute(step, owner_id)?,
             Some(owner_id) => {
-                let owner_team_id = self.world.get::<Team>(owner_id).unwrap().0;
+                let owner_team_id = self.world.component::<Team>(owner_id)?.0;
                 let owner_team = &self.teams[owner_team_id as usize];
                 let owner_team_human = owner_team.human();
-                let owner_pos = self.world.get::<Position>(owner_id).unwrap().0;
-                let owner_dir = self.world.get::<Animation>(owner_id).unwrap().dir;
+                let owner_pos = self.world.component::<Position>(owner_id)?.0;
+                let owner_dir = self.world.component::<Animation>(owner_id)?.dir;
+                let aim_dir = self.aim_dir(owner_team_id as usize, owner_pos, owner_dir);
+                let style = owner_team.personality.style();
+                let pass_range = PASS_RANGE * style.pass_range;
                 // possible targets are all the other players on owner's team ...
                 let mut targets: Vec<ShootTarget> = self
-                    .world
-                    .query::<(&Team, &Position)>()
+                    .grid
+                    .within(owner_pos, pass_range)
                     .iter()
-                    .filter(|(id, _)| id != &owner_id)
-                    .filter(|(_, (t, _))| t.0 == owner_team_id)
-                    .map(|(id, (_, p))| ShootTarget::Player(*p, id))
+                    .filter(|p| p.id != owner_id && p.team == owner_team_id)
+                    .map(|p| ShootTarget::Player(Position(p.pos), p.id))
                     .collect();
                 // ... plus the opposing goal
                 targets.push(ShootTarget::Goal(Position(vec2(
@@ -755,55 +3163,82 @@ impl Game {
                 ))));
                 targets.retain(|st| {
                     let shoot_vec = st.position().0 - owner_pos;
-                    if shoot_vec.length() <= 0.0 || shoot_vec.length() >= 300.0 {
+                    if shoot_vec.length() <= 0.0 || shoot_vec.length() >= pass_range {
                         return false;
                     }
                     // if owner is a computer, filter out interceptable passes
-                    if !owner_team_human {
-                        for (_, (opp_pos, team)) in &mut self.world.query::<(&Position, &Team)>() {
-                            if team.0 != owner_team_id {
-                                let v1 = (opp_pos.0 - owner_pos).normalize();
-                                if v1.length() > 0.0
-                                    && v1.length() < shoot_vec.length()
-                                    && shoot_vec.normalize().dot(v1) > 0.8
-                                {
-                                    return false;
-                                }
-                            }
-                        }
+                    if !owner_team_human
+                        && matches!(st, ShootTarget::Player(_, _))
+                        && self.pass_cut_out(owner_pos, st.position().0, owner_team_id)
+                    {
+                        return false;
                     }
-                    let source_dir = self.world.get::<Animation>(owner_id).unwrap().dir;
-                    shoot_vec.normalize().dot(Angle::to_vec(source_dir)) > 0.8
+                    shoot_vec.normalize().dot(aim_dir) > 0.8
                 });
+                for st in &targets {
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Trace,
+                        "{} could kick to {}, cost {:.0}",
+                        self.player_name(owner_id),
+                        self.target_name(st),
+                        self.cost(st.position().0, owner_team_id, 0.)
+                    );
+                }
                 let best_target = targets
                     .iter()
                     .min_by(|a, b| cmp_dist(a.position().0, b.position().0, owner_pos));
-                self.debug_shoot_target = best_target.map(|st| st.position().0);
-                let do_shoot;
-                if owner_team.human() {
-                    do_shoot = is_key_pressed(owner_team.controls.unwrap().shoot)
+                if let Some(st) = best_target {
+                    self.debug_shoot_target = Some((owner_pos, st.position().0));
+                }
+                // a human team's other players act for themselves if they have a second ball
+                let computer_kick =
+                    !(owner_team.human() && owner_team.active_player == Some(owner_id));
+                let do_shoot = if !computer_kick {
+                    // a press shortly before getting the ball counts too
+                    self.shoot_pressed[owner_team_id as usize] || owner_team.shoot_buffer > 0
+                } else if !self.ai_enabled {
+                    false
                 } else {
-                    // computer players shoot if target is lower cost than current position
-                    let ball_timer = self.world.get_mut::<Timer>(self.ball).unwrap().0;
-                    do_shoot = match best_target {
+                    // computer players shoot if target is lower cost than current position, give or
+                    // take how keen the team is to get rid of the ball, and how cautious the
+                    // difficulty makes them about shooting
+                    let ball_timer = self.world.component::<Timer>(ball)?.0;
+                    match best_target {
                         None => false,
                         Some(st) => {
+                            let caution = match st {
+                                ShootTarget::Goal(_) => self.difficulty.shot_caution,
+                                ShootTarget::Player(..) => 0.0,
+                            };
                             ball_timer <= 0
-                                && self.cost(st.position().0, owner_team_id, 0.)
-                                    < self.cost(owner_pos, owner_team_id, 0.)
+                                && self.cost(st.position().0, owner_team_id, 0.) + caution
+                                    < self.cost(owner_pos, owner_team_id, 0.) + style.kick_eagerness
                         }
-                    };
+                    }
+                };
+                if let Some(st) = best_target.filter(|_| !do_shoot) {
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Debug,
+                        "{} keeps the ball rather than kick to {}, cost {:.0} where they are",
+                        self.player_name(owner_id),
+                        self.target_name(st),
+                        self.cost(owner_pos, owner_team_id, 0.)
+                    );
                 }
                 self.shoot_now[owner_team_id as usize] = do_shoot;
                 if do_shoot {
+                    self.teams[owner_team_id as usize].shoot_buffer = 0;
+                    let shot = matches!(best_target, Some(ShootTarget::Goal(_)));
+                    let drag = self.weather.ball_drag();
                     let shoot_vec;
                     match best_target {
                         Some(t) => {
-                            match t {
-                                ShootTarget::Player(_, id) => {
-                                    self.teams[owner_team_id as usize].active_player = Some(*id);
-                                }
-                                _ => (),
+                            if let ShootTarget::Player(_, id) = t {
+                                self.teams[owner_team_id as usize].active_player = Some(*id);
                             }
                             if owner_team_human
                                 && matches!(best_target, Some(ShootTarget::Player(_, _)))
@@ -813,108 +3248,394 @@ impl Game {
                                 for _ in 1..=8 {
                                     targ = t.position().0 + Angle::to_vec(owner_dir) * lead;
                                     let length = (targ - owner_pos).length();
-                                    lead = HUMAN_PLAYER_WITHOUT_BALL_SPEED * steps(length) as f32;
+                                    let speed = KICK_STRENGTH * pass_strength(length, drag);
+                                    lead = self.balance.human_without_ball_speed
+                                        * steps(length, speed, drag) as f32;
                                 }
                                 shoot_vec = targ - owner_pos;
+                            } else if shot && computer_kick {
+                                // the computer doesn't aim perfectly, less so on easier levels
+                                let noise = self.difficulty.aim_noise;
+                                let (sin, cos) = gen_range(-noise, noise).sin_cos();
+                                let aim = t.position().0 - owner_pos;
+                                shoot_vec =
+                                    vec2(aim.x * cos - aim.y * sin, aim.x * sin + aim.y * cos);
                             } else {
                                 shoot_vec = t.position().0 - owner_pos;
                             }
                         }
                         None => {
-                            shoot_vec =
-                                Angle::to_vec(self.world.get::<Animation>(owner_id).unwrap().dir);
+                            shoot_vec = aim_dir;
                             // take a guess at which player we should activate
                             let dest = owner_pos + shoot_vec.normalize() * 250.0;
                             let closest_player = self
-                                .world
-                                .query::<(&Team, &Position)>()
-                                .iter()
-                                .filter(|(_, (t, _))| t.0 == owner_team_id)
-                                .map(|(id, (_, p))| (id, p))
-                                .min_by(|a, b| cmp_dist((a.1).0, (b.1).0, dest))
-                                .map(|(id, _)| id);
+                                .grid
+                                .nearest(dest, |p| p.team == owner_team_id)
+                                .map(|p| p.id);
                             self.teams[owner_team_id as usize].active_player = closest_player;
                         }
                     }
-                    self.world.get_mut::<Timer>(owner_id).unwrap().0 = 10;
-                    self.ball_owner = None;
-                    self.world
-                        .insert_one(self.ball, shoot_vec.normalize() * KICK_STRENGTH)
-                        .unwrap();
+                    sim_log!(
+                        self.log,
+                        Ball,
+                        Info,
+                        "{} kicks to {}",
+                        self.player_name(owner_id),
+                        best_target
+                            .map_or("nobody in particular".to_owned(), |st| self.target_name(st))
+                    );
+                    // passes are weighted for how far they're going and shots hit harder from
+                    // further out; a kick at nobody in particular just goes at the usual strength
+                    let length = shoot_vec.length();
+                    let strength = match best_target {
+                        Some(ShootTarget::Goal(_)) => shot_strength(length, drag),
+                        Some(ShootTarget::Player(..)) => pass_strength(length, drag),
+                        None => 1.0,
+                    };
+                    self.release_ball(step, owner_id, shot, shoot_vec, strength)?;
                 }
             }
         }
+        Ok(())
+    }
+
+    // each human team's stick, for spotting a double tap
+    fn read_double_taps(&mut self) -> Result<(), GameError> {
+        for t in 0..=1 {
+            let stick = self.movement(t);
+            self.teams[t].taps.update(stick);
+        }
+        Ok(())
+    }
+
+    // a human's player on the ball who double-taps a direction knocks it on that way and goes
+    // after it
+    fn knock_ball_on(&mut self, step: &mut BallStep) -> Result<(), GameError> {
+        let owner = match step.owner {
+            Some(owner) => owner,
+            None => return Ok(()),
+        };
+        let team = self.world.component::<Team>(owner)?.0 as usize;
+        let dir = match self.movement(team).try_normalize() {
+            Some(dir) if self.teams[team].taps.double_tapped => dir,
+            _ => return Ok(()),
+        };
+        if self.teams[team].active_player != Some(owner) || self.holding(owner) {
+            return Ok(());
+        }
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "{} knocks the ball on",
+            self.player_name(owner)
+        );
+        step.owner = None;
+        self.world.component_mut::<Timer>(owner)?.0 = KNOCK_ON_DELAY;
+        self.teams[team].knock_on = Some(KnockOn::new(owner));
+        self.world
+            .add_component(self.balls[step.b].entity, dir * KNOCK_ON_SPEED)?;
+        Ok(())
+    }
+
+    // a goalkeeper holding the ball waits, then throws or kicks it to a teammate
+    fn distribute(&mut self, step: &mut BallStep, keeper: Entity) -> Result<(), GameError> {
+        if !self.ready_to_distribute() {
+            return Ok(());
+        }
+        let (receiver, strength) = match self.distribution_target(keeper)? {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+        sim_log!(
+            self.log,
+            Ball,
+            Info,
+            "{} {} the ball to {}",
+            self.player_name(keeper),
+            if strength < 1.0 { "throws" } else { "kicks" },
+            self.player_name(receiver)
+        );
+        let team = self.world.component::<Team>(keeper)?.0;
+        let shoot_vec = self.world.component::<Position>(receiver)?.0
+            - self.world.component::<Position>(keeper)?.0;
+        self.teams[team as usize].active_player = Some(receiver);
+        self.release_ball(step, keeper, false, shoot_vec, strength)
+    }
+
+    // the ball leaves its owner's foot (or hands) in the given direction, at strength times the
+    // speed of a normal kick
+    fn release_ball(
+        &mut self,
+        step: &mut BallStep,
+        owner_id: Entity,
+        shot: bool,
+        shoot_vec: Vector,
+        strength: f32,
+    ) -> Result<(), GameError> {
+        let team = self.world.component::<Team>(owner_id)?.0;
+        self.events.push(GameEvent::Kick {
+            team: team as usize,
+            shot,
+        });
+        self.kick = Some(KickInFlight {
+            from: owner_id,
+            team,
+            shot,
+            steps_left: KICK_WINDOW_FRAMES,
+        });
+        self.world.component_mut::<Timer>(owner_id)?.0 = 10;
+        step.owner = None;
+        let power = self.world.component::<Attributes>(owner_id)?.kick_factor();
+        self.world.add_component(
+            self.balls[step.b].entity,
+            shoot_vec.normalize()
+                * KICK_STRENGTH
+                * strength
+                * power
+                * self.mutators.kick_strength()
+                * powerups::kick_strength(&self.world, owner_id),
+        )?;
+        Ok(())
     }
 
-    fn switch_players(&mut self) {
+    fn switch_players(&mut self) -> Result<(), GameError> {
         if self.kickoff_player.is_some() {
-            return;
+            return Ok(());
         }
         for t in 0..=1 {
-            if !self.shoot_now[t] {
-                if let Some(controls) = self.teams[t].controls {
-                    if is_key_pressed(controls.shoot) {
-                        let ball_pos = self.world.get::<Position>(self.ball).unwrap().0;
-                        // switch control to the nearest player to the ball
-                        let dir_bias = if self.ball_owner.is_some() {
-                            2.0 * t as f32 - 1.0
-                        } else {
-                            0.0
-                        };
-                        self.teams[t].active_player = self
-                            .world
-                            .query::<(&Team, &Position)>()
-                            .iter()
-                            .filter(|(_, (tn, _))| tn.0 as usize == t)
-                            .min_by(|a, b| {
-                                cmp_dist_weighted(((a.1).1).0, ((b.1).1).0, ball_pos, dir_bias)
-                            })
-                            .map(|(id, _)| id);
-                    }
+            // shoot_pressed and switch_pressed are only ever set for human teams
+            if !self.shoot_now[t] && self.shoot_pressed[t] {
+                // switch control to the nearest player to the ball
+                self.teams[t].active_player = self.players_nearest_ball(t)?.first().copied();
+            } else if self.switch_pressed[t] && !self.team_has_ball(t)? {
+                let order = self.players_nearest_ball(t)?;
+                let team = &mut self.teams[t];
+                let mut next = match self.switch_mode {
+                    SwitchMode::Cycle if team.switch_cycle_timer > 0 => team.switch_cycle + 1,
+                    _ => 0,
+                } % order.len();
+                // a switch should always move to somebody else
+                if Some(order[next]) == team.active_player {
+                    next = (next + 1) % order.len();
+                }
+                team.active_player = Some(order[next]);
+                team.switch_cycle = next;
+                team.switch_cycle_timer = SWITCH_CYCLE_FRAMES;
+                self.events.push(GameEvent::Switch { team: t });
+            } else if self.auto_switch && self.teams[t].human() {
+                let lost_ball = self
+                    .events
+                    .iter()
+                    .any(|e| matches!(e, GameEvent::Possession { team } if *team != t));
+                if lost_ball {
+                    self.teams[t].active_player = self.players_nearest_ball(t)?.first().copied();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // send a teammate of the active player on a run towards the goal we're attacking
+    fn call_for_pass(&mut self) -> Result<(), GameError> {
+        let close_enough = self.balance.dribble_x;
+        for t in 0..=1 {
+            let team = &mut self.teams[t];
+            if let Some(run) = &mut team.run {
+                run.timer = run.timer.saturating_sub(1);
+                // the run is over once they get there, run out of time or we take control of them
+                let arrived = self
+                    .world
+                    .get::<Position>(run.player)
+                    .map_or(true, |pos| (pos.0 - run.dest).length() < close_enough);
+                if run.timer == 0 || arrived || team.active_player == Some(run.player) {
+                    team.run = None;
                 }
             }
+            // call_pressed is only ever set for human teams
+            if !self.call_pressed[t] || self.kickoff_player.is_some() {
+                continue;
+            }
+            let caller = match self.teams[t].active_player {
+                Some(id) => id,
+                None => continue,
+            };
+            let caller_pos = self.world.component::<Position>(caller)?.0;
+            let runner = self
+                .grid
+                .nearest(caller_pos, |p| {
+                    p.team as usize == t
+                        && p.id != caller
+                        && (p.pos - caller_pos).length() < RUN_CALL_RANGE
+                })
+                .map(|p| (p.id, p.pos));
+            if let Some((player, pos)) = runner {
+                let direction = 2.0 * t as f32 - 1.0;
+                let mut dest = pos + vec2(0.0, RUN_DISTANCE * direction);
+                dest.x = dest.x.clamp(AI_MIN_X, AI_MAX_X);
+                dest.y = dest.y.clamp(AI_MIN_Y, AI_MAX_Y);
+                self.teams[t].run = Some(Run {
+                    player,
+                    dest,
+                    timer: RUN_FRAMES,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // a team's players, sorted by how well placed they are to get to the ball
+    fn players_nearest_ball(&self, t: usize) -> Result<Vec<Entity>, GameError> {
+        // with more than one ball, go by the one nearest the player we're controlling now
+        let active_pos = self.teams[t]
+            .active_player
+            .and_then(|id| self.world.get::<Position>(id).ok().map(|p| p.0))
+            .unwrap_or(vec2(HALF_LEVEL_W, HALF_LEVEL_H));
+        let ball = self.nearest_ball(active_pos)?;
+        let ball_pos = self.ball_pos(ball)?;
+        // when someone has the ball, prefer players between it and our own goal
+        let dir_bias = if ball.owner.is_some() {
+            2.0 * t as f32 - 1.0
+        } else {
+            0.0
+        };
+        let mut players: Vec<(Entity, Vector)> = self
+            .grid
+            .players()
+            .iter()
+            .filter(|p| p.team as usize == t)
+            .map(|p| (p.id, p.pos))
+            .collect();
+        players.sort_by(|a, b| cmp_dist_weighted(a.1, b.1, ball_pos, dir_bias));
+        Ok(players.into_iter().map(|(id, _)| id).collect())
+    }
+}
+
+struct FixedTimestep {
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    fn new() -> Self {
+        Self { accumulator: 0.0 }
+    }
+
+    // number of simulation steps to run this frame
+    fn steps(&mut self) -> u32 {
+        let mut dt = get_frame_time();
+        // absorb timing jitter on 60Hz displays so we don't alternate between 0 and 2 steps
+        if (dt - TIMESTEP).abs() < 0.0005 {
+            dt = TIMESTEP;
+        }
+        self.accumulator += dt;
+        let mut steps = 0;
+        while self.accumulator >= TIMESTEP {
+            if steps == MAX_STEPS_PER_FRAME {
+                self.accumulator = 0.0;
+                break;
+            }
+            self.accumulator -= TIMESTEP;
+            steps += 1;
         }
+        steps
     }
 }
 
 fn build_ball(eb: &mut EntityBuilder) {
-    eb.add(Position(vec2(HALF_LEVEL_W as f32, HALF_LEVEL_H as f32)));
+    eb.add(Position(vec2(HALF_LEVEL_W, HALF_LEVEL_H)));
     eb.add::<Vector>(vec2(0.0, 0.0));
     eb.add(Timer(0));
     eb.add(Ball);
 }
 
-fn build_player(eb: &mut EntityBuilder, x: f32, y: f32, offs: f32, team: u8) {
+// where a player lines up for a kickoff: their home spot squeezed into their own half
+fn kickoff_pos(home: Vector, team: u8) -> Vector {
+    let offs = if team == 0 { 550. } else { 150. };
+    vec2(home.x, home.y / 2. + offs)
+}
+
+fn build_player(eb: &mut EntityBuilder, x: f32, y: f32, team: u8) {
     let x = x + gen_range(-32., 32.);
     let y = y + gen_range(-32., 32.);
     eb.add(Home(vec2(x, y)));
-    let start = vec2(x, y / 2. + offs);
-    eb.add(Position(start.clone()));
+    let start = kickoff_pos(vec2(x, y), team);
+    eb.add(Position(start));
     eb.add(Target::new(start));
     eb.add(Team(team));
     eb.add(Timer(0));
     eb.add(Animation::new());
+    eb.add(Momentum(Vector::zero()));
     eb.add(Lead(None, None));
+    eb.add(SupportRun(None));
 }
 
-fn update_players(world: &mut World, ball: Entity) {
-    let ball_pos = world.get::<Position>(ball).unwrap();
-    for (_, (target, pos, anim)) in &mut world.query::<(&Target, &mut Position, &mut Animation)>() {
-        let vector = target.pos - pos.0;
+// grip is how much of the way each player's velocity can move towards where they want to go in
+// one step (see Weather::player_grip)
+// speeds multiply how fast each team can move (and each player's pace, and whether they're injured,
+// how fast they can), and players standing still face the nearest ball
+// the distance each player covers is added to their stats, and players on the kept_out team can't
+// step into the centre circle
+fn update_players(
+    world: &mut World,
+    ball_positions: &[Vector],
+    grip: f32,
+    speeds: [f32; 2],
+    kept_out: Option<u8>,
+    stats: &mut MatchStats,
+) {
+    for (_, (target, pos, anim, momentum, team, squad, attributes, injured)) in &mut world.query::<(
+        &Target,
+        &mut Position,
+        &mut Animation,
+        &mut Momentum,
+        &Team,
+        &Squad,
+        &Attributes,
+        Option<&Injured>,
+    )>() {
+        let injury = if injured.is_some() {
+            INJURED_SPEED
+        } else {
+            1.0
+        };
+        let speed = speeds[team.0 as usize] * attributes.pace_factor() * injury;
+        let wanted = (target.pos - pos.0).with_max_length(target.speed * speed);
+        let mut vector = if grip < 1.0 {
+            momentum.0.lerp(wanted, grip)
+        } else {
+            wanted
+        };
+        if wanted.length() == 0.0 && vector.length() < 0.05 {
+            // finish sliding to a halt
+            vector = Vector::zero();
+        }
+        momentum.0 = vector;
         let target_dir;
-        let length = vector.length();
-        if length == 0.0 {
-            target_dir = Angle::from_vec(ball_pos.0 - pos.0);
+        if vector.length() == 0.0 {
+            let ball_pos = ball_positions
+                .iter()
+                .min_by(|a, b| cmp_dist(**a, **b, pos.0))
+                .unwrap();
+            target_dir = Angle::from_vec(*ball_pos - pos.0);
             anim.frame = 0.0;
         } else {
-            let vector = vector.with_max_length(target.speed);
-            target_dir = Angle::from_vec(vector);
-            if allow_movement(pos.0.x + vector.x, pos.0.y) {
+            // face where we're trying to go, even if sliding somewhere else
+            target_dir = Angle::from_vec(if wanted.length() > 0.0 {
+                wanted
+            } else {
+                vector
+            });
+            let old_pos = pos.0;
+            let allow = |x, y| {
+                allow_movement(x, y) && !(kept_out == Some(team.0) && in_centre_circle(vec2(x, y)))
+            };
+            if allow(pos.0.x + vector.x, pos.0.y) {
                 pos.0.x += vector.x;
             }
-            if allow_movement(pos.0.x, pos.0.y + vector.y) {
+            if allow(pos.0.x, pos.0.y + vector.y) {
                 pos.0.y += vector.y;
             }
+            stats.get_mut(team.0, squad).distance += (pos.0 - old_pos).length();
             anim.frame += vector.length().min(4.5);
             anim.frame %= 72.0;
         }
@@ -931,25 +3652,77 @@ fn avg(a: f32, b: f32) -> f32 {
     }
 }
 
-fn ball_physics(pos: f32, vel: f32, bounds: (f32, f32)) -> (f32, f32) {
-    let mut pos = pos;
-    let mut vel = vel;
-    pos += vel;
-    if pos < bounds.0 || pos > bounds.1 {
-        pos -= vel;
-        vel = -vel;
-    }
-    (pos, vel * DRAG)
+// the x and y limits a loose ball at pos bounces off: the sides of the goal once it's past the end
+// of the pitch, and the back of the net once it's in line with the goal
+fn ball_bounds(pos: Vector) -> ((f32, f32), (f32, f32)) {
+    let bounds_x = if (pos.y - HALF_LEVEL_H).abs() > HALF_PITCH_H {
+        GOAL_BOUNDS_X
+    } else {
+        PITCH_BOUNDS_X
+    };
+    let bounds_y = if (pos.x - HALF_LEVEL_W).abs() < HALF_GOAL_W {
+        GOAL_BOUNDS_Y
+    } else {
+        PITCH_BOUNDS_Y
+    };
+    (bounds_x, bounds_y)
+}
+
+fn in_net(pos: Vector) -> bool {
+    (pos.y - HALF_LEVEL_H).abs() > HALF_PITCH_H
 }
 
-fn steps(distance: f32) -> i32 {
-    if distance < 574.0 {
-        ((1.0 - (distance * (1.0 - DRAG)) / KICK_STRENGTH).log(DRAG)).ceil() as i32
+// a step of a loose ball rolling: it bounces off the posts and the edges of the pitch, and once
+// it's over the line the net soaks up its speed rather than sending it back out
+fn roll_ball(pos: Vector, vel: Vector, drag: f32) -> (Vector, Vector, Option<Contact>) {
+    if let Some(vel) = hit_post(pos, vel) {
+        return (pos, vel, Some(Contact::Post));
+    }
+    let (bounds_x, bounds_y) = ball_bounds(pos);
+    let (px, vx) = ball_physics(pos.x, vel.x, bounds_x, drag);
+    let (py, vy) = ball_physics(pos.y, vel.y, bounds_y, drag);
+    let (new_pos, new_vel) = (vec2(px, py), vec2(vx, vy));
+    if !in_net(new_pos) {
+        (new_pos, new_vel, None)
+    } else if in_net(pos) {
+        (new_pos, new_vel * NET_DRAG, None)
     } else {
-        190 // ball comes to rest after 190 frames having travelled 574 pixels
+        (new_pos, new_vel * NET_DRAG, Some(Contact::Net))
     }
 }
 
+// number of steps a ball kicked at speed takes to travel the given distance, or to come to rest if
+// it doesn't get that far; the ball loses a fraction 1 - drag of its speed each step, so it's gone
+// speed - distance * (1 - drag) by the time it's travelled distance (a full-strength kick on a
+// normal pitch, where 1 - drag = 0.02, comes to rest after 190 steps having gone 562 pixels)
+fn steps(distance: f32, speed: f32, drag: f32) -> i32 {
+    let arrival = speed - distance * (1.0 - drag);
+    (arrival.max(REST_SPEED) / speed).log(drag).ceil() as i32
+}
+
+// how hard to kick the ball, compared with a normal kick, for it to still be going at arrival when
+// it's travelled the given distance, within the limits given
+fn kick_strength(distance: f32, arrival: f32, drag: f32, limits: (f32, f32)) -> f32 {
+    ((arrival + distance * (1.0 - drag)) / KICK_STRENGTH).clamp(limits.0, limits.1)
+}
+
+fn pass_strength(distance: f32, drag: f32) -> f32 {
+    kick_strength(distance, PASS_ARRIVAL_SPEED, drag, (MIN_PASS_STRENGTH, 1.0))
+}
+
+fn shot_strength(distance: f32, drag: f32) -> f32 {
+    kick_strength(distance, SHOT_ARRIVAL_SPEED, drag, (1.0, MAX_SHOT_STRENGTH))
+}
+
+// how far along the passing lane from a marked player to the ball their marker stands, as a
+// fraction of the way: the personality's usual distance for a mark who's no threat, closing in
+// towards the mark as they get nearer to the goal the marker is defending
+fn lane_fraction(marking: f32, mark_pos: Vector, team: u8) -> f32 {
+    let own_goal_y = (1 - team) as f32 * LEVEL_H;
+    let threat = (1.0 - (mark_pos.y - own_goal_y).abs() / LEVEL_H).clamp(0.0, 1.0);
+    marking * (1.0 - LANE_THREAT_WEIGHT * threat)
+}
+
 fn cmp_dist(v1: Vector, v2: Vector, dest: Vector) -> std::cmp::Ordering {
     (v1 - dest)
         .length()
@@ -974,396 +3747,920 @@ fn cmp_dist_weighted(v1: Vector, v2: Vector, dest: Vector, bias: f32) -> std::cm
 }
 
 fn window_conf() -> Conf {
-    return Conf {
+    Conf {
         window_title: "Substitute Soccer".to_owned(),
         window_width: WIDTH as i32,
         window_height: HEIGHT as i32,
-        window_resizable: false,
+        window_resizable: true,
+        high_dpi: true,
         ..Default::default()
-    };
-}
-
-fn allow_movement(x: f32, y: f32) -> bool {
-    if (x - HALF_LEVEL_W).abs() > HALF_LEVEL_W {
-        // Trying to walk off the left or right side of the level
-        false
-    } else if (x - HALF_LEVEL_W).abs() < HALF_GOAL_W + 20.0 {
-        // Player is within the bounds of the goals on the X axis, don't let them walk into, through or behind the goal
-        // +20 takes with of player sprite into account
-        return (y - HALF_LEVEL_H).abs() < HALF_PITCH_H;
-    } else {
-        // Player is outside the bounds of the goals on the X axis, so they can walk off the pitch and to the edge
-        // of the level
-        (y - HALF_LEVEL_H).abs() < HALF_LEVEL_H
     }
 }
 
-struct Textures(HashMap<String, Texture2D>);
-
-impl Textures {
-    fn new() -> Self {
-        return Self(HashMap::new());
-    }
-    async fn preload(&mut self, key: impl Into<String>) {
-        let key: String = key.into();
-        let texture = load_texture(&format!("images/{}.png", key)).await.unwrap();
-        self.0.insert(key, texture);
-    }
-    fn get(&self, key: &str) -> Texture2D {
-        *self.0.get(key).unwrap()
-    }
+struct Sounds {
+    // each sound, and whether it's music rather than a sound effect
+    sounds: HashMap<String, (Sound, bool)>,
+    music_volume: f32,
+    sfx_volume: f32,
 }
 
-struct Sounds(HashMap<String, macroquad::audio::Sound>);
-
 impl Sounds {
     fn new() -> Self {
-        return Self(HashMap::new());
+        Self {
+            sounds: HashMap::new(),
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+        }
     }
-    async fn preload(&mut self, key: impl Into<String>, is_music: bool) {
+    // missing sounds are replaced with silence rather than stopping the game from running, and
+    // the path of the missing file is returned as an error
+    async fn preload(&mut self, key: impl Into<String>, is_music: bool) -> Result<(), String> {
         let key: String = key.into();
         let dir = if is_music { "music" } else { "sounds" };
-        let sound = load_sound(&format!("{}/{}.ogg", dir, key)).await.unwrap();
-        self.0.insert(key, sound);
+        let path = format!("{}/{}.ogg", dir, key);
+        let (bytes, result) = match assets::load(&path).await {
+            Ok(bytes) => (bytes, Ok(())),
+            Err(_) => (synth::silence(), Err(path)),
+        };
+        let sound = load_sound_from_bytes(&bytes).await.unwrap();
+        self.sounds.insert(key, (sound, is_music));
+        result
+    }
+    async fn generate(&mut self, key: impl Into<String>, bytes: &[u8]) {
+        let sound = load_sound_from_bytes(bytes).await.unwrap();
+        self.sounds.insert(key.into(), (sound, false));
     }
     fn get(&self, key: &str) -> Sound {
-        *self.0.get(key).unwrap()
-    }
-}
-
-#[macroquad::main(window_conf())]
-async fn main() {
-    macroquad::rand::srand(macroquad::miniquad::date::now() as u64);
-    // load all the textures
-    let mut textures = Textures::new();
-    textures.preload("pitch").await;
-    textures.preload("ball").await;
-    textures.preload("balls").await;
-    textures.preload("arrow0").await;
-    textures.preload("arrow1").await;
-    textures.preload("goal").await;
-    textures.preload("goal0").await;
-    textures.preload("goal1").await;
-    textures.preload("bar").await;
-    textures.preload("over0").await;
-    textures.preload("over1").await;
-    for d in 0..=7 {
-        for f in 0..=4 {
-            textures.preload(format!("player0{}{}", d, f)).await;
-            textures.preload(format!("player1{}{}", d, f)).await;
-            textures.preload(format!("players{}{}", d, f)).await;
-        }
-    }
-    for k in vec!["01", "02", "10", "11", "12"] {
-        textures.preload(format!("menu{}", k)).await;
-    }
-    for k in 0..=9 {
-        textures.preload(format!("s{}", k)).await;
-        textures.preload(format!("l0{}", k)).await;
-        textures.preload(format!("l1{}", k)).await;
-    }
-    let mut sounds = Sounds::new();
-    sounds.preload("theme", true).await;
-    sounds.preload("crowd", false).await;
-    sounds.preload("move", false).await;
-    sounds.preload("goal0", false).await;
-    sounds.preload("goal1", false).await;
-    for k in 0..=3 {
-        sounds.preload(format!("kick{}", k), false).await;
+        self.sounds.get(key).unwrap().0
+    }
+    // the player's volume setting for music or sound effects, whichever this sound is
+    fn volume(&self, key: &str) -> f32 {
+        if self.sounds.get(key).unwrap().1 {
+            self.music_volume
+        } else {
+            self.sfx_volume
+        }
+    }
+    fn play(&self, key: &str) {
+        play_sound(
+            self.get(key),
+            PlaySoundParams {
+                looped: false,
+                volume: self.volume(key),
+            },
+        );
+    }
+    fn play_looped(&self, key: &str, volume: f32) {
+        play_sound(
+            self.get(key),
+            PlaySoundParams {
+                looped: true,
+                volume: volume * self.volume(key),
+            },
+        );
+    }
+    fn set_volume(&self, key: &str, volume: f32) {
+        set_sound_volume(self.get(key), volume * self.volume(key));
+    }
+}
+
+// a match set up the way the title menu's choices say
+fn new_match(settings: &Settings, profiles: &Profiles) -> Game {
+    // a human team plays with its profile's squad
+    let rosters = [0, 1].map(|t| {
+        let human = t == 0 || matches!(settings.num_players, NumPlayers::Two);
+        profiles.roster(settings.profiles[t].filter(|_| human), t)
+    });
+    new_match_with_squads(settings, rosters)
+}
+
+// the same, but with the squads given rather than taken from the profiles, as in a replay
+fn new_match_with_squads(settings: &Settings, rosters: [Roster; 2]) -> Game {
+    let mut game = match settings.num_players {
+        NumPlayers::One => {
+            let mut game = Game::new(get_difficulty(settings.difficulty_level));
+            game.teams[0].controls = Some(settings.one_player_controls());
+            game.teams[1].controls = None;
+            game
+        }
+        NumPlayers::Two => {
+            let mut game = Game::new(get_difficulty(DifficultyLevel::Hard));
+            game.teams[0].controls = Some(TEAM_CONTROLS[0]);
+            game.teams[1].controls = Some(TEAM_CONTROLS[1]);
+            game.split_screen = settings.split_screen;
+            game
+        }
+    };
+    game.rosters = rosters;
+    game.apply_settings(settings);
+    game
+}
+
+// give a new match random numbers of its own, so that its replay can start from the same place
+fn seed_match() -> u64 {
+    let seed = macroquad::rand::rand() as u64;
+    macroquad::rand::srand(seed);
+    seed
+}
+
+fn main() {
+    let options = LaunchOptions::from_args();
+    balance::load();
+    options.sim_log.use_for_new_matches();
+    macroquad::rand::srand(
+        options
+            .seed
+            .unwrap_or(macroquad::miniquad::date::now() as u64),
+    );
+    if let Some(entrants) = &options.tournament {
+        bots::run_tournament(entrants, &options);
+    } else if options.headless {
+        launch::run_headless(&options);
+    } else {
+        macroquad::Window::from_config(window_conf(), run(options));
     }
+}
+
+async fn run(options: LaunchOptions) {
+    let use_images = options.use_images;
+    let themes = assets::subdirectories("images");
+    let mut strings = Strings::load(0).await;
+    let (mut textures, mut sounds) = loading::load_assets(use_images, None).await;
     let mut state = State::Menu(MenuState::NumPlayers, Settings::new());
     let mut game = Game::new(get_difficulty(DifficultyLevel::Hard));
-    let mut debug_draw = false;
+    if let Some(new_game) = options.new_game() {
+        game = new_game;
+        state = State::Play;
+    }
+    let mut history = History::load();
+    let mut replays = Replays::load();
+    let mut profiles = Profiles::load();
+    let mut checksums = checksum::Checksums::from_args();
+    let mut recording = Recording::from_args();
+    let mut render_options = RenderOptions {
+        debug_draw: false,
+        overlays: OVERLAYS.map(|(_, _, _, on)| on),
+        player_labels: PlayerLabels::Active,
+        accessibility: Accessibility::new(),
+    };
     let mut sound_state = SoundState::None;
+    let mut sudden_death_music = false;
+    let mut scaler = Scaler::new();
+    let split_targets = [
+        render_target(WIDTH as u32 / 2, HEIGHT as u32),
+        render_target(WIDTH as u32 / 2, HEIGHT as u32),
+    ];
+    let mut timestep = FixedTimestep::new();
+    let mut precipitation = Precipitation::new();
+    let mut camera_effects = true;
+    let mut captions = Captions::new();
+    let mut crowd_noise = CrowdNoise::new();
+    let mut console = Console::new();
+    let mut inspector = Inspector::new();
+    let mut perf = PerfHud::new();
+    // when set, the match only advances a step at a time, on F10
+    let mut frame_step = false;
+    let mut background_pause = true;
+    let mut focus = FocusWatch::new();
+    // a match stops while this is set, until a key is pressed or the window comes back into focus
+    let mut paused = false;
+    // a match also stops while this is open, from Escape
+    let mut pause_menu: Option<PauseMenu> = None;
+    let mut rewind = Rewind::new();
+    let mut touch = TouchControls::new();
+    let mut pointer = Pointer::new();
+    // only while a training session is being used to teach the controls
+    let mut tutorial: Option<Tutorial> = None;
+    // the match being recorded for its replay, or the replay being played instead
+    let mut match_record: Option<MatchRecord> = None;
+    let mut playback: Option<Playback> = None;
+    let mut replay_controls = ReplayControls::new();
+    let mut highlights = Highlights::new();
+    let mut screenshots = Screenshots::new();
+    let mut photo: Option<PhotoMode> = None;
+    // the other player, in a match played over the network
+    let mut net: Option<NetMatch> = None;
+    let mut chat = Chat::new();
+    let mut broadcast = Broadcast::from_args();
+    if let Some(address) = &options.spectate {
+        match Spectator::connect(address) {
+            Ok(spectator) => state = State::Spectate(Box::new(spectator)),
+            Err(e) => warn!("couldn't watch {}: {}", address, e),
+        }
+    }
+    if let Some((replay_game, replay)) = options.replay.as_deref().and_then(replays::open_file) {
+        game = replay_game;
+        playback = Some(replay);
+        state = State::Play;
+    }
+    let mut renderer = Renderer::new();
+    #[cfg(feature = "hot-reload")]
+    let mut watcher = hot_reload::Watcher::new(&textures);
     loop {
+        perf.begin_frame();
+        #[cfg(feature = "hot-reload")]
+        if watcher.update(&mut textures, scaler.filter()) {
+            game.balance = balance::current();
+        }
+        let steps = timestep.steps();
+        touch.update(&scaler);
+        pointer.update(&scaler);
+        match focus.update() {
+            // the other player wouldn't be able to carry on either
+            FocusChange::Lost => {
+                paused |= background_pause && matches!(state, State::Play) && net.is_none()
+            }
+            FocusChange::Regained => paused = false,
+            FocusChange::None => (),
+        }
+        game.set_camera_effects(camera_effects);
+        // a network match can't carry on once the other player has gone, and until then they may have
+        // said something
+        if let (Some(other), State::Play) = (&mut net, &state) {
+            match other.receive(&mut game) {
+                Ok(heard) => {
+                    // what's kept for rewinding belongs to the match before it was resynced
+                    if heard.iter().any(|h| matches!(h, Heard::Resynced)) {
+                        rewind.clear();
+                    }
+                    chat.update(other, &heard, &sounds);
+                }
+                Err(e) => {
+                    warn!("network match abandoned: {}", e);
+                    net = None;
+                    match_record = None;
+                    state = State::Menu(
+                        MenuState::Lobby(Box::new(Lobby::failed(e))),
+                        Settings::new(),
+                    );
+                    game = Game::new(get_difficulty(DifficultyLevel::Hard));
+                }
+            }
+        }
         match state {
             State::Menu(ref mut menu_state, ref mut settings) => {
-                if is_key_pressed(KeyCode::Space) {
-                    match menu_state {
-                        MenuState::Difficulty => {
-                            game = Game::new(get_difficulty(settings.difficulty_level));
-                            game.teams[0].controls = Some(TEAM_CONTROLS[0]);
+                if let MenuState::Options(page, ref mut selected) = *menu_state {
+                    let mut theme = textures.theme().map(str::to_owned);
+                    let mut language = strings.language();
+                    let filter = scaler.filter();
+                    let mut options = OptionsContext {
+                        settings,
+                        scaler: &mut scaler,
+                        sounds: &mut sounds,
+                        theme: &mut theme,
+                        themes: &themes,
+                        camera_effects: &mut camera_effects,
+                        background_pause: &mut background_pause,
+                        accessibility: &mut render_options.accessibility,
+                        language: &mut language,
+                        strings: &strings,
+                        history: &history,
+                        replays: &replays,
+                        profiles: &mut profiles,
+                    };
+                    let exit = update_options_menu(page, selected, &mut options, &pointer);
+                    let reload = use_images && theme.as_deref() != textures.theme();
+                    if reload {
+                        textures.delete();
+                        textures = loading::load_textures(theme).await;
+                        #[cfg(feature = "hot-reload")]
+                        {
+                            watcher = hot_reload::Watcher::new(&textures);
+                        }
+                    }
+                    if reload || scaler.filter() != filter {
+                        textures.set_filter(scaler.filter());
+                    }
+                    if language != strings.language() {
+                        strings = Strings::load(language).await;
+                    }
+                    match exit {
+                        OptionsExit::Stay => (),
+                        OptionsExit::Menu(next) => *menu_state = next,
+                        OptionsExit::StartTraining | OptionsExit::StartTutorial => {
+                            // the tutorial's prompts are written for the keyboard controls, and
+                            // don't need dummies or targets getting in the way
+                            let teach = matches!(exit, OptionsExit::StartTutorial);
+                            // there's no opposition for the difficulty to affect
+                            game = Game::new(get_difficulty(DifficultyLevel::Easy));
+                            game.teams[0].controls = Some(if teach {
+                                TEAM_CONTROLS[0]
+                            } else {
+                                settings.one_player_controls()
+                            });
                             game.teams[1].controls = None;
+                            game.apply_settings(settings);
+                            game.start_training(if teach {
+                                TrainingSetup {
+                                    dummies: false,
+                                    targets: false,
+                                }
+                            } else {
+                                settings.training
+                            });
+                            tutorial = teach.then(Tutorial::new);
                             state = State::Play;
                         }
-                        MenuState::NumPlayers => match settings.num_players {
-                            NumPlayers::One => {
-                                *menu_state = MenuState::Difficulty;
-                            }
-                            NumPlayers::Two => {
-                                game = Game::new(get_difficulty(DifficultyLevel::Hard));
-                                game.teams[0].controls = Some(TEAM_CONTROLS[0]);
-                                game.teams[1].controls = Some(TEAM_CONTROLS[1]);
+                        OptionsExit::StartPenalties => state = State::Penalties(Penalties::new()),
+                        OptionsExit::PlayReplay(n) => {
+                            if let Some((replay_game, replay)) = replays.open(n) {
+                                game = replay_game;
+                                playback = Some(replay);
                                 state = State::Play;
                             }
-                        },
+                        }
+                    }
+                } else if let MenuState::RosterEditor(ref mut editor) = *menu_state {
+                    if editor.update(&pointer, &sounds, &mut profiles) {
+                        let item = OPTIONS_PAGES[PROFILES_PAGE].items.iter().position(
+                            |item| matches!(item, OptionItem::EditRoster(t) if *t == editor.team),
+                        );
+                        *menu_state = MenuState::Options(PROFILES_PAGE, item.unwrap_or(0));
+                    }
+                } else if let MenuState::Lobby(ref mut lobby) = *menu_state {
+                    match lobby.update(&pointer, &sounds) {
+                        LobbyExit::Stay => (),
+                        LobbyExit::Leave => *menu_state = MenuState::Options(NETWORK_PAGE, 0),
+                        LobbyExit::Start(kickoff) => {
+                            let Kickoff {
+                                game: net_game,
+                                net: net_match,
+                            } = *kickoff;
+                            game = net_game;
+                            net = Some(net_match);
+                            chat = Chat::new();
+                            state = State::Play;
+                        }
+                    }
+                } else if update_title_menu(
+                    menu_state,
+                    settings,
+                    &pointer,
+                    touch.tapped(),
+                    &strings,
+                    &sounds,
+                ) {
+                    let seed = seed_match();
+                    game = new_match(settings, &profiles);
+                    match_record = Some(MatchRecord::new(seed, settings, &game));
+                    state = State::Play;
+                }
+                // a match that's just been started waits for its first step in State::Play, where
+                // it's recorded
+                if state.is_menu() {
+                    for _ in 0..steps {
+                        game.update();
+                        renderer.stadium.crowd.update(&game.events);
+                    }
+                }
+            }
+            // whatever's done from the console isn't recorded, so the match can't be replayed
+            State::Play if console.is_open() => {
+                console.update(&mut game);
+                match_record = None;
+            }
+            // the key or click that carries on isn't passed to the match as well
+            State::Play if paused => {
+                paused = get_last_key_pressed().is_none()
+                    && !is_mouse_button_pressed(MouseButton::Left)
+                    && !touch.tapped();
+            }
+            State::Play if pause_menu.is_some() => {
+                let menu = pause_menu.as_mut().unwrap();
+                if menu.update(&mut game, &pointer, &sounds) {
+                    pause_menu = None;
+                }
+            }
+            State::Play => {
+                if net.is_some() && is_key_pressed(KeyCode::Escape) {
+                    // there's no pausing a match the other player is still playing, only leaving
+                    if let Some(net) = net.take() {
+                        net.close();
+                    }
+                    match_record = None;
+                    state = State::Menu(MenuState::Options(NETWORK_PAGE, 0), Settings::new());
+                    game = Game::new(get_difficulty(DifficultyLevel::Hard));
+                } else if net.is_some() {
+                    // nor stopping it for a photo
+                } else if is_key_pressed(KeyCode::P)
+                    || (photo.is_some() && is_key_pressed(KeyCode::Escape))
+                {
+                    photo = match photo {
+                        Some(_) => None,
+                        None => Some(PhotoMode::new(&game.camera)),
                     };
+                } else if photo.is_some() {
+                    // the match's own keys wait until it carries on
+                } else if game.training.is_some() {
+                    if is_key_pressed(KeyCode::Escape) {
+                        let mut settings = Settings::new();
+                        settings.training = game.training.as_ref().unwrap().setup;
+                        state = State::Menu(MenuState::Options(TRAINING_PAGE, 0), settings);
+                        game = Game::new(get_difficulty(DifficultyLevel::Hard));
+                        tutorial = None;
+                    } else if is_key_pressed(KeyCode::R) {
+                        game.score_timer = 0;
+                        game.restart = Restart::Kickoff { team: 0 };
+                        game.reset();
+                    }
+                } else if playback.is_some() && is_key_pressed(KeyCode::Escape) {
+                    state = State::Menu(MenuState::Options(REPLAYS_PAGE, 0), Settings::new());
+                    game = Game::new(get_difficulty(DifficultyLevel::Hard));
+                    playback = None;
+                } else if is_key_pressed(KeyCode::Escape) {
+                    pause_menu = Some(PauseMenu::new());
+                }
+                if render_options.debug_draw {
+                    inspector.update(&game);
+                    renderer.movement.focus = inspector.selected();
+                }
+                // a replay's human teams get their input from it, a step at a time, and in photo
+                // mode the keys are flying the camera
+                if playback.is_none() && photo.is_none() {
+                    game.aim_at(scaler.mouse_position());
+                    for t in 0..=1 {
+                        // in a network match the other team's input comes from the other player,
+                        // and this player has the one player keys whichever team they are
+                        let controls = match &net {
+                            Some(net) if net.local_team == t => Some(TEAM_CONTROLS[0]),
+                            Some(_) => None,
+                            None => game.teams[t].controls,
+                        };
+                        if let Some(controls) = controls {
+                            let input = Keyboard(controls).read(&game, t);
+                            game.apply_input(t, input);
+                        }
+                    }
+                    if net.is_none() {
+                        game.apply_touch(&touch);
+                    }
+                }
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                let steps = if net.is_some() {
+                    steps
+                } else if let Some(photo) = &mut photo {
+                    photo.update()
+                } else if frame_step {
+                    if is_key_pressed(KeyCode::F10) && shift {
+                        rewind.step_back(&mut game);
+                        match_record = None;
+                        if let Some(playback) = &mut playback {
+                            playback.step_back();
+                        }
+                    }
+                    (is_key_pressed(KeyCode::F10) && !shift) as u32
+                } else if let Some(playback) = &mut playback {
+                    let step = playback.step();
+                    let steps = replay_controls.update(playback, &mut game, &pointer, steps);
+                    // after a seek, what's kept for rewinding belongs to another part of the match
+                    if playback.step() != step {
+                        rewind.clear();
+                    }
+                    steps
                 } else {
-                    let mut change = MenuChange::NoChange;
-                    if is_key_pressed(KeyCode::Up) {
-                        change = MenuChange::Up;
-                    } else if is_key_pressed(KeyCode::Down) {
-                        change = MenuChange::Down;
+                    steps
+                };
+                for _ in 0..steps {
+                    if let Some(playback) = &mut playback {
+                        playback.apply(&mut game);
                     }
-                    if change != MenuChange::NoChange {
-                        play_sound_once(sounds.get("move"));
-                        match menu_state {
-                            MenuState::NumPlayers => {
-                                settings.num_players = match settings.num_players {
-                                    NumPlayers::One => NumPlayers::Two,
-                                    NumPlayers::Two => NumPlayers::One,
-                                }
+                    if let Some(net) = &mut net {
+                        // wait for the other player to catch up
+                        if !net.ready() {
+                            break;
+                        }
+                        net.apply(&mut game);
+                    }
+                    rewind.record(&game);
+                    if let Some(recording) = &mut recording {
+                        recording.record(&game);
+                    }
+                    if let Some(record) = &mut match_record {
+                        record.record(&game);
+                    }
+                    let game_over = game.teams[0].score.max(game.teams[1].score)
+                        == game.goals_to_win
+                        && game.score_timer == 1;
+                    game.update();
+                    if let Some(broadcast) = &mut broadcast {
+                        broadcast.update(&game);
+                    }
+                    highlights.update(&game, &rewind);
+                    renderer.movement.record(&game);
+                    if let Some(checksums) = &mut checksums {
+                        checksums.update(&game);
+                    }
+                    if let Some(tutorial) = &mut tutorial {
+                        tutorial.update(&mut game);
+                    }
+                    play_game_sounds(
+                        &game,
+                        &mut crowd_noise,
+                        &sounds,
+                        &render_options.accessibility,
+                    );
+                    captions.update(&game.events);
+                    renderer.stadium.crowd.update(&game.events);
+                    if game_over {
+                        // watching a replay doesn't count as playing the match again
+                        if playback.is_none() {
+                            let result = game.result();
+                            profiles.record(game.profiles, result.score);
+                            if let Some(record) = match_record.take() {
+                                replays.save(&record, &result);
+                            } else if let Some(net) = &net {
+                                // a network match keeps its own, which is still right after
+                                // resyncing
+                                replays.save(net.record(), &result);
                             }
-                            MenuState::Difficulty => {
-                                settings.difficulty_level =
-                                    match (settings.difficulty_level, change) {
-                                        (DifficultyLevel::Easy, MenuChange::Up) => {
-                                            DifficultyLevel::Hard
-                                        }
-                                        (DifficultyLevel::Easy, MenuChange::Down) => {
-                                            DifficultyLevel::Medium
-                                        }
-                                        (DifficultyLevel::Medium, MenuChange::Up) => {
-                                            DifficultyLevel::Easy
-                                        }
-                                        (DifficultyLevel::Medium, MenuChange::Down) => {
-                                            DifficultyLevel::Hard
-                                        }
-                                        (DifficultyLevel::Hard, MenuChange::Up) => {
-                                            DifficultyLevel::Medium
-                                        }
-                                        (DifficultyLevel::Hard, MenuChange::Down) => {
-                                            DifficultyLevel::Easy
-                                        }
-                                        (_, MenuChange::NoChange) => unreachable!(),
-                                    }
+                            history.record(result);
+                            if let Some(recording) = &recording {
+                                recording.save();
                             }
                         }
+                        sounds.play("full_time");
+                        state = State::GameOver;
+                        break;
+                    }
+                }
+            }
+            State::Penalties(ref mut penalties) => {
+                if is_key_pressed(KeyCode::Escape) {
+                    state = State::Menu(MenuState::Options(TRAINING_PAGE, 0), Settings::new());
+                } else {
+                    penalties.camera.effects = camera_effects;
+                    penalties.latch_input(touch.tapped());
+                    for _ in 0..steps {
+                        penalties.update();
+                        play_event_sounds(
+                            &penalties.events,
+                            &sounds,
+                            &render_options.accessibility,
+                        );
+                        if penalties.result_ending() {
+                            sounds.set_volume("crowd", 0.5);
+                        }
+                        renderer.stadium.crowd.update(&penalties.events);
                     }
                 }
-                game.update();
             }
-            State::Play => {
-                if game.teams[0].score.max(game.teams[1].score) == GOALS_TO_WIN
-                    && game.score_timer == 1
-                {
-                    state = State::GameOver;
+            State::Spectate(ref mut spectator) => {
+                if is_key_pressed(KeyCode::Escape) {
+                    state = State::Menu(MenuState::NumPlayers, Settings::new());
+                } else {
+                    spectator.update();
                 }
-                game.update();
             }
             State::GameOver => {
-                if is_key_pressed(KeyCode::Space) {
-                    state = State::Menu(MenuState::NumPlayers, Settings::new());
+                if is_key_pressed(KeyCode::Space) || touch.tapped() {
+                    // a replay goes back to the list it was chosen from
+                    let menu_state = match playback.take() {
+                        Some(_) => MenuState::Options(REPLAYS_PAGE, 0),
+                        None => MenuState::NumPlayers,
+                    };
+                    state = State::Menu(menu_state, Settings::new());
                     game = Game::new(get_difficulty(DifficultyLevel::Hard));
                 }
             }
         }
 
-        if is_key_pressed(KeyCode::F1) {
-            debug_draw = !debug_draw;
+        // the console only belongs to a match in progress
+        let playing = matches!(state, State::Play);
+        if !playing {
+            rewind.clear();
+            replay_controls = ReplayControls::new();
+            photo = None;
+            if let Some(net) = net.take() {
+                net.close();
+            }
         }
-
-        let offs_x = (game.camera_focus.x - WIDTH as f32 / 2.)
-            .min(LEVEL_W - WIDTH)
-            .max(0.0) as f32;
-        let offs_y = (game.camera_focus.y - HEIGHT as f32 / 2.)
-            .min(LEVEL_H - HEIGHT)
-            .max(0.0) as f32;
-        draw_texture(textures.get("pitch"), -offs_x, -offs_y, WHITE);
-
-        let mut sprites: Vec<(String, f32, f32, f32)> = Vec::new();
-
-        for (_id, (pos, team, anim)) in &mut game.world.query::<(&Position, &Team, &Animation)>() {
-            let suffix = format!("{}{}", anim.dir.0, (anim.frame as u32 / 18));
-            sprites.push((
-                format!("player{}{}", team.0, suffix).to_owned(),
-                pos.0.x - offs_x - 25., // hardcoded anchor
-                pos.0.y - offs_y - 37., // hardcoded anchor
-                pos.0.y,
-            ));
-            draw_texture(
-                textures.get(&format!("players{}", suffix)),
-                pos.0.x - offs_x - 25.,
-                pos.0.y - offs_y - 37.,
-                WHITE,
-            );
+        if state.is_menu() {
+            highlights.clear();
         }
-
-        // draw ball
-        let ball_pos = &*game.world.get::<Position>(game.ball).unwrap();
-        sprites.push((
-            "ball".to_owned(),
-            ball_pos.0.x - offs_x - 12.5,
-            ball_pos.0.y - offs_y - 12.5,
-            ball_pos.0.y,
-        ));
-        draw_texture(
-            textures.get("balls"),
-            ball_pos.0.x - offs_x - 12.5,
-            ball_pos.0.y - offs_y - 12.5,
-            WHITE,
-        );
-
-        // draw goals
-        sprites.push((
-            "goal0".to_owned(),
-            HALF_LEVEL_W - offs_x - 100.0,
-            0.0 - offs_y - 81.0,
-            0.0,
-        ));
-        sprites.push((
-            "goal1".to_owned(),
-            HALF_LEVEL_W - offs_x - 100.0,
-            LEVEL_H - offs_y - 125.0,
-            LEVEL_H,
-        ));
-
-        sprites.sort_unstable_by(|(_, _, _, y1), (_, _, _, y2)| {
-            y1.partial_cmp(y2).unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        for (key, x, y, _) in sprites {
-            draw_texture(textures.get(&key), x, y, WHITE);
+        // nor can it be used in a network match, where it would only change one side's match
+        if (playing && net.is_none() && is_key_pressed(KeyCode::GraveAccent))
+            || (!playing && console.is_open())
+        {
+            console.toggle();
         }
-
-        for t in 0..=1 {
-            if game.teams[t].human() {
-                if let Some(id) = game.teams[t].active_player {
-                    if let Ok(pos) = game.world.get::<Position>(id) {
-                        draw_texture(
-                            textures.get(&format!("arrow{}", t)),
-                            pos.0.x - offs_x - 11.,
-                            pos.0.y - offs_y - 45.,
-                            WHITE,
-                        )
+        // the console needs all the letters for typing
+        if !console.is_open() {
+            if is_key_pressed(KeyCode::F1) {
+                render_options.debug_draw = !render_options.debug_draw;
+            }
+            if render_options.debug_draw {
+                for (overlay, key, _, _) in OVERLAYS {
+                    if is_key_pressed(key) {
+                        let on = &mut render_options.overlays[overlay as usize];
+                        *on = !*on;
                     }
                 }
             }
+            if is_key_pressed(KeyCode::N) {
+                render_options.player_labels = render_options.player_labels.next();
+            }
+            if is_key_pressed(KeyCode::F8) {
+                perf.toggle();
+            }
+            if is_key_pressed(KeyCode::F9) {
+                frame_step = !frame_step;
+            }
+            if is_key_pressed(KeyCode::F11) {
+                scaler.toggle_fullscreen();
+            }
+            if is_key_pressed(KeyCode::F12) {
+                screenshots.request();
+            }
+            if is_key_pressed(KeyCode::G) && matches!(state, State::Play | State::GameOver) {
+                highlights.save();
+            }
+        }
+        perf.end_sim();
+
+        highlights.export(&mut renderer, &textures, &render_options);
+
+        if let State::Penalties(penalties) = &state {
+            set_camera(&scaler.with_target(penalties.camera.world_camera()));
+            clear_background(BLACK);
+            textures.draw(Sprite::Pitch, 0.0, 0.0);
+            renderer.stadium.crowd.draw();
+            penalties.draw_world(&textures);
+            set_camera(&scaler.screen_camera());
+        } else if let State::Spectate(spectator) = &state {
+            spectator.draw_world(&mut renderer, &textures, &scaler, &render_options);
+        } else if game.split_screen
+            && matches!(state, State::Play)
+            && photo.is_none()
+            && replay_controls.camera().is_none()
+        {
+            // each team gets its own half of the screen, team 1 on the left to match the scoreboard
+            for (half, rt) in split_targets.iter().enumerate() {
+                let team = 1 - half;
+                renderer.draw(
+                    &game,
+                    &textures,
+                    &game.team_cameras[team],
+                    *rt,
+                    &render_options,
+                );
+                set_camera(&Camera2D {
+                    render_target: Some(*rt),
+                    ..Camera2D::from_display_rect(Rect::new(0.0, 0.0, WIDTH / 2.0, HEIGHT))
+                });
+                draw_score_bar(&game, &textures, WIDTH / 4.0);
+            }
+            set_camera(&scaler.screen_camera());
+            for (half, rt) in split_targets.iter().enumerate() {
+                draw_texture_ex(
+                    rt.texture,
+                    half as f32 * WIDTH / 2.0,
+                    0.0,
+                    WHITE,
+                    DrawTextureParams {
+                        flip_y: true,
+                        ..Default::default()
+                    },
+                );
+            }
+            draw_line(
+                HALF_WINDOW_WIDTH,
+                0.0,
+                HALF_WINDOW_WIDTH,
+                HEIGHT,
+                4.0,
+                BLACK,
+            );
+        } else {
+            // draw the level in level coordinates through the game camera, or a free one
+            let camera = match &photo {
+                Some(photo) => photo.camera(),
+                None => replay_controls.camera().unwrap_or(&game.camera),
+            };
+            renderer.draw(&game, &textures, camera, scaler.target(), &render_options);
+            if render_options.debug_draw {
+                inspector.draw_marker(&game);
+            }
+            set_camera(&scaler.screen_camera());
+        }
+
+        if !state.is_menu() {
+            precipitation.update(game.weather, get_frame_time());
+            precipitation.draw();
         }
 
         match state {
+            State::Menu(MenuState::Options(page, selected), ref mut settings) => {
+                let mut theme = textures.theme().map(str::to_owned);
+                let mut language = strings.language();
+                let options = OptionsContext {
+                    settings,
+                    scaler: &mut scaler,
+                    sounds: &mut sounds,
+                    theme: &mut theme,
+                    themes: &themes,
+                    camera_effects: &mut camera_effects,
+                    background_pause: &mut background_pause,
+                    accessibility: &mut render_options.accessibility,
+                    language: &mut language,
+                    strings: &strings,
+                    history: &history,
+                    replays: &replays,
+                    profiles: &mut profiles,
+                };
+                draw_options_menu(page, selected, &options);
+            }
+            State::Menu(MenuState::RosterEditor(ref editor), _) => editor.draw(&profiles, &strings),
+            State::Menu(MenuState::Lobby(ref lobby), _) => lobby.draw(&strings),
             State::Menu(ref menu_state, ref settings) => {
-                let key = match menu_state {
-                    MenuState::NumPlayers => {
-                        format!("menu0{}", settings.num_players as usize).to_owned()
-                    }
-                    MenuState::Difficulty => {
-                        format!("menu1{}", settings.difficulty_level as usize).to_owned()
+                let (page, selected) = match menu_state {
+                    MenuState::NumPlayers => (0, settings.num_players as u8),
+                    MenuState::Difficulty => (1, settings.difficulty_level as u8),
+                    MenuState::Options(_, _) | MenuState::RosterEditor(_) | MenuState::Lobby(_) => {
+                        unreachable!()
                     }
                 };
-                draw_texture(textures.get(&key), 0.0, 0.0, WHITE);
+                // the menu images have English text on them, so other languages get text menus
+                if strings.matches_images() {
+                    textures.draw(Sprite::Menu(page, selected), 0.0, 0.0);
+                } else {
+                    draw_title_text(page, selected, &strings);
+                }
+                if let MenuState::NumPlayers = menu_state {
+                    for s in settings_shortcuts(&strings) {
+                        draw_text(s.label, s.pos.x, s.pos.y, SHORTCUT_FONT_SIZE, WHITE);
+                    }
+                }
+                if let (MenuState::NumPlayers, NumPlayers::Two) = (menu_state, settings.num_players)
+                {
+                    let (label, pos) = split_screen_label(settings, &strings);
+                    draw_text(&label, pos.x, pos.y, 24., WHITE);
+                }
             }
+            // nothing gets in the way of a photo
+            State::Play if photo.is_some() => (),
             State::Play => {
-                draw_texture(textures.get("bar"), HALF_WINDOW_WIDTH - 176., 0., WHITE);
-                for i in 0..=1 {
-                    draw_texture(
-                        textures.get(&format!("s{}", game.teams[i].score)),
-                        HALF_WINDOW_WIDTH + 7. - 39. * i as f32,
-                        6.,
-                        WHITE,
+                if let Some(tutorial) = &tutorial {
+                    tutorial.draw(&strings);
+                } else if let Some(training) = &game.training {
+                    training.draw(&strings);
+                } else if render_options.accessibility.large_text {
+                    accessibility::draw_large_score(
+                        [game.teams[0].score, game.teams[1].score],
+                        &strings,
                     );
+                    captions.draw(&strings);
+                } else if !game.split_screen {
+                    draw_score_bar(&game, &textures, HALF_WINDOW_WIDTH);
                 }
-                if game.score_timer > 0 {
-                    draw_texture(
-                        textures.get("goal"),
-                        HALF_WINDOW_WIDTH - 300.,
-                        HEIGHT / 2. - 88.,
-                        WHITE,
-                    );
+                if game.mutators.power_ups {
+                    powerups::draw_hud(&game.world, HALF_WINDOW_WIDTH);
                 }
-            }
-            State::GameOver => {
-                draw_texture(
-                    textures.get(if game.teams[0].score > game.teams[1].score {
-                        "over0"
+                if game.score_timer > 0 {
+                    let offset = goal_banner_offset(game.score_timer);
+                    if strings.matches_images() {
+                        textures.draw(
+                            Sprite::GoalBanner,
+                            HALF_WINDOW_WIDTH - 300. + offset,
+                            HEIGHT / 2. - 88.,
+                        );
                     } else {
-                        "over1"
-                    }),
-                    0.0,
-                    0.0,
-                    WHITE,
-                );
-                for i in 0..=1 {
-                    draw_texture(
-                        textures.get(&format!("l{}{}", i, game.teams[i].score)),
-                        HALF_WINDOW_WIDTH + 25. - 125. * i as f32,
-                        144.,
-                        WHITE,
+                        draw_text_centred(
+                            strings.get("hud.goal"),
+                            HALF_WINDOW_WIDTH + offset,
+                            HEIGHT / 2. + 30.,
+                            120.,
+                        );
+                    }
+                    if let Some(scorer) = &game.scorer {
+                        draw_scorer(scorer, &strings, HALF_WINDOW_WIDTH + offset);
+                    }
+                }
+                if let Some(booking) = &game.booking {
+                    draw_booking(booking, &strings);
+                }
+                if let Some(substitution) = &game.substitution {
+                    draw_substitution(substitution, &strings);
+                }
+                if game.added_board > 0 {
+                    draw_added_time(game.added_minutes(), &strings);
+                }
+                if let Some(count) = game.kickoff_count() {
+                    draw_text_centred(
+                        &count.to_string(),
+                        HALF_WINDOW_WIDTH,
+                        HEIGHT / 2. + 30.,
+                        120.,
                     );
                 }
-            }
-        }
-
-        if debug_draw {
-            draw_text("DEBUG MODE", 10., 10., 16., WHITE);
-            // show player movement targets
-            for (_, (pos, target)) in &mut game.world.query::<(&Position, &Target)>() {
-                debug_draw_line(offs_x, offs_y, pos.0, target.pos, 1.0, RED);
-            }
-            // show shoot target
-            match (game.debug_shoot_target, game.ball_owner) {
-                (Some(v1), Some(owner_id)) => {
-                    let v2 = game.world.get::<Position>(owner_id).unwrap().0;
-                    debug_draw_line(offs_x, offs_y, v1, v2, 2.0, MAGENTA);
+                if let Some(net) = &net {
+                    chat.draw(net.local_team, &strings);
+                    let status = match net.reconnecting() {
+                        Some(seconds) => {
+                            strings.format("hud.reconnecting", &[&seconds.to_string()])
+                        }
+                        None if !net.ready() => strings.get("hud.waiting").to_owned(),
+                        None => String::new(),
+                    };
+                    draw_text_centred(&status, HALF_WINDOW_WIDTH, HEIGHT - 10.0, 16.0);
                 }
-                _ => (),
-            }
-            // show peers
-            for (_, (pos, peer)) in &mut game.world.query::<(&Position, &Peer)>() {
-                let peer_pos = game.world.get::<Position>(peer.0).unwrap();
-                debug_draw_line(offs_x, offs_y, pos.0, peer_pos.0, 1.0, BLUE);
-            }
-            // show leads
-            for (_, (pos, lead)) in &mut game.world.query::<(&Position, &Lead)>() {
-                if let Lead(dist, Some(index)) = lead {
-                    draw_text(
-                        &format!("LEAD {}", index).to_owned(),
-                        pos.0.x - offs_x + 15.0,
-                        pos.0.y - offs_y,
-                        24.0,
-                        BLACK,
+                if let Some(playback) = &playback {
+                    replay_controls.draw(playback, &strings);
+                    draw_text_centred(
+                        strings.get("hud.replay_keys"),
+                        HALF_WINDOW_WIDTH,
+                        HEIGHT - 10.0,
+                        16.0,
                     );
-                    if dist.is_some() && game.ball_owner.is_some() {
-                        let v2 = game
-                            .world
-                            .get::<Position>(game.ball_owner.unwrap())
-                            .unwrap()
-                            .0;
-                        debug_draw_line(offs_x, offs_y, pos.0, v2, 2.0, BLACK)
-                    }
                 }
+                touch.draw(&strings);
             }
-            if let Some(owner_id) = game.ball_owner {
-                let ball_owner_team = game.world.get::<Team>(owner_id).unwrap().0;
-                for x in (0..(LEVEL_W as i32)).step_by(60) {
-                    for y in (0..(LEVEL_H as i32)).step_by(26) {
-                        let v = vec2(x as f32, y as f32);
-                        let c = game.cost(v, ball_owner_team, 0.0);
-                        draw_text(
-                            &format!("{:.0}", c).to_owned(),
-                            v.x - offs_x,
-                            v.y - offs_y,
-                            24.0,
-                            GRAY,
+            State::Penalties(ref penalties) => penalties.draw_hud(&strings),
+            State::Spectate(ref spectator) => spectator.draw_hud(&strings),
+            State::GameOver => {
+                let winner = if game.teams[0].score > game.teams[1].score {
+                    0
+                } else {
+                    1
+                };
+                if strings.matches_images() {
+                    textures.draw(Sprite::GameOver(winner), 0.0, 0.0);
+                    for i in 0..=1 {
+                        textures.draw(
+                            Sprite::FinalScore(i as u8, game.teams[i].score),
+                            HALF_WINDOW_WIDTH + 25. - 125. * i as f32,
+                            144.,
                         );
                     }
+                } else {
+                    draw_game_over_text(&game, winner, &strings);
                 }
+                game.stats.draw(&game.rosters, &textures, &strings);
             }
         }
 
+        if render_options.debug_draw {
+            draw_text("DEBUG MODE", 10., 10., 16., WHITE);
+            for (i, (overlay, key, name, _)) in OVERLAYS.iter().enumerate() {
+                let on = render_options.overlays[*overlay as usize];
+                draw_text(
+                    &format!("{:?} {} {}", key, name, if on { "on" } else { "off" }),
+                    10.,
+                    42. + i as f32 * 16.,
+                    16.,
+                    if on { WHITE } else { GRAY },
+                );
+            }
+            if matches!(state, State::Play) {
+                inspector.draw_panel(&game);
+            }
+        }
+        if paused && matches!(state, State::Play) {
+            draw_rectangle(0., 0., WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.5));
+            draw_text_centred(strings.get("hud.paused"), HALF_WINDOW_WIDTH, 220., 48.);
+            draw_text_centred(
+                strings.get("hud.paused_resume"),
+                HALF_WINDOW_WIDTH,
+                260.,
+                24.,
+            );
+        }
+        if let (Some(menu), State::Play) = (&pause_menu, &state) {
+            menu.draw(&game, &strings);
+        }
+        if frame_step && matches!(state, State::Play) {
+            let text = format!(
+                "STEP {}: F10 NEXT, SHIFT+F10 BACK ({} KEPT)",
+                game.steps_played,
+                rewind.len()
+            );
+            let size = measure_text(&text, None, 16, 1.0);
+            draw_text(&text, WIDTH - size.width - 10., 26., 16., YELLOW);
+        }
+        if let Some(step) = checksums.as_ref().and_then(|c| c.desync) {
+            draw_text(&format!("DESYNC AT STEP {}", step), 10., 26., 16., RED);
+        }
+        highlights.draw(&strings);
+        if console.is_open() {
+            console.draw();
+        }
+        perf.draw(&game);
+        // the confirmation doesn't belong in the screenshot
+        screenshots.take(&scaler, &strings);
+        screenshots.draw();
+
         if state.is_menu() {
             if sound_state != SoundState::Menu {
                 sound_state = SoundState::Menu;
                 stop_sound(sounds.get("crowd"));
-                play_sound_looped(sounds.get("theme"), 1.0);
+                sounds.play_looped("theme", 1.0);
             }
         } else {
             if let SoundState::Play(vol) = sound_state {
                 if vol <= 0.0 {
                     stop_sound(sounds.get("theme"));
                 } else {
-                    set_sound_volume(sounds.get("theme"), vol);
-                    sound_state = SoundState::Play(vol - 1.0 / 30.0);
+                    sounds.set_volume("theme", vol);
+                    sound_state = SoundState::Play(vol - 2.0 * get_frame_time());
                 }
                 if vol == 1.0 {
                     stop_sound(sounds.get("goal0"));
@@ -1371,40 +4668,763 @@ async fn main() {
                 }
             } else {
                 sound_state = SoundState::Play(1.0);
-                play_sound_looped(sounds.get("crowd"), 0.5);
-            }
-            if game.score_timer == GOAL_FRAMES {
-                // scored a goal this frame
-                let n = rand::gen_range(0, 2);
-                play_sound_once(sounds.get(&format!("goal{}", n)));
+                sounds.play_looped("crowd", 0.5);
             }
-            if game.shoot_now[0] || game.shoot_now[1] {
-                let n = rand::gen_range(0, 4);
-                play_sound_once(sounds.get(&format!("kick{}", n)));
+        }
+        // the sudden death music plays for as long as the match is in sudden death, however it got
+        // there: a goal, a head start (see handicaps) or the console; a goal in sudden death ends
+        // the match, and the music with it
+        let sudden_death = matches!(state, State::Play) && game.sudden_death();
+        if sudden_death != sudden_death_music {
+            sudden_death_music = sudden_death;
+            if sudden_death {
+                sounds.play_looped("sudden_death", 1.0);
+            } else {
+                stop_sound(sounds.get("sudden_death"));
             }
         }
 
+        scaler.present();
+        perf.end_frame();
         next_frame().await;
     }
 }
 
-fn play_sound_looped(sound: Sound, volume: f32) {
-    play_sound(
-        sound,
-        PlaySoundParams {
-            looped: true,
-            volume,
+// the goal banner slides in from the left, pauses in the middle and then slides off to the right
+fn goal_banner_offset(score_timer: i32) -> f32 {
+    let ease_out = |t: f32| 1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3);
+    let slide_in = (GOAL_FRAMES - score_timer) as f32 / BANNER_SLIDE_FRAMES as f32;
+    let slide_out = score_timer as f32 / BANNER_SLIDE_FRAMES as f32;
+    WIDTH * ((1.0 - ease_out(slide_out)) - (1.0 - ease_out(slide_in)))
+}
+
+fn draw_score_bar(game: &Game, textures: &Textures, centre_x: f32) {
+    textures.draw(Sprite::ScoreBar, centre_x - 176., 0.);
+    if game.sudden_death() && (get_time() * SUDDEN_DEATH_FLASH_RATE).fract() < 0.5 {
+        draw_rectangle(
+            centre_x - 176.,
+            0.,
+            352.,
+            36.,
+            Color::new(1.0, 0.8, 0.0, 0.5),
+        );
+    }
+    for i in 0..=1 {
+        textures.draw(
+            Sprite::ScoreDigit(game.teams[i].score),
+            centre_x + 7. - 39. * i as f32,
+            6.,
+        );
+    }
+    let added = game.added_minutes();
+    if added > 0 {
+        draw_text(&format!("+{}", added), centre_x + 184., 26., 24., WHITE);
+    }
+}
+
+// a key on the title screen that leads to a page of options, with its label
+struct Shortcut<'a> {
+    key: KeyCode,
+    label: &'a str,
+    pos: Vec2,
+    page: usize,
+}
+
+fn settings_shortcuts(strings: &Strings) -> [Shortcut<'_>; 3] {
+    [
+        Shortcut {
+            key: KeyCode::T,
+            label: strings.get("title.training_shortcut"),
+            pos: Vec2::new(10., HEIGHT - 46.),
+            page: TRAINING_PAGE,
+        },
+        Shortcut {
+            key: KeyCode::A,
+            label: strings.get("title.accessibility_shortcut"),
+            pos: Vec2::new(10., HEIGHT - 28.),
+            page: ACCESSIBILITY_PAGE,
+        },
+        Shortcut {
+            key: KeyCode::O,
+            label: strings.get("title.options_shortcut"),
+            pos: Vec2::new(10., HEIGHT - 10.),
+            page: OPTIONS_PAGE,
+        },
+    ]
+}
+
+// the split screen option shown under the title screen in two player mode, and where it goes
+fn split_screen_label(settings: &Settings, strings: &Strings) -> (String, Vec2) {
+    let label = strings.format(
+        "title.split_screen",
+        &[strings.get(if settings.split_screen {
+            "options.on"
+        } else {
+            "options.off"
+        })],
+    );
+    let size = measure_text(&label, None, 24, 1.0);
+    (
+        label,
+        Vec2::new(HALF_WINDOW_WIDTH - size.width / 2., HEIGHT - 20.),
+    )
+}
+
+// handles the title and difficulty screens, returning true when it's time to kick off
+fn update_title_menu(
+    menu_state: &mut MenuState,
+    settings: &mut Settings,
+    pointer: &Pointer,
+    tapped: bool,
+    strings: &Strings,
+    sounds: &Sounds,
+) -> bool {
+    let (mut selected, count) = match menu_state {
+        MenuState::NumPlayers => (settings.num_players as usize - 1, NUM_PLAYERS_OPTIONS.len()),
+        MenuState::Difficulty => (settings.difficulty_level as usize, DIFFICULTY_OPTIONS.len()),
+        MenuState::Options(_, _) | MenuState::RosterEditor(_) | MenuState::Lobby(_) => {
+            unreachable!()
+        }
+    };
+    if let MenuState::NumPlayers = menu_state {
+        for s in settings_shortcuts(strings) {
+            if is_key_pressed(s.key)
+                || pointer.clicked_in(menu::text_rect(s.label, s.pos, SHORTCUT_FONT_SIZE))
+            {
+                sounds.play("move");
+                *menu_state = MenuState::Options(s.page, 0);
+                return false;
+            }
+        }
+        if let NumPlayers::Two = settings.num_players {
+            let (label, pos) = split_screen_label(settings, strings);
+            if pointer.clicked_in(menu::text_rect(&label, pos, 24.)) {
+                sounds.play("move");
+                settings.split_screen = !settings.split_screen;
+                return false;
+            }
+        }
+    }
+    let mut action = menu::navigate(&mut selected, count, menu::TITLE_LAYOUT, pointer);
+    if tapped && action == MenuAction::None {
+        action = MenuAction::Confirm;
+    }
+    match menu_state {
+        MenuState::NumPlayers => {
+            settings.num_players = NUM_PLAYERS_OPTIONS[selected];
+            match (action, settings.num_players) {
+                (MenuAction::Moved, _) => sounds.play("move"),
+                (MenuAction::Adjust(_), NumPlayers::Two) => {
+                    sounds.play("move");
+                    settings.split_screen = !settings.split_screen;
+                }
+                (MenuAction::Confirm, NumPlayers::One) => *menu_state = MenuState::Difficulty,
+                (MenuAction::Confirm, NumPlayers::Two) => return true,
+                _ => (),
+            }
+        }
+        _ => {
+            settings.difficulty_level = DIFFICULTY_OPTIONS[selected];
+            match action {
+                MenuAction::Moved => sounds.play("move"),
+                MenuAction::Back => {
+                    sounds.play("move");
+                    *menu_state = MenuState::NumPlayers;
+                }
+                MenuAction::Confirm => return true,
+                _ => (),
+            }
+        }
+    }
+    false
+}
+
+// handles a page of options, and says where to go once the player's done with it
+fn update_options_menu(
+    page: usize,
+    selected: &mut usize,
+    options: &mut OptionsContext,
+    pointer: &Pointer,
+) -> OptionsExit {
+    let items = OPTIONS_PAGES[page].items;
+    // typed characters queue up until they're read, so take them every frame whether or not
+    // anyone's typing a name
+    let mut typed = Vec::new();
+    while let Some(c) = get_char_pressed() {
+        typed.push(c);
+    }
+    if let OptionItem::NewProfile = items[*selected] {
+        let name = &mut options.settings.new_profile_name;
+        for c in typed {
+            if c.is_ascii_alphanumeric() && name.len() < profiles::MAX_NAME_LENGTH {
+                name.push(c.to_ascii_uppercase());
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            name.pop();
+        }
+    }
+    let action = menu::navigate(selected, items.len(), menu::SETTINGS_LAYOUT, pointer);
+    if action != MenuAction::None {
+        options.sounds.play("move");
+    }
+    match action {
+        MenuAction::Back if page == OPTIONS_PAGE || page == TRAINING_PAGE => {
+            OptionsExit::Menu(MenuState::NumPlayers)
+        }
+        // go back to the item on the first page that led here
+        MenuAction::Back => OptionsExit::Menu(MenuState::Options(
+            OPTIONS_PAGE,
+            OPTIONS_PAGES[OPTIONS_PAGE]
+                .items
+                .iter()
+                .position(|item| matches!(item, OptionItem::Page(p) if *p == page))
+                .unwrap_or(0),
+        )),
+        MenuAction::Confirm | MenuAction::Adjust(_) => {
+            let dir = if action == MenuAction::Adjust(-1) {
+                -1
+            } else {
+                1
+            };
+            match items[*selected] {
+                OptionItem::Page(next) => OptionsExit::Menu(MenuState::Options(next, 0)),
+                OptionItem::StartTraining if action == MenuAction::Confirm => {
+                    OptionsExit::StartTraining
+                }
+                OptionItem::StartTutorial if action == MenuAction::Confirm => {
+                    OptionsExit::StartTutorial
+                }
+                OptionItem::StartPenalties if action == MenuAction::Confirm => {
+                    OptionsExit::StartPenalties
+                }
+                OptionItem::Replay(n) if action == MenuAction::Confirm => {
+                    OptionsExit::PlayReplay(n)
+                }
+                OptionItem::HostMatch | OptionItem::FindMatch if action == MenuAction::Confirm => {
+                    // a player is known by their profile's name, if the first team has one
+                    let name = match options.settings.profiles[0] {
+                        Some(profile) => options.profiles.get(profile).name.clone(),
+                        None => options.strings.get("lobby.default_name").to_owned(),
+                    };
+                    let lobby = match items[*selected] {
+                        OptionItem::HostMatch => Lobby::host(name, options.settings),
+                        _ => Lobby::find(name),
+                    };
+                    OptionsExit::Menu(MenuState::Lobby(Box::new(lobby)))
+                }
+                OptionItem::EditRoster(team) => match options.settings.profiles[team] {
+                    Some(profile) => OptionsExit::Menu(MenuState::RosterEditor(Box::new(
+                        RosterEditor::new(team, profile, options.profiles),
+                    ))),
+                    None => OptionsExit::Stay,
+                },
+                item => {
+                    adjust_option(item, dir, options);
+                    OptionsExit::Stay
+                }
+            }
+        }
+        MenuAction::Moved | MenuAction::None => OptionsExit::Stay,
+    }
+}
+
+// change an option to its next (dir = 1) or previous (dir = -1) value
+fn adjust_option(item: OptionItem, dir: i32, options: &mut OptionsContext) {
+    let settings = &mut *options.settings;
+    // move one step through a list of choices, wrapping around at either end
+    let step =
+        |current: usize, count: usize| (current as i32 + dir).rem_euclid(count as i32) as usize;
+    let volume_step = |volume: f32| (volume + dir as f32 * VOLUME_STEP).clamp(0.0, 1.0);
+    match item {
+        OptionItem::Profile(team) => {
+            let current = settings.profiles[team].map_or(0, |p| p + 1);
+            let next = step(current, options.profiles.len() + 1);
+            settings.profiles[team] = next.checked_sub(1);
+        }
+        OptionItem::NewProfile => {
+            if let Some(index) = options.profiles.add(&settings.new_profile_name) {
+                settings.new_profile_name.clear();
+                // give it to the first team that hasn't got one
+                if let Some(slot) = settings.profiles.iter_mut().find(|p| p.is_none()) {
+                    *slot = Some(index);
+                }
+            }
+        }
+        OptionItem::Page(_)
+        | OptionItem::EditRoster(_)
+        | OptionItem::HistoryRecord(_)
+        | OptionItem::Replay(_)
+        | OptionItem::StartTraining
+        | OptionItem::StartTutorial
+        | OptionItem::StartPenalties
+        | OptionItem::HostMatch
+        | OptionItem::FindMatch => (),
+        OptionItem::Language => *options.language = step(*options.language, i18n::LANGUAGES.len()),
+        OptionItem::MusicVolume => {
+            options.sounds.music_volume = volume_step(options.sounds.music_volume);
+            // the menu music is playing, so let the player hear the change straight away
+            options.sounds.set_volume("theme", 1.0);
+        }
+        OptionItem::SfxVolume => options.sounds.sfx_volume = volume_step(options.sounds.sfx_volume),
+        OptionItem::Fullscreen => options.scaler.toggle_fullscreen(),
+        OptionItem::Scaling => {
+            options.scaler.set_mode(match options.scaler.mode() {
+                ScaleMode::Smooth => ScaleMode::Integer,
+                ScaleMode::Integer => ScaleMode::Smooth,
+            });
+        }
+        OptionItem::Theme => {
+            // cycle through the default images followed by each theme
+            let themes = options.themes;
+            let current = match options.theme {
+                Some(name) => themes.iter().position(|t| t == name).map_or(0, |i| i + 1),
+                None => 0,
+            };
+            let next = step(current, themes.len() + 1);
+            *options.theme = next.checked_sub(1).map(|i| themes[i].clone());
+        }
+        OptionItem::CameraEffects => *options.camera_effects = !*options.camera_effects,
+        OptionItem::BackgroundPause => *options.background_pause = !*options.background_pause,
+        OptionItem::MatchLength => {
+            let current = MATCH_LENGTHS
+                .iter()
+                .position(|n| *n == settings.goals_to_win)
+                .unwrap_or(0);
+            settings.goals_to_win = MATCH_LENGTHS[step(current, MATCH_LENGTHS.len())];
+        }
+        OptionItem::Weather => settings.weather = settings.weather.next(),
+        OptionItem::Lighting => {
+            settings.lighting = match settings.lighting {
+                Some(Lighting::Day) => Some(Lighting::Evening),
+                Some(Lighting::Evening) => Some(Lighting::Night),
+                Some(Lighting::Night) => None,
+                None => Some(Lighting::Day),
+            }
+        }
+        OptionItem::Switching => {
+            settings.switch_mode = match settings.switch_mode {
+                SwitchMode::Nearest => SwitchMode::Cycle,
+                SwitchMode::Cycle => SwitchMode::Nearest,
+            }
+        }
+        OptionItem::AutoSwitch => settings.auto_switch = !settings.auto_switch,
+        OptionItem::Assist => {
+            settings.assist = match settings.assist {
+                Assist::AutoPosition => Assist::Pressure,
+                Assist::Pressure => Assist::AutoPosition,
+            }
+        }
+        OptionItem::MouseAim => settings.mouse_aim = !settings.mouse_aim,
+        OptionItem::LargeText => {
+            options.accessibility.large_text = !options.accessibility.large_text
+        }
+        OptionItem::TeamMarkers => {
+            options.accessibility.team_markers = !options.accessibility.team_markers
+        }
+        OptionItem::AudioCues => {
+            options.accessibility.audio_cues = !options.accessibility.audio_cues;
+            // play an example so it's clear what the cues sound like
+            if options.accessibility.audio_cues {
+                options.sounds.play("cue_goal0");
+            }
+        }
+        OptionItem::MultiBall => settings.mutators.multi_ball = !settings.mutators.multi_ball,
+        OptionItem::GiantBall => settings.mutators.giant_ball = !settings.mutators.giant_ball,
+        OptionItem::PowerUps => settings.mutators.power_ups = !settings.mutators.power_ups,
+        OptionItem::GameSpeed => {
+            let current = mutators::SPEEDS
+                .iter()
+                .position(|s| *s == settings.mutators.speed)
+                .unwrap_or(0);
+            settings.mutators.speed = mutators::SPEEDS[step(current, mutators::SPEEDS.len())];
+        }
+        OptionItem::HeadStart(team) => {
+            let handicap = &mut settings.handicaps[team];
+            handicap.head_start =
+                step(handicap.head_start as usize, MAX_HEAD_START as usize + 1) as u8;
+        }
+        OptionItem::TeamSpeed(team) => {
+            let handicap = &mut settings.handicaps[team];
+            let current = HANDICAP_SPEEDS
+                .iter()
+                .position(|s| *s == handicap.speed)
+                .unwrap_or(0);
+            handicap.speed = HANDICAP_SPEEDS[step(current, HANDICAP_SPEEDS.len())];
+        }
+        OptionItem::Pressing(team) => {
+            let tactics = &mut settings.tactics[team];
+            tactics.pressing = tactics::step(tactics.pressing, dir);
+        }
+        OptionItem::DefensiveLine(team) => {
+            let tactics = &mut settings.tactics[team];
+            tactics.line = tactics::step(tactics.line, dir);
+        }
+        OptionItem::TrainingDummies => settings.training.dummies = !settings.training.dummies,
+        OptionItem::TrainingTargets => settings.training.targets = !settings.training.targets,
+    }
+}
+
+fn option_label(item: OptionItem, options: &OptionsContext) -> String {
+    let strings = options.strings;
+    let on_off = |on| strings.get(if on { "options.on" } else { "options.off" });
+    // an option's name followed by its current value, which is also text from the language file
+    let choice = |key: &str, value: &str| {
+        strings.format(
+            &format!("options.{}", key),
+            &[strings.get(&format!("options.{}_{}", key, value))],
+        )
+    };
+    let settings = &*options.settings;
+    match item {
+        OptionItem::Page(page) => strings.get(OPTIONS_PAGES[page].title).to_owned(),
+        OptionItem::Language => {
+            strings.format("options.language", &[i18n::LANGUAGES[*options.language].1])
+        }
+        OptionItem::MusicVolume => strings.format(
+            "options.music_volume",
+            &[&(options.sounds.music_volume * 100.0).round().to_string()],
+        ),
+        OptionItem::SfxVolume => strings.format(
+            "options.sfx_volume",
+            &[&(options.sounds.sfx_volume * 100.0).round().to_string()],
+        ),
+        OptionItem::Fullscreen => {
+            strings.format("options.fullscreen", &[on_off(options.scaler.fullscreen)])
+        }
+        OptionItem::Scaling => choice(
+            "scaling",
+            match options.scaler.mode() {
+                ScaleMode::Smooth => "smooth",
+                ScaleMode::Integer => "pixel_perfect",
+            },
+        ),
+        OptionItem::Theme => {
+            let default = strings.get("options.theme_default").to_owned();
+            let theme = options.theme.as_ref().map_or(default, |t| t.to_uppercase());
+            strings.format("options.theme", &[&theme])
+        }
+        OptionItem::CameraEffects => {
+            strings.format("options.camera_effects", &[on_off(*options.camera_effects)])
+        }
+        OptionItem::BackgroundPause => strings.format(
+            "options.background_pause",
+            &[on_off(*options.background_pause)],
+        ),
+        OptionItem::MatchLength => strings.format(
+            "options.match_length",
+            &[&settings.goals_to_win.to_string()],
+        ),
+        OptionItem::Weather => choice("weather", settings.weather.key()),
+        OptionItem::Lighting => choice(
+            "lighting",
+            settings.lighting.map_or("random", Lighting::key),
+        ),
+        OptionItem::Switching => choice("switching", settings.switch_mode.key()),
+        OptionItem::AutoSwitch => {
+            strings.format("options.auto_switch", &[on_off(settings.auto_switch)])
+        }
+        OptionItem::Assist => choice("assist", settings.assist.key()),
+        OptionItem::MouseAim => strings.format("options.mouse_aim", &[on_off(settings.mouse_aim)]),
+        OptionItem::LargeText => strings.format(
+            "options.large_text",
+            &[on_off(options.accessibility.large_text)],
+        ),
+        OptionItem::AudioCues => strings.format(
+            "options.audio_cues",
+            &[on_off(options.accessibility.audio_cues)],
+        ),
+        OptionItem::TeamMarkers => strings.format(
+            "options.team_markers",
+            &[on_off(options.accessibility.team_markers)],
+        ),
+        OptionItem::MultiBall => strings.format(
+            "options.multi_ball",
+            &[on_off(settings.mutators.multi_ball)],
+        ),
+        OptionItem::GiantBall => strings.format(
+            "options.giant_ball",
+            &[on_off(settings.mutators.giant_ball)],
+        ),
+        OptionItem::PowerUps => {
+            strings.format("options.power_ups", &[on_off(settings.mutators.power_ups)])
+        }
+        OptionItem::GameSpeed => strings.format(
+            "options.game_speed",
+            &[&(settings.mutators.speed * 100.0).round().to_string()],
+        ),
+        OptionItem::HeadStart(team) => strings.format(
+            "options.head_start",
+            &[
+                &(team + 1).to_string(),
+                &settings.handicaps[team].head_start.to_string(),
+            ],
+        ),
+        OptionItem::TeamSpeed(team) => strings.format(
+            "options.team_speed",
+            &[
+                &(team + 1).to_string(),
+                &(settings.handicaps[team].speed * 100.0).round().to_string(),
+            ],
+        ),
+        OptionItem::Pressing(team) => strings.format(
+            "options.pressing",
+            &[
+                &(team + 1).to_string(),
+                strings.get(tactics::PRESSING_KEYS[settings.tactics[team].pressing as usize]),
+            ],
+        ),
+        OptionItem::DefensiveLine(team) => strings.format(
+            "options.defensive_line",
+            &[
+                &(team + 1).to_string(),
+                strings.get(tactics::LINE_KEYS[settings.tactics[team].line as usize]),
+            ],
+        ),
+        OptionItem::Replay(n) => options.replays.label(n, strings),
+        OptionItem::HistoryRecord(mode) => {
+            let mode = history::MODES[mode];
+            let (won, lost) = options.history.record_for(mode);
+            let key = if mode == "two_players" {
+                "history.two_player_record"
+            } else {
+                "history.record"
+            };
+            strings.format(
+                key,
+                &[
+                    strings.get(&format!("title.{}", mode)),
+                    &won.to_string(),
+                    &lost.to_string(),
+                ],
+            )
+        }
+        OptionItem::Profile(team) => {
+            let name = match settings.profiles[team] {
+                Some(p) => options.profiles.get(p).name.as_str(),
+                None => strings.get("options.profile_none"),
+            };
+            strings.format("options.profile", &[&(team + 1).to_string(), name])
+        }
+        OptionItem::NewProfile => {
+            strings.format("options.new_profile", &[&settings.new_profile_name])
+        }
+        OptionItem::EditRoster(team) => match settings.profiles[team] {
+            Some(p) => strings.format("options.edit_roster", &[&options.profiles.get(p).name]),
+            None => strings.format("options.edit_roster_none", &[&(team + 1).to_string()]),
         },
+        OptionItem::TrainingDummies => {
+            strings.format("options.dummies", &[on_off(settings.training.dummies)])
+        }
+        OptionItem::TrainingTargets => {
+            strings.format("options.targets", &[on_off(settings.training.targets)])
+        }
+        OptionItem::StartTraining => strings.get("options.start_training").to_owned(),
+        OptionItem::StartTutorial => strings.get("options.start_tutorial").to_owned(),
+        OptionItem::StartPenalties => strings.get("options.start_penalties").to_owned(),
+        OptionItem::HostMatch => strings.get("options.host_match").to_owned(),
+        OptionItem::FindMatch => strings.get("options.find_match").to_owned(),
+    }
+}
+
+fn draw_options_menu(page: usize, selected: usize, options: &OptionsContext) {
+    draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+    let strings = options.strings;
+    draw_text(
+        strings.get(OPTIONS_PAGES[page].title),
+        100.,
+        100.,
+        48.,
+        WHITE,
+    );
+    for (i, item) in OPTIONS_PAGES[page].items.iter().enumerate() {
+        let colour = if i == selected { YELLOW } else { WHITE };
+        draw_text(
+            &option_label(*item, options),
+            120.,
+            menu::SETTINGS_LAYOUT.baseline(i),
+            32.,
+            colour,
+        );
+    }
+    if page == HISTORY_PAGE {
+        let items = OPTIONS_PAGES[page].items.len();
+        options
+            .history
+            .draw_recent(strings, menu::SETTINGS_LAYOUT.baseline(items) + 10.);
+    }
+    if page == PROFILES_PAGE {
+        let items = OPTIONS_PAGES[page].items.len();
+        options.profiles.draw_stats(
+            options.settings.profiles,
+            strings,
+            menu::SETTINGS_LAYOUT.baseline(items) + 10.,
+        );
+    }
+    draw_text(strings.get("options.back"), 10., HEIGHT - 10., 16., WHITE);
+}
+
+// sound effects triggered by the most recent simulation step, and the crowd reacting to it
+fn play_game_sounds(
+    game: &Game,
+    crowd_noise: &mut CrowdNoise,
+    sounds: &Sounds,
+    accessibility: &Accessibility,
+) {
+    play_event_sounds(&game.events, sounds, accessibility);
+    if let Some(reaction) = crowd_noise.update(game) {
+        sounds.play(reaction);
+    }
+    sounds.set_volume("crowd", crowd_noise.volume());
+}
+
+// which of count recordings of a sound to play; this doesn't draw on macroquad's random numbers,
+// which the match uses and a replay has to repeat exactly even when it's played without sound
+fn sound_variant(count: usize) -> usize {
+    (get_time() * 1000.0) as usize % count
+}
+
+fn play_event_sounds(events: &[GameEvent], sounds: &Sounds, accessibility: &Accessibility) {
+    for event in events {
+        if accessibility.audio_cues {
+            if let Some(cue) = accessibility::cue_for(event) {
+                sounds.play(&cue);
+            }
+        }
+        match event {
+            GameEvent::Goal { .. } => {
+                sounds.play(&format!("goal{}", sound_variant(2)));
+                // the crowd gets louder while the scorer celebrates; in a match, CrowdNoise
+                // takes it from there
+                sounds.set_volume("crowd", 1.0);
+            }
+            GameEvent::Kick { .. } => {
+                sounds.play(&format!("kick{}", sound_variant(4)));
+            }
+            GameEvent::Whistle | GameEvent::Card { .. } | GameEvent::Substitution { .. } => {
+                sounds.play("whistle")
+            }
+            // the crowd's reactions to these come from CrowdNoise
+            GameEvent::Possession { .. }
+            | GameEvent::Switch { .. }
+            | GameEvent::NearMiss { .. }
+            | GameEvent::Save { .. } => (),
+        }
+    }
+}
+
+// under the goal banner; teams are numbered from 1 as on the scoreboard
+fn draw_scorer(scorer: &Scorer, strings: &Strings, x: f32) {
+    let team = (scorer.team + 1).to_string();
+    let number = scorer.squad.number.to_string();
+    let args: [&str; 3] = [&team, &number, &scorer.squad.name];
+    let text = if scorer.own_goal {
+        strings.format("hud.own_goal_scorer", &args)
+    } else {
+        strings.format("hud.scorer", &args)
+    };
+    draw_text_centred(&text, x, HEIGHT / 2. + 120., 32.);
+}
+
+// the card the referee showed, with who it was for, below the score bar
+fn draw_booking(booking: &Booking, strings: &Strings) {
+    let team = (booking.team + 1).to_string();
+    let number = booking.squad.number.to_string();
+    let args: [&str; 3] = [&team, &number, &booking.squad.name];
+    let (key, colour) = if booking.red {
+        ("hud.sent_off", RED)
+    } else {
+        ("hud.booked", YELLOW)
+    };
+    let text = strings.format(key, &args);
+    let width = measure_text(&text, None, 24, 1.0).width;
+    let left = HALF_WINDOW_WIDTH - (width + 30.) / 2.;
+    draw_rectangle(left, 50., 18., 26., colour);
+    draw_rectangle_lines(left, 50., 18., 26., 2., BLACK);
+    draw_text(&text, left + 30., 72., 24., WHITE);
+}
+
+// the fourth official's board, below where a substitution would be
+fn draw_added_time(minutes: u32, strings: &Strings) {
+    let text = strings.format("hud.added_time", &[&minutes.to_string()]);
+    let width = measure_text(&text, None, 32, 1.0).width;
+    let left = HALF_WINDOW_WIDTH - width / 2. - 10.;
+    draw_rectangle(left, 116., width + 20., 36., BLACK);
+    draw_rectangle_lines(left, 116., width + 20., 36., 2., WHITE);
+    draw_text(&text, left + 10., 144., 32., GREEN);
+}
+
+// who came on for who, below where a card would be
+fn draw_substitution(substitution: &Substitution, strings: &Strings) {
+    let team = (substitution.team + 1).to_string();
+    let (on, off) = (&substitution.on, &substitution.off);
+    let text = strings.format(
+        "hud.substitution",
+        &[
+            &team,
+            &on.number.to_string(),
+            &on.name,
+            &off.number.to_string(),
+            &off.name,
+        ],
     );
+    draw_text_centred(&text, HALF_WINDOW_WIDTH, 104., 24.);
+}
+
+// title and difficulty screens drawn as text, for languages the menu images aren't in
+fn draw_title_text(page: u8, selected: u8, strings: &Strings) {
+    draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+    draw_text_centred(strings.get("title.name"), HALF_WINDOW_WIDTH, 120.0, 64.0);
+    let items: &[&str] = if page == 0 {
+        &["title.one_player", "title.two_players"]
+    } else {
+        &["title.easy", "title.medium", "title.hard"]
+    };
+    // like the images, the first page numbers its options from 1 rather than 0
+    let highlighted = if page == 0 { selected - 1 } else { selected };
+    for (i, key) in items.iter().enumerate() {
+        let text = strings.get(key);
+        let size = measure_text(text, None, 40, 1.0);
+        let colour = if i == highlighted as usize {
+            YELLOW
+        } else {
+            WHITE
+        };
+        let y = menu::TITLE_LAYOUT.baseline(i);
+        draw_text(text, HALF_WINDOW_WIDTH - size.width / 2.0, y, 40.0, colour);
+    }
 }
 
-fn debug_draw_line(offs_x: f32, offs_y: f32, v1: Vector, v2: Vector, t: f32, c: Color) {
-    draw_line(
-        v1.x - offs_x,
-        v1.y - offs_y,
-        v2.x - offs_x,
-        v2.y - offs_y,
-        t,
-        c,
+fn draw_game_over_text(game: &Game, winner: u8, strings: &Strings) {
+    draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+    let team = (winner + 1).to_string();
+    draw_text_centred(
+        &strings.format("hud.winner", &[&team]),
+        HALF_WINDOW_WIDTH,
+        120.0,
+        64.0,
     );
+    // team 2 on the left, as on the score bar
+    let score = strings.format(
+        "hud.final_score",
+        &[
+            &game.teams[1].score.to_string(),
+            &game.teams[0].score.to_string(),
+        ],
+    );
+    draw_text_centred(&score, HALF_WINDOW_WIDTH, 220.0, 80.0);
+    draw_text_centred(strings.get("hud.continue"), HALF_WINDOW_WIDTH, 400.0, 24.0);
+}
+
+// outlined text centred horizontally on x
+fn draw_text_centred(text: &str, x: f32, y: f32, font_size: f32) {
+    let size = measure_text(text, None, font_size as u16, 1.0);
+    draw_text_outlined(text, x - size.width / 2.0, y, font_size);
+}
+
+fn draw_text_outlined(text: &str, x: f32, y: f32, font_size: f32) {
+    for (dx, dy) in [(-1., 0.), (1., 0.), (0., -1.), (0., 1.)] {
+        draw_text(text, x + dx, y + dy, font_size, BLACK);
+    }
+    draw_text(text, x, y, font_size, WHITE);
 }
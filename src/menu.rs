@@ -0,0 +1,141 @@
+use crate::scaler::Scaler;
+use crate::WIDTH;
+use macroquad::prelude::*;
+
+// options in the settings pages, drawn as text
+pub const SETTINGS_LAYOUT: Layout = Layout {
+    top: 134.0,
+    spacing: 32.0,
+};
+// long lists, such as a whole squad, in smaller text
+pub const LIST_LAYOUT: Layout = Layout {
+    top: 110.0,
+    spacing: 30.0,
+};
+// options on the title and difficulty screens, which are baked into the menu images
+pub const TITLE_LAYOUT: Layout = Layout {
+    top: 205.0,
+    spacing: 50.0,
+};
+
+// what the player did to a menu this frame
+#[derive(Copy, Clone, PartialEq)]
+pub enum MenuAction {
+    None,
+    // a different option is highlighted
+    Moved,
+    // the highlighted option was chosen, with Space or by clicking on it
+    Confirm,
+    // Left (-1) or Right (1) on the highlighted option
+    Adjust(i32),
+    Back,
+}
+
+// where a menu's options are on the screen: a column of full-width rows, the first starting at top
+#[derive(Copy, Clone)]
+pub struct Layout {
+    top: f32,
+    spacing: f32,
+}
+
+impl Layout {
+    // y coordinate to draw the label for a row at, for text about as tall as the row
+    pub fn baseline(&self, row: usize) -> f32 {
+        self.top + self.spacing * (row as f32 + 0.8)
+    }
+
+    fn row_at(&self, pos: Vec2, count: usize) -> Option<usize> {
+        let row = ((pos.y - self.top) / self.spacing).floor();
+        (pos.x >= 0.0 && pos.x < WIDTH && row >= 0.0 && (row as usize) < count)
+            .then_some(row as usize)
+    }
+}
+
+// the mouse, in game screen coordinates
+pub struct Pointer {
+    pos: Vec2,
+    moved: bool,
+    clicked: bool,
+    back: bool,
+}
+
+impl Pointer {
+    pub fn new() -> Self {
+        Self {
+            pos: Vec2::ZERO,
+            moved: false,
+            clicked: false,
+            back: false,
+        }
+    }
+
+    // called once per frame
+    pub fn update(&mut self, scaler: &Scaler) {
+        let pos = scaler.mouse_position();
+        self.moved = pos != self.pos;
+        self.pos = pos;
+        self.clicked = is_mouse_button_pressed(MouseButton::Left);
+        self.back = is_mouse_button_pressed(MouseButton::Right);
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.pos
+    }
+
+    // the right button, which goes back a page
+    pub fn clicked_back(&self) -> bool {
+        self.back
+    }
+
+    pub fn clicked_in(&self, rect: Rect) -> bool {
+        self.clicked && rect.contains(self.pos)
+    }
+}
+
+// area covered by a line of text drawn at pos, which is the left end of its baseline
+pub fn text_rect(text: &str, pos: Vec2, font_size: f32) -> Rect {
+    let size = measure_text(text, None, font_size as u16, 1.0);
+    Rect::new(pos.x, pos.y - font_size, size.width, font_size)
+}
+
+// move the highlight around a menu of count options using the keyboard or mouse, and report what
+// else the player did; macroquad doesn't read gamepads yet, but they would feed in here too
+pub fn navigate(
+    selected: &mut usize,
+    count: usize,
+    layout: Layout,
+    pointer: &Pointer,
+) -> MenuAction {
+    if is_key_pressed(KeyCode::Escape) || pointer.back {
+        return MenuAction::Back;
+    }
+    if is_key_pressed(KeyCode::Up) {
+        *selected = (*selected + count - 1) % count;
+        return MenuAction::Moved;
+    }
+    if is_key_pressed(KeyCode::Down) {
+        *selected = (*selected + 1) % count;
+        return MenuAction::Moved;
+    }
+    if is_key_pressed(KeyCode::Space) {
+        return MenuAction::Confirm;
+    }
+    if is_key_pressed(KeyCode::Left) {
+        return MenuAction::Adjust(-1);
+    }
+    if is_key_pressed(KeyCode::Right) {
+        return MenuAction::Adjust(1);
+    }
+    if let Some(row) = layout.row_at(pointer.pos, count) {
+        if pointer.clicked {
+            *selected = row;
+            return MenuAction::Confirm;
+        }
+        // only follow the mouse when it moves, so it doesn't fight with the keyboard
+        if pointer.moved && row != *selected {
+            *selected = row;
+            return MenuAction::Moved;
+        }
+    }
+    MenuAction::None
+}
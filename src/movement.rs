@@ -0,0 +1,126 @@
+// part of debug mode: where every player has been over the match, as a heatmap of the time each
+// has spent in each part of the pitch, and the trail each has left over the last couple of seconds,
+// for seeing how the AI's positioning holds up and whether the teams keep their shape
+//
+// this is kept by the renderer rather than the game, since it only matters for drawing and would
+// make every rewind snapshot much bigger
+
+use crate::{Game, Position, Team, Vector, LEVEL_H, LEVEL_W};
+use hecs::Entity;
+use macroquad::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+const CELL: f32 = 25.0;
+const COLS: usize = (LEVEL_W / CELL) as usize;
+const ROWS: usize = (LEVEL_H / CELL) as usize;
+// a trail has a point every TRAIL_INTERVAL steps, going back TRAIL_POINTS of them
+const TRAIL_INTERVAL: u32 = 4;
+const TRAIL_POINTS: usize = 30;
+const TEAM_TINTS: [Color; 2] = [RED, BLUE];
+
+struct History {
+    team: u8,
+    // steps spent in each cell of the pitch, a row at a time
+    heat: Vec<u32>,
+    trail: VecDeque<Vector>,
+}
+
+pub struct Movement {
+    players: HashMap<Entity, History>,
+    last_step: u32,
+    // the player the inspector has picked, whose heatmap is shown on its own
+    pub focus: Option<Entity>,
+}
+
+impl Movement {
+    pub fn new() -> Self {
+        Self {
+            players: HashMap::new(),
+            last_step: 0,
+            focus: None,
+        }
+    }
+
+    // call after each step of a match
+    pub fn record(&mut self, game: &Game) {
+        // the step count only goes backwards for a new match, or rewinding, and either way what's
+        // been recorded no longer leads up to now
+        if game.steps_played < self.last_step {
+            self.players.clear();
+        }
+        self.last_step = game.steps_played;
+        // anyone who's been sent off or substituted keeps their heatmap but leaves no trail
+        for (id, history) in self.players.iter_mut() {
+            if !game.world.contains(*id) {
+                history.trail.clear();
+            }
+        }
+        for (id, (pos, team)) in game.world.query::<(&Position, &Team)>().iter() {
+            let history = self.players.entry(id).or_insert_with(|| History {
+                team: team.0,
+                heat: vec![0; COLS * ROWS],
+                trail: VecDeque::new(),
+            });
+            let col = (pos.0.x / CELL).clamp(0.0, COLS as f32 - 1.0) as usize;
+            let row = (pos.0.y / CELL).clamp(0.0, ROWS as f32 - 1.0) as usize;
+            history.heat[row * COLS + col] += 1;
+            if game.steps_played.is_multiple_of(TRAIL_INTERVAL) {
+                if history.trail.len() == TRAIL_POINTS {
+                    history.trail.pop_front();
+                }
+                history.trail.push_back(pos.0);
+            }
+        }
+    }
+
+    // each team's time in each cell shaded in its colour, or just the focused player's
+    pub fn draw_heatmap(&self) {
+        let mut teams = [vec![0; COLS * ROWS], vec![0; COLS * ROWS]];
+        let focused = self.focus.and_then(|id| self.players.get(&id));
+        for history in focused
+            .into_iter()
+            .chain(self.players.values().filter(|_| focused.is_none()))
+        {
+            for (total, heat) in teams[history.team as usize].iter_mut().zip(&history.heat) {
+                *total += heat;
+            }
+        }
+        for (team, heat) in teams.iter().enumerate() {
+            let most = heat.iter().copied().max().unwrap_or(0).max(1) as f32;
+            for (i, steps) in heat.iter().enumerate().filter(|(_, steps)| **steps > 0) {
+                let tint = TEAM_TINTS[team];
+                draw_rectangle(
+                    (i % COLS) as f32 * CELL,
+                    (i / COLS) as f32 * CELL,
+                    CELL,
+                    CELL,
+                    Color::new(tint.r, tint.g, tint.b, 0.5 * *steps as f32 / most),
+                );
+            }
+        }
+    }
+
+    // each player's recent trail in their team's colour, fading out towards its oldest end
+    pub fn draw_trails(&self) {
+        for history in self.players.values() {
+            let tint = TEAM_TINTS[history.team as usize];
+            let points = history.trail.len() as f32;
+            for (i, (from, to)) in history
+                .trail
+                .iter()
+                .zip(history.trail.iter().skip(1))
+                .enumerate()
+            {
+                let alpha = 0.7 * (i + 1) as f32 / points;
+                draw_line(
+                    from.x,
+                    from.y,
+                    to.x,
+                    to.y,
+                    3.0,
+                    Color::new(tint.r, tint.g, tint.b, alpha),
+                );
+            }
+        }
+    }
+}
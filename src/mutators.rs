@@ -0,0 +1,66 @@
+// game speeds to choose from, as multiples of normal
+pub const SPEEDS: [f32; 4] = [1.0, 1.25, 1.5, 2.0];
+// the giant ball is drawn this much bigger, can be reached from this much further away, and
+// travels at this fraction of the normal speed when kicked
+const GIANT_BALL_SCALE: f32 = 2.0;
+const GIANT_BALL_REACH: f32 = 1.5;
+const GIANT_BALL_KICK: f32 = 0.7;
+
+// silly modifiers for a less serious match
+#[derive(Copy, Clone)]
+pub struct Mutators {
+    // two balls in play at once
+    pub multi_ball: bool,
+    pub giant_ball: bool,
+    // pickups appear on the pitch now and then (see powerups.rs)
+    pub power_ups: bool,
+    // one of SPEEDS, applied to everyone and the ball
+    pub speed: f32,
+}
+
+impl Mutators {
+    pub fn new() -> Self {
+        Self {
+            multi_ball: false,
+            giant_ball: false,
+            power_ups: false,
+            speed: 1.0,
+        }
+    }
+
+    pub fn ball_count(&self) -> usize {
+        if self.multi_ball {
+            2
+        } else {
+            1
+        }
+    }
+
+    // how much bigger than normal to draw the ball
+    pub fn ball_scale(&self) -> f32 {
+        if self.giant_ball {
+            GIANT_BALL_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    // multiplies the distance from which players can take the ball
+    pub fn ball_reach(&self) -> f32 {
+        if self.giant_ball {
+            GIANT_BALL_REACH
+        } else {
+            1.0
+        }
+    }
+
+    // multiplies the speed the ball leaves a player's foot at
+    pub fn kick_strength(&self) -> f32 {
+        let giant = if self.giant_ball {
+            GIANT_BALL_KICK
+        } else {
+            1.0
+        };
+        giant * self.speed
+    }
+}
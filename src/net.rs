@@ -0,0 +1,898 @@
+// network play: two copies of the game, one hosting and one that joined it from the lobby (see
+// lobby.rs), play the same match in lockstep; each sends the other what its player asked for at
+// every step, INPUT_DELAY steps ahead of when it's used, and neither plays a step until it has both
+// teams' input for it. the host sets the match up from a seed, and sends the seed, settings and
+// squads (in the form replays are saved in, see replays.rs) for the other copy to set up the same
+// match, so as long as the simulation is deterministic nothing else needs sending
+//
+// if the connection drops, or nothing's been heard for SILENT_SECONDS while waiting for input, both
+// sides stop: the host listens (and announces the match) again, and whoever joined keeps trying to
+// connect to it, for up to RECONNECT_SECONDS. someone whose game closed can rejoin from the lobby
+// too. once they're back in touch, the host sends the match so far as a replay (see replays.rs),
+// since there's no other way of writing the world down, and the other copy plays it through to the
+// step the host is at; both then start the lockstep again from there, without any input for the
+// first INPUT_DELAY steps
+//
+// messages are lines of text over TCP, on GAME_PORT:
+//   name TEAM NAME          who's playing for TEAM
+//   ready TEAM 0|1          whether they're ready to start
+//   settings LINES          the host's settings, on the LINES lines that follow
+//   start LINES             the match, as a data file on the LINES lines that follow
+//   input STEP X Y PRESSES  the sender's input for STEP (see input.rs)
+//   chat TEAM PHRASE        something said with quick chat, as its place in chat::PHRASES
+//   resume LINES            the match so far, as a replay on the LINES lines that follow
+//   bye                     the sender is leaving
+//
+// hosts also announce themselves to the local network, with a UDP broadcast of "ANNOUNCE PORT
+// NAME" to DISCOVERY_PORT once a second, for the lobby to list
+//
+//   --host        in a headless match, wait for another copy to join and play it over the network
+//   --join ADDR   join the match hosted at ADDR (an address, with or without the port)
+//
+// there's no network play in the browser, which can't open sockets
+
+use crate::chat;
+use crate::data;
+use crate::input::{self, FrameInput};
+use crate::replays::{self, MatchRecord, Playback};
+use crate::roster::Roster;
+use crate::{Game, Settings};
+use macroquad::miniquad::date;
+use macroquad::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+pub const GAME_PORT: u16 = 45017;
+const DISCOVERY_PORT: u16 = 45018;
+// the start of an announcement, and the version of this protocol; a different version means a
+// different build of the game, which wouldn't play the match the same way
+const ANNOUNCE: &str = "substitute-soccer 3";
+const ANNOUNCE_SECONDS: f64 = 1.0;
+// a host that hasn't been heard from for this long has gone
+const FORGET_SECONDS: f64 = 3.5;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+// the longest closing a connection waits for the other end to take what's still to be sent
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+// four steps, long enough for input to cross a local network before it's needed
+const INPUT_DELAY: u32 = 4;
+// waiting this long for the other copy's input without hearing anything means the connection's gone,
+// even if nothing's said so
+const SILENT_SECONDS: f64 = 3.0;
+// how long to keep trying to get back in touch before giving up on the match
+const RECONNECT_SECONDS: f64 = 60.0;
+// how often whoever joined tries to connect to the host again
+const RETRY_SECONDS: f64 = 2.0;
+
+pub enum Message {
+    Name { team: usize, name: String },
+    Ready { team: usize, ready: bool },
+    Settings(String),
+    Start(String),
+    Input { step: u32, input: FrameInput },
+    Chat { team: usize, phrase: usize },
+    Resume(String),
+    Bye,
+}
+
+impl Message {
+    fn write(&self, out: &mut String) {
+        let block = |out: &mut String, kind: &str, text: &str| {
+            out.push_str(&format!("{} {}\n{}", kind, text.lines().count(), text));
+            if !text.is_empty() && !text.ends_with('\n') {
+                out.push('\n');
+            }
+        };
+        match self {
+            Message::Name { team, name } => out.push_str(&format!("name {} {}\n", team, name)),
+            Message::Ready { team, ready } => {
+                out.push_str(&format!("ready {} {}\n", team, *ready as u8))
+            }
+            Message::Settings(text) => block(out, "settings", text),
+            Message::Start(text) => block(out, "start", text),
+            Message::Resume(text) => block(out, "resume", text),
+            Message::Input { step, input } => {
+                out.push_str(&format!("input {} ", step));
+                input::write_input(out, *input);
+                out.push('\n');
+            }
+            Message::Chat { team, phrase } => out.push_str(&format!("chat {} {}\n", team, phrase)),
+            Message::Bye => out.push_str("bye\n"),
+        }
+    }
+}
+
+// a message of more than one line, and how many more of its lines are still to come
+struct Block {
+    kind: String,
+    left: usize,
+    text: String,
+}
+
+// makes messages of the lines that arrive, one line at a time
+struct Reader {
+    block: Option<Block>,
+}
+
+// a TCP connection carrying lines of text, never waiting to send or receive; the broadcast of a
+// match to spectators uses one of these too (see broadcast.rs)
+pub struct Lines {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Lines {
+    pub fn new(stream: TcpStream) -> Result<Self, String> {
+        stream
+            .set_nonblocking(true)
+            .and_then(|_| stream.set_nodelay(true))
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    // connect to an address with or without a port, using port if it hasn't one; this waits until
+    // it's connected
+    pub fn connect(address: &str, port: u16) -> Result<Self, String> {
+        let with_port = if address.contains(':') {
+            address.to_owned()
+        } else {
+            format!("{}:{}", address, port)
+        };
+        let addr = with_port
+            .to_socket_addrs()
+            .map_err(|e| format!("{}: {}", address, e))?
+            .next()
+            .ok_or_else(|| format!("{}: no such address", address))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+            .map_err(|e| format!("{}: {}", address, e))?;
+        Self::new(stream)
+    }
+
+    // text made of whole lines
+    pub fn send(&mut self, text: &str) {
+        self.outgoing.extend_from_slice(text.as_bytes());
+        // anything that goes wrong will turn up on the next receive
+        self.flush().ok();
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err("connection closed".to_owned()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(())
+    }
+
+    // how much sent text the other end hasn't taken yet
+    pub fn backlog(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    // the lines that have arrived since last time, having sent whatever's still waiting to go; an
+    // error once the connection has gone, though not until every line that arrived before then has
+    // been handed over
+    pub fn receive(&mut self) -> Result<Vec<String>, String> {
+        let mut gone = self.flush().err();
+        let mut buf = [0; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    gone.get_or_insert_with(|| "connection closed".to_owned());
+                    break;
+                }
+                Ok(n) => self.incoming.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    gone.get_or_insert(e.to_string());
+                    break;
+                }
+            }
+        }
+        let mut lines = Vec::new();
+        while let Some(end) = self.incoming.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line[..end]).into_owned());
+        }
+        match gone {
+            Some(e) if lines.is_empty() => Err(e),
+            _ => Ok(lines),
+        }
+    }
+
+    // send the last of the text, waiting until everything still to be sent has gone, or for
+    // CLOSE_TIMEOUT if the other end isn't taking it; the other end may well have gone already
+    pub fn close(mut self, last: &str) {
+        self.outgoing.extend_from_slice(last.as_bytes());
+        self.stream
+            .set_nonblocking(false)
+            .and_then(|_| self.stream.set_write_timeout(Some(CLOSE_TIMEOUT)))
+            .and_then(|_| self.stream.write_all(&self.outgoing))
+            .ok();
+    }
+}
+
+// one end of a connection to another copy of the game
+pub struct Connection {
+    lines: Lines,
+    reader: Reader,
+    // lines that arrived after a match started, which belong to the match rather than the lobby
+    unread: VecDeque<String>,
+}
+
+impl Connection {
+    fn new(lines: Lines) -> Self {
+        Self {
+            lines,
+            reader: Reader { block: None },
+            unread: VecDeque::new(),
+        }
+    }
+
+    // join a host, at an address with or without a port; this waits until it's connected, so
+    // Connecting is for joining from inside the game
+    pub fn connect(address: &str) -> Result<Self, String> {
+        Lines::connect(address, GAME_PORT).map(Self::new)
+    }
+
+    pub fn send(&mut self, message: &Message) {
+        let mut text = String::new();
+        message.write(&mut text);
+        self.lines.send(&text);
+    }
+
+    // the messages that have arrived since last time; an error once the connection has gone, or if
+    // the other end says something that makes no sense. a start or resume message is the last
+    // handed over at once, since the host's input for the next steps can come straight after it
+    // and whoever set the match up needs to hand the connection over to it before that's read
+    pub fn receive(&mut self) -> Result<Vec<Message>, String> {
+        let arrived = self.lines.receive()?;
+        self.unread.extend(arrived);
+        let mut messages = Vec::new();
+        while let Some(line) = self.unread.pop_front() {
+            if let Some(message) = self.reader.read_line(line)? {
+                let start = matches!(message, Message::Start(_) | Message::Resume(_));
+                messages.push(message);
+                if start {
+                    break;
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    // say goodbye, for leaving politely rather than just disappearing
+    pub fn close(self) {
+        let mut text = String::new();
+        Message::Bye.write(&mut text);
+        self.lines.close(&text);
+    }
+}
+
+impl Reader {
+    // the message the line finishes, if it finishes one; an error if it makes no sense
+    fn read_line(&mut self, line: String) -> Result<Option<Message>, String> {
+        if let Some(block) = &mut self.block {
+            block.text.push_str(&line);
+            block.text.push('\n');
+            block.left -= 1;
+            if block.left > 0 {
+                return Ok(None);
+            }
+            let block = self.block.take().unwrap();
+            return Ok(Some(match block.kind.as_str() {
+                "settings" => Message::Settings(block.text),
+                "resume" => Message::Resume(block.text),
+                _ => Message::Start(block.text),
+            }));
+        }
+        let fields: Vec<&str> = line.splitn(3, ' ').collect();
+        let number = |i: usize| -> Option<u32> { fields.get(i)?.parse().ok() };
+        let team = || number(1).filter(|t| *t <= 1).map(|t| t as usize);
+        let message = match fields[0] {
+            "name" => team().map(|team| Message::Name {
+                team,
+                name: fields.get(2).unwrap_or(&"").to_string(),
+            }),
+            "ready" => team().map(|team| Message::Ready {
+                team,
+                ready: fields.get(2) == Some(&"1"),
+            }),
+            "settings" | "start" | "resume" => match number(1) {
+                Some(0) => Some(match fields[0] {
+                    "settings" => Message::Settings(String::new()),
+                    "resume" => Message::Resume(String::new()),
+                    _ => Message::Start(String::new()),
+                }),
+                Some(left) => {
+                    self.block = Some(Block {
+                        kind: fields[0].to_owned(),
+                        left: left as usize,
+                        text: String::new(),
+                    });
+                    return Ok(None);
+                }
+                None => None,
+            },
+            "input" => {
+                let rest: Vec<&str> = fields.get(2).unwrap_or(&"").split(' ').collect();
+                match (number(1), rest.len()) {
+                    (Some(step), 3) => {
+                        input::parse_input(&rest).map(|input| Message::Input { step, input })
+                    }
+                    _ => None,
+                }
+            }
+            "chat" => match fields.get(2).and_then(|p| p.parse().ok()) {
+                Some(phrase) if phrase < chat::PHRASES.len() => {
+                    team().map(|team| Message::Chat { team, phrase })
+                }
+                _ => None,
+            },
+            "bye" => Some(Message::Bye),
+            _ => None,
+        };
+        message
+            .map(Some)
+            .ok_or_else(|| format!("didn't understand \"{}\"", line))
+    }
+}
+
+// joining a host without waiting: connecting can take up to CONNECT_TIMEOUT to fail, which would
+// stop the game for that long, so it's done on a thread of its own
+pub struct Connecting {
+    pub address: String,
+    made: Receiver<Result<Lines, String>>,
+}
+
+impl Connecting {
+    pub fn new(address: &str) -> Self {
+        let (send, made) = mpsc::channel();
+        let to = address.to_owned();
+        std::thread::spawn(move || {
+            // whoever asked for the connection may have stopped waiting for it
+            send.send(Lines::connect(&to, GAME_PORT)).ok();
+        });
+        Self {
+            address: address.to_owned(),
+            made,
+        }
+    }
+
+    // call once a frame; the connection, or why there isn't one, once connecting has finished
+    pub fn poll(&self) -> Option<Result<Connection, String>> {
+        match self.made.try_recv() {
+            Ok(made) => Some(made.map(Connection::new)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(format!("{}: couldn't connect", self.address)))
+            }
+        }
+    }
+}
+
+// what the host sends to start a match: the seed it starts from, and its settings and squads
+pub fn start_text(seed: u64, settings: &Settings, game: &Game) -> String {
+    format!(
+        "seed = \"{}\"\n{}",
+        seed,
+        replays::match_header(settings, game)
+    )
+}
+
+// the seed, settings and squads in a start message
+pub fn read_start(text: &str) -> Result<(u64, Settings, [Roster; 2]), String> {
+    let doc = data::parse(text)?;
+    let values = doc.sections.get("").ok_or("empty match")?;
+    let seed = values
+        .get("seed")
+        .and_then(data::Value::as_str)
+        .and_then(|s| s.parse().ok())
+        .ok_or("no seed")?;
+    let (settings, rosters) = replays::read_match_header(values)?;
+    Ok((seed, settings, rosters))
+}
+
+// waits for someone to join, telling the local network it's there in the meantime
+pub struct Host {
+    listener: TcpListener,
+    announcer: UdpSocket,
+    announcement: String,
+    last_announced: f64,
+}
+
+impl Host {
+    pub fn new(name: &str) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", GAME_PORT)).map_err(|e| e.to_string())?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let announcer = UdpSocket::bind(("0.0.0.0", 0))
+            .and_then(|socket| socket.set_broadcast(true).map(|_| socket))
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            listener,
+            announcer,
+            announcement: format!("{} {} {}", ANNOUNCE, GAME_PORT, name),
+            last_announced: f64::NEG_INFINITY,
+        })
+    }
+
+    // call once a frame; the connection to whoever's joined, once someone has
+    pub fn accept(&mut self) -> Result<Option<Connection>, String> {
+        let now = date::now();
+        if now - self.last_announced >= ANNOUNCE_SECONDS {
+            self.last_announced = now;
+            // nobody on the local network hearing about the match isn't a reason to stop waiting
+            if let Err(e) = self.announcer.send_to(
+                self.announcement.as_bytes(),
+                ("255.255.255.255", DISCOVERY_PORT),
+            ) {
+                warn!("couldn't announce match: {}", e);
+            }
+        }
+        match self.listener.accept() {
+            Ok((stream, _)) => Lines::new(stream).map(|lines| Some(Connection::new(lines))),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// a match being hosted somewhere on the local network
+pub struct Found {
+    pub address: SocketAddr,
+    pub name: String,
+    seen: f64,
+}
+
+// listens for hosts announcing their matches
+pub struct Finder {
+    socket: UdpSocket,
+    pub found: Vec<Found>,
+}
+
+impl Finder {
+    pub fn new() -> Result<Self, String> {
+        let socket = UdpSocket::bind(("0.0.0.0", DISCOVERY_PORT))
+            .and_then(|socket| socket.set_nonblocking(true).map(|_| socket))
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            socket,
+            found: Vec::new(),
+        })
+    }
+
+    // call once a frame
+    pub fn update(&mut self) {
+        let now = date::now();
+        let mut buf = [0; 256];
+        while let Ok((n, from)) = self.socket.recv_from(&mut buf) {
+            let text = String::from_utf8_lossy(&buf[..n]);
+            let rest = match text.strip_prefix(ANNOUNCE) {
+                Some(rest) => rest.trim_start(),
+                None => continue,
+            };
+            let (port, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            let port = match port.parse() {
+                Ok(port) => port,
+                Err(_) => continue,
+            };
+            let address = SocketAddr::new(from.ip(), port);
+            self.found.retain(|found| found.address != address);
+            self.found.push(Found {
+                address,
+                name: name.to_owned(),
+                seen: now,
+            });
+        }
+        self.found.retain(|found| now - found.seen < FORGET_SECONDS);
+    }
+}
+
+// how to get back in touch with the other player if the connection drops
+pub enum Rejoin {
+    // listen for them again, announcing the match under this name
+    Host(String),
+    // connect to the host again, at this address
+    Join(String),
+}
+
+// what's become of the connection
+enum Link {
+    // playing in lockstep
+    Up,
+    // gone, and trying to get back in touch: the host listening again once it's been able to, and
+    // whoever joined trying to connect every RETRY_SECONDS
+    Down {
+        reason: String,
+        host: Option<Host>,
+        connecting: Option<Connecting>,
+        last_try: f64,
+    },
+    // back in touch, and waiting for the host to send the match so far
+    Resyncing,
+}
+
+// what's happened in a network match since the last frame, besides input arriving
+pub enum Heard {
+    // the other player said something with quick chat (see chat.rs)
+    Chat(usize),
+    // the match has been replaced with the host's, after reconnecting
+    Resynced,
+}
+
+// a match being played over a connection, from this copy's side of it
+pub struct NetMatch {
+    // None while it's down
+    connection: Option<Connection>,
+    link: Link,
+    rejoin: Rejoin,
+    pub local_team: usize,
+    // the match as it's been played, for the host to bring the other copy up to date with
+    record: MatchRecord,
+    // the next step to be played
+    step: u32,
+    // where the lockstep last started from, with nobody's input to wait for until INPUT_DELAY steps
+    // later
+    restart: u32,
+    // this player's input for each step from this one on, already sent
+    local: VecDeque<FrameInput>,
+    // the other player's, by step
+    remote: HashMap<u32, FrameInput>,
+    // why the other player has gone, if they have; they may have sent everything needed to finish
+    // the match first, if they got to full time a little sooner
+    gone: Option<String>,
+    // when anything last arrived, and when the connection was lost, if it's not been got back yet
+    last_heard: f64,
+    lost_since: Option<f64>,
+}
+
+impl NetMatch {
+    // record has to have been started at kickoff
+    pub fn new(
+        connection: Connection,
+        local_team: usize,
+        rejoin: Rejoin,
+        record: MatchRecord,
+    ) -> Self {
+        let mut net = Self {
+            connection: Some(connection),
+            link: Link::Up,
+            rejoin,
+            local_team,
+            record,
+            step: 0,
+            restart: 0,
+            local: VecDeque::new(),
+            remote: HashMap::new(),
+            gone: None,
+            last_heard: 0.0,
+            lost_since: None,
+        };
+        net.start_from(0);
+        net
+    }
+
+    // for someone whose game closed, rejoining from the lobby: the match the host sent, played
+    // through to where the host has got to, and the match to carry on playing as whoever joined
+    pub fn rejoined(
+        connection: Connection,
+        text: &str,
+        rejoin: Rejoin,
+    ) -> Result<(Game, Self), String> {
+        let mut playback = Playback::from_text(text)?;
+        let (game, record) = playback.play_through();
+        let mut net = Self::new(connection, 1, rejoin, record);
+        net.start_from(playback.step() as u32);
+        Ok((game, net))
+    }
+
+    // (re)start the lockstep at step
+    fn start_from(&mut self, step: u32) {
+        self.step = step;
+        self.restart = step;
+        // nobody can have asked for anything before it started
+        self.local = std::iter::repeat_n(FrameInput::none(), INPUT_DELAY as usize).collect();
+        self.remote.clear();
+        self.link = Link::Up;
+        self.last_heard = date::now();
+        self.lost_since = None;
+    }
+
+    // call once a frame; what's been heard from the other player, or an error once they've gone and
+    // the match can't go on. this can replace the match with the host's after reconnecting
+    pub fn receive(&mut self, game: &mut Game) -> Result<Vec<Heard>, String> {
+        let now = date::now();
+        let mut heard = Vec::new();
+        if self.gone.is_none() {
+            if let Link::Down { .. } = self.link {
+                self.reconnect(now);
+            } else if let Some(connection) = &mut self.connection {
+                match connection.receive() {
+                    Ok(messages) => {
+                        if !messages.is_empty() {
+                            self.last_heard = now;
+                        }
+                        for message in messages {
+                            self.read(message, game, &mut heard);
+                        }
+                    }
+                    Err(e) => self.lose(e, now),
+                }
+            }
+            if matches!(self.link, Link::Up | Link::Resyncing)
+                && !self.ready()
+                && now - self.last_heard > SILENT_SECONDS
+            {
+                self.lose("nothing heard from the other player".to_owned(), now);
+            }
+        }
+        if let Some(reason) = &self.gone {
+            if !self.ready() {
+                return Err(reason.clone());
+            }
+        }
+        match (&self.link, self.lost_since) {
+            (Link::Down { reason, .. }, Some(since)) if now - since > RECONNECT_SECONDS => {
+                Err(reason.clone())
+            }
+            _ => Ok(heard),
+        }
+    }
+
+    fn read(&mut self, message: Message, game: &mut Game, heard: &mut Vec<Heard>) {
+        match message {
+            Message::Input { step, input } => {
+                self.remote.insert(step, input);
+            }
+            Message::Chat { phrase, .. } => heard.push(Heard::Chat(phrase)),
+            Message::Resume(text) if matches!(self.link, Link::Resyncing) => {
+                let mut playback = match Playback::from_text(&text) {
+                    Ok(playback) => playback,
+                    Err(e) => {
+                        self.gone = Some(e);
+                        return;
+                    }
+                };
+                let (caught_up, record) = playback.play_through();
+                *game = caught_up;
+                self.record = record;
+                self.start_from(playback.step() as u32);
+                heard.push(Heard::Resynced);
+            }
+            Message::Bye => self.gone = Some("the other player left".to_owned()),
+            _ => (),
+        }
+    }
+
+    fn lose(&mut self, reason: String, now: f64) {
+        warn!("lost the other player: {}", reason);
+        // dropping the connection lets the other end know, if it can still hear
+        self.connection = None;
+        self.link = Link::Down {
+            reason,
+            host: None,
+            connecting: None,
+            last_try: f64::NEG_INFINITY,
+        };
+        self.lost_since.get_or_insert(now);
+    }
+
+    fn reconnect(&mut self, now: f64) {
+        let connection = match (&mut self.link, &self.rejoin) {
+            (Link::Down { host, last_try, .. }, Rejoin::Host(name)) => {
+                if host.is_none() && now - *last_try >= RETRY_SECONDS {
+                    *last_try = now;
+                    match Host::new(name) {
+                        Ok(listening) => *host = Some(listening),
+                        Err(e) => warn!("couldn't listen for the other player: {}", e),
+                    }
+                }
+                match host.as_mut().map(Host::accept) {
+                    Some(Ok(connection)) => connection,
+                    Some(Err(e)) => {
+                        warn!("couldn't accept the other player: {}", e);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            (
+                Link::Down {
+                    connecting,
+                    last_try,
+                    ..
+                },
+                Rejoin::Join(address),
+            ) => {
+                if connecting.is_none() && now - *last_try >= RETRY_SECONDS {
+                    *last_try = now;
+                    *connecting = Some(Connecting::new(address));
+                }
+                match connecting.as_ref().and_then(Connecting::poll) {
+                    Some(Ok(connection)) => Some(connection),
+                    // the host may not be listening again yet
+                    Some(Err(_)) => {
+                        *connecting = None;
+                        None
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(mut connection) = connection {
+            if let Rejoin::Host(_) = self.rejoin {
+                connection.send(&Message::Resume(self.record.so_far()));
+                self.connection = Some(connection);
+                self.start_from(self.step);
+            } else {
+                self.connection = Some(connection);
+                self.link = Link::Resyncing;
+                self.last_heard = now;
+            }
+        }
+    }
+
+    // whether the other player's input for the next step has arrived, so it can be played
+    pub fn ready(&self) -> bool {
+        matches!(self.link, Link::Up)
+            && (self.step < self.restart + INPUT_DELAY || self.remote.contains_key(&self.step))
+    }
+
+    // how many seconds are left to get back in touch, while the connection's down
+    pub fn reconnecting(&self) -> Option<u32> {
+        self.lost_since
+            .map(|since| (RECONNECT_SECONDS - (date::now() - since)).max(0.0) as u32)
+    }
+
+    // call just before each step, with this player's input for it applied: sends that on for the
+    // step INPUT_DELAY from now, and gives both teams what they asked for at this one
+    pub fn apply(&mut self, game: &mut Game) {
+        let input = game.input(self.local_team);
+        if let Some(connection) = &mut self.connection {
+            connection.send(&Message::Input {
+                step: self.step + INPUT_DELAY,
+                input,
+            });
+        }
+        self.local.push_back(input);
+        let local = self.local.pop_front().unwrap_or_else(FrameInput::none);
+        let remote = self
+            .remote
+            .remove(&self.step)
+            .unwrap_or_else(FrameInput::none);
+        game.replace_input(self.local_team, local);
+        game.replace_input(1 - self.local_team, remote);
+        // recording the step reseeds the random numbers, so they're the same on both sides however
+        // they're used between steps
+        self.record.record(game);
+        self.step += 1;
+    }
+
+    pub fn say(&mut self, phrase: usize) {
+        if let Some(connection) = &mut self.connection {
+            connection.send(&Message::Chat {
+                team: self.local_team,
+                phrase,
+            });
+        }
+    }
+
+    // the match as it's been played, for saving as a replay
+    pub fn record(&self) -> &MatchRecord {
+        &self.record
+    }
+
+    pub fn close(self) {
+        if let Some(connection) = self.connection {
+            connection.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(text: &str) -> Result<Vec<Message>, String> {
+        let mut reader = Reader { block: None };
+        let mut messages = Vec::new();
+        for line in text.lines() {
+            messages.extend(reader.read_line(line.to_owned())?);
+        }
+        Ok(messages)
+    }
+
+    #[test]
+    fn messages_read_back_as_written() {
+        let input = FrameInput {
+            movement: euclid::vec2(-1.0, 0.5),
+            shoot: true,
+            switch: false,
+            call: false,
+            tackle: true,
+        };
+        let mut text = String::new();
+        for message in [
+            Message::Name {
+                team: 1,
+                name: "TWO WORDS".to_owned(),
+            },
+            Message::Ready {
+                team: 0,
+                ready: true,
+            },
+            Message::Start("seed = \"1\"\n\n[home]\n".to_owned()),
+            Message::Input { step: 12, input },
+            Message::Settings(String::new()),
+            Message::Chat { team: 1, phrase: 5 },
+            Message::Bye,
+        ] {
+            message.write(&mut text);
+        }
+        let messages = read_all(&text).unwrap();
+        assert_eq!(messages.len(), 7);
+        assert!(matches!(&messages[0], Message::Name { team: 1, name } if name == "TWO WORDS"));
+        assert!(matches!(
+            messages[1],
+            Message::Ready {
+                team: 0,
+                ready: true
+            }
+        ));
+        assert!(matches!(&messages[2], Message::Start(text) if text == "seed = \"1\"\n\n[home]\n"));
+        assert!(matches!(messages[3], Message::Input { step: 12, input: read } if read == input));
+        assert!(matches!(&messages[4], Message::Settings(text) if text.is_empty()));
+        assert!(matches!(messages[5], Message::Chat { team: 1, phrase: 5 }));
+        assert!(matches!(messages[6], Message::Bye));
+    }
+
+    #[test]
+    fn a_block_waits_for_all_its_lines() {
+        let messages = read_all("resume 3\n[replay]\nversion = 1\n").unwrap();
+        assert!(messages.is_empty());
+        let messages = read_all("resume 2\n[replay]\nversion = 1\n").unwrap();
+        assert!(
+            matches!(&messages[..], [Message::Resume(text)] if text == "[replay]\nversion = 1\n")
+        );
+    }
+
+    #[test]
+    fn messages_with_missing_or_bad_fields() {
+        for line in [
+            "",
+            "name",
+            "name 2 SOMEONE",
+            "ready",
+            "start",
+            "start many",
+            "input",
+            "input 12",
+            "input 12 0 0",
+            "input 12 left 0 -",
+            "chat 0",
+            "chat 0 99",
+            "chat 3 0",
+            "hello",
+        ] {
+            let mut reader = Reader { block: None };
+            assert!(
+                reader.read_line(line.to_owned()).is_err(),
+                "read {:?}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// the menu Escape brings up during a match, which stops play while it's open; from it a human team
+// can bring on a substitute, choosing first who comes off and then who goes on, or change its
+// tactics
+//
+// a substitute takes over from the player they replace right where they're standing, rather than
+// waiting for the ball to go out of play
+
+use crate::i18n::Strings;
+use crate::menu::{self, MenuAction, Pointer};
+use crate::tactics;
+use crate::{Game, Sounds, HEIGHT, PLAYER_START_POS, WIDTH};
+use macroquad::prelude::*;
+
+#[derive(Copy, Clone)]
+enum Page {
+    // carry on, or make a substitution or change tactics for one of the human teams
+    Main,
+    // which position on a team to make a substitution in
+    Off(usize),
+    // who from the team's bench to bring on in that position
+    On(usize, usize),
+    // a team's pressing and defensive line, changed with left and right
+    Tactics(usize),
+}
+
+pub struct PauseMenu {
+    page: Page,
+    selected: usize,
+}
+
+// the human teams, which follow carrying on in the main page's rows
+fn human_teams(game: &Game) -> Vec<usize> {
+    (0..=1).filter(|t| game.teams[*t].human()).collect()
+}
+
+// the main page's rows for each human team: substitutions, then tactics
+const TEAM_ROWS: usize = 2;
+
+// the positions on a team that somebody is playing in
+fn filled_positions(game: &Game, team: usize) -> Vec<usize> {
+    (0..PLAYER_START_POS.len())
+        .filter(|n| !game.lineups[team].empty(*n))
+        .collect()
+}
+
+fn player_label(game: &Game, team: usize, member: usize, strings: &Strings) -> String {
+    let squad = game.rosters[team].squad(member);
+    let key = if game.lineups[team].injured(member) {
+        "pause.injured_player"
+    } else {
+        "pause.player"
+    };
+    strings.format(key, &[&squad.number.to_string(), &squad.name])
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self {
+            page: Page::Main,
+            selected: 0,
+        }
+    }
+
+    fn rows(&self, game: &Game) -> usize {
+        match self.page {
+            Page::Main => 1 + TEAM_ROWS * human_teams(game).len(),
+            Page::Off(team) => filled_positions(game, team).len(),
+            Page::On(team, _) => game.lineups[team].bench().len(),
+            Page::Tactics(_) => 2,
+        }
+    }
+
+    // called once a frame while the menu is open; true once it's been closed
+    pub fn update(&mut self, game: &mut Game, pointer: &Pointer, sounds: &Sounds) -> bool {
+        let rows = self.rows(game);
+        match menu::navigate(&mut self.selected, rows, menu::SETTINGS_LAYOUT, pointer) {
+            MenuAction::Moved => sounds.play("move"),
+            MenuAction::Back => {
+                let (page, selected) = match self.page {
+                    Page::Main => return true,
+                    Page::Off(team) | Page::Tactics(team) => {
+                        let row = human_teams(game).iter().position(|t| *t == team);
+                        let offset = if let Page::Off(_) = self.page { 1 } else { 2 };
+                        (Page::Main, row.map_or(0, |row| row * TEAM_ROWS + offset))
+                    }
+                    Page::On(team, _) => (Page::Off(team), 0),
+                };
+                sounds.play("move");
+                self.page = page;
+                self.selected = selected;
+            }
+            MenuAction::Confirm => match self.page {
+                Page::Main if self.selected == 0 => return true,
+                Page::Main if (self.selected - 1) % TEAM_ROWS == 1 => {
+                    sounds.play("move");
+                    self.page = Page::Tactics(human_teams(game)[(self.selected - 1) / TEAM_ROWS]);
+                    self.selected = 0;
+                }
+                Page::Main => {
+                    let team = human_teams(game)[(self.selected - 1) / TEAM_ROWS];
+                    if game.lineups[team].can_substitute() {
+                        sounds.play("move");
+                        self.page = Page::Off(team);
+                        self.selected = 0;
+                    }
+                }
+                Page::Off(team) => {
+                    sounds.play("move");
+                    self.page = Page::On(team, filled_positions(game, team)[self.selected]);
+                    self.selected = 0;
+                }
+                Page::On(team, position) => {
+                    if let Err(e) = game.substitute(team, position, self.selected) {
+                        game.recover(e);
+                    }
+                    sounds.play("whistle");
+                    self.page = Page::Main;
+                    self.selected = 0;
+                }
+                Page::Tactics(team) => self.adjust(game, team, 1, sounds),
+            },
+            MenuAction::Adjust(dir) => {
+                if let Page::Tactics(team) = self.page {
+                    self.adjust(game, team, dir, sounds);
+                }
+            }
+            MenuAction::None => (),
+        }
+        false
+    }
+
+    fn adjust(&self, game: &mut Game, team: usize, dir: i32, sounds: &Sounds) {
+        let tactics = &mut game.teams[team].tactics;
+        if self.selected == 0 {
+            tactics.pressing = tactics::step(tactics.pressing, dir);
+        } else {
+            tactics.line = tactics::step(tactics.line, dir);
+        }
+        sounds.play("move");
+    }
+
+    // in screen coordinates, over the match
+    pub fn draw(&self, game: &Game, strings: &Strings) {
+        draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+        let (title, labels): (String, Vec<String>) = match self.page {
+            Page::Main => (
+                strings.get("hud.paused").to_owned(),
+                std::iter::once(strings.get("pause.resume").to_owned())
+                    .chain(human_teams(game).into_iter().flat_map(|t| {
+                        let key = if game.lineups[t].can_substitute() {
+                            "pause.substitute"
+                        } else {
+                            "pause.no_substitutes"
+                        };
+                        let team = (t + 1).to_string();
+                        [
+                            strings.format(key, &[&team]),
+                            strings.format("pause.tactics", &[&team]),
+                        ]
+                    }))
+                    .collect(),
+            ),
+            Page::Off(team) => (
+                strings.get("pause.coming_off").to_owned(),
+                filled_positions(game, team)
+                    .into_iter()
+                    .map(|n| player_label(game, team, game.lineups[team].member(n), strings))
+                    .collect(),
+            ),
+            Page::On(team, _) => (
+                strings.get("pause.coming_on").to_owned(),
+                game.lineups[team]
+                    .bench()
+                    .iter()
+                    .map(|member| player_label(game, team, *member, strings))
+                    .collect(),
+            ),
+            Page::Tactics(team) => {
+                let tactics = game.teams[team].tactics;
+                let number = (team + 1).to_string();
+                (
+                    strings.get("options.page_tactics").to_owned(),
+                    vec![
+                        strings.format(
+                            "options.pressing",
+                            &[
+                                &number,
+                                strings.get(tactics::PRESSING_KEYS[tactics.pressing as usize]),
+                            ],
+                        ),
+                        strings.format(
+                            "options.defensive_line",
+                            &[
+                                &number,
+                                strings.get(tactics::LINE_KEYS[tactics.line as usize]),
+                            ],
+                        ),
+                    ],
+                )
+            }
+        };
+        draw_text(&title, 100., 100., 48., WHITE);
+        for (i, label) in labels.iter().enumerate() {
+            let colour = if i == self.selected { YELLOW } else { WHITE };
+            draw_text(label, 120., menu::SETTINGS_LAYOUT.baseline(i), 32., colour);
+        }
+        draw_text(strings.get("options.back"), 10., HEIGHT - 10., 16., WHITE);
+    }
+}
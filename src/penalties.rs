@@ -0,0 +1,350 @@
+use crate::camera::Camera;
+use crate::i18n::Strings;
+use crate::textures::{Sprite, Textures};
+use crate::{
+    draw_text_centred, GameEvent, Vector, GOAL_BOUNDS_X, HALF_LEVEL_W, HALF_WINDOW_WIDTH, HEIGHT,
+    KICK_STRENGTH, PITCH_BOUNDS_Y, WIDTH,
+};
+use euclid::vec2;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+const KICKS_PER_ROUND: u32 = 5;
+const SPOT_Y: f32 = PITCH_BOUNDS_Y.0 + 170.0;
+const GOAL_LINE_Y: f32 = PITCH_BOUNDS_Y.0;
+// the aim can go a little way past the posts, so it's possible to miss
+const AIM_RANGE: (f32, f32) = (GOAL_BOUNDS_X.0 - 25.0, GOAL_BOUNDS_X.1 + 25.0);
+const AIM_SPEED: f32 = 2.5;
+// the power meter fills and empties again over this many steps until the ball is struck
+const POWER_CYCLE_FRAMES: u32 = 80;
+// strikes harder than this sail over the bar, and softer than SLOW_POWER are easy to save
+const OVER_BAR_POWER: f32 = 0.9;
+const SLOW_POWER: f32 = 0.4;
+const MIN_SHOT_SPEED: f32 = 4.0;
+const KEEPER_Y: f32 = GOAL_LINE_Y + 12.0;
+const KEEPER_SPEED: f32 = 2.6;
+const KEEPER_REACH: f32 = 24.0;
+// chance of the keeper guessing which way the shot is going, as opposed to picking at random
+const KEEPER_READ_CHANCE: f32 = 0.35;
+// how long to show each result before the next kick, in steps
+const RESULT_FRAMES: u32 = 100;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Outcome {
+    Goal,
+    Saved,
+    Wide,
+    OverTheBar,
+}
+
+impl Outcome {
+    // names the outcome in the language files
+    fn key(self) -> &'static str {
+        match self {
+            Self::Goal => "penalties.goal",
+            Self::Saved => "penalties.saved",
+            Self::Wide => "penalties.wide",
+            Self::OverTheBar => "penalties.over_the_bar",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Phase {
+    // moving the aim along the goal
+    Aim,
+    // the power meter is running, and the ball is struck when the player presses again
+    Power(u32),
+    Flight(Outcome),
+    // showing how the kick went, for the given number of steps
+    Result(Outcome, u32),
+    // every kick in the round has been taken
+    Finished,
+}
+
+// a single penalty: the kicker aims with left and right, then strikes the ball with good timing
+// to get it past the keeper
+struct PenaltyKick {
+    phase: Phase,
+    aim_x: f32,
+    ball: Vector,
+    ball_vel: Vector,
+    keeper: Vector,
+    keeper_dest: f32,
+    // how far the keeper has moved, for their running animation
+    keeper_distance: f32,
+}
+
+impl PenaltyKick {
+    fn new() -> Self {
+        Self {
+            phase: Phase::Aim,
+            aim_x: HALF_LEVEL_W,
+            ball: vec2(HALF_LEVEL_W, SPOT_Y),
+            ball_vel: Vector::zero(),
+            keeper: vec2(HALF_LEVEL_W, KEEPER_Y),
+            keeper_dest: HALF_LEVEL_W,
+            keeper_distance: 0.0,
+        }
+    }
+
+    // how full the power meter is, from 0 to 1
+    fn power(frames: u32) -> f32 {
+        let t = (frames % POWER_CYCLE_FRAMES) as f32 / POWER_CYCLE_FRAMES as f32;
+        1.0 - (2.0 * t - 1.0).abs()
+    }
+
+    fn strike(&mut self, power: f32, events: &mut Vec<GameEvent>) {
+        let target = vec2(self.aim_x, GOAL_LINE_Y);
+        let speed = MIN_SHOT_SPEED + (KICK_STRENGTH - MIN_SHOT_SPEED) * power;
+        self.ball_vel = (target - self.ball).normalize() * speed;
+        // the keeper commits to a side as soon as the ball is struck
+        self.keeper_dest = if gen_range(0.0, 1.0) < KEEPER_READ_CHANCE {
+            self.aim_x
+        } else {
+            gen_range(GOAL_BOUNDS_X.0, GOAL_BOUNDS_X.1)
+        };
+        // a soft shot gives the keeper time to get across
+        if power < SLOW_POWER {
+            self.keeper_dest = self.aim_x;
+        }
+        let outcome = if power > OVER_BAR_POWER {
+            Outcome::OverTheBar
+        } else if self.aim_x < GOAL_BOUNDS_X.0 || self.aim_x >= GOAL_BOUNDS_X.1 {
+            Outcome::Wide
+        } else {
+            // decided when the ball reaches the keeper
+            Outcome::Goal
+        };
+        self.phase = Phase::Flight(outcome);
+        events.push(GameEvent::Kick {
+            team: 0,
+            shot: true,
+        });
+    }
+
+    // called once per simulation step; pressed is true if the kick button was pressed since the
+    // last step
+    fn update(&mut self, pressed: bool, events: &mut Vec<GameEvent>) {
+        match self.phase {
+            Phase::Aim => {
+                if is_key_down(KeyCode::Left) {
+                    self.aim_x -= AIM_SPEED;
+                }
+                if is_key_down(KeyCode::Right) {
+                    self.aim_x += AIM_SPEED;
+                }
+                self.aim_x = self.aim_x.clamp(AIM_RANGE.0, AIM_RANGE.1);
+                if pressed {
+                    self.phase = Phase::Power(0);
+                }
+            }
+            Phase::Power(frames) => {
+                if pressed {
+                    self.strike(Self::power(frames), events);
+                } else {
+                    self.phase = Phase::Power(frames + 1);
+                }
+            }
+            Phase::Flight(mut outcome) => {
+                self.ball += self.ball_vel;
+                let to_dest = (self.keeper_dest - self.keeper.x).clamp(-KEEPER_SPEED, KEEPER_SPEED);
+                self.keeper.x += to_dest;
+                self.keeper_distance += to_dest.abs();
+                if self.ball.y <= KEEPER_Y && outcome == Outcome::Goal {
+                    if (self.ball.x - self.keeper.x).abs() < KEEPER_REACH {
+                        outcome = Outcome::Saved;
+                        self.ball_vel = Vector::zero();
+                    }
+                    self.phase = Phase::Flight(outcome);
+                }
+                if self.ball.y <= GOAL_LINE_Y || self.ball_vel == Vector::zero() {
+                    if outcome == Outcome::Goal {
+                        events.push(GameEvent::Goal { team: 0 });
+                    }
+                    self.phase = Phase::Result(outcome, RESULT_FRAMES);
+                }
+            }
+            Phase::Result(outcome, frames) => {
+                // the ball carries on into the net, or past the goal if it missed
+                if outcome != Outcome::Saved && self.ball.y > GOAL_LINE_Y - 40.0 {
+                    self.ball += self.ball_vel * 0.5;
+                }
+                self.phase = Phase::Result(outcome, frames.saturating_sub(1));
+            }
+            Phase::Finished => (),
+        }
+    }
+
+    fn draw(&self, textures: &Textures) {
+        let keeper_frame = textures.player_frame(self.keeper_distance);
+        textures.draw_anchored(
+            Sprite::PlayerShadow {
+                dir: 4,
+                frame: keeper_frame,
+            },
+            self.keeper.x,
+            self.keeper.y,
+        );
+        textures.draw_anchored(
+            Sprite::PlayerShadow { dir: 0, frame: 0 },
+            HALF_LEVEL_W,
+            SPOT_Y + 20.0,
+        );
+        textures.draw_anchored(Sprite::BallShadow, self.ball.x, self.ball.y);
+        // the keeper, ball and kicker are always in that order from top to bottom
+        textures.draw_anchored(
+            Sprite::Player {
+                team: 1,
+                dir: 4,
+                frame: keeper_frame,
+            },
+            self.keeper.x,
+            self.keeper.y,
+        );
+        textures.draw_anchored(Sprite::Ball, self.ball.x, self.ball.y);
+        textures.draw_anchored(
+            Sprite::Player {
+                team: 0,
+                dir: 0,
+                frame: 0,
+            },
+            HALF_LEVEL_W,
+            SPOT_Y + 20.0,
+        );
+        if matches!(self.phase, Phase::Aim | Phase::Power(_)) {
+            let colour = Color::new(1.0, 1.0, 0.0, 0.8);
+            draw_circle_lines(self.aim_x, GOAL_LINE_Y - 10.0, 8.0, 2.0, colour);
+            draw_line(
+                self.aim_x,
+                GOAL_LINE_Y - 22.0,
+                self.aim_x,
+                GOAL_LINE_Y + 2.0,
+                1.0,
+                colour,
+            );
+        }
+    }
+}
+
+// a round of penalties against the computer goalkeeper
+pub struct Penalties {
+    pub camera: Camera,
+    kick: PenaltyKick,
+    kicks_taken: u32,
+    goals: u32,
+    // kick presses since the last update, so they aren't missed when a frame doesn't run one
+    pressed: bool,
+    pub events: Vec<GameEvent>,
+}
+
+impl Penalties {
+    pub fn new() -> Self {
+        Self {
+            camera: Camera::new(vec2(HALF_LEVEL_W, SPOT_Y - 60.0), vec2(WIDTH, HEIGHT)),
+            kick: PenaltyKick::new(),
+            kicks_taken: 0,
+            goals: 0,
+            pressed: false,
+            events: Vec::new(),
+        }
+    }
+
+    // tapped is true if the touch screen was tapped this frame
+    pub fn latch_input(&mut self, tapped: bool) {
+        self.pressed |= is_key_pressed(KeyCode::Space) || tapped;
+    }
+
+    // called once per simulation step
+    pub fn update(&mut self) {
+        self.events.clear();
+        let pressed = std::mem::take(&mut self.pressed);
+        match self.kick.phase {
+            Phase::Result(outcome, 0) => {
+                self.kicks_taken += 1;
+                if outcome == Outcome::Goal {
+                    self.goals += 1;
+                }
+                self.kick = PenaltyKick::new();
+                if self.kicks_taken == KICKS_PER_ROUND {
+                    self.kick.phase = Phase::Finished;
+                }
+            }
+            Phase::Finished if pressed => *self = Self::new(),
+            _ => self.kick.update(pressed, &mut self.events),
+        }
+        self.camera.react(&self.events);
+        self.camera.track(self.camera.focus, 1.0);
+    }
+
+    // true on the last step of showing a result, when the crowd should quieten down after a goal
+    pub fn result_ending(&self) -> bool {
+        matches!(self.kick.phase, Phase::Result(_, 1))
+    }
+
+    // draw in level coordinates, over the pitch
+    pub fn draw_world(&self, textures: &Textures) {
+        // the net hangs behind everyone
+        textures.draw_anchored(Sprite::Goal(0), HALF_LEVEL_W, 0.0);
+        self.kick.draw(textures);
+    }
+
+    // draw in screen coordinates
+    pub fn draw_hud(&self, strings: &Strings) {
+        let total = KICKS_PER_ROUND.to_string();
+        if self.kick.phase == Phase::Finished {
+            let score = strings.format("penalties.final", &[&self.goals.to_string(), &total]);
+            draw_text_centred(&score, HALF_WINDOW_WIDTH, HEIGHT / 2.0, 48.0);
+            draw_text_centred(
+                strings.get("penalties.again"),
+                HALF_WINDOW_WIDTH,
+                HEIGHT - 10.0,
+                16.0,
+            );
+            return;
+        }
+        let status = strings.format(
+            "penalties.status",
+            &[
+                &(self.kicks_taken + 1).to_string(),
+                &total,
+                &self.goals.to_string(),
+            ],
+        );
+        draw_text_centred(&status, HALF_WINDOW_WIDTH, 30.0, 28.0);
+        let prompt = match self.kick.phase {
+            Phase::Aim => strings.get("penalties.aim"),
+            Phase::Power(_) => strings.get("penalties.power"),
+            Phase::Result(outcome, _) => {
+                draw_text_centred(
+                    strings.get(outcome.key()),
+                    HALF_WINDOW_WIDTH,
+                    HEIGHT / 2.0,
+                    80.0,
+                );
+                ""
+            }
+            Phase::Flight(_) | Phase::Finished => "",
+        };
+        draw_text_centred(prompt, HALF_WINDOW_WIDTH, HEIGHT - 10.0, 16.0);
+        if let Phase::Power(frames) = self.kick.phase {
+            draw_power_meter(PenaltyKick::power(frames));
+        }
+    }
+}
+
+// a vertical bar on the right of the screen, marked red at the top where the ball goes over
+fn draw_power_meter(power: f32) {
+    let (x, y, w, h) = (WIDTH - 60.0, 120.0, 24.0, 240.0);
+    draw_rectangle(x, y, w, h, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_rectangle(
+        x,
+        y,
+        w,
+        h * (1.0 - OVER_BAR_POWER),
+        Color::new(1.0, 0.0, 0.0, 0.5),
+    );
+    let fill = h * power;
+    draw_rectangle(x + 4.0, y + h - fill, w - 8.0, fill, YELLOW);
+    draw_rectangle_lines(x, y, w, h, 2.0, WHITE);
+}
@@ -0,0 +1,139 @@
+// a performance display, toggled with F8, for noticing straight away when a change makes frames
+// slower: frame rate, how much of each frame went on simulating and how much on drawing, how many
+// entities there are, and a graph of the last few seconds
+
+use crate::{Game, Position, Team, HEIGHT};
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+// how many frames the graph goes back
+const HISTORY: usize = 180;
+const GRAPH_X: f32 = 10.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+// milliseconds of frame time that fill the graph's height
+const GRAPH_MS: f32 = 33.3;
+const FRAME_60_MS: f32 = 1000.0 / 60.0;
+
+// milliseconds spent on each part of a frame
+#[derive(Copy, Clone, Default)]
+struct Sample {
+    sim: f32,
+    render: f32,
+}
+
+pub struct PerfHud {
+    visible: bool,
+    frame_start: f64,
+    sim_end: f64,
+    samples: VecDeque<Sample>,
+}
+
+impl PerfHud {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_start: get_time(),
+            sim_end: get_time(),
+            samples: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // call at the start of the frame, before handling input and updating the game
+    pub fn begin_frame(&mut self) {
+        self.frame_start = get_time();
+    }
+
+    // call once the game is updated and drawing is about to begin
+    pub fn end_sim(&mut self) {
+        self.sim_end = get_time();
+    }
+
+    // call when everything has been drawn, just before the frame is shown
+    pub fn end_frame(&mut self) {
+        let now = get_time();
+        if self.samples.len() == HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            sim: ((self.sim_end - self.frame_start) * 1000.0) as f32,
+            render: ((now - self.sim_end) * 1000.0) as f32,
+        });
+    }
+
+    fn average(&self) -> Sample {
+        let n = self.samples.len().max(1) as f32;
+        let total = self.samples.iter().fold(Sample::default(), |t, s| Sample {
+            sim: t.sim + s.sim,
+            render: t.render + s.render,
+        });
+        Sample {
+            sim: total.sim / n,
+            render: total.render / n,
+        }
+    }
+
+    // draw in screen coordinates; drawing this counts towards the render time it shows
+    pub fn draw(&self, game: &Game) {
+        if !self.visible {
+            return;
+        }
+        let top = HEIGHT - GRAPH_HEIGHT - 70.0;
+        let players = game.world.query::<(&Position, &Team)>().iter().count();
+        let average = self.average();
+        let lines = [
+            format!(
+                "{} fps, frame {:.1} ms",
+                get_fps(),
+                get_frame_time() * 1000.0
+            ),
+            format!("sim {:.2} ms, render {:.2} ms", average.sim, average.render),
+            format!(
+                "entities {} ({} players) in {} archetypes",
+                game.world.len(),
+                players,
+                game.world.archetypes().len()
+            ),
+        ];
+        draw_rectangle(
+            GRAPH_X - 5.0,
+            top - 5.0,
+            HISTORY as f32 + 190.0,
+            GRAPH_HEIGHT + 70.0,
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(line, GRAPH_X, top + 12.0 + i as f32 * 16.0, 16.0, WHITE);
+        }
+        // a bar a frame, simulation at the bottom and drawing stacked on top of it
+        let bottom = top + 55.0 + GRAPH_HEIGHT;
+        let scale = GRAPH_HEIGHT / GRAPH_MS;
+        for (i, sample) in self.samples.iter().enumerate() {
+            let x = GRAPH_X + i as f32;
+            let sim = (sample.sim * scale).min(GRAPH_HEIGHT);
+            let render = (sample.render * scale).min(GRAPH_HEIGHT - sim);
+            draw_line(x, bottom, x, bottom - sim, 1.0, GREEN);
+            draw_line(x, bottom - sim, x, bottom - sim - render, 1.0, SKYBLUE);
+        }
+        // anything above this line means dropping below 60 frames a second
+        let y = bottom - FRAME_60_MS * scale;
+        draw_line(GRAPH_X, y, GRAPH_X + HISTORY as f32, y, 1.0, RED);
+        draw_text(
+            "sim",
+            GRAPH_X + HISTORY as f32 + 10.0,
+            bottom - 20.0,
+            16.0,
+            GREEN,
+        );
+        draw_text(
+            "render",
+            GRAPH_X + HISTORY as f32 + 10.0,
+            bottom - 4.0,
+            16.0,
+            SKYBLUE,
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// how a computer team likes to play: where its leads close the ball down, how tightly it marks,
+// and how readily its players give the ball away rather than dribble with it
+//
+// Balanced is how every computer team played before teams had personalities, so the others are
+// for variety, such as opponents in a tournament (see bots.rs)
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Personality {
+    Balanced,
+    // sits off the ball and marks tightly, and won't risk a pass it doesn't have to make
+    Defensive,
+    // keeps the ball moving with short passes
+    Possession,
+    // gets the ball forward early with long passes
+    LongBall,
+    // closes down the ball from further away and stands off its marks to get to the ball first
+    Pressing,
+}
+
+pub struct Style {
+    // how far in front of whoever has the ball the first and second leads aim for, as multiples of
+    // the balance file's lead distances
+    pub lead_scale: [f32; 2],
+    // how far from a marked player towards the ball a marker stands, as a fraction of the way
+    pub marking: f32,
+    // added to the cost of where someone with the ball is when they decide whether to kick it, so
+    // that above zero they pass sooner and below it they dribble for longer
+    pub kick_eagerness: f32,
+    // multiplies how far away a teammate can be to be passed to
+    pub pass_range: f32,
+}
+
+// in the order of Personality's variants
+const STYLES: [Style; 5] = [
+    Style {
+        lead_scale: [1.0, 1.0],
+        marking: 0.5,
+        kick_eagerness: 0.0,
+        pass_range: 1.0,
+    },
+    Style {
+        lead_scale: [1.0, 0.6],
+        marking: 0.35,
+        kick_eagerness: -10.0,
+        pass_range: 1.0,
+    },
+    Style {
+        lead_scale: [1.0, 1.0],
+        marking: 0.5,
+        kick_eagerness: 30.0,
+        pass_range: 0.7,
+    },
+    Style {
+        lead_scale: [1.0, 1.0],
+        marking: 0.4,
+        kick_eagerness: 15.0,
+        pass_range: 1.5,
+    },
+    Style {
+        lead_scale: [3.0, 1.8],
+        marking: 0.7,
+        kick_eagerness: 0.0,
+        pass_range: 1.0,
+    },
+];
+
+pub const ALL: [Personality; 5] = [
+    Personality::Balanced,
+    Personality::Defensive,
+    Personality::Possession,
+    Personality::LongBall,
+    Personality::Pressing,
+];
+
+impl Personality {
+    pub fn style(self) -> &'static Style {
+        &STYLES[self as usize]
+    }
+
+    // as it's asked for on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Balanced => "balanced",
+            Self::Defensive => "defensive",
+            Self::Possession => "possession",
+            Self::LongBall => "long-ball",
+            Self::Pressing => "pressing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL.iter().copied().find(|p| p.name() == name)
+    }
+}
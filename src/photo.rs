@@ -0,0 +1,29 @@
+// photo mode: P stops the match where it is, hides the HUD and sets the camera free of the match
+// to be flown about (see Camera::fly) and framed for a screenshot with F12; Period plays a single
+// step, for catching the right moment, and P or Escape carries on with the match
+
+use crate::camera::Camera;
+use macroquad::prelude::*;
+
+pub struct PhotoMode {
+    camera: Camera,
+}
+
+impl PhotoMode {
+    // starting from where the match camera is
+    pub fn new(camera: &Camera) -> Self {
+        Self {
+            camera: camera.clone(),
+        }
+    }
+
+    // call once a frame; returns how many steps of the match to play
+    pub fn update(&mut self) -> u32 {
+        self.camera.fly();
+        is_key_pressed(KeyCode::Period) as u32
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+}
@@ -0,0 +1,121 @@
+// stand-in graphics drawn with macroquad primitives, used for any sprite whose image couldn't be
+// loaded so that the game can be run without the original art
+
+use crate::textures::Sprite;
+use crate::{
+    CENTRE_CIRCLE_RADIUS, GOAL_BOUNDS_X, GOAL_BOUNDS_Y, GOAL_DEPTH, GOAL_WIDTH, HALF_LEVEL_H,
+    HALF_LEVEL_W, HEIGHT, LEVEL_H, LEVEL_W, PITCH_BOUNDS_X, PITCH_BOUNDS_Y, WIDTH,
+};
+use macroquad::prelude::*;
+
+const GRASS: Color = Color::new(0.2, 0.55, 0.2, 1.0);
+const LINES: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+const SHADOW: Color = Color::new(0.0, 0.0, 0.0, 0.3);
+const TEAM_COLOURS: [Color; 2] = [
+    Color::new(0.9, 0.2, 0.2, 1.0),
+    Color::new(0.2, 0.4, 0.95, 1.0),
+];
+
+const PLAYER_RADIUS: f32 = 10.0;
+const BALL_RADIUS: f32 = 5.0;
+
+// x and y are where the sprite would be anchored, i.e. the top left corner for sprites with no
+// anchor in the manifest
+pub fn draw(sprite: Sprite, x: f32, y: f32) {
+    match sprite {
+        Sprite::Pitch => draw_pitch(),
+        Sprite::Player { team, dir, .. } => {
+            let centre = vec2(x, y - PLAYER_RADIUS);
+            let facing = (dir as f32) * std::f32::consts::PI / 4.0;
+            let tip = centre + vec2(facing.sin(), -facing.cos()) * PLAYER_RADIUS * 1.6;
+            draw_circle(
+                centre.x,
+                centre.y,
+                PLAYER_RADIUS,
+                TEAM_COLOURS[team as usize],
+            );
+            draw_circle_lines(centre.x, centre.y, PLAYER_RADIUS, 1.0, BLACK);
+            draw_line(centre.x, centre.y, tip.x, tip.y, 2.0, BLACK);
+        }
+        Sprite::PlayerShadow { .. } => draw_circle(x + 3.0, y - 2.0, PLAYER_RADIUS, SHADOW),
+        Sprite::Ball => {
+            draw_circle(x, y, BALL_RADIUS, WHITE);
+            draw_circle_lines(x, y, BALL_RADIUS, 1.0, BLACK);
+        }
+        Sprite::BallShadow => draw_circle(x + 2.0, y + 2.0, BALL_RADIUS, SHADOW),
+        Sprite::Arrow(team) => draw_triangle(
+            vec2(x - 6.0, y - 40.0),
+            vec2(x + 6.0, y - 40.0),
+            vec2(x, y - 30.0),
+            TEAM_COLOURS[team as usize],
+        ),
+        Sprite::Goal(end) => {
+            let top = if end == 0 {
+                GOAL_BOUNDS_Y.0
+            } else {
+                PITCH_BOUNDS_Y.1
+            };
+            draw_rectangle_lines(GOAL_BOUNDS_X.0, top, GOAL_WIDTH, GOAL_DEPTH, 4.0, WHITE);
+        }
+        Sprite::GoalBanner => draw_centred("GOAL!", y + 88.0, 96.0, YELLOW),
+        Sprite::ScoreBar => draw_rectangle(x, y, 352.0, 36.0, Color::new(0.0, 0.0, 0.0, 0.6)),
+        Sprite::ScoreDigit(n) => draw_text(&n.to_string(), x + 6.0, y + 24.0, 32.0, WHITE),
+        Sprite::GameOver(winner) => {
+            draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.0, 0.0, 0.0, 0.7));
+            draw_centred(
+                &format!("TEAM {} WINS", winner + 1),
+                100.0,
+                64.0,
+                TEAM_COLOURS[winner as usize],
+            );
+            draw_centred("PRESS SPACE", HEIGHT - 60.0, 32.0, WHITE);
+        }
+        Sprite::FinalScore(team, n) => draw_text(
+            &n.to_string(),
+            x + 20.0,
+            y + 80.0,
+            96.0,
+            TEAM_COLOURS[team as usize],
+        ),
+        Sprite::Menu(page, item) => draw_menu(page, item),
+    }
+}
+
+fn draw_pitch() {
+    draw_rectangle(0.0, 0.0, LEVEL_W, LEVEL_H, GRASS);
+    let (left, right) = PITCH_BOUNDS_X;
+    let (top, bottom) = PITCH_BOUNDS_Y;
+    draw_rectangle_lines(left, top, right - left, bottom - top, 4.0, LINES);
+    draw_line(left, HALF_LEVEL_H, right, HALF_LEVEL_H, 2.0, LINES);
+    draw_circle_lines(HALF_LEVEL_W, HALF_LEVEL_H, CENTRE_CIRCLE_RADIUS, 2.0, LINES);
+    for (y, dir) in [(top, 1.0), (bottom, -1.0)] {
+        let depth = 160.0 * dir;
+        draw_rectangle_lines(
+            HALF_LEVEL_W - 200.0,
+            y.min(y + depth),
+            400.0,
+            depth.abs(),
+            2.0,
+            LINES,
+        );
+    }
+}
+
+fn draw_menu(page: u8, selected: u8) {
+    clear_background(GRASS);
+    draw_centred("SUBSTITUTE SOCCER", 120.0, 64.0, WHITE);
+    let items: &[(u8, &str)] = if page == 0 {
+        &[(1, "1 PLAYER"), (2, "2 PLAYERS")]
+    } else {
+        &[(0, "EASY"), (1, "MEDIUM"), (2, "HARD")]
+    };
+    for (i, (item, label)) in items.iter().enumerate() {
+        let colour = if *item == selected { YELLOW } else { WHITE };
+        draw_centred(label, 240.0 + 50.0 * i as f32, 40.0, colour);
+    }
+}
+
+fn draw_centred(text: &str, y: f32, font_size: f32, colour: Color) {
+    let size = measure_text(text, None, font_size as u16, 1.0);
+    draw_text(text, (WIDTH - size.width) / 2.0, y, font_size, colour);
+}
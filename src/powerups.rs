@@ -0,0 +1,242 @@
+use crate::rewind::copy_component;
+use crate::{Position, Target, Team, Vector, PITCH_BOUNDS_X, PITCH_BOUNDS_Y};
+use euclid::vec2;
+use hecs::{Component, Entity, EntityBuilder, EntityRef, World};
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+// how long to wait between pickups appearing, in steps
+const SPAWN_FRAMES: (u32, u32) = (360, 600);
+// no more than this many pickups lie on the pitch at once
+const MAX_PICKUPS: usize = 2;
+// pickups don't appear closer than this to the edge of the pitch
+const SPAWN_MARGIN: f32 = 60.0;
+// a player this close to a pickup collects it
+const PICKUP_RADIUS: f32 = 20.0;
+// how long each effect lasts, in steps
+const SPEED_BOOST_FRAMES: u32 = 300;
+const SUPER_KICK_FRAMES: u32 = 300;
+const FREEZE_FRAMES: u32 = 120;
+// how much faster a boosted player runs, and how much harder a super kick strikes the ball
+const SPEED_BOOST: f32 = 1.5;
+const SUPER_KICK: f32 = 1.5;
+const ICON_RADIUS: f32 = 10.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PowerUp {
+    Speed,
+    Kick,
+    Freeze,
+}
+
+impl PowerUp {
+    fn random() -> Self {
+        match gen_range(0, 3) {
+            0 => Self::Speed,
+            1 => Self::Kick,
+            _ => Self::Freeze,
+        }
+    }
+
+    fn colour(self) -> Color {
+        match self {
+            Self::Speed => YELLOW,
+            Self::Kick => RED,
+            Self::Freeze => SKYBLUE,
+        }
+    }
+}
+
+// lying on the pitch waiting to be run over
+#[derive(Clone)]
+struct Pickup(PowerUp);
+
+// the effects a player can be under, each with the steps it has left to run
+#[derive(Clone)]
+struct SpeedBoost(u32);
+#[derive(Clone)]
+struct SuperKick(u32);
+#[derive(Clone)]
+struct Frozen(u32);
+
+// spawns pickups now and then during a match with the power-ups mutator on
+#[derive(Clone)]
+pub struct PowerUps {
+    spawn_timer: u32,
+}
+
+impl PowerUps {
+    pub fn new() -> Self {
+        Self {
+            spawn_timer: gen_range(SPAWN_FRAMES.0, SPAWN_FRAMES.1),
+        }
+    }
+
+    // called once per simulation step, before players move
+    pub fn update(&mut self, world: &mut World) {
+        count_down::<SpeedBoost>(world, |e| &mut e.0);
+        count_down::<SuperKick>(world, |e| &mut e.0);
+        count_down::<Frozen>(world, |e| &mut e.0);
+        self.spawn_timer = self.spawn_timer.saturating_sub(1);
+        if self.spawn_timer == 0 {
+            self.spawn_timer = gen_range(SPAWN_FRAMES.0, SPAWN_FRAMES.1);
+            if world.query::<&Pickup>().iter().count() < MAX_PICKUPS {
+                let pos = vec2(
+                    gen_range(
+                        PITCH_BOUNDS_X.0 + SPAWN_MARGIN,
+                        PITCH_BOUNDS_X.1 - SPAWN_MARGIN,
+                    ),
+                    gen_range(
+                        PITCH_BOUNDS_Y.0 + SPAWN_MARGIN,
+                        PITCH_BOUNDS_Y.1 - SPAWN_MARGIN,
+                    ),
+                );
+                world.spawn((Position(pos), Pickup(PowerUp::random())));
+            }
+        }
+        collect(world);
+        apply_effects(world);
+    }
+}
+
+// take a step off every effect of one kind, removing the ones that have run out
+fn count_down<T: Component>(world: &mut World, frames: impl Fn(&mut T) -> &mut u32) {
+    let mut expired = Vec::new();
+    for (id, effect) in &mut world.query::<&mut T>() {
+        let left = frames(effect);
+        *left = left.saturating_sub(1);
+        if *left == 0 {
+            expired.push(id);
+        }
+    }
+    for id in expired {
+        world.remove_one::<T>(id).unwrap();
+    }
+}
+
+// give each pickup a player is standing on to them, or in the case of a freeze, to the other team
+fn collect(world: &mut World) {
+    let mut collected: Vec<(Entity, Entity, PowerUp)> = Vec::new();
+    for (pickup_id, (pickup_pos, pickup)) in &mut world.query::<(&Position, &Pickup)>() {
+        let player = world
+            .query::<(&Position, &Team)>()
+            .iter()
+            .find(|(_, (pos, _))| (pos.0 - pickup_pos.0).length() <= PICKUP_RADIUS)
+            .map(|(id, _)| id);
+        if let Some(player_id) = player {
+            collected.push((pickup_id, player_id, pickup.0));
+        }
+    }
+    for (pickup_id, player_id, power_up) in collected {
+        world.despawn(pickup_id).unwrap();
+        match power_up {
+            PowerUp::Speed => world
+                .insert_one(player_id, SpeedBoost(SPEED_BOOST_FRAMES))
+                .unwrap(),
+            PowerUp::Kick => world
+                .insert_one(player_id, SuperKick(SUPER_KICK_FRAMES))
+                .unwrap(),
+            PowerUp::Freeze => {
+                let team = world.get::<Team>(player_id).unwrap().0;
+                let opponents: Vec<Entity> = world
+                    .query::<&Team>()
+                    .iter()
+                    .filter(|(_, t)| t.0 != team)
+                    .map(|(id, _)| id)
+                    .collect();
+                for id in opponents {
+                    world.insert_one(id, Frozen(FREEZE_FRAMES)).unwrap();
+                }
+            }
+        }
+    }
+}
+
+// boosted players run faster towards their targets, and frozen players stay where they are
+fn apply_effects(world: &mut World) {
+    for (_, (target, _)) in &mut world.query::<(&mut Target, &SpeedBoost)>() {
+        target.speed *= SPEED_BOOST;
+    }
+    for (_, (target, pos, _)) in &mut world.query::<(&mut Target, &Position, &Frozen)>() {
+        target.pos = pos.0;
+    }
+}
+
+pub fn frozen(world: &World, player: Entity) -> bool {
+    world.get::<Frozen>(player).is_ok()
+}
+
+// how much harder than normal the player kicks the ball
+pub fn kick_strength(world: &World, player: Entity) -> f32 {
+    if world.get::<SuperKick>(player).is_ok() {
+        SUPER_KICK
+    } else {
+        1.0
+    }
+}
+
+pub fn draw_icon(power_up: PowerUp, pos: Vector) {
+    draw_circle(pos.x, pos.y, ICON_RADIUS, power_up.colour());
+    draw_circle_lines(pos.x, pos.y, ICON_RADIUS, 2.0, WHITE);
+}
+
+// the pickups lying on the pitch and where they are
+pub fn pickups(world: &World, out: &mut Vec<(PowerUp, Vector)>) {
+    out.clear();
+    for (_, (pos, pickup)) in &mut world.query::<(&Position, &Pickup)>() {
+        out.push((pickup.0, pos.0));
+    }
+}
+
+// the most steps left on any effect of one kind held by a player on the team
+fn team_effect<T: Component>(world: &World, team: u8, frames: impl Fn(&T) -> u32) -> Option<u32> {
+    world
+        .query::<(&T, &Team)>()
+        .iter()
+        .filter(|(_, (_, t))| t.0 == team)
+        .map(|(_, (effect, _))| frames(effect))
+        .max()
+}
+
+// draw in screen coordinates: the effects each team is under, either side of the score, with a
+// bar under each showing how long it has left
+pub fn draw_hud(world: &World, centre_x: f32) {
+    for team in 0..=1 {
+        let effects = [
+            (
+                PowerUp::Speed,
+                team_effect::<SpeedBoost>(world, team, |e| e.0),
+                SPEED_BOOST_FRAMES,
+            ),
+            (
+                PowerUp::Kick,
+                team_effect::<SuperKick>(world, team, |e| e.0),
+                SUPER_KICK_FRAMES,
+            ),
+            (
+                PowerUp::Freeze,
+                team_effect::<Frozen>(world, team, |e| e.0),
+                FREEZE_FRAMES,
+            ),
+        ];
+        // team 1 is on the left, as on the scoreboard
+        let side = if team == 0 { 1.0 } else { -1.0 };
+        let mut x = centre_x + side * 200.0;
+        for (power_up, left, total) in effects {
+            if let Some(left) = left {
+                draw_icon(power_up, vec2(x, 20.0));
+                let width = ICON_RADIUS * 2.0 * left as f32 / total as f32;
+                draw_rectangle(x - ICON_RADIUS, 34.0, width, 3.0, WHITE);
+                x += side * (ICON_RADIUS * 2.0 + 6.0);
+            }
+        }
+    }
+}
+
+// for the rewind buffer, which needs to copy every component without being able to see these
+pub fn copy_components(from: &EntityRef, to: &mut EntityBuilder) {
+    copy_component::<Pickup>(from, to);
+    copy_component::<SpeedBoost>(from, to);
+    copy_component::<SuperKick>(from, to);
+    copy_component::<Frozen>(from, to);
+}
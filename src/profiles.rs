@@ -0,0 +1,206 @@
+use crate::i18n::Strings;
+use crate::lineup::SQUAD_SIZE;
+use crate::roster::{Roster, RosterPlayer};
+use crate::{data, storage};
+use macroquad::prelude::*;
+use std::convert::TryInto;
+
+const FILE_NAME: &str = "profiles.toml";
+pub const MAX_NAME_LENGTH: usize = 12;
+// everyone starts here, and a result moves both players' ratings by up to K_FACTOR
+const START_RATING: f32 = 1500.0;
+const K_FACTOR: f32 = 32.0;
+
+pub struct Profile {
+    pub name: String,
+    pub rating: f32,
+    pub won: u32,
+    pub lost: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    // the squad they've made in the squad editor, if they've been in it
+    pub roster: Option<Roster>,
+}
+
+impl Profile {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            rating: START_RATING,
+            won: 0,
+            lost: 0,
+            goals_for: 0,
+            goals_against: 0,
+            roster: None,
+        }
+    }
+
+    // count a finished match with the given goals for and against
+    fn record(&mut self, scored: u8, conceded: u8) {
+        if scored > conceded {
+            self.won += 1;
+        } else {
+            self.lost += 1;
+        }
+        self.goals_for += scored as u32;
+        self.goals_against += conceded as u32;
+    }
+}
+
+// the named players who've played on this computer, kept in the config directory with each
+// profile as a numbered section of a data file
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Self {
+            profiles: Vec::new(),
+        }
+    }
+
+    pub fn load() -> Self {
+        let mut profiles = Self::new();
+        let text = match storage::read(FILE_NAME) {
+            Some(text) => text,
+            None => return profiles,
+        };
+        let doc = match data::parse(&text) {
+            Ok(doc) => doc,
+            Err(e) => {
+                warn!("{}: {}", FILE_NAME, e);
+                return profiles;
+            }
+        };
+        let mut numbered: Vec<(u32, Profile)> = doc
+            .sections
+            .iter()
+            .filter_map(|(number, values)| {
+                let count = |key| values.get(key).and_then(|v| v.as_f32()).unwrap_or(0.0) as u32;
+                // a squad member that won't load loses the whole squad, rather than mixing it
+                // with the team's own
+                let players: Option<Vec<RosterPlayer>> = (1..=SQUAD_SIZE)
+                    .map(|n| RosterPlayer::from_value(values.get(&format!("squad{}", n))?))
+                    .collect();
+                let profile = Profile {
+                    name: values.get("name")?.as_str()?.to_owned(),
+                    rating: values.get("rating")?.as_f32()?,
+                    won: count("won"),
+                    lost: count("lost"),
+                    goals_for: count("goals_for"),
+                    goals_against: count("goals_against"),
+                    roster: players.and_then(|players| {
+                        Some(Roster {
+                            players: players.try_into().ok()?,
+                        })
+                    }),
+                };
+                Some((number.parse().ok()?, profile))
+            })
+            .collect();
+        numbered.sort_by_key(|(n, _)| *n);
+        profiles.profiles = numbered.into_iter().map(|(_, p)| p).collect();
+        profiles
+    }
+
+    fn save(&self) {
+        let mut text = String::new();
+        for (i, p) in self.profiles.iter().enumerate() {
+            text.push_str(&format!(
+                "[{}]\nname = \"{}\"\nrating = {:.1}\nwon = {}\nlost = {}\ngoals_for = {}\ngoals_against = {}\n",
+                i + 1,
+                p.name,
+                p.rating,
+                p.won,
+                p.lost,
+                p.goals_for,
+                p.goals_against
+            ));
+            if let Some(roster) = &p.roster {
+                for (n, player) in roster.players.iter().enumerate() {
+                    text.push_str(&format!("squad{} = {}\n", n + 1, player.to_value()));
+                }
+            }
+            text.push('\n');
+        }
+        if let Err(e) = storage::write(FILE_NAME, &text) {
+            warn!("couldn't save profiles: {}", e);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn get(&self, index: usize) -> &Profile {
+        &self.profiles[index]
+    }
+
+    // the squad a profile takes to the given team, or the team's own for a team without a profile
+    pub fn roster(&self, index: Option<usize>, team: usize) -> Roster {
+        index
+            .and_then(|i| self.profiles[i].roster.clone())
+            .unwrap_or_else(|| Roster::default_for(team))
+    }
+
+    pub fn set_roster(&mut self, index: usize, roster: Roster) {
+        self.profiles[index].roster = Some(roster);
+        self.save();
+    }
+
+    // add a profile and return its index, or None if the name is empty or already taken
+    pub fn add(&mut self, name: &str) -> Option<usize> {
+        if name.is_empty() || self.profiles.iter().any(|p| p.name == name) {
+            return None;
+        }
+        self.profiles.push(Profile::new(name.to_owned()));
+        self.save();
+        Some(self.profiles.len() - 1)
+    }
+
+    // count a finished match for whichever teams had a profile chosen, with the score in team
+    // order; the ratings only change when two different profiles played each other
+    pub fn record(&mut self, players: [Option<usize>; 2], score: (u8, u8)) {
+        if players == [None, None] {
+            return;
+        }
+        if let [Some(a), Some(b)] = players {
+            if a != b {
+                let expected = 1.0
+                    / (1.0
+                        + 10f32.powf((self.profiles[b].rating - self.profiles[a].rating) / 400.0));
+                let actual = if score.0 > score.1 { 1.0 } else { 0.0 };
+                let change = K_FACTOR * (actual - expected);
+                self.profiles[a].rating += change;
+                self.profiles[b].rating -= change;
+            }
+        }
+        if let Some(a) = players[0] {
+            self.profiles[a].record(score.0, score.1);
+        }
+        if let Some(b) = players[1] {
+            self.profiles[b].record(score.1, score.0);
+        }
+        self.save();
+    }
+
+    // draw in screen coordinates, a line for each chosen profile starting with the baseline at top
+    pub fn draw_stats(&self, players: [Option<usize>; 2], strings: &Strings, top: f32) {
+        for (i, p) in players.iter().flatten().enumerate() {
+            let p = &self.profiles[*p];
+            let text = strings.format(
+                "profiles.stats",
+                &[
+                    &p.name,
+                    &p.rating.round().to_string(),
+                    &p.won.to_string(),
+                    &p.lost.to_string(),
+                    &p.goals_for.to_string(),
+                    &p.goals_against.to_string(),
+                ],
+            );
+            draw_text(&text, 120.0, top + i as f32 * 22.0, 20.0, WHITE);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+// drawing the match in level coordinates; the renderer first copies what it needs out of the game
+// into a Frame and then draws only from that, so nothing it draws depends on how the game happens
+// to store things, and the same drawing serves a single camera, both halves of split screen or a
+// recorded frame
+//
+// the debug overlays that show what the AI thinks of the pitch are the exception: they ask the
+// game itself, since that's the whole point of them
+
+use crate::behaviour::Behaviour;
+use crate::camera::Camera;
+use crate::crowd::Crowd;
+use crate::lighting::{LightMaps, Lighting};
+use crate::movement::Movement;
+use crate::powerups::{self, PowerUp};
+use crate::textures::{Sprite, Textures};
+use crate::training::Training;
+use crate::{
+    accessibility, draw_text_outlined, Animation, Game, Lead, NetRipple, Overlay, Peer,
+    PlayerLabels, Position, RenderOptions, Squad, Target, Team, Vector, GOAL_BOUNDS_X,
+    HALF_LEVEL_H, HALF_LEVEL_W, LEVEL_H, LEVEL_W, RIPPLE_FRAMES,
+};
+use euclid::vec2;
+use macroquad::prelude::*;
+use std::fmt::Write;
+
+// the aim indicator is a row of dots the length of the longest pass
+const AIM_DOTS: u32 = 15;
+const AIM_DOT_SPACING: f32 = 20.0;
+const HEATMAP_CELL: f32 = 20.0;
+// a predicted ball path has a dot every this many steps
+const PATH_DOT_STEPS: usize = 3;
+// a net ripple is RIPPLE_RINGS arcs spreading RIPPLE_SPREAD from where the ball went in, each drawn
+// with RIPPLE_SEGMENTS lines
+const RIPPLE_RINGS: u32 = 3;
+const RIPPLE_SPREAD: f32 = 40.0;
+const RIPPLE_SEGMENTS: u32 = 12;
+
+// the surroundings of the pitch, which are just for show and don't affect play
+pub struct Stadium {
+    pub crowd: Crowd,
+    pub light_maps: LightMaps,
+}
+
+// a player as they'll be drawn
+#[derive(Clone)]
+pub struct PlayerView {
+    pub pos: Vector,
+    pub team: u8,
+    pub dir: u8,
+    // how far they've run, which picks the frame of their animation
+    pub distance: f32,
+    pub number: Option<u8>,
+    pub name: String,
+    // whether they have the ball or are a human team's active player
+    pub involved: bool,
+    pub target: Option<Vector>,
+    pub peer: Option<Vector>,
+    pub lead: Option<(Option<f32>, u8)>,
+}
+
+// a human team aiming with the mouse: the cursor, and the line the ball will take if they kick it
+#[derive(Copy, Clone)]
+pub struct Aim {
+    pub cursor: Vector,
+    pub line: Option<(Vector, Vector)>,
+}
+
+// everything drawn about one moment of the match
+#[derive(Clone)]
+pub struct Frame {
+    pub players: Vec<PlayerView>,
+    pub balls: Vec<Vector>,
+    pub ball_scale: f32,
+    pub pickups: Vec<(PowerUp, Vector)>,
+    pub training: Option<Training>,
+    pub lighting: Lighting,
+    // where each human team's active player is
+    pub arrows: [Option<Vector>; 2],
+    pub aims: [Option<Aim>; 2],
+    // where the player with the first ball is, and their team
+    pub owner: Option<(Vector, u8)>,
+    pub shoot_target: Option<(Vector, Vector)>,
+    pub net_ripple: Option<NetRipple>,
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+            balls: Vec::new(),
+            ball_scale: 1.0,
+            pickups: Vec::new(),
+            training: None,
+            lighting: Lighting::Day,
+            arrows: [None, None],
+            aims: [None, None],
+            owner: None,
+            shoot_target: None,
+            net_ripple: None,
+        }
+    }
+
+    // copy the game into this frame, reusing what was allocated for the last one
+    pub fn capture(&mut self, game: &Game) {
+        let mut n = 0;
+        for (id, (pos, team, anim, squad, target, peer, lead)) in &mut game.world.query::<(
+            &Position,
+            &Team,
+            &Animation,
+            Option<&Squad>,
+            Option<&Target>,
+            Option<&Peer>,
+            Option<&Lead>,
+        )>() {
+            let view = PlayerView {
+                pos: pos.0 + game.jostle(id),
+                team: team.0,
+                dir: anim.dir.0 as u8,
+                distance: anim.frame,
+                number: squad.map(|s| s.number),
+                name: String::new(),
+                involved: game.has_ball(id)
+                    || game
+                        .teams
+                        .iter()
+                        .any(|t| t.human() && t.active_player == Some(id)),
+                target: target.map(|t| t.pos),
+                peer: peer.and_then(|p| game.world.get::<Position>(p.0).ok().map(|p| p.0)),
+                lead: lead.and_then(|l| l.1.map(|index| (l.0, index))),
+            };
+            if n == self.players.len() {
+                self.players.push(view);
+            } else {
+                // keep the old name's buffer
+                let name = std::mem::take(&mut self.players[n].name);
+                self.players[n] = PlayerView { name, ..view };
+            }
+            let name = &mut self.players[n].name;
+            name.clear();
+            if let Some(squad) = squad {
+                name.push_str(&squad.name);
+            }
+            n += 1;
+        }
+        self.players.truncate(n);
+
+        self.balls.clear();
+        self.balls
+            .extend(game.balls.iter().filter_map(|b| game.ball_pos(*b).ok()));
+        self.ball_scale = game.mutators.ball_scale();
+        powerups::pickups(&game.world, &mut self.pickups);
+        self.training.clone_from(&game.training);
+        self.lighting = game.lighting;
+        self.arrows = [0, 1].map(|t| {
+            let team = &game.teams[t];
+            team.active_player
+                .filter(|_| team.human())
+                .and_then(|id| game.world.get::<Position>(id).ok().map(|p| p.0))
+        });
+        self.aims = [0, 1].map(|t| {
+            if !game.teams[t].controls.is_some_and(|c| c.mouse_aim) {
+                return None;
+            }
+            let owner = game.balls.iter().filter_map(|b| b.owner).find(|id| {
+                game.world
+                    .get::<Team>(*id)
+                    .is_ok_and(|team| team.0 as usize == t)
+            });
+            let line = owner.and_then(|id| {
+                let pos = game.world.get::<Position>(id).ok()?.0;
+                let facing = game.world.get::<Animation>(id).ok()?.dir;
+                Some((pos, game.aim_dir(t, pos, facing)))
+            });
+            Some(Aim {
+                cursor: game.cursor,
+                line,
+            })
+        });
+        self.owner = game.balls[0].owner.and_then(|id| {
+            let pos = game.world.get::<Position>(id).ok()?.0;
+            let team = game.world.get::<Team>(id).ok()?.0;
+            Some((pos, team))
+        });
+        self.shoot_target = game.debug_shoot_target;
+        self.net_ripple = game.net_ripple;
+    }
+}
+
+pub struct Renderer {
+    pub stadium: Stadium,
+    pub movement: Movement,
+    frame: Frame,
+    // scratch space, kept from one frame to the next so that drawing doesn't allocate: each
+    // sprite with where to draw it, the y coordinate to sort it by and how much to scale it
+    sprites: Vec<(Sprite, f32, f32, f32, f32)>,
+    label: String,
+    // the cost heatmap's cells, by row, and the same sorted
+    costs: Vec<f32>,
+    sorted_costs: Vec<f32>,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            stadium: Stadium {
+                crowd: Crowd::new(),
+                light_maps: LightMaps::new(),
+            },
+            movement: Movement::new(),
+            frame: Frame::new(),
+            sprites: Vec::new(),
+            label: String::new(),
+            costs: Vec::new(),
+            sorted_costs: Vec::new(),
+        }
+    }
+
+    // draw the game as camera sees it into target, whose camera is left as the current one
+    pub fn draw(
+        &mut self,
+        game: &Game,
+        textures: &Textures,
+        camera: &Camera,
+        target: RenderTarget,
+        options: &RenderOptions,
+    ) {
+        set_world_camera(camera, target);
+        clear_background(BLACK);
+        self.frame.capture(game);
+        self.draw_frame(textures, options);
+        self.draw_overlays(game, options);
+    }
+
+    // draw a frame that came from somewhere other than a game here, such as a broadcast (see
+    // broadcast.rs); there's no game to ask for the debug overlays
+    pub fn draw_recorded(
+        &mut self,
+        frame: &Frame,
+        textures: &Textures,
+        camera: &Camera,
+        target: RenderTarget,
+        options: &RenderOptions,
+    ) {
+        set_world_camera(camera, target);
+        clear_background(BLACK);
+        self.frame.clone_from(frame);
+        self.draw_frame(textures, options);
+    }
+
+    fn draw_frame(&mut self, textures: &Textures, options: &RenderOptions) {
+        let frame = &self.frame;
+        textures.draw(Sprite::Pitch, 0.0, 0.0);
+        self.stadium.crowd.draw();
+
+        let sprites = &mut self.sprites;
+        sprites.clear();
+
+        for player in &frame.players {
+            let (pos, dir) = (player.pos, player.dir);
+            let anim_frame = textures.player_frame(player.distance);
+            sprites.push((
+                Sprite::Player {
+                    team: player.team,
+                    dir,
+                    frame: anim_frame,
+                },
+                pos.x,
+                pos.y,
+                pos.y,
+                1.0,
+            ));
+            textures.draw_anchored(
+                Sprite::PlayerShadow {
+                    dir,
+                    frame: anim_frame,
+                },
+                pos.x,
+                pos.y,
+            );
+            if options.accessibility.team_markers {
+                accessibility::draw_team_marker(player.team as usize, pos);
+            }
+        }
+
+        // draw balls
+        let scale = frame.ball_scale;
+        for ball_pos in &frame.balls {
+            sprites.push((Sprite::Ball, ball_pos.x, ball_pos.y, ball_pos.y, scale));
+            textures.draw_anchored_scaled(Sprite::BallShadow, ball_pos.x, ball_pos.y, scale);
+        }
+
+        // draw goals
+        sprites.push((Sprite::Goal(0), HALF_LEVEL_W, 0.0, 0.0, 1.0));
+        sprites.push((Sprite::Goal(1), HALF_LEVEL_W, LEVEL_H, LEVEL_H, 1.0));
+
+        for (power_up, pos) in &frame.pickups {
+            powerups::draw_icon(*power_up, *pos);
+        }
+
+        sprites.sort_unstable_by(|(_, _, _, y1, _), (_, _, _, y2, _)| {
+            y1.partial_cmp(y2).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        for (sprite, x, y, _, scale) in sprites.iter() {
+            textures.draw_anchored_scaled(*sprite, *x, *y, *scale);
+        }
+
+        if let Some(ripple) = frame.net_ripple {
+            draw_net_ripple(ripple);
+        }
+
+        if let Some(training) = &frame.training {
+            training.draw_target();
+        }
+
+        self.stadium.light_maps.draw(frame.lighting);
+
+        for t in 0..=1 {
+            if let Some(pos) = frame.arrows[t] {
+                textures.draw_anchored(Sprite::Arrow(t as u8), pos.x, pos.y);
+                if options.accessibility.team_markers {
+                    accessibility::draw_arrow_marker(t, pos);
+                }
+            }
+            if let Some(aim) = frame.aims[t] {
+                draw_aim(aim);
+            }
+        }
+
+        // show squad numbers and names, either for everyone or just the players involved in play
+        if options.player_labels != PlayerLabels::Off {
+            for player in &frame.players {
+                let number = match player.number {
+                    Some(number) => number,
+                    None => continue,
+                };
+                if options.player_labels == PlayerLabels::All || player.involved {
+                    let label = &mut self.label;
+                    label.clear();
+                    write!(label, "{} {}", number, player.name).unwrap();
+                    let size = measure_text(label, None, 14, 1.0);
+                    draw_text_outlined(
+                        label,
+                        player.pos.x - size.width / 2.,
+                        player.pos.y - 50.,
+                        14.,
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_overlays(&mut self, game: &Game, options: &RenderOptions) {
+        let owner_team = self.frame.owner.map(|(_, team)| team);
+        // under everything else so the lines stay visible
+        if let (true, Some(team)) = (options.shows(Overlay::CostHeatmap), owner_team) {
+            self.draw_cost_heatmap(game, team);
+        }
+        let Self {
+            frame,
+            movement,
+            label,
+            ..
+        } = self;
+        if options.shows(Overlay::PositionHeatmap) {
+            movement.draw_heatmap();
+        }
+        if options.shows(Overlay::Trails) {
+            movement.draw_trails();
+        }
+        if options.shows(Overlay::BallPath) {
+            draw_ball_paths(game);
+        }
+        // show player movement targets
+        if options.shows(Overlay::Targets) {
+            for player in &frame.players {
+                if let Some(target) = player.target {
+                    debug_draw_line(player.pos, target, 1.0, RED);
+                }
+            }
+        }
+        // show shoot target
+        if options.shows(Overlay::ShootTarget) {
+            if let Some((v1, v2)) = frame.shoot_target {
+                debug_draw_line(v1, v2, 2.0, MAGENTA);
+            }
+        }
+        // show peers
+        if options.shows(Overlay::Peers) {
+            for player in &frame.players {
+                if let Some(peer_pos) = player.peer {
+                    debug_draw_line(player.pos, peer_pos, 1.0, BLUE);
+                }
+            }
+        }
+        // show leads
+        if options.shows(Overlay::Leads) {
+            for player in &frame.players {
+                if let Some((dist, index)) = player.lead {
+                    let pos = player.pos;
+                    label.clear();
+                    write!(label, "LEAD {}", index).unwrap();
+                    draw_text(label, pos.x + 15.0, pos.y, 24.0, BLACK);
+                    if let (Some(_), Some((v2, _))) = (dist, frame.owner) {
+                        debug_draw_line(pos, v2, 2.0, BLACK)
+                    }
+                }
+            }
+        }
+        if let (true, Some(ball_owner_team)) = (options.shows(Overlay::Costs), owner_team) {
+            for x in (0..(LEVEL_W as i32)).step_by(60) {
+                for y in (0..(LEVEL_H as i32)).step_by(26) {
+                    let v = vec2(x as f32, y as f32);
+                    let c = game.cost(v, ball_owner_team, 0.0);
+                    label.clear();
+                    write!(label, "{:.0}", c).unwrap();
+                    draw_text(label, v.x, v.y, 24.0, GRAY);
+                }
+            }
+        }
+    }
+
+    // shade the pitch by how much the team with the ball would like to dribble there, from blue
+    // where it's cheapest to red where it's dearest
+    fn draw_cost_heatmap(&mut self, game: &Game, team: u8) {
+        let cols = (LEVEL_W / HEATMAP_CELL) as usize;
+        let rows = (LEVEL_H / HEATMAP_CELL) as usize;
+        let costs = &mut self.costs;
+        costs.clear();
+        costs.extend((0..rows * cols).map(|i| {
+            let x = ((i % cols) as f32 + 0.5) * HEATMAP_CELL;
+            let y = ((i / cols) as f32 + 0.5) * HEATMAP_CELL;
+            game.cost(vec2(x, y), team, 0.0)
+        }));
+        // the cost shoots up right next to players, so spread the colours over the range most of
+        // the pitch falls in rather than from the very lowest to the very highest
+        let sorted = &mut self.sorted_costs;
+        sorted.clone_from(costs);
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let low = sorted[sorted.len() / 20];
+        let high = sorted[sorted.len() * 19 / 20];
+        for (i, cost) in costs.iter().enumerate() {
+            let t = ((cost - low) / (high - low).max(1.0)).clamp(0.0, 1.0);
+            draw_rectangle(
+                (i % cols) as f32 * HEATMAP_CELL,
+                (i / cols) as f32 * HEATMAP_CELL,
+                HEATMAP_CELL,
+                HEATMAP_CELL,
+                Color::new(t, 0.0, 1.0 - t, 0.35),
+            );
+        }
+    }
+}
+
+// level coordinates, as camera sees them, drawn into target
+fn set_world_camera(camera: &Camera, target: RenderTarget) {
+    set_camera(&Camera2D {
+        render_target: Some(target),
+        ..camera.world_camera()
+    });
+}
+
+// arcs spreading out through the net from where the ball hit it and fading as they go, kept
+// between the posts
+fn draw_net_ripple(ripple: NetRipple) {
+    let progress = 1.0 - ripple.steps_left as f32 / RIPPLE_FRAMES as f32;
+    // the net is behind the goal line, away from the middle of the pitch
+    let back = if ripple.pos.y < HALF_LEVEL_H {
+        -1.0
+    } else {
+        1.0
+    };
+    let alpha = 0.8 * ripple.strength * (1.0 - progress);
+    for ring in 0..RIPPLE_RINGS {
+        let radius =
+            (progress + ring as f32 / RIPPLE_RINGS as f32) * RIPPLE_SPREAD * ripple.strength;
+        let point = |i: u32| {
+            let angle = std::f32::consts::PI * i as f32 / RIPPLE_SEGMENTS as f32;
+            let x = (ripple.pos.x + radius * angle.cos()).clamp(GOAL_BOUNDS_X.0, GOAL_BOUNDS_X.1);
+            vec2(x, ripple.pos.y + back * radius * angle.sin())
+        };
+        for i in 0..RIPPLE_SEGMENTS {
+            let (from, to): (Vector, Vector) = (point(i), point(i + 1));
+            draw_line(
+                from.x,
+                from.y,
+                to.x,
+                to.y,
+                1.5,
+                Color::new(1.0, 1.0, 1.0, alpha),
+            );
+        }
+    }
+}
+
+// where each loose ball is going, as a dotted line, and where each player chasing one expects to
+// get to it
+fn draw_ball_paths(game: &Game) {
+    for ball in &game.balls {
+        for pos in game
+            .predict_ball_path(*ball)
+            .unwrap_or_default()
+            .iter()
+            .step_by(PATH_DOT_STEPS)
+        {
+            draw_circle(pos.x, pos.y, 2.0, WHITE);
+        }
+    }
+    for (_, (pos, target)) in game.world.query::<(&Position, &Target)>().iter() {
+        if target.behaviour == Behaviour::InterceptLoose {
+            debug_draw_line(pos.0, target.pos, 1.0, YELLOW);
+            draw_circle_lines(target.pos.x, target.pos.y, 6.0, 2.0, YELLOW);
+        }
+    }
+}
+
+// crosshair under the mouse cursor, with a dotted line showing where the ball will go if we have it
+fn draw_aim(aim: Aim) {
+    let cursor = aim.cursor;
+    let colour = Color::new(1.0, 1.0, 1.0, 0.8);
+    draw_circle_lines(cursor.x, cursor.y, 8.0, 2.0, colour);
+    draw_line(
+        cursor.x - 12.0,
+        cursor.y,
+        cursor.x + 12.0,
+        cursor.y,
+        1.0,
+        colour,
+    );
+    draw_line(
+        cursor.x,
+        cursor.y - 12.0,
+        cursor.x,
+        cursor.y + 12.0,
+        1.0,
+        colour,
+    );
+    if let Some((owner_pos, dir)) = aim.line {
+        for i in 1..=AIM_DOTS {
+            let p = owner_pos + dir * (i as f32 * AIM_DOT_SPACING);
+            draw_circle(p.x, p.y, 2.0, colour);
+        }
+    }
+}
+
+fn debug_draw_line(v1: Vector, v2: Vector, t: f32, c: Color) {
+    draw_line(v1.x, v1.y, v2.x, v2.y, t, c);
+}
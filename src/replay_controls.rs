@@ -0,0 +1,124 @@
+// controls for watching a replay (see replays.rs): Space pauses, and while paused Right and Left
+// go a step forward or back; otherwise Right and Left skip SKIP_STEPS either way; Up and Down
+// change the speed; C frees the camera from the ball, to be moved with WASD and zoomed with Q and
+// E; and clicking on the timeline along the bottom of the screen seeks to that point in the match
+
+use crate::camera::Camera;
+use crate::i18n::Strings;
+use crate::menu::Pointer;
+use crate::replays::Playback;
+use crate::{draw_text_centred, Game, HALF_WINDOW_WIDTH, HEIGHT, TIMESTEP};
+use macroquad::prelude::*;
+
+const SPEEDS: [f32; 3] = [0.5, 1.0, 2.0];
+const NORMAL_SPEED: usize = 1;
+// five seconds of steps
+const SKIP_STEPS: usize = 300;
+const TIMELINE: Rect = Rect {
+    x: 100.0,
+    y: HEIGHT - 40.0,
+    w: 600.0,
+    h: 10.0,
+};
+
+pub struct ReplayControls {
+    paused: bool,
+    speed: usize,
+    // the part of a step left over at speeds that don't go evenly into frames
+    carry: f32,
+    // a copy of the match camera that's no longer kept up to date by the match, while C is on
+    free_camera: Option<Camera>,
+}
+
+impl ReplayControls {
+    pub fn new() -> Self {
+        Self {
+            paused: false,
+            speed: NORMAL_SPEED,
+            carry: 0.0,
+            free_camera: None,
+        }
+    }
+
+    // called once per frame with the number of steps the frame would take at normal speed; seeks,
+    // if asked to, and returns how many steps of the replay to play
+    pub fn update(
+        &mut self,
+        playback: &mut Playback,
+        game: &mut Game,
+        pointer: &Pointer,
+        steps: u32,
+    ) -> u32 {
+        if is_key_pressed(KeyCode::Space) {
+            self.paused = !self.paused;
+            self.carry = 0.0;
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.speed = self.speed.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::C) {
+            self.free_camera = match self.free_camera {
+                Some(_) => None,
+                None => Some(game.camera.clone()),
+            };
+        }
+        if let Some(camera) = &mut self.free_camera {
+            camera.fly();
+        }
+        let step = playback.step();
+        let seek = if pointer.clicked_in(TIMELINE) {
+            let along = (pointer.position().x - TIMELINE.x) / TIMELINE.w;
+            Some((along * playback.last_step() as f32) as usize)
+        } else if is_key_pressed(KeyCode::Left) {
+            Some(step.saturating_sub(if self.paused { 1 } else { SKIP_STEPS }))
+        } else if is_key_pressed(KeyCode::Right) && !self.paused {
+            Some(step + SKIP_STEPS)
+        } else {
+            None
+        };
+        if let Some(target) = seek {
+            playback.seek(game, target);
+            return 0;
+        }
+        if self.paused {
+            return is_key_pressed(KeyCode::Right) as u32;
+        }
+        self.carry += steps as f32 * SPEEDS[self.speed];
+        let played = self.carry as u32;
+        self.carry -= played as f32;
+        played
+    }
+
+    // the camera to draw the match through instead of its own, if it's been freed
+    pub fn camera(&self) -> Option<&Camera> {
+        self.free_camera.as_ref()
+    }
+
+    pub fn draw(&self, playback: &Playback, strings: &Strings) {
+        let last = playback.last_step().max(1);
+        let along = playback.step().min(last) as f32 / last as f32;
+        let Rect { x, y, w, h } = TIMELINE;
+        draw_rectangle(x, y, w, h, Color::new(0.0, 0.0, 0.0, 0.5));
+        draw_rectangle(x, y, w * along, h, YELLOW);
+        draw_rectangle_lines(x, y, w, h, 1.0, WHITE);
+        let state = if self.paused {
+            strings.get("hud.paused").to_owned()
+        } else {
+            format!("{}X", SPEEDS[self.speed])
+        };
+        let text = strings.format(
+            "hud.replay_time",
+            &[&clock(playback.step()), &clock(last), &state],
+        );
+        draw_text_centred(&text, HALF_WINDOW_WIDTH, y - 6.0, 16.0);
+    }
+}
+
+// a step of the match as minutes and seconds
+fn clock(step: usize) -> String {
+    let seconds = (step as f32 * TIMESTEP) as usize;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
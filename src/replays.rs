@@ -0,0 +1,628 @@
+// replays: every match played from the title menu is started from a seed of its own, and saved at
+// full time as that seed, the settings and squads it was played with and what the human teams asked
+// for at each step, which is enough to play it again exactly; the last REPLAY_SLOTS are kept in the
+// config directory (see storage.rs), the oldest overwritten by the newest, and listed on the
+// replays page of the options menu
+//
+//   --replay PATH   play a replay file from anywhere, such as one someone else has shared; with
+//                   --headless, print the result instead
+//
+// a replay is a data file (see data.rs) with everything in a [replay] section: version, which has
+// to be FORMAT_VERSION for the file to play; number, counting every replay saved; the seed (as a
+// string, since it doesn't fit in a data file's numbers), date, mode and score as in the match
+// history; the settings, named as in the language files; each team's squad as in the profiles
+// file; substitutions and tactics changes made from the pause menu, as "step sub team position
+// bench" and "step tactics team pressing line"; and the inputs, as "count x y presses x y presses"
+// for each run of identical steps (see input.rs), followed by the world position of the cursor if
+// anyone was aiming with the mouse
+//
+// the console and rewinding change the match in ways that aren't recorded, so a match that used
+// either isn't saved
+//
+// a playback keeps the match as it was every KEYFRAME_STEPS steps, so that it can seek to any step
+// by going back to the keyframe before it and playing on from there (see replay_controls.rs);
+// macroquad's random numbers aren't part of a snapshot, so both the recording and the playback
+// reseed them from the match's seed at each of those steps for the match to go the same way again
+
+use crate::data::{self, Value};
+use crate::history::MatchResult;
+use crate::i18n::Strings;
+use crate::input::{self, FrameInput};
+use crate::lighting::Lighting;
+use crate::lineup::{Lineup, SQUAD_SIZE};
+use crate::roster::{Roster, RosterPlayer};
+use crate::storage;
+use crate::tactics::Tactics;
+use crate::weather::Weather;
+use crate::{
+    new_match_with_squads, Assist, DifficultyLevel, Game, NumPlayers, Settings, SwitchMode, Vector,
+    DIFFICULTY_OPTIONS, PLAYER_START_POS,
+};
+use euclid::vec2;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt::Write;
+
+pub const REPLAY_SLOTS: usize = 8;
+// bumped whenever a change to the format, or to the match itself, would stop older replays
+// playing back the way they went
+const FORMAT_VERSION: f32 = 2.0;
+// five seconds of steps
+const KEYFRAME_STEPS: usize = 300;
+
+const WEATHERS: [Weather; 3] = [Weather::Clear, Weather::Rain, Weather::Snow];
+const LIGHTINGS: [Lighting; 3] = [Lighting::Day, Lighting::Evening, Lighting::Night];
+const SWITCH_MODES: [SwitchMode; 2] = [SwitchMode::Nearest, SwitchMode::Cycle];
+const ASSISTS: [Assist; 2] = [Assist::AutoPosition, Assist::Pressure];
+
+fn file_name(slot: usize) -> String {
+    format!("replay{}.toml", slot + 1)
+}
+
+// call just before each step, from the recording and the playback alike, and from a network match
+// (see net.rs)
+pub fn reseed(seed: u64, step: usize) {
+    if step.is_multiple_of(KEYFRAME_STEPS) {
+        macroquad::rand::srand(seed.wrapping_add(step as u64));
+    }
+}
+
+// something done to a human team from the pause menu between two steps
+#[derive(Copy, Clone)]
+enum Change {
+    Substitution {
+        team: usize,
+        position: usize,
+        bench: usize,
+    },
+    Tactics {
+        team: usize,
+        tactics: Tactics,
+    },
+}
+
+// both teams' input for one step
+#[derive(Copy, Clone)]
+struct Step {
+    inputs: [FrameInput; 2],
+    cursor: Option<Vector>,
+}
+
+// the match being played, as it will be written to its replay
+pub struct MatchRecord {
+    seed: u64,
+    // the settings and squads, already in the form they're saved in
+    header: String,
+    // whether anyone's aiming with the mouse, so the cursor needs recording too
+    aiming: bool,
+    // each run of identical steps, as the number of steps and the line for one of them
+    runs: Vec<(u32, String)>,
+    changes: Vec<String>,
+    steps: u32,
+    // the human teams as they were at the last step, to spot changes from the pause menu
+    lineups: [Lineup; 2],
+    tactics: [Tactics; 2],
+}
+
+impl MatchRecord {
+    // call once the match has been set up from seed and settings
+    pub fn new(seed: u64, settings: &Settings, game: &Game) -> Self {
+        Self {
+            seed,
+            header: match_header(settings, game),
+            aiming: game
+                .teams
+                .iter()
+                .any(|team| team.controls.is_some_and(|c| c.mouse_aim)),
+            runs: Vec::new(),
+            changes: Vec::new(),
+            steps: 0,
+            lineups: game.lineups.clone(),
+            tactics: [0, 1].map(|t| game.teams[t].tactics),
+        }
+    }
+
+    // call just before each step, once the input for it has been applied
+    pub fn record(&mut self, game: &Game) {
+        for t in (0..=1).filter(|t| game.teams[*t].human()) {
+            // work out which bench player went on where by making the same substitutions again
+            let mut lineup = self.lineups[t].clone();
+            for position in 0..PLAYER_START_POS.len() {
+                let member = game.lineups[t].member(position);
+                if lineup.member(position) == member {
+                    continue;
+                }
+                if let Some(bench) = lineup.bench().iter().position(|m| *m == member) {
+                    lineup.substitute(position, bench);
+                    self.changes
+                        .push(format!("{} sub {} {} {}", self.steps, t, position, bench));
+                }
+            }
+            let tactics = game.teams[t].tactics;
+            if tactics != self.tactics[t] {
+                self.changes.push(format!(
+                    "{} tactics {} {} {}",
+                    self.steps, t, tactics.pressing, tactics.line
+                ));
+            }
+        }
+        self.lineups = game.lineups.clone();
+        self.tactics = [0, 1].map(|t| game.teams[t].tactics);
+        let mut line = String::new();
+        for t in 0..=1 {
+            if t > 0 {
+                line.push(' ');
+            }
+            input::write_input(&mut line, game.input(t));
+        }
+        if self.aiming {
+            write!(line, " {} {}", game.cursor.x, game.cursor.y).unwrap();
+        }
+        match self.runs.last_mut() {
+            Some((count, last)) if *last == line => *count += 1,
+            _ => self.runs.push((1, line)),
+        }
+        reseed(self.seed, self.steps as usize);
+        self.steps += 1;
+    }
+
+    // the whole replay file, for a match that ended with result
+    pub fn to_text(&self, result: &MatchResult, number: u32) -> String {
+        self.text(number, &result.date, result.mode, result.score)
+    }
+
+    // the match as far as it's gone, in the same form, for a network match to bring someone who's
+    // rejoined it up to date (see net.rs); it hasn't ended, so it has no number, date or score yet
+    pub fn so_far(&self) -> String {
+        self.text(0, "", "", (0, 0))
+    }
+
+    fn text(&self, number: u32, date: &str, mode: &str, score: (u8, u8)) -> String {
+        format!(
+            "# a replay saved by the game (see replays.rs)\n[replay]\nversion = {}\nnumber = {}\n\
+             seed = \"{}\"\ndate = \"{}\"\nmode = \"{}\"\nscore = [{}, {}]\n{}changes = [{}]\n\
+             inputs = [{}]\n",
+            FORMAT_VERSION,
+            number,
+            self.seed,
+            date,
+            mode,
+            score.0,
+            score.1,
+            self.header,
+            quoted(self.changes.iter().cloned()),
+            quoted(
+                self.runs
+                    .iter()
+                    .map(|(count, line)| format!("{} {}", count, line))
+            )
+        )
+    }
+}
+
+// the settings and squads a match was set up with, as they're written in a replay, and as the host
+// of a network match sends them to the other player (see net.rs)
+pub fn match_header(settings: &Settings, game: &Game) -> String {
+    let mut header = settings_text(settings);
+    for (t, roster) in game.rosters.iter().enumerate() {
+        for (n, player) in roster.players.iter().enumerate() {
+            writeln!(
+                header,
+                "team{}_squad{} = {}",
+                t + 1,
+                n + 1,
+                player.to_value()
+            )
+            .unwrap();
+        }
+    }
+    header
+}
+
+// the settings and squads written by match_header
+pub fn read_match_header(
+    values: &HashMap<String, Value>,
+) -> Result<(Settings, [Roster; 2]), String> {
+    let settings = settings_from(values).ok_or("settings missing or not recognised")?;
+    let roster = |t: usize| {
+        let players: Option<Vec<RosterPlayer>> = (1..=SQUAD_SIZE)
+            .map(|n| RosterPlayer::from_value(values.get(&format!("team{}_squad{}", t + 1, n))?))
+            .collect();
+        Some(Roster {
+            players: players?.try_into().ok()?,
+        })
+    };
+    match (roster(0), roster(1)) {
+        (Some(r1), Some(r2)) => Ok((settings, [r1, r2])),
+        _ => Err("squads missing or not recognised".to_owned()),
+    }
+}
+
+// the settings as they're written in a replay, and as a network lobby shows them (see lobby.rs)
+pub fn settings_text(settings: &Settings) -> String {
+    let mutators = &settings.mutators;
+    let [h1, h2] = settings.handicaps;
+    let [t1, t2] = settings.tactics;
+    format!(
+        "players = {}\ndifficulty = \"{}\"\nsplit_screen = {}\nweather = \"{}\"\n\
+         lighting = \"{}\"\nswitch_mode = \"{}\"\nauto_switch = {}\nassist = \"{}\"\n\
+         mouse_aim = {}\ngoals_to_win = {}\nmulti_ball = {}\ngiant_ball = {}\npower_ups = {}\n\
+         speed = {}\nhead_start = [{}, {}]\nteam_speed = [{}, {}]\npressing = [{}, {}]\n\
+         line = [{}, {}]\n",
+        settings.num_players as u8,
+        settings.difficulty_level.key(),
+        settings.split_screen,
+        settings.weather.key(),
+        settings.lighting.map_or("random", Lighting::key),
+        settings.switch_mode.key(),
+        settings.auto_switch,
+        settings.assist.key(),
+        settings.mouse_aim,
+        settings.goals_to_win,
+        mutators.multi_ball,
+        mutators.giant_ball,
+        mutators.power_ups,
+        mutators.speed,
+        h1.head_start,
+        h2.head_start,
+        h1.speed,
+        h2.speed,
+        t1.pressing,
+        t2.pressing,
+        t1.line,
+        t2.line
+    )
+}
+
+// the option whose key is name
+fn pick<T: Copy>(options: &[T], name: &str, key: fn(T) -> &'static str) -> Option<T> {
+    options.iter().copied().find(|option| key(*option) == name)
+}
+
+// the settings written by settings_text, or None if any are missing or don't make sense
+pub fn settings_from(values: &HashMap<String, Value>) -> Option<Settings> {
+    let number = |key: &str| values.get(key)?.as_f32();
+    let text = |key: &str| values.get(key)?.as_str();
+    let flag = |key: &str| match values.get(key)? {
+        Value::Bool(on) => Some(*on),
+        _ => None,
+    };
+    let pair = |key: &str| values.get(key)?.as_pair();
+    let mut settings = Settings::new();
+    settings.num_players = match number("players")? as u8 {
+        2 => NumPlayers::Two,
+        _ => NumPlayers::One,
+    };
+    settings.difficulty_level = pick(
+        &DIFFICULTY_OPTIONS,
+        text("difficulty")?,
+        DifficultyLevel::key,
+    )?;
+    settings.split_screen = flag("split_screen")?;
+    settings.weather = pick(&WEATHERS, text("weather")?, Weather::key)?;
+    settings.lighting = match text("lighting")? {
+        "random" => None,
+        name => Some(pick(&LIGHTINGS, name, Lighting::key)?),
+    };
+    settings.switch_mode = pick(&SWITCH_MODES, text("switch_mode")?, SwitchMode::key)?;
+    settings.auto_switch = flag("auto_switch")?;
+    settings.assist = pick(&ASSISTS, text("assist")?, Assist::key)?;
+    settings.mouse_aim = flag("mouse_aim")?;
+    settings.goals_to_win = number("goals_to_win")? as u8;
+    settings.mutators.multi_ball = flag("multi_ball")?;
+    settings.mutators.giant_ball = flag("giant_ball")?;
+    settings.mutators.power_ups = flag("power_ups")?;
+    settings.mutators.speed = number("speed")?;
+    let (head_start, speed) = (pair("head_start")?, pair("team_speed")?);
+    let (pressing, line) = (pair("pressing")?, pair("line")?);
+    for (t, handicap) in settings.handicaps.iter_mut().enumerate() {
+        let pick = |(first, second): (f32, f32)| if t == 0 { first } else { second };
+        handicap.head_start = pick(head_start) as u8;
+        handicap.speed = pick(speed);
+        settings.tactics[t] = Tactics {
+            pressing: pick(pressing) as u8,
+            line: pick(line) as u8,
+        };
+    }
+    Some(settings)
+}
+
+fn parse_change(line: &str) -> Option<(u32, Change)> {
+    let fields: Vec<&str> = line.split(' ').collect();
+    let field = |i: usize| -> Option<usize> { fields.get(i)?.parse().ok() };
+    let team = field(2).filter(|t| *t <= 1)?;
+    let change = match *fields.get(1)? {
+        "sub" => Change::Substitution {
+            team,
+            position: field(3)?,
+            bench: field(4)?,
+        },
+        "tactics" => Change::Tactics {
+            team,
+            tactics: Tactics {
+                pressing: field(3)? as u8,
+                line: field(4)? as u8,
+            },
+        },
+        _ => return None,
+    };
+    Some((fields[0].parse().ok()?, change))
+}
+
+// lines as the items of a list in a data file
+fn quoted(lines: impl Iterator<Item = String>) -> String {
+    lines
+        .map(|line| format!("\"{}\"", line))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// a run of identical steps, and how many of them there are
+fn parse_run(line: &str) -> Option<(usize, Step)> {
+    let fields: Vec<&str> = line.split(' ').collect();
+    if fields.len() < 7 {
+        return None;
+    }
+    let cursor = match fields.get(7..9) {
+        Some([x, y]) => Some(vec2(x.parse().ok()?, y.parse().ok()?)),
+        _ => None,
+    };
+    let step = Step {
+        inputs: [
+            input::parse_input(&fields[1..4])?,
+            input::parse_input(&fields[4..7])?,
+        ],
+        cursor,
+    };
+    Some((fields[0].parse().ok()?, step))
+}
+
+// a saved match, ready to play again
+pub struct Playback {
+    seed: u64,
+    settings: Settings,
+    rosters: [Roster; 2],
+    steps: Vec<Step>,
+    changes: Vec<(u32, Change)>,
+    step: usize,
+    // the match just before every KEYFRAME_STEPS-th step, as far as it's been played
+    keyframes: Vec<Game>,
+}
+
+impl Playback {
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let doc = data::parse(text)?;
+        let values = doc.sections.get("replay").ok_or("no [replay] section")?;
+        let version = values.get("version").and_then(Value::as_f32);
+        if version != Some(FORMAT_VERSION) {
+            return Err("saved by a different version of the game".to_owned());
+        }
+        let seed = values
+            .get("seed")
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok())
+            .ok_or("no seed")?;
+        let (settings, rosters) = read_match_header(values)?;
+        let list = |key: &str| match values.get(key) {
+            Some(Value::List(items)) => Ok(items.iter().filter_map(Value::as_str).collect()),
+            _ => Err(format!("no {}", key)),
+        };
+        let lines: Vec<&str> = list("changes")?;
+        let changes = lines
+            .iter()
+            .map(|line| parse_change(line))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("changes not recognised")?;
+        let mut steps = Vec::new();
+        let lines: Vec<&str> = list("inputs")?;
+        for line in lines {
+            let (count, step) = parse_run(line).ok_or("inputs not recognised")?;
+            steps.extend(std::iter::repeat_n(step, count));
+        }
+        Ok(Self {
+            seed,
+            settings,
+            rosters,
+            steps,
+            changes,
+            step: 0,
+            keyframes: Vec::new(),
+        })
+    }
+
+    // the match as it was at kickoff
+    pub fn new_game(&self) -> Game {
+        macroquad::rand::srand(self.seed);
+        new_match_with_squads(&self.settings, self.rosters.clone())
+    }
+
+    // the match as it was at the end of the recording, and a record of it to carry on from, for a
+    // network match bringing someone who's rejoined it up to date (see net.rs)
+    pub fn play_through(&mut self) -> (Game, MatchRecord) {
+        let mut game = self.new_game();
+        let mut record = MatchRecord::new(self.seed, &self.settings, &game);
+        while self.step < self.steps.len() {
+            self.apply(&mut game);
+            record.record(&game);
+            game.update();
+        }
+        (game, record)
+    }
+
+    // call just before each step to have the human teams do what they did at that step
+    pub fn apply(&mut self, game: &mut Game) {
+        if self.step == self.keyframes.len() * KEYFRAME_STEPS {
+            self.keyframes.push(game.snapshot());
+        }
+        for (_, change) in self
+            .changes
+            .iter()
+            .filter(|(step, _)| *step as usize == self.step)
+        {
+            match *change {
+                Change::Substitution {
+                    team,
+                    position,
+                    bench,
+                } => {
+                    if let Err(e) = game.substitute(team, position, bench) {
+                        game.recover(e);
+                    }
+                }
+                Change::Tactics { team, tactics } => game.teams[team].tactics = tactics,
+            }
+        }
+        // once the recording runs out, everyone stands still
+        let step = self.steps.get(self.step).copied().unwrap_or(Step {
+            inputs: [FrameInput::none(); 2],
+            cursor: None,
+        });
+        for (t, input) in step.inputs.iter().enumerate() {
+            if game.teams[t].human() {
+                game.apply_input(t, *input);
+            }
+        }
+        if let Some(cursor) = step.cursor {
+            game.cursor = cursor;
+        }
+        reseed(self.seed, self.step);
+        self.step += 1;
+    }
+
+    // when a step has been rewound
+    pub fn step_back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+
+    // the next step to be played
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    // the step at which the match ended, as far as playback can go without finishing it
+    pub fn last_step(&self) -> usize {
+        self.steps.len().saturating_sub(1)
+    }
+
+    // put the match just before the given step, playing on from the nearest keyframe before it or
+    // from where it is now if that's nearer
+    pub fn seek(&mut self, game: &mut Game, step: usize) {
+        let step = step.min(self.last_step());
+        let keyframe = (step / KEYFRAME_STEPS).min(self.keyframes.len().saturating_sub(1));
+        if self.step > step || self.step < keyframe * KEYFRAME_STEPS {
+            if let Some(snapshot) = self.keyframes.get(keyframe) {
+                *game = snapshot.snapshot();
+                self.step = keyframe * KEYFRAME_STEPS;
+            }
+        }
+        while self.step < step {
+            self.apply(game);
+            game.update();
+        }
+    }
+}
+
+// a replay file from anywhere, and the match it starts from
+pub fn open_file(path: &str) -> Option<(Game, Playback)> {
+    let playback = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Playback::from_text(&text));
+    match playback {
+        Ok(playback) => Some((playback.new_game(), playback)),
+        Err(e) => {
+            warn!("couldn't play replay {}: {}", path, e);
+            None
+        }
+    }
+}
+
+// what the replays page shows for a saved match
+struct Saved {
+    slot: usize,
+    number: u32,
+    date: String,
+    mode: String,
+    score: (u8, u8),
+}
+
+// the matches saved in the config directory, newest first
+pub struct Replays {
+    saved: Vec<Saved>,
+}
+
+impl Replays {
+    pub fn load() -> Self {
+        let mut saved: Vec<Saved> = (0..REPLAY_SLOTS)
+            .filter_map(|slot| {
+                let doc = data::parse(&storage::read(&file_name(slot))?).ok()?;
+                let values = doc.sections.get("replay")?;
+                let (home, away) = values.get("score")?.as_pair()?;
+                Some(Saved {
+                    slot,
+                    number: values.get("number")?.as_f32()? as u32,
+                    date: values.get("date")?.as_str()?.to_owned(),
+                    mode: values.get("mode")?.as_str()?.to_owned(),
+                    score: (home as u8, away as u8),
+                })
+            })
+            .collect();
+        saved.sort_by_key(|s| std::cmp::Reverse(s.number));
+        Self { saved }
+    }
+
+    // write a finished match over the oldest replay, or into an empty slot
+    pub fn save(&mut self, record: &MatchRecord, result: &MatchResult) {
+        let number = self.saved.first().map_or(0, |s| s.number) + 1;
+        let slot = match self.saved.len() {
+            n if n < REPLAY_SLOTS => (0..REPLAY_SLOTS)
+                .find(|slot| self.saved.iter().all(|s| s.slot != *slot))
+                .unwrap_or(0),
+            _ => self.saved.last().map_or(0, |s| s.slot),
+        };
+        let text = record.to_text(result, number);
+        if let Err(e) = storage::write(&file_name(slot), &text) {
+            warn!("couldn't save replay: {}", e);
+            return;
+        }
+        self.saved.retain(|s| s.slot != slot);
+        self.saved.insert(
+            0,
+            Saved {
+                slot,
+                number,
+                date: result.date.clone(),
+                mode: result.mode.to_owned(),
+                score: result.score,
+            },
+        );
+    }
+
+    // the label for the nth most recent replay on the replays page
+    pub fn label(&self, n: usize, strings: &Strings) -> String {
+        match self.saved.get(n) {
+            Some(s) => strings.format(
+                "replays.saved",
+                &[
+                    &s.date,
+                    strings.get(&format!("title.{}", s.mode)),
+                    &s.score.0.to_string(),
+                    &s.score.1.to_string(),
+                ],
+            ),
+            None => strings.get("replays.empty").to_owned(),
+        }
+    }
+
+    // the nth most recent replay, and the match it starts from
+    pub fn open(&self, n: usize) -> Option<(Game, Playback)> {
+        let name = file_name(self.saved.get(n)?.slot);
+        match Playback::from_text(&storage::read(&name)?) {
+            Ok(playback) => Some((playback.new_game(), playback)),
+            Err(e) => {
+                warn!("{}: {}", name, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// debugging aid: keeps the last few seconds of a match so that, while frame-stepping (F9), the
+// match can be taken back a step at a time and played on again from there
+//
+// macroquad's random numbers aren't part of a snapshot, so a match played on from an earlier step
+// won't necessarily go the same way it did the first time (replays.rs gets round this for seeking by
+// reseeding them at its keyframes)
+
+use crate::support::SupportRun;
+use crate::tackle::Slide;
+use crate::{
+    powerups, Animation, Attributes, Ball, Game, Home, Injured, Lead, Mark, Momentum, Peer,
+    Position, Squad, Target, Team, Timer, Vector,
+};
+use hecs::{Component, EntityBuilder, EntityRef, World};
+use std::collections::VecDeque;
+
+// ten seconds of steps
+const CAPACITY: usize = 600;
+
+pub struct Rewind {
+    // oldest first, each taken just before the step after it
+    snapshots: VecDeque<Game>,
+}
+
+impl Rewind {
+    pub fn new() -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(CAPACITY),
+        }
+    }
+
+    // call before each step of the match
+    pub fn record(&mut self, game: &Game) {
+        if self.snapshots.len() == CAPACITY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(game.snapshot());
+    }
+
+    // put the game back to how it was a step ago, unless we've run out of history
+    pub fn step_back(&mut self, game: &mut Game) {
+        if let Some(snapshot) = self.snapshots.pop_back() {
+            *game = snapshot;
+        }
+    }
+
+    // throw everything away, for when a new match starts
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    // up to the last count steps, oldest first
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &Game> {
+        self.snapshots
+            .iter()
+            .skip(self.snapshots.len().saturating_sub(count))
+    }
+}
+
+pub fn copy_component<T: Component + Clone>(from: &EntityRef, to: &mut EntityBuilder) {
+    if let Some(component) = from.get::<T>() {
+        to.add((*component).clone());
+    }
+}
+
+// every entity with every component it has, keeping the same ids so that anything which refers to
+// an entity still does; a component that isn't listed here is lost on rewinding
+fn copy_world(world: &World) -> World {
+    let mut copy = World::new();
+    let mut eb = EntityBuilder::new();
+    for entity in world.iter() {
+        copy_component::<Position>(&entity, &mut eb);
+        copy_component::<Home>(&entity, &mut eb);
+        copy_component::<Team>(&entity, &mut eb);
+        copy_component::<Ball>(&entity, &mut eb);
+        copy_component::<Peer>(&entity, &mut eb);
+        copy_component::<Squad>(&entity, &mut eb);
+        copy_component::<Target>(&entity, &mut eb);
+        copy_component::<Animation>(&entity, &mut eb);
+        copy_component::<Timer>(&entity, &mut eb);
+        copy_component::<Momentum>(&entity, &mut eb);
+        copy_component::<Injured>(&entity, &mut eb);
+        copy_component::<Attributes>(&entity, &mut eb);
+        copy_component::<Lead>(&entity, &mut eb);
+        copy_component::<Mark>(&entity, &mut eb);
+        copy_component::<SupportRun>(&entity, &mut eb);
+        copy_component::<Vector>(&entity, &mut eb);
+        copy_component::<Slide>(&entity, &mut eb);
+        powerups::copy_components(&entity, &mut eb);
+        copy.spawn_at(entity.entity(), eb.build());
+    }
+    copy
+}
+
+impl Game {
+    pub fn snapshot(&self) -> Game {
+        Game {
+            difficulty: self.difficulty.clone(),
+            camera: self.camera.clone(),
+            split_screen: self.split_screen,
+            team_cameras: self.team_cameras.clone(),
+            weather: self.weather,
+            lighting: self.lighting,
+            switch_mode: self.switch_mode,
+            auto_switch: self.auto_switch,
+            assist: self.assist,
+            presser: self.presser,
+            mutators: self.mutators,
+            balance: self.balance.clone(),
+            power_ups: self.power_ups.clone(),
+            world: copy_world(&self.world),
+            grid: self.grid.clone(),
+            balls: self.balls.clone(),
+            kickoff_player: self.kickoff_player,
+            kickoff_countdown: self.kickoff_countdown,
+            restart: self.restart,
+            teams: self.teams.clone(),
+            goals_to_win: self.goals_to_win,
+            scoring_team: self.scoring_team,
+            score_timer: self.score_timer,
+            profiles: self.profiles,
+            steps_played: self.steps_played,
+            last_touch: self.last_touch,
+            assister: self.assister,
+            kick: self.kick,
+            scorer: self.scorer.clone(),
+            lineups: self.lineups.clone(),
+            rosters: self.rosters.clone(),
+            booking: self.booking.clone(),
+            substitution: self.substitution.clone(),
+            added_seconds: self.added_seconds,
+            added_board: self.added_board,
+            distribution: self.distribution,
+            net_ripple: self.net_ripple,
+            duel: self.duel,
+            stats: self.stats.clone(),
+            celebration: self.celebration,
+            debug_shoot_target: self.debug_shoot_target,
+            shoot_now: self.shoot_now,
+            shoot_pressed: self.shoot_pressed,
+            switch_pressed: self.switch_pressed,
+            call_pressed: self.call_pressed,
+            tackle_pressed: self.tackle_pressed,
+            cursor: self.cursor,
+            sticks: self.sticks,
+            training: self.training.clone(),
+            events: self.events.clone(),
+            log: self.log.clone(),
+            ai_enabled: self.ai_enabled,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// a team's squad: every member's name and attribute points, in the same order as SQUAD_NUMBERS;
+// each team has its own to start with (see attributes::generate), and a profile can have one of its
+// own, edited in the squad editor, that it takes to whichever team it plays as
+
+use crate::attributes::{self, Attributes, AVERAGE_POINTS};
+use crate::data::Value;
+use crate::lineup::SQUAD_SIZE;
+use crate::profiles::MAX_NAME_LENGTH;
+use crate::{Squad, SQUAD_NAMES, SQUAD_NUMBERS};
+
+// every squad member has this many points to share between their attributes
+pub const POINTS_PER_PLAYER: u8 = 5 * AVERAGE_POINTS;
+
+#[derive(Clone, PartialEq)]
+pub struct RosterPlayer {
+    pub name: String,
+    pub attributes: Attributes,
+}
+
+impl RosterPlayer {
+    pub fn points(&self) -> u8 {
+        let a = &self.attributes;
+        a.pace + a.kick_power + a.tackling + a.control + a.strength
+    }
+
+    // as it's kept in the profiles file: ["name", pace, kick power, tackling, control, strength]
+    pub fn to_value(&self) -> String {
+        let a = &self.attributes;
+        format!(
+            "[\"{}\", {}, {}, {}, {}, {}]",
+            self.name, a.pace, a.kick_power, a.tackling, a.control, a.strength
+        )
+    }
+
+    // None unless it's a name and attribute points that add up to no more than POINTS_PER_PLAYER;
+    // a squad saved before players had strength gets the average
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let items = match value {
+            Value::List(items) if items.len() == 5 || items.len() == 6 => items,
+            _ => return None,
+        };
+        let points = |i: usize| {
+            let p = items[i].as_f32()? as u8;
+            (attributes::MIN_POINTS..=attributes::MAX_POINTS)
+                .contains(&p)
+                .then_some(p)
+        };
+        let player = Self {
+            name: items[0].as_str()?.chars().take(MAX_NAME_LENGTH).collect(),
+            attributes: Attributes {
+                pace: points(1)?,
+                kick_power: points(2)?,
+                tackling: points(3)?,
+                control: points(4)?,
+                strength: match items.get(5) {
+                    Some(_) => points(5)?,
+                    None => AVERAGE_POINTS,
+                },
+            },
+        };
+        (player.points() <= POINTS_PER_PLAYER).then_some(player)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Roster {
+    pub players: [RosterPlayer; SQUAD_SIZE],
+}
+
+impl Roster {
+    // the squad a team has when nobody's edited it
+    pub fn default_for(team: usize) -> Self {
+        let attributes = attributes::generate(team);
+        Self {
+            players: std::array::from_fn(|member| RosterPlayer {
+                name: SQUAD_NAMES[team][member].to_owned(),
+                attributes: attributes[member],
+            }),
+        }
+    }
+
+    pub fn squad(&self, member: usize) -> Squad {
+        Squad {
+            number: SQUAD_NUMBERS[member],
+            name: self.players[member].name.clone(),
+        }
+    }
+
+    pub fn attributes(&self, member: usize) -> Attributes {
+        self.players[member].attributes
+    }
+}
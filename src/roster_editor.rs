@@ -0,0 +1,166 @@
+// the squad editor, reached from the profiles page: a list of a profile's squad, and for each
+// member a page where their name can be typed over and their attribute points moved about, up to
+// roster::POINTS_PER_PLAYER in total; the squad is saved to the profile on the way out
+
+use crate::attributes::{MAX_POINTS, MIN_POINTS};
+use crate::i18n::Strings;
+use crate::lineup::SQUAD_SIZE;
+use crate::menu::{self, MenuAction, Pointer};
+use crate::profiles::{Profiles, MAX_NAME_LENGTH};
+use crate::roster::{Roster, POINTS_PER_PLAYER};
+use crate::{Sounds, HEIGHT, SQUAD_NUMBERS};
+use macroquad::prelude::*;
+
+// the rows of a squad member's page, after their name, in the order Attributes::points_mut
+// numbers them
+const ATTRIBUTE_KEYS: [&str; 5] = [
+    "roster.pace",
+    "roster.kick_power",
+    "roster.tackling",
+    "roster.control",
+    "roster.strength",
+];
+
+pub struct RosterEditor {
+    // the team whose profile is being edited, for going back to the right row
+    pub team: usize,
+    profile: usize,
+    roster: Roster,
+    // the squad member being edited, or None for the list of them
+    member: Option<usize>,
+    selected: usize,
+}
+
+impl RosterEditor {
+    pub fn new(team: usize, profile: usize, profiles: &Profiles) -> Self {
+        Self {
+            team,
+            profile,
+            roster: profiles.roster(Some(profile), team),
+            member: None,
+            selected: 0,
+        }
+    }
+
+    // called once a frame; true once the player has left, with the squad saved
+    pub fn update(&mut self, pointer: &Pointer, sounds: &Sounds, profiles: &mut Profiles) -> bool {
+        // typed characters queue up until they're read, so take them every frame whether or not
+        // anyone's typing a name
+        let mut typed = Vec::new();
+        while let Some(c) = get_char_pressed() {
+            typed.push(c);
+        }
+        let (rows, layout) = match self.member {
+            None => (SQUAD_SIZE, menu::LIST_LAYOUT),
+            Some(_) => (1 + ATTRIBUTE_KEYS.len(), menu::SETTINGS_LAYOUT),
+        };
+        if let (Some(member), 0) = (self.member, self.selected) {
+            let name = &mut self.roster.players[member].name;
+            for c in typed {
+                if c.is_ascii_alphabetic() && name.len() < MAX_NAME_LENGTH {
+                    name.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+        }
+        let action = menu::navigate(&mut self.selected, rows, layout, pointer);
+        if action != MenuAction::None {
+            sounds.play("move");
+        }
+        match (action, self.member) {
+            (MenuAction::Back, None) => {
+                profiles.set_roster(self.profile, self.roster.clone());
+                return true;
+            }
+            (MenuAction::Back, Some(member)) => {
+                // nobody can be left without a name
+                let player = &mut self.roster.players[member];
+                if player.name.is_empty() {
+                    player.name = Roster::default_for(self.team).players[member].name.clone();
+                }
+                self.member = None;
+                self.selected = member;
+            }
+            (MenuAction::Confirm, None) => {
+                self.member = Some(self.selected);
+                self.selected = 0;
+            }
+            (MenuAction::Confirm, Some(member)) | (MenuAction::Adjust(_), Some(member))
+                if self.selected > 0 =>
+            {
+                let player = &mut self.roster.players[member];
+                let spare = POINTS_PER_PLAYER - player.points();
+                let points = player.attributes.points_mut(self.selected - 1);
+                if action == MenuAction::Adjust(-1) {
+                    if *points > MIN_POINTS {
+                        *points -= 1;
+                    }
+                } else if spare > 0 && *points < MAX_POINTS {
+                    *points += 1;
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+
+    pub fn draw(&self, profiles: &Profiles, strings: &Strings) {
+        let name = &profiles.get(self.profile).name;
+        draw_text(
+            &strings.format("roster.title", &[name]),
+            100.,
+            100.,
+            48.,
+            WHITE,
+        );
+        let colour = |i| if i == self.selected { YELLOW } else { WHITE };
+        match self.member {
+            None => {
+                for (i, player) in self.roster.players.iter().enumerate() {
+                    let a = &player.attributes;
+                    let label = strings.format(
+                        "roster.player",
+                        &[
+                            &SQUAD_NUMBERS[i].to_string(),
+                            &player.name,
+                            &a.pace.to_string(),
+                            &a.kick_power.to_string(),
+                            &a.tackling.to_string(),
+                            &a.control.to_string(),
+                            &a.strength.to_string(),
+                        ],
+                    );
+                    draw_text(&label, 120., menu::LIST_LAYOUT.baseline(i), 24., colour(i));
+                }
+            }
+            Some(member) => {
+                let player = &self.roster.players[member];
+                let mut labels = vec![strings.format("roster.name", &[&player.name])];
+                for (n, key) in ATTRIBUTE_KEYS.iter().enumerate() {
+                    let points = player.attributes.points(n).to_string();
+                    labels.push(strings.format(key, &[&points]));
+                }
+                for (i, label) in labels.iter().enumerate() {
+                    draw_text(
+                        label,
+                        120.,
+                        menu::SETTINGS_LAYOUT.baseline(i),
+                        32.,
+                        colour(i),
+                    );
+                }
+                let spare = (POINTS_PER_PLAYER - player.points()).to_string();
+                draw_text(
+                    &strings.format("roster.points_left", &[&spare]),
+                    120.,
+                    menu::SETTINGS_LAYOUT.baseline(labels.len()) + 10.,
+                    24.,
+                    WHITE,
+                );
+            }
+        }
+        draw_text(strings.get("options.back"), 10., HEIGHT - 10., 16., WHITE);
+    }
+}
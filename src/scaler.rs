@@ -0,0 +1,123 @@
+use crate::{HEIGHT, WIDTH};
+use macroquad::prelude::*;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ScaleMode {
+    Smooth,
+    // largest whole-number multiple that fits, with no filtering so pixels stay sharp
+    Integer,
+}
+
+// the game is always drawn at its native 800x480 resolution into an off-screen target, which is
+// then scaled to fit the window with black bars filling any leftover space
+pub struct Scaler {
+    target: RenderTarget,
+    pub fullscreen: bool,
+    mode: ScaleMode,
+}
+
+impl Scaler {
+    pub fn new() -> Self {
+        Self {
+            target: render_target(WIDTH as u32, HEIGHT as u32),
+            fullscreen: false,
+            mode: ScaleMode::Smooth,
+        }
+    }
+
+    pub fn mode(&self) -> ScaleMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ScaleMode) {
+        self.mode = mode;
+        self.target.texture.set_filter(self.filter());
+    }
+
+    // filter mode that textures should use to match the scaling mode
+    pub fn filter(&self) -> FilterMode {
+        match self.mode {
+            ScaleMode::Smooth => FilterMode::Linear,
+            ScaleMode::Integer => FilterMode::Nearest,
+        }
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        self.fullscreen = !self.fullscreen;
+        // note that miniquad doesn't support switching at runtime on every platform
+        unsafe { get_internal_gl() }
+            .quad_context
+            .set_fullscreen(self.fullscreen);
+    }
+
+    // the off-screen target the game area is drawn into
+    pub fn target(&self) -> RenderTarget {
+        self.target
+    }
+
+    // redirect a camera so that it draws into the game area rather than straight to the window
+    pub fn with_target(&self, camera: Camera2D) -> Camera2D {
+        Camera2D {
+            render_target: Some(self.target),
+            ..camera
+        }
+    }
+
+    // camera for drawing in game screen coordinates, i.e. (0, 0) to (WIDTH, HEIGHT)
+    pub fn screen_camera(&self) -> Camera2D {
+        self.with_target(Camera2D::from_display_rect(Rect::new(
+            0.0, 0.0, WIDTH, HEIGHT,
+        )))
+    }
+
+    fn scale(&self) -> f32 {
+        let scale = (screen_width() / WIDTH).min(screen_height() / HEIGHT);
+        match self.mode {
+            ScaleMode::Smooth => scale,
+            ScaleMode::Integer => scale.floor().max(1.0),
+        }
+    }
+
+    fn origin(&self) -> Vec2 {
+        let scale = self.scale();
+        vec2(
+            (screen_width() - WIDTH * scale) / 2.0,
+            (screen_height() - HEIGHT * scale) / 2.0,
+        )
+    }
+
+    // where the mouse is, in game screen coordinates
+    pub fn mouse_position(&self) -> Vec2 {
+        let (x, y) = mouse_position();
+        self.to_game(vec2(x, y))
+    }
+
+    // convert a position in the window, in pixels, to game screen coordinates
+    pub fn to_game(&self, pos: Vec2) -> Vec2 {
+        (pos - self.origin()) / self.scale()
+    }
+
+    // the game area as drawn so far this frame
+    pub fn capture(&self) -> Image {
+        unsafe { get_internal_gl() }.flush();
+        self.target.texture.get_texture_data()
+    }
+
+    pub fn present(&self) {
+        set_default_camera();
+        clear_background(BLACK);
+        let origin = self.origin();
+        let scale = self.scale();
+        draw_texture_ex(
+            self.target.texture,
+            origin.x,
+            origin.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(WIDTH * scale, HEIGHT * scale)),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+}
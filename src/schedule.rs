@@ -0,0 +1,54 @@
+// the order things happen in during a step of the match: each system is a Game method that
+// does one job, and Game::step runs them all in turn, so something new (statistics, events,
+// effects) goes in as one more line in the right place rather than another chunk of step
+//
+// the order matters: the golden tests will notice if moving a system changes how a match goes
+
+use crate::{Game, GameError};
+use hecs::Entity;
+
+type System = fn(&mut Game) -> Result<(), GameError>;
+
+pub const SYSTEMS: [(&str, System); 19] = [
+    ("timers", Game::count_down_timers),
+    ("goal check", Game::check_goals),
+    ("player grid", Game::rebuild_grid),
+    ("behaviours", Game::set_behaviours),
+    ("support runs", Game::plan_support_runs),
+    ("targets", Game::set_player_targets),
+    ("celebration", Game::celebrate),
+    ("power-ups", Game::update_power_ups),
+    ("movement", Game::move_players),
+    // the players have moved, so the grid needs doing again before anyone looks for the ball
+    ("player grid", Game::rebuild_grid),
+    ("double taps", Game::read_double_taps),
+    ("slide tackles", Game::slide_tackles),
+    ("balls", Game::update_balls),
+    ("deflections", Game::deflect_off_players),
+    ("training", Game::count_training_shots),
+    ("switching", Game::switch_players),
+    ("calling for a pass", Game::call_for_pass),
+    ("camera", Game::move_cameras),
+    ("input", Game::finish_input),
+];
+
+// what the systems for one ball pass along to each other
+pub struct BallStep {
+    // index into Game::balls
+    pub b: usize,
+    // who has the ball now; written back to Game::balls once all the systems have run
+    pub owner: Option<Entity>,
+    // the team that had the ball at the start of the step, even if they've since lost it
+    pub owner_team: Option<u8>,
+}
+
+type BallSystem = fn(&mut Game, &mut BallStep) -> Result<(), GameError>;
+
+// run for each ball in turn by the "balls" system
+pub const BALL_SYSTEMS: [(&str, BallSystem); 5] = [
+    ("near misses", Game::check_near_miss),
+    ("physics", Game::move_ball),
+    ("possession", Game::take_possession),
+    ("knock-ons", Game::knock_ball_on),
+    ("kicking", Game::kick_ball),
+];
@@ -0,0 +1,163 @@
+// screenshots: F12 saves the game screen as it is at the end of the frame, at its native 800x480
+// whatever the size of the window, as a PNG named after when it was taken, in screenshots in the
+// config directory (see storage.rs); in the browser, the page downloads it instead (see
+// web/download.js)
+//
+// the PNG is put together here rather than with an image library, and isn't compressed, so it's
+// bigger than it needs to be
+
+use crate::i18n::Strings;
+use crate::scaler::Scaler;
+use crate::{draw_text_centred, HALF_WINDOW_WIDTH};
+use macroquad::prelude::*;
+
+// how long the message saying where it went stays up
+const CONFIRM_SECONDS: f32 = 2.0;
+// the most a stored deflate block can hold
+const MAX_BLOCK: usize = 65535;
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn soccer_download(name: *const u8, name_len: usize, data: *const u8, data_len: usize);
+}
+
+pub struct Screenshots {
+    wanted: bool,
+    // the message, and how much longer to show it
+    confirmation: Option<(String, f32)>,
+}
+
+impl Screenshots {
+    pub fn new() -> Self {
+        Self {
+            wanted: false,
+            confirmation: None,
+        }
+    }
+
+    // take one at the end of this frame
+    pub fn request(&mut self) {
+        self.wanted = true;
+    }
+
+    // call once everything but the confirmation has been drawn
+    pub fn take(&mut self, scaler: &Scaler, strings: &Strings) {
+        if !self.wanted {
+            return;
+        }
+        self.wanted = false;
+        let name = format!("screenshot-{}.png", timestamp());
+        match save(&name, &encode_png(&scaler.capture())) {
+            Ok(place) => {
+                self.confirmation =
+                    Some((strings.format("hud.screenshot", &[&place]), CONFIRM_SECONDS))
+            }
+            Err(e) => warn!("couldn't save screenshot: {}", e),
+        }
+    }
+
+    pub fn draw(&mut self) {
+        if let Some((message, seconds)) = &mut self.confirmation {
+            draw_text_centred(message, HALF_WINDOW_WIDTH, 60.0, 16.0);
+            *seconds -= get_frame_time();
+            if *seconds <= 0.0 {
+                self.confirmation = None;
+            }
+        }
+    }
+}
+
+// where the screenshot went, for the confirmation
+#[cfg(not(target_arch = "wasm32"))]
+fn save(name: &str, png: &[u8]) -> Result<String, String> {
+    let path = crate::storage::dir("screenshots")?.join(name);
+    std::fs::write(&path, png)
+        .map(|_| path.display().to_string())
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save(name: &str, png: &[u8]) -> Result<String, String> {
+    unsafe { soccer_download(name.as_ptr(), name.len(), png.as_ptr(), png.len()) };
+    Ok(name.to_owned())
+}
+
+// the date and time in UTC as yyyy-mm-dd-hhmmss
+fn timestamp() -> String {
+    let seconds = macroquad::miniquad::date::now() as u64 % 86400;
+    format!(
+        "{}-{:02}{:02}{:02}",
+        crate::history::today(),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// a PNG of the image, which like everything read back from the GPU is upside down, with every
+// pixel made opaque
+fn encode_png(image: &Image) -> Vec<u8> {
+    let (width, height) = (image.width as usize, image.height as usize);
+    let mut pixels = Vec::with_capacity((width * 4 + 1) * height);
+    for row in image.bytes.chunks(width * 4).rev() {
+        // each row starts with the filter it uses, which is none
+        pixels.push(0);
+        for pixel in row.chunks(4) {
+            pixels.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+        }
+    }
+    // zlib data made of stored blocks, which aren't compressed at all
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = pixels.chunks(MAX_BLOCK).count();
+    for (n, block) in pixels.chunks(MAX_BLOCK).enumerate() {
+        let len = block.len() as u16;
+        zlib.push((n + 1 == blocks) as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&pixels).to_be_bytes());
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, and the only compression, filtering and interlacing there are
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
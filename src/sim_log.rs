@@ -0,0 +1,122 @@
+// a text log of the decisions the AI makes each step, for working out why a player did something
+// odd without having to catch it happening with the debug overlay; it's off unless asked for on the
+// command line, with a level for everything or for each area separately:
+//
+//   --sim-log debug
+//   --sim-log behaviours=debug,targets=trace,ball=info
+//
+// lines go to stderr, so redirect it to keep them: cargo run -- --sim-log debug 2> ai.log
+//
+// the command line is read once when the game starts (see launch.rs), and every match from then on
+// logs at the levels it asked for; anything else, such as a test, gets no log
+
+use std::sync::RwLock;
+
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub enum Level {
+    Off,
+    // things that happen now and then: possession changing hands, kicks
+    Info,
+    // what each step decided: who's marking the goal, who the leads are, where a kick was aimed
+    Debug,
+    // everything considered along the way: every player's target, every possible pass
+    Trace,
+}
+
+impl Level {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum Area {
+    // marking and leads, from set_behaviours
+    Behaviours,
+    // where each player is heading, from set_player_targets
+    Targets,
+    // possession and kicking, from update_ball
+    Ball,
+}
+
+const AREAS: [(Area, &str); 3] = [
+    (Area::Behaviours, "behaviours"),
+    (Area::Targets, "targets"),
+    (Area::Ball, "ball"),
+];
+
+// the levels every new match logs at
+static CHOSEN: RwLock<[Level; 3]> = RwLock::new([Level::Off; 3]);
+
+#[derive(Clone)]
+pub struct SimLog {
+    levels: [Level; 3],
+    // the step being logged, to start each line with
+    pub step: u32,
+}
+
+impl SimLog {
+    pub fn off() -> Self {
+        Self {
+            levels: [Level::Off; 3],
+            step: 0,
+        }
+    }
+
+    // the levels given to --sim-log
+    pub fn parse(spec: &str) -> Self {
+        let mut log = Self::off();
+        for part in spec.split(',') {
+            match part.split_once('=') {
+                Some((name, level)) => {
+                    let area = AREAS.iter().position(|(_, n)| *n == name);
+                    match (area, Level::parse(level)) {
+                        (Some(area), Some(level)) => log.levels[area] = level,
+                        _ => eprintln!("--sim-log: don't understand {}", part),
+                    }
+                }
+                None => match Level::parse(part) {
+                    Some(level) => log.levels = [level; 3],
+                    None => eprintln!("--sim-log: don't understand {}", part),
+                },
+            }
+        }
+        log
+    }
+
+    // have every match from now on log at these levels
+    pub fn use_for_new_matches(&self) {
+        *CHOSEN.write().unwrap() = self.levels;
+    }
+
+    // what a new match logs
+    pub fn current() -> Self {
+        Self {
+            levels: *CHOSEN.read().unwrap(),
+            step: 0,
+        }
+    }
+
+    pub fn enabled(&self, area: Area, level: Level) -> bool {
+        level <= self.levels[area as usize]
+    }
+
+    pub fn write(&self, area: Area, args: std::fmt::Arguments) {
+        eprintln!("{:>6} {:<10} {}", self.step, AREAS[area as usize].1, args);
+    }
+}
+
+// log a line if the area is being logged at the level, only formatting it if it is
+macro_rules! sim_log {
+    ($log:expr, $area:ident, $level:ident, $($arg:tt)*) => {
+        if $log.enabled($crate::sim_log::Area::$area, $crate::sim_log::Level::$level) {
+            $log.write($crate::sim_log::Area::$area, format_args!($($arg)*));
+        }
+    };
+}
@@ -0,0 +1,187 @@
+// a grid over the level recording which players are in each square, so that finding the players
+// near somewhere only means looking at the squares around it rather than at everyone; it's rebuilt
+// whenever the players have moved (see Game::step)
+//
+// players are kept in the order the world lists them and searches break ties in that order, so
+// the grid picks out exactly the same players as the queries over the world it replaces
+
+use crate::{Position, Team, Vector, LEVEL_H, LEVEL_W};
+use hecs::{Entity, World};
+
+const CELL_SIZE: f32 = 100.0;
+const COLS: usize = (LEVEL_W / CELL_SIZE) as usize;
+const ROWS: usize = (LEVEL_H / CELL_SIZE) as usize;
+
+#[derive(Copy, Clone)]
+pub struct GridPlayer {
+    pub id: Entity,
+    pub team: u8,
+    pub pos: Vector,
+}
+
+#[derive(Clone)]
+pub struct PlayerGrid {
+    players: Vec<GridPlayer>,
+    // indices into players for each cell, a row at a time
+    cells: Vec<Vec<usize>>,
+}
+
+// the cell a point is in, counting anything off the level as in the nearest cell on it
+fn cell_of(pos: Vector) -> (usize, usize) {
+    let col = (pos.x / CELL_SIZE).clamp(0.0, (COLS - 1) as f32) as usize;
+    let row = (pos.y / CELL_SIZE).clamp(0.0, (ROWS - 1) as f32) as usize;
+    (col, row)
+}
+
+impl PlayerGrid {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+            cells: vec![Vec::new(); COLS * ROWS],
+        }
+    }
+
+    pub fn rebuild(&mut self, world: &World) {
+        self.players.clear();
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        for (id, (pos, team)) in &mut world.query::<(&Position, &Team)>() {
+            let (col, row) = cell_of(pos.0);
+            self.cells[row * COLS + col].push(self.players.len());
+            self.players.push(GridPlayer {
+                id,
+                team: team.0,
+                pos: pos.0,
+            });
+        }
+    }
+
+    // everyone, in the world's order
+    pub fn players(&self) -> &[GridPlayer] {
+        &self.players
+    }
+
+    // indices of the players in the cells that are exactly ring squares away from the given one
+    fn ring(&self, (col, row): (usize, usize), ring: usize) -> impl Iterator<Item = usize> + '_ {
+        let (col, row, ring) = (col as isize, row as isize, ring as isize);
+        (row - ring..=row + ring)
+            .flat_map(move |r| (col - ring..=col + ring).map(move |c| (c, r)))
+            .filter(move |(c, r)| (c - col).abs().max((r - row).abs()) == ring)
+            .filter(|(c, r)| (0..COLS as isize).contains(c) && (0..ROWS as isize).contains(r))
+            .flat_map(move |(c, r)| self.cells[r as usize * COLS + c as usize].iter().copied())
+    }
+
+    // the nearest player to pos of those that pass the filter
+    pub fn nearest(&self, pos: Vector, filter: impl Fn(&GridPlayer) -> bool) -> Option<GridPlayer> {
+        let cell = cell_of(pos);
+        let mut best: Option<(f32, usize)> = None;
+        for ring in 0..COLS.max(ROWS) {
+            // everyone in this ring and beyond is at least this far away
+            let bound = ring.saturating_sub(1) as f32 * CELL_SIZE;
+            if best.is_some_and(|(dist, _)| dist < bound) {
+                break;
+            }
+            for i in self.ring(cell, ring) {
+                let player = &self.players[i];
+                if !filter(player) {
+                    continue;
+                }
+                let dist = (player.pos - pos).length();
+                if best.is_none_or(|best| (dist, i) < best) {
+                    best = Some((dist, i));
+                }
+            }
+        }
+        best.map(|(_, i)| self.players[i])
+    }
+
+    // the players no further than radius from pos, in the world's order
+    pub fn within(&self, pos: Vector, radius: f32) -> Vec<GridPlayer> {
+        let cell = cell_of(pos);
+        let rings = (radius / CELL_SIZE).ceil() as usize + 1;
+        let mut found: Vec<usize> = (0..=rings)
+            .flat_map(|ring| self.ring(cell, ring))
+            .filter(|i| (self.players[*i].pos - pos).length() <= radius)
+            .collect();
+        found.sort_unstable();
+        found.into_iter().map(|i| self.players[i]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp_dist;
+    use crate::test_rng::Rng;
+    use euclid::vec2;
+
+    const CASES: usize = 200;
+
+    // a point on the level or a little way off it, sometimes snapped to a coarse lattice so that
+    // there are plenty of ties
+    fn random_point(rng: &mut Rng) -> Vector {
+        let p = vec2(
+            rng.range(-100.0, LEVEL_W + 100.0),
+            rng.range(-100.0, LEVEL_H + 100.0),
+        );
+        if rng.next().is_multiple_of(2) {
+            (p / 50.0).round() * 50.0
+        } else {
+            p
+        }
+    }
+
+    fn random_world(rng: &mut Rng) -> World {
+        let mut world = World::new();
+        for _ in 0..rng.next() % 40 {
+            let team = (rng.next() % 2) as u8;
+            world.spawn((Position(random_point(rng)), Team(team)));
+        }
+        world
+    }
+
+    #[test]
+    fn nearest_matches_a_scan() {
+        let mut rng = Rng(1);
+        let mut grid = PlayerGrid::new();
+        for _ in 0..CASES {
+            let world = random_world(&mut rng);
+            grid.rebuild(&world);
+            for _ in 0..20 {
+                let pos = random_point(&mut rng);
+                let team = (rng.next() % 2) as u8;
+                let expected = world
+                    .query::<(&Team, &Position)>()
+                    .iter()
+                    .filter(|(_, (t, _))| t.0 == team)
+                    .min_by(|(_, (_, p1)), (_, (_, p2))| cmp_dist(p1.0, p2.0, pos))
+                    .map(|(id, _)| id);
+                let found = grid.nearest(pos, |p| p.team == team).map(|p| p.id);
+                assert_eq!(found, expected, "nearest to {:?} on team {}", pos, team);
+            }
+        }
+    }
+
+    #[test]
+    fn within_matches_a_scan() {
+        let mut rng = Rng(2);
+        let mut grid = PlayerGrid::new();
+        for _ in 0..CASES {
+            let world = random_world(&mut rng);
+            grid.rebuild(&world);
+            for _ in 0..20 {
+                let pos = random_point(&mut rng);
+                let radius = rng.range(0.0, 400.0);
+                let expected: Vec<Entity> = world
+                    .query::<&Position>()
+                    .iter()
+                    .filter(|(_, p)| (pos - p.0).length() <= radius)
+                    .map(|(id, _)| id)
+                    .collect();
+                let found: Vec<Entity> = grid.within(pos, radius).iter().map(|p| p.id).collect();
+                assert_eq!(found, expected, "within {} of {:?}", radius, pos);
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::i18n::Strings;
+use crate::lineup::SQUAD_SIZE;
+use crate::roster::Roster;
+use crate::textures::{Sprite, Textures};
+use crate::{draw_text_centred, Squad, HALF_WINDOW_WIDTH, WIDTH};
+use macroquad::prelude::*;
+
+// the pitch is 1244 pixels long, and a real one about 105 metres
+const METRES_PER_PIXEL: f32 = 105.0 / 1244.0;
+// how much each contribution counts towards being player of the match
+const GOAL_RATING: f32 = 10.0;
+const ASSIST_RATING: f32 = 6.0;
+const OWN_GOAL_RATING: f32 = -5.0;
+const INTERCEPTION_RATING: f32 = 2.0;
+const SAVE_RATING: f32 = 3.0;
+const METRES_PER_RATING: f32 = 100.0;
+// top of the player of the match panel on the game over screen
+const PANEL_Y: f32 = 250.0;
+
+#[derive(Copy, Clone, Default)]
+pub struct PlayerStats {
+    pub goals: u32,
+    pub own_goals: u32,
+    pub assists: u32,
+    // times the player won the ball from the other team
+    pub interceptions: u32,
+    // kicks to a teammate that reached one, or that the other team got to first
+    pub passes_completed: u32,
+    pub passes_intercepted: u32,
+    // shots that went just wide, and other team's shots the player got to before they went in
+    pub near_misses: u32,
+    pub saves: u32,
+    // tackles from behind, and how many of them earned a card
+    pub fouls: u32,
+    pub bookings: u32,
+    // in pixels
+    pub distance: f32,
+}
+
+impl PlayerStats {
+    fn rating(&self) -> f32 {
+        self.goals as f32 * GOAL_RATING
+            + self.own_goals as f32 * OWN_GOAL_RATING
+            + self.assists as f32 * ASSIST_RATING
+            + self.interceptions as f32 * INTERCEPTION_RATING
+            + self.saves as f32 * SAVE_RATING
+            + self.distance * METRES_PER_PIXEL / METRES_PER_RATING
+    }
+}
+
+// what every player did over a match; players are respawned at each kickoff, so they're kept by
+// team and squad member rather than on the entities
+#[derive(Clone)]
+pub struct MatchStats {
+    players: [[PlayerStats; SQUAD_SIZE]; 2],
+}
+
+impl MatchStats {
+    pub fn new() -> Self {
+        Self {
+            players: [[PlayerStats::default(); SQUAD_SIZE]; 2],
+        }
+    }
+
+    pub fn get_mut(&mut self, team: u8, squad: &Squad) -> &mut PlayerStats {
+        &mut self.players[team as usize][squad.member()]
+    }
+
+    // passes completed and intercepted between a team's players
+    pub fn passes(&self, team: usize) -> (u32, u32) {
+        self.players[team]
+            .iter()
+            .fold((0, 0), |(completed, intercepted), p| {
+                (
+                    completed + p.passes_completed,
+                    intercepted + p.passes_intercepted,
+                )
+            })
+    }
+
+    // team and squad member of whoever contributed most
+    fn player_of_the_match(&self) -> (usize, usize) {
+        let mut best = (0, 0);
+        for (t, team) in self.players.iter().enumerate() {
+            for (n, player) in team.iter().enumerate() {
+                if player.rating() > self.players[best.0][best.1].rating() {
+                    best = (t, n);
+                }
+            }
+        }
+        best
+    }
+
+    // draw in screen coordinates, on the game over screen, with the names from the teams' rosters
+    pub fn draw(&self, rosters: &[Roster; 2], textures: &Textures, strings: &Strings) {
+        let (team, n) = self.player_of_the_match();
+        let squad = rosters[team].squad(n);
+        let stats = &self.players[team][n];
+        draw_rectangle(0.0, PANEL_Y, WIDTH, 110.0, Color::new(0.0, 0.0, 0.0, 0.6));
+        textures.draw_anchored(
+            Sprite::Player {
+                team: team as u8,
+                dir: 4,
+                frame: 0,
+            },
+            HALF_WINDOW_WIDTH,
+            PANEL_Y + 55.0,
+        );
+        draw_text_centred(
+            &strings.format(
+                "hud.player_of_the_match",
+                &[&squad.number.to_string(), &squad.name],
+            ),
+            HALF_WINDOW_WIDTH,
+            PANEL_Y + 80.0,
+            24.0,
+        );
+        let metres = (stats.distance * METRES_PER_PIXEL).round();
+        draw_text_centred(
+            &strings.format(
+                "hud.match_stats",
+                &[
+                    &stats.goals.to_string(),
+                    &stats.assists.to_string(),
+                    &stats.interceptions.to_string(),
+                    &stats.passes_completed.to_string(),
+                    &(stats.passes_completed + stats.passes_intercepted).to_string(),
+                    &metres.to_string(),
+                ],
+            ),
+            HALF_WINDOW_WIDTH,
+            PANEL_Y + 102.0,
+            18.0,
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// files the game writes for itself, kept in the player's config directory rather than alongside the
+// assets; in the browser they're kept in the page's localStorage instead, one entry per file, through
+// the functions web/storage.js adds to the page
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+// also the start of each file's localStorage key, to keep clear of anything else on the page
+const DIR_NAME: &str = "substitute-soccer";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn config_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        var("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        var("HOME")?.join("Library/Application Support")
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))?
+    };
+    Some(base.join(DIR_NAME))
+}
+
+// the contents of a file in the config directory, or None if it isn't there
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Option<String> {
+    std::fs::read_to_string(config_dir()?.join(name)).ok()
+}
+
+// add text to the end of a file in the config directory, creating it if need be
+#[cfg(not(target_arch = "wasm32"))]
+pub fn append(name: &str, text: &str) -> Result<(), String> {
+    use std::io::Write;
+    let dir = config_dir().ok_or("no config directory")?;
+    let path = dir.join(name);
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// replace a file in the config directory, creating the directory if need be
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, text: &str) -> Result<(), String> {
+    let dir = config_dir().ok_or("no config directory")?;
+    let path = dir.join(name);
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&path, text))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// a directory in the config directory for files the game doesn't read back, such as screenshots,
+// created if it isn't there yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn dir(name: &str) -> Result<PathBuf, String> {
+    let path = config_dir().ok_or("no config directory")?.join(name);
+    std::fs::create_dir_all(&path)
+        .map(|_| path.clone())
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// a new, empty directory in the config directory for files the game doesn't read back, such as
+// saved highlights, called name-1, name-2 and so on after whichever are there already
+#[cfg(not(target_arch = "wasm32"))]
+pub fn new_dir(name: &str) -> Result<PathBuf, String> {
+    let dir = config_dir().ok_or("no config directory")?;
+    let path = (1..)
+        .map(|n| dir.join(format!("{}-{}", name, n)))
+        .find(|path| !path.exists())
+        .unwrap();
+    std::fs::create_dir_all(&path)
+        .map(|_| path.clone())
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // the length in bytes of the stored value, or -1 if there isn't one
+    fn soccer_storage_len(key: *const u8, key_len: usize) -> i32;
+    fn soccer_storage_read(key: *const u8, key_len: usize, buf: *mut u8, buf_len: usize);
+    // 0 if the value was stored, otherwise the browser refused (usually for being full)
+    fn soccer_storage_write(
+        key: *const u8,
+        key_len: usize,
+        value: *const u8,
+        value_len: usize,
+    ) -> i32;
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(name: &str) -> String {
+    format!("{}/{}", DIR_NAME, name)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Option<String> {
+    let key = storage_key(name);
+    let len = unsafe { soccer_storage_len(key.as_ptr(), key.len()) };
+    if len < 0 {
+        return None;
+    }
+    let mut buf = vec![0; len as usize];
+    unsafe { soccer_storage_read(key.as_ptr(), key.len(), buf.as_mut_ptr(), buf.len()) };
+    String::from_utf8(buf).ok()
+}
+
+// localStorage can't add to the end of a value, so this reads the whole thing and writes it back
+#[cfg(target_arch = "wasm32")]
+pub fn append(name: &str, text: &str) -> Result<(), String> {
+    let mut contents = read(name).unwrap_or_default();
+    contents.push_str(text);
+    write(name, &contents)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, text: &str) -> Result<(), String> {
+    let key = storage_key(name);
+    let result =
+        unsafe { soccer_storage_write(key.as_ptr(), key.len(), text.as_ptr(), text.len()) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(format!("localStorage refused to store {}", key))
+    }
+}
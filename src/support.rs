@@ -0,0 +1,138 @@
+// where teammates of whoever has the ball run to give them someone to pass to: each takes a
+// channel of their own across the pitch, so that they don't bunch up, and whoever ends up out wide
+// overlaps, running on beyond the opposition's last defender
+//
+// the runs are planned once a step for the whole team, then followed in choose_target as the
+// support behaviour
+
+use crate::error::{GameError, WorldExt};
+use crate::{
+    cmp_dist, Game, Home, Position, Team, Vector, AI_MAX_X, AI_MAX_Y, AI_MIN_X, AI_MIN_Y,
+    HALF_LEVEL_W, LEVEL_H,
+};
+use euclid::vec2;
+use hecs::Entity;
+
+// the channels' distances from the middle of the pitch, as multiples of CHANNEL_WIDTH; the outside
+// ones are the wide ones
+const CHANNELS: [f32; 5] = [-2.0, -1.0, 0.0, 1.0, 2.0];
+const CHANNEL_WIDTH: f32 = 150.0;
+// supporting players head halfway between their own position and this far up the pitch from the
+// ball
+const SUPPORT_AHEAD: f32 = 400.0;
+// an overlapping run finishes this far beyond the last defender, but never more than OVERLAP_RANGE
+// ahead of the ball
+const OVERLAP_BEYOND: f32 = 40.0;
+const OVERLAP_RANGE: f32 = 350.0;
+// anyone left without a channel keeps at least this far from everyone else's run
+const MIN_SPACING: f32 = 80.0;
+
+// where a player is running to support the attack, if they are
+#[derive(Clone)]
+pub struct SupportRun(pub Option<Vector>);
+
+fn channel_x(channel: usize) -> f32 {
+    HALF_LEVEL_W + CHANNELS[channel] * CHANNEL_WIDTH
+}
+
+impl Game {
+    pub fn plan_support_runs(&mut self) -> Result<(), GameError> {
+        for (_, run) in self.world.query_mut::<&mut SupportRun>() {
+            run.0 = None;
+        }
+        let owners: Vec<Entity> = self.balls.iter().filter_map(|b| b.owner).collect();
+        for owner in owners {
+            for (id, dest) in self.support_runs(owner)? {
+                self.world.component_mut::<SupportRun>(id)?.0 = Some(dest);
+            }
+        }
+        Ok(())
+    }
+
+    fn support_runs(&self, owner: Entity) -> Result<Vec<(Entity, Vector)>, GameError> {
+        let team = self.world.component::<Team>(owner)?.0;
+        let ball_pos = self.world.component::<Position>(owner)?.0;
+        let info = &self.teams[team as usize];
+        let direction = if team == 0 { -1.0 } else { 1.0 };
+        // everyone on the team close enough to the ball to join in who isn't already busy,
+        // including with the other ball
+        let mut supporters: Vec<(Entity, Vector)> = self
+            .world
+            .query::<(&Team, &Home, &Position, &SupportRun)>()
+            .iter()
+            .filter(|(id, (t, _, pos, run))| {
+                t.0 == team
+                    && *id != owner
+                    && run.0.is_none()
+                    && !(info.human() && info.active_player == Some(*id))
+                    && info.run.is_none_or(|run| run.player != *id)
+                    && (ball_pos.y - pos.0.y).abs() < info.tactics.active_range()
+            })
+            .map(|(id, (_, home, _, _))| (id, info.tactics.home(home.0, team)))
+            .collect();
+        // those playing nearest the ball get first pick of the channels
+        supporters.sort_by(|a, b| cmp_dist(a.1, b.1, ball_pos));
+        let owner_channel = (0..CHANNELS.len())
+            .min_by(|a, b| {
+                let dx = |c: &usize| (channel_x(*c) - ball_pos.x).abs();
+                dx(a).total_cmp(&dx(b))
+            })
+            .unwrap_or(0);
+        let mut free: Vec<usize> = (0..CHANNELS.len())
+            .filter(|c| *c != owner_channel)
+            .collect();
+        let overlap_y = self
+            .last_defender_y(team)
+            .map(|y| y + OVERLAP_BEYOND * direction)
+            .filter(|y| (y - ball_pos.y) * direction > 0.0)
+            .map(|y| ball_pos.y + ((y - ball_pos.y) * direction).min(OVERLAP_RANGE) * direction);
+        let mut runs: Vec<(Entity, Vector)> = Vec::new();
+        for (id, home) in supporters {
+            let depth = (home.y + ball_pos.y + SUPPORT_AHEAD * direction) / 2.0;
+            let nearest = free.iter().enumerate().min_by(|a, b| {
+                let dx = |c: &usize| (channel_x(*c) - home.x).abs();
+                dx(a.1).total_cmp(&dx(b.1))
+            });
+            let mut dest = match nearest.map(|(i, c)| (i, *c)) {
+                Some((i, channel)) => {
+                    free.remove(i);
+                    let wide = CHANNELS[channel].abs() == CHANNELS[0].abs();
+                    vec2(
+                        channel_x(channel),
+                        overlap_y.filter(|_| wide).unwrap_or(depth),
+                    )
+                }
+                None => {
+                    // no channel left, so drop back out of everyone else's way
+                    let mut dest = vec2((home.x + ball_pos.x) / 2.0, depth);
+                    while runs
+                        .iter()
+                        .any(|(_, other)| (*other - dest).length() < MIN_SPACING)
+                    {
+                        dest.y -= MIN_SPACING * direction;
+                    }
+                    dest
+                }
+            };
+            dest.x = dest.x.clamp(AI_MIN_X, AI_MAX_X);
+            dest.y = dest.y.clamp(AI_MIN_Y, AI_MAX_Y);
+            runs.push((id, dest));
+        }
+        Ok(runs)
+    }
+
+    // how far up the pitch the team attacking a goal has to get to be past all the defenders but
+    // the one nearest the goal, who's taken to be keeping it
+    fn last_defender_y(&self, attacking_team: u8) -> Option<f32> {
+        let goal_y = attacking_team as f32 * LEVEL_H;
+        let mut defenders: Vec<f32> = self
+            .world
+            .query::<(&Team, &Position)>()
+            .iter()
+            .filter(|(_, (t, _))| t.0 != attacking_team)
+            .map(|(_, (_, pos))| pos.0.y)
+            .collect();
+        defenders.sort_by(|a, b| (a - goal_y).abs().total_cmp(&(b - goal_y).abs()));
+        defenders.get(1).copied()
+    }
+}
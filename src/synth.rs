@@ -0,0 +1,47 @@
+// generates simple sounds as in-memory wav files, for when there's no recording to play
+
+const SAMPLE_RATE: u32 = 44100;
+// fade each note in and out over this many samples to avoid clicks
+const FADE_SAMPLES: usize = 200;
+
+// a short, silent sound
+pub fn silence() -> Vec<u8> {
+    wav(&[0; 512])
+}
+
+// a sequence of beeps, each given as (frequency in Hz, duration in seconds)
+pub fn tones(notes: &[(f32, f32)]) -> Vec<u8> {
+    let mut samples = Vec::new();
+    for &(freq, duration) in notes {
+        let len = (duration * SAMPLE_RATE as f32) as usize;
+        for i in 0..len {
+            let fade = (i.min(len - i) as f32 / FADE_SAMPLES as f32).min(1.0);
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let value = (t * freq * std::f32::consts::TAU).sin() * fade * 0.4;
+            samples.push((value * i16::MAX as f32) as i16);
+        }
+    }
+    wav(&samples)
+}
+
+// 16-bit mono wav file
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // pcm
+    wav.extend_from_slice(&1u16.to_le_bytes()); // channels
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // bytes per second
+    wav.extend_from_slice(&2u16.to_le_bytes()); // bytes per sample
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
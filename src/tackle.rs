@@ -0,0 +1,174 @@
+// slide tackles: a human team's active player can press the tackle key to lunge the way they're
+// facing, and if a ball comes into the cone in front of them while they're sliding they win it,
+// whoever has it (unless they've come in from behind, which is a foul); if they don't get it they
+// end up on the ground, where they can't move or touch the ball until their Timer runs out
+
+use crate::error::{GameError, WorldExt};
+use crate::geometry::Angle;
+use crate::{powerups, Animation, Game, GameEvent, Position, Team, Timer, Vector};
+use hecs::Entity;
+
+// steps a slide lasts, how fast the player goes while sliding, and how long they're on the ground
+// for afterwards if they missed
+const SLIDE_FRAMES: u8 = 12;
+pub const SLIDE_SPEED: f32 = 5.0;
+const RECOVERY_FRAMES: i8 = 45;
+// a ball is won if it's this close to a sliding player and no further from the way they're facing
+// than SLIDE_CONE (the cosine of the angle either side)
+const SLIDE_REACH: f32 = 30.0;
+const SLIDE_CONE: f32 = 0.7;
+// how long the player who's been tackled can't take the ball back for
+const TACKLED_FRAMES: i8 = 60;
+
+// a player sliding in, or on the ground after missing
+#[derive(Copy, Clone)]
+pub struct Slide {
+    pub dir: Vector,
+    steps_left: u8,
+}
+
+impl Slide {
+    pub fn sliding(&self) -> bool {
+        self.steps_left > 0
+    }
+}
+
+impl Game {
+    // start a slide for each human team that's pressed tackle, and see whether anyone sliding has
+    // got to a ball
+    pub fn slide_tackles(&mut self) -> Result<(), GameError> {
+        for t in 0..=1 {
+            let id = match self.teams[t].active_player {
+                Some(id) if self.tackle_pressed[t] && self.kickoff_player.is_none() => id,
+                _ => continue,
+            };
+            if self.has_ball(id)
+                || self.world.get::<Slide>(id).is_ok()
+                || self.world.component::<Timer>(id)?.0 > 0
+                || powerups::frozen(&self.world, id)
+            {
+                continue;
+            }
+            let dir = Angle::to_vec(self.world.component::<Animation>(id)?.dir);
+            self.world.add_component(
+                id,
+                Slide {
+                    dir,
+                    steps_left: SLIDE_FRAMES,
+                },
+            )?;
+            sim_log!(self.log, Ball, Info, "{} slides in", self.player_name(id));
+        }
+        let slides: Vec<(Entity, Slide)> = self
+            .world
+            .query::<&Slide>()
+            .iter()
+            .map(|(id, slide)| (id, *slide))
+            .collect();
+        for (id, slide) in slides {
+            if !slide.sliding() {
+                // getting up
+                if self.world.component::<Timer>(id)?.0 == 0 {
+                    self.world.take_component::<Slide>(id)?;
+                }
+                continue;
+            }
+            if let Some(b) = self.ball_in_reach(id, slide.dir)? {
+                self.win_ball(id, b)?;
+                if !self.world.contains(id) {
+                    // sent off
+                } else if self.has_ball(id) {
+                    self.world.take_component::<Slide>(id)?;
+                } else {
+                    // fouled, and left on the ground until their Timer runs out
+                    self.world.component_mut::<Slide>(id)?.steps_left = 0;
+                }
+                continue;
+            }
+            let steps_left = slide.steps_left - 1;
+            self.world.component_mut::<Slide>(id)?.steps_left = steps_left;
+            if steps_left == 0 {
+                sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{} misses the tackle",
+                    self.player_name(id)
+                );
+                self.world.component_mut::<Timer>(id)?.0 = RECOVERY_FRAMES;
+            }
+        }
+        Ok(())
+    }
+
+    // the first ball in the cone in front of a sliding player that their team doesn't already have
+    fn ball_in_reach(&self, id: Entity, dir: Vector) -> Result<Option<usize>, GameError> {
+        let pos = self.world.component::<Position>(id)?.0;
+        let team = self.world.component::<Team>(id)?.0;
+        for (b, ball) in self.balls.iter().enumerate() {
+            let offset = self.ball_pos(*ball)? - pos;
+            let teammates = match ball.owner {
+                Some(owner) => self.world.component::<Team>(owner)?.0 == team,
+                None => false,
+            };
+            if !teammates
+                && offset.length() < SLIDE_REACH
+                && offset
+                    .try_normalize()
+                    .is_none_or(|o| o.dot(dir) > SLIDE_CONE)
+            {
+                return Ok(Some(b));
+            }
+        }
+        Ok(None)
+    }
+
+    // a sliding player gets to a ball: it's theirs, unless they've taken someone from behind, in
+    // which case it's a foul and they're left on the ground
+    fn win_ball(&mut self, id: Entity, b: usize) -> Result<(), GameError> {
+        let ball = self.balls[b];
+        let pos = self.world.component::<Position>(id)?.0;
+        let team = self.world.component::<Team>(id)?.0;
+        match ball.owner {
+            Some(owner) if self.tackled_from_behind(owner, pos)? => {
+                self.foul(id)?;
+                return Ok(());
+            }
+            Some(owner) => {
+                sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{} slides in and takes the ball off {}",
+                    self.player_name(id),
+                    self.player_name(owner)
+                );
+                self.world.component_mut::<Timer>(owner)?.0 = TACKLED_FRAMES;
+                self.stats_for(id)?.interceptions += 1;
+            }
+            None => {
+                sim_log!(
+                    self.log,
+                    Ball,
+                    Info,
+                    "{} slides in and gets to the ball",
+                    self.player_name(id)
+                );
+                self.world.take_component::<Vector>(ball.entity)?;
+            }
+        }
+        let previous_team = self
+            .last_touch
+            .and_then(|last| self.world.get::<Team>(last).ok().map(|t| t.0));
+        if previous_team != Some(team) {
+            self.events.push(GameEvent::Possession {
+                team: team as usize,
+            });
+        }
+        self.balls[b].owner = Some(id);
+        self.last_touch = Some(id);
+        self.assister = None;
+        self.kick = None;
+        Ok(())
+    }
+}
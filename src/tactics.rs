@@ -0,0 +1,62 @@
+// a team's tactics, set for each team on the tactics page before a match and changed during one
+// from the pause menu: how far from the ball its players will go to get involved, and how far up
+// the pitch it holds its shape
+//
+// the middle level of each is how every team played before it could choose
+
+use crate::Vector;
+use euclid::vec2;
+
+pub const LEVELS: u8 = 5;
+const DEFAULT_LEVEL: u8 = 2;
+// how far up or down the pitch from the ball a player can be and still support the attack or mark
+// someone, rather than going back to their position, at each pressing level
+const PRESSING_RANGES: [f32; LEVELS as usize] = [280.0, 340.0, 400.0, 460.0, 520.0];
+// how far towards the opposition's goal the team's positions are moved at each line level
+const LINE_OFFSETS: [f32; LEVELS as usize] = [-100.0, -50.0, 0.0, 50.0, 100.0];
+// the levels' names in the language files
+pub const PRESSING_KEYS: [&str; LEVELS as usize] = [
+    "tactics.pressing_0",
+    "tactics.pressing_1",
+    "tactics.pressing_2",
+    "tactics.pressing_3",
+    "tactics.pressing_4",
+];
+pub const LINE_KEYS: [&str; LEVELS as usize] = [
+    "tactics.line_0",
+    "tactics.line_1",
+    "tactics.line_2",
+    "tactics.line_3",
+    "tactics.line_4",
+];
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Tactics {
+    pub pressing: u8,
+    pub line: u8,
+}
+
+impl Tactics {
+    pub fn new() -> Self {
+        Self {
+            pressing: DEFAULT_LEVEL,
+            line: DEFAULT_LEVEL,
+        }
+    }
+
+    pub fn active_range(&self) -> f32 {
+        PRESSING_RANGES[self.pressing as usize]
+    }
+
+    // where a player plays on a team with these tactics, given their Home
+    pub fn home(&self, home: Vector, team: u8) -> Vector {
+        // team 0 attacks towards the top of the level
+        let direction = if team == 0 { -1.0 } else { 1.0 };
+        home + vec2(0.0, LINE_OFFSETS[self.line as usize] * direction)
+    }
+}
+
+// move a level one step up or down, wrapping around at either end like the options do
+pub fn step(level: u8, dir: i32) -> u8 {
+    (level as i32 + dir).rem_euclid(LEVELS as i32) as u8
+}
@@ -0,0 +1,31 @@
+// the pseudo-random numbers the tests draw on (see geometry.rs, spatial.rs and fuzz_tests.rs):
+// xorshift, kept separate from macroquad's generator so they don't disturb the golden tests, and
+// seeded so that any failure reproduces
+
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+
+    // -1, 0 or 1, as one axis of a stick
+    pub fn direction(&mut self) -> f32 {
+        self.below(3) as f32 - 1.0
+    }
+
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (self.next() >> 40) as f32 / (1u64 << 24) as f32 * (high - low)
+    }
+}
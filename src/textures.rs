@@ -0,0 +1,360 @@
+use crate::{assets, data, placeholder};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+const MANIFEST_PATH: &str = "data/sprites.toml";
+
+// images no bigger than this in either dimension are packed together into a single atlas texture
+const MAX_PACKED_SIZE: u16 = 128;
+const ATLAS_WIDTH: u16 = 1024;
+// transparent gap between packed images so filtering doesn't bleed neighbours into each other
+const ATLAS_PADDING: u16 = 2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Sprite {
+    Pitch,
+    Ball,
+    BallShadow,
+    Arrow(u8),
+    // goal frames at the top (0) and bottom (1) of the pitch
+    Goal(u8),
+    GoalBanner,
+    ScoreBar,
+    ScoreDigit(u8),
+    // game over screen, by winning team
+    GameOver(u8),
+    FinalScore(u8, u8),
+    Menu(u8, u8),
+    Player { team: u8, dir: u8, frame: u8 },
+    PlayerShadow { dir: u8, frame: u8 },
+}
+
+impl Sprite {
+    // name of the image file (without extension) this sprite is loaded from
+    pub fn key(self) -> String {
+        match self {
+            Self::Pitch => "pitch".to_owned(),
+            Self::Ball => "ball".to_owned(),
+            Self::BallShadow => "balls".to_owned(),
+            Self::Arrow(t) => format!("arrow{}", t),
+            Self::Goal(e) => format!("goal{}", e),
+            Self::GoalBanner => "goal".to_owned(),
+            Self::ScoreBar => "bar".to_owned(),
+            Self::ScoreDigit(n) => format!("s{}", n),
+            Self::GameOver(t) => format!("over{}", t),
+            Self::FinalScore(t, n) => format!("l{}{}", t, n),
+            Self::Menu(page, item) => format!("menu{}{}", page, item),
+            Self::Player { team, dir, frame } => format!("player{}{}{}", team, dir, frame),
+            Self::PlayerShadow { dir, frame } => format!("players{}{}", dir, frame),
+        }
+    }
+
+    // name of the manifest section describing this sprite, if it has one
+    fn group(self) -> Option<&'static str> {
+        match self {
+            Self::Player { .. } => Some("player"),
+            Self::PlayerShadow { .. } => Some("player_shadow"),
+            Self::Ball => Some("ball"),
+            Self::BallShadow => Some("ball_shadow"),
+            Self::Arrow(_) => Some("arrow"),
+            Self::Goal(0) => Some("goal0"),
+            Self::Goal(_) => Some("goal1"),
+            _ => None,
+        }
+    }
+
+    pub fn all(player_frames: u8) -> Vec<Sprite> {
+        let mut all = vec![
+            Self::Pitch,
+            Self::Ball,
+            Self::BallShadow,
+            Self::Arrow(0),
+            Self::Arrow(1),
+            Self::GoalBanner,
+            Self::Goal(0),
+            Self::Goal(1),
+            Self::ScoreBar,
+            Self::GameOver(0),
+            Self::GameOver(1),
+        ];
+        for dir in 0..=7 {
+            for frame in 0..player_frames {
+                all.push(Self::Player {
+                    team: 0,
+                    dir,
+                    frame,
+                });
+                all.push(Self::Player {
+                    team: 1,
+                    dir,
+                    frame,
+                });
+                all.push(Self::PlayerShadow { dir, frame });
+            }
+        }
+        for (page, item) in [(0, 1), (0, 2), (1, 0), (1, 1), (1, 2)] {
+            all.push(Self::Menu(page, item));
+        }
+        for n in 0..=9 {
+            all.push(Self::ScoreDigit(n));
+            all.push(Self::FinalScore(0, n));
+            all.push(Self::FinalScore(1, n));
+        }
+        all
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Region {
+    texture: Texture2D,
+    source: Rect,
+}
+
+// anchors and animation layout read from the sprite manifest
+pub struct Manifest {
+    anchors: HashMap<String, Vec2>,
+    player_frames: u8,
+    frame_distance: f32,
+}
+
+impl Manifest {
+    pub async fn load() -> Self {
+        // fall back to the copy of the manifest built into the game if the file isn't there, or has
+        // a mistake in it
+        let built_in = include_str!("../data/sprites.toml");
+        let text = match assets::load(MANIFEST_PATH).await {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(_) => built_in.to_owned(),
+        };
+        let doc = data::parse(&text).unwrap_or_else(|e| {
+            warn!("{}: {}", MANIFEST_PATH, e);
+            data::parse(built_in).unwrap_or_default()
+        });
+        let mut anchors = HashMap::new();
+        for (name, section) in &doc.sections {
+            if let Some((x, y)) = section.get("anchor").and_then(|v| v.as_pair()) {
+                anchors.insert(name.clone(), vec2(x, y));
+            }
+        }
+        let number = |key| doc.get("player", key).and_then(|v| v.as_f32());
+        Self {
+            anchors,
+            player_frames: number("frames").unwrap_or(1.0).max(1.0) as u8,
+            frame_distance: number("frame_distance").unwrap_or(1.0),
+        }
+    }
+
+    fn anchor(&self, sprite: Sprite) -> Vec2 {
+        sprite
+            .group()
+            .and_then(|g| self.anchors.get(g))
+            .copied()
+            .unwrap_or(Vec2::ZERO)
+    }
+}
+
+pub struct Textures {
+    regions: HashMap<Sprite, Region>,
+    manifest: Manifest,
+    theme: Option<String>,
+}
+
+// loads sprite images one at a time so that progress can be shown; any sprite that isn't loaded
+// is drawn with placeholder graphics instead
+pub struct TextureLoader {
+    manifest: Manifest,
+    theme: Option<String>,
+    regions: HashMap<Sprite, Region>,
+    small: Vec<(Sprite, Image)>,
+}
+
+impl TextureLoader {
+    // a theme is a subdirectory of images containing replacements for some or all of the default
+    // images, which are used for anything the theme doesn't include
+    pub async fn new(theme: Option<String>) -> Self {
+        Self {
+            manifest: Manifest::load().await,
+            theme,
+            regions: HashMap::new(),
+            small: Vec::new(),
+        }
+    }
+
+    pub fn sprites(&self) -> Vec<Sprite> {
+        Sprite::all(self.manifest.player_frames)
+    }
+
+    // on failure, returns the path of the image that couldn't be loaded
+    pub async fn load(&mut self, sprite: Sprite) -> Result<(), String> {
+        let mut themed = None;
+        if let Some(theme) = &self.theme {
+            let path = format!("images/{}/{}.png", theme, sprite.key());
+            themed = assets::load(&path).await.ok();
+        }
+        let bytes = match themed {
+            Some(bytes) => bytes,
+            None => {
+                let path = format!("images/{}.png", sprite.key());
+                assets::load(&path)
+                    .await
+                    .map_err(|_| assets::on_disk(&path))?
+            }
+        };
+        let image = Image::from_file_with_format(&bytes, None);
+        if image.width <= MAX_PACKED_SIZE && image.height <= MAX_PACKED_SIZE {
+            self.small.push((sprite, image));
+        } else {
+            let texture = Texture2D::from_image(&image);
+            let source = Rect::new(0.0, 0.0, texture.width(), texture.height());
+            self.regions.insert(sprite, Region { texture, source });
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Textures {
+        pack(&mut self.regions, self.small);
+        Textures {
+            regions: self.regions,
+            manifest: self.manifest,
+            theme: self.theme,
+        }
+    }
+}
+
+impl Textures {
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    // free the GPU memory used by all of the textures
+    pub fn delete(self) {
+        let mut deleted = Vec::new();
+        for region in self.regions.values() {
+            if !deleted.contains(&region.texture) {
+                region.texture.delete();
+                deleted.push(region.texture);
+            }
+        }
+    }
+
+    // file the image for a sprite comes from, preferring the theme's version if it has one
+    #[cfg(feature = "hot-reload")]
+    pub fn image_path(&self, sprite: Sprite) -> String {
+        if let Some(theme) = &self.theme {
+            let path = assets::on_disk(&format!("images/{}/{}.png", theme, sprite.key()));
+            if std::path::Path::new(&path).exists() {
+                return path;
+            }
+        }
+        assets::on_disk(&format!("images/{}.png", sprite.key()))
+    }
+    // animation frame to show for a player who has run the given distance
+    pub fn player_frame(&self, distance: f32) -> u8 {
+        (distance / self.manifest.frame_distance) as u8 % self.manifest.player_frames
+    }
+
+    // draw a sprite positioned by its anchor point rather than its top left corner
+    pub fn draw_anchored(&self, sprite: Sprite, x: f32, y: f32) {
+        let anchor = self.manifest.anchor(sprite);
+        self.draw(sprite, x - anchor.x, y - anchor.y);
+    }
+
+    // draw_anchored, but with the sprite scaled about its anchor point; placeholders stay the same size
+    pub fn draw_anchored_scaled(&self, sprite: Sprite, x: f32, y: f32, scale: f32) {
+        let region = match self.regions.get(&sprite) {
+            Some(region) if scale != 1.0 => region,
+            _ => return self.draw_anchored(sprite, x, y),
+        };
+        let anchor = self.manifest.anchor(sprite) * scale;
+        draw_texture_ex(
+            region.texture,
+            x - anchor.x,
+            y - anchor.y,
+            WHITE,
+            DrawTextureParams {
+                source: Some(region.source),
+                dest_size: Some(region.source.size() * scale),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn draw(&self, sprite: Sprite, x: f32, y: f32) {
+        let region = match self.regions.get(&sprite) {
+            Some(region) => region,
+            None => {
+                let anchor = self.manifest.anchor(sprite);
+                placeholder::draw(sprite, x + anchor.x, y + anchor.y);
+                return;
+            }
+        };
+        draw_texture_ex(
+            region.texture,
+            x,
+            y,
+            WHITE,
+            DrawTextureParams {
+                source: Some(region.source),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn sprites(&self) -> Vec<Sprite> {
+        Sprite::all(self.manifest.player_frames)
+    }
+
+    // swap in a new image for a sprite, outside of the atlas
+    #[cfg(feature = "hot-reload")]
+    pub fn replace(&mut self, sprite: Sprite, image: &Image, filter: FilterMode) {
+        let texture = Texture2D::from_image(image);
+        texture.set_filter(filter);
+        let source = Rect::new(0.0, 0.0, texture.width(), texture.height());
+        if let Some(old) = self.regions.insert(sprite, Region { texture, source }) {
+            if !self.regions.values().any(|r| r.texture == old.texture) {
+                old.texture.delete();
+            }
+        }
+    }
+
+    pub fn set_filter(&self, filter: FilterMode) {
+        for region in self.regions.values() {
+            region.texture.set_filter(filter);
+        }
+    }
+}
+
+// simple shelf packing: place images left to right, tallest first, starting a new row when full
+fn pack(regions: &mut HashMap<Sprite, Region>, mut images: Vec<(Sprite, Image)>) {
+    if images.is_empty() {
+        return;
+    }
+    images.sort_by_key(|(_, image)| std::cmp::Reverse(image.height));
+    let mut placements = Vec::new();
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for (_, image) in &images {
+        if x + image.width > ATLAS_WIDTH {
+            x = 0;
+            y += shelf_height + ATLAS_PADDING;
+            shelf_height = 0;
+        }
+        placements.push((x, y));
+        x += image.width + ATLAS_PADDING;
+        shelf_height = shelf_height.max(image.height);
+    }
+    let mut atlas = Image::gen_image_color(ATLAS_WIDTH, y + shelf_height, BLANK);
+    for ((_, image), (x, y)) in images.iter().zip(&placements) {
+        let row_bytes = image.width as usize * 4;
+        for row in 0..image.height as usize {
+            let src = row * row_bytes;
+            let dest = ((*y as usize + row) * ATLAS_WIDTH as usize + *x as usize) * 4;
+            atlas.bytes[dest..dest + row_bytes].copy_from_slice(&image.bytes[src..src + row_bytes]);
+        }
+    }
+    let texture = Texture2D::from_image(&atlas);
+    for ((sprite, image), (x, y)) in images.into_iter().zip(placements) {
+        let source = Rect::new(x as f32, y as f32, image.width as f32, image.height as f32);
+        regions.insert(sprite, Region { texture, source });
+    }
+}
@@ -0,0 +1,127 @@
+use crate::i18n::Strings;
+use crate::scaler::Scaler;
+use crate::{HEIGHT, WIDTH};
+use macroquad::prelude::*;
+
+// the joystick sits in the bottom left corner and the kick button in the bottom right
+const STICK_CENTRE: (f32, f32) = (90.0, HEIGHT - 90.0);
+const STICK_RADIUS: f32 = 60.0;
+const KNOB_RADIUS: f32 = 25.0;
+const KICK_CENTRE: (f32, f32) = (WIDTH - 90.0, HEIGHT - 90.0);
+const KICK_RADIUS: f32 = 45.0;
+
+// an on-screen joystick and kick button, which only appear once the screen has been touched; a
+// touch on the left half of the screen steers and one on the right half kicks
+pub struct TouchControls {
+    pub enabled: bool,
+    // touch steering the joystick, and how far it's been pushed from the centre in [-1, 1]
+    stick_touch: Option<u64>,
+    stick: Vec2,
+    kick_touch: Option<u64>,
+    // a touch started this frame
+    tapped: bool,
+    kicked: bool,
+}
+
+impl TouchControls {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            stick_touch: None,
+            stick: Vec2::ZERO,
+            kick_touch: None,
+            tapped: false,
+            kicked: false,
+        }
+    }
+
+    // called once per frame
+    pub fn update(&mut self, scaler: &Scaler) {
+        self.tapped = false;
+        self.kicked = false;
+        let touches = touches();
+        if !self.enabled && !touches.is_empty() {
+            // touches would otherwise show up as mouse clicks too
+            self.enabled = true;
+            simulate_mouse_with_touch(false);
+        }
+        for touch in touches {
+            let pos = scaler.to_game(touch.position);
+            match touch.phase {
+                TouchPhase::Started => {
+                    self.tapped = true;
+                    if pos.x < WIDTH / 2.0 {
+                        self.stick_touch = Some(touch.id);
+                        self.stick = stick_offset(pos);
+                    } else {
+                        self.kick_touch = Some(touch.id);
+                        self.kicked = true;
+                    }
+                }
+                TouchPhase::Moved | TouchPhase::Stationary => {
+                    if self.stick_touch == Some(touch.id) {
+                        self.stick = stick_offset(pos);
+                    }
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    if self.stick_touch == Some(touch.id) {
+                        self.stick_touch = None;
+                        self.stick = Vec2::ZERO;
+                    }
+                    if self.kick_touch == Some(touch.id) {
+                        self.kick_touch = None;
+                    }
+                }
+            }
+        }
+    }
+
+    // true on the frame the screen was touched anywhere, for menus
+    pub fn tapped(&self) -> bool {
+        self.tapped
+    }
+
+    // true on the frame the kick button was touched
+    pub fn kicked(&self) -> bool {
+        self.kicked
+    }
+
+    // how far the joystick is pushed in each direction, or None if nobody's touching it
+    pub fn stick(&self) -> Option<Vec2> {
+        self.stick_touch.map(|_| self.stick)
+    }
+
+    // draw in screen coordinates
+    pub fn draw(&self, strings: &Strings) {
+        if !self.enabled {
+            return;
+        }
+        let faint = Color::new(1.0, 1.0, 1.0, 0.25);
+        let solid = Color::new(1.0, 1.0, 1.0, 0.5);
+        let (x, y) = STICK_CENTRE;
+        draw_circle_lines(x, y, STICK_RADIUS, 3.0, faint);
+        let knob = vec2(x, y) + self.stick * STICK_RADIUS;
+        draw_circle(knob.x, knob.y, KNOB_RADIUS, solid);
+        let (x, y) = KICK_CENTRE;
+        let colour = if self.kick_touch.is_some() {
+            solid
+        } else {
+            faint
+        };
+        draw_circle(x, y, KICK_RADIUS, colour);
+        let label = strings.get("hud.kick");
+        let size = measure_text(label, None, 24, 1.0);
+        draw_text(
+            label,
+            x - size.width / 2.0,
+            y + size.height / 2.0,
+            24.0,
+            WHITE,
+        );
+    }
+}
+
+// where a touch at pos pushes the joystick, limited to its edge
+fn stick_offset(pos: Vec2) -> Vec2 {
+    ((pos - vec2(STICK_CENTRE.0, STICK_CENTRE.1)) / STICK_RADIUS).clamp_length_max(1.0)
+}
@@ -0,0 +1,113 @@
+use crate::i18n::Strings;
+use crate::{
+    draw_text_centred, GameEvent, GOAL_BOUNDS_X, GOAL_BOUNDS_Y, GOAL_WIDTH, HALF_WINDOW_WIDTH,
+    HEIGHT, PITCH_BOUNDS_Y,
+};
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+// the goal is split into this many zones, one of which is the target at a time
+const TARGET_ZONES: usize = 3;
+const ZONE_WIDTH: f32 = GOAL_WIDTH / TARGET_ZONES as f32;
+// how much of its speed the ball keeps when it bounces off a dummy
+pub const DUMMY_BOUNCE: f32 = 0.6;
+
+// what the player chose on the training page of the options menu
+#[derive(Copy, Clone)]
+pub struct TrainingSetup {
+    // the other team stands still on the pitch to be passed and shot around, rather than being
+    // taken off altogether
+    pub dummies: bool,
+    pub targets: bool,
+}
+
+impl TrainingSetup {
+    pub fn new() -> Self {
+        Self {
+            dummies: false,
+            targets: true,
+        }
+    }
+}
+
+// a practice session for team 1, attacking the top goal with nobody to stop them
+#[derive(Clone)]
+pub struct Training {
+    pub setup: TrainingSetup,
+    // which zone of the goal to aim for next, counting from the left
+    target: usize,
+    shots: u32,
+    goals: u32,
+    hits: u32,
+}
+
+impl Training {
+    pub fn new(setup: TrainingSetup) -> Self {
+        Self {
+            setup,
+            target: gen_range(0, TARGET_ZONES),
+            shots: 0,
+            goals: 0,
+            hits: 0,
+        }
+    }
+
+    // called once per simulation step with the events from that step
+    pub fn count_shots(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::Kick {
+                team: 0,
+                shot: true,
+            } = event
+            {
+                self.shots += 1;
+            }
+        }
+    }
+
+    // the ball crossed the goal line at x; if it was in the target zone, move the target elsewhere
+    pub fn goal(&mut self, x: f32) {
+        self.goals += 1;
+        let (left, right) = self.target_zone();
+        if self.setup.targets && x >= left && x < right {
+            self.hits += 1;
+            self.target = (self.target + gen_range(1, TARGET_ZONES)) % TARGET_ZONES;
+        }
+    }
+
+    fn target_zone(&self) -> (f32, f32) {
+        let left = GOAL_BOUNDS_X.0 + self.target as f32 * ZONE_WIDTH;
+        (left, left + ZONE_WIDTH)
+    }
+
+    // draw in level coordinates
+    pub fn draw_target(&self) {
+        if !self.setup.targets {
+            return;
+        }
+        let (left, _) = self.target_zone();
+        let top = GOAL_BOUNDS_Y.0;
+        let colour = Color::new(1.0, 1.0, 0.0, 0.4);
+        draw_rectangle(left, top, ZONE_WIDTH, PITCH_BOUNDS_Y.0 - top, colour);
+        draw_rectangle_lines(left, top, ZONE_WIDTH, PITCH_BOUNDS_Y.0 - top, 3.0, YELLOW);
+    }
+
+    // draw in screen coordinates, in place of the score
+    pub fn draw(&self, strings: &Strings) {
+        let mut text = strings.format(
+            "hud.training_goals",
+            &[&self.goals.to_string(), &self.shots.to_string()],
+        );
+        if self.setup.targets {
+            text.push_str("   ");
+            text.push_str(&strings.format("hud.training_hits", &[&self.hits.to_string()]));
+        }
+        draw_text_centred(&text, HALF_WINDOW_WIDTH, 30.0, 28.0);
+        draw_text_centred(
+            strings.get("hud.training_keys"),
+            HALF_WINDOW_WIDTH,
+            HEIGHT - 10.0,
+            16.0,
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::i18n::Strings;
+use crate::{draw_text_centred, Game, GameEvent, HALF_LEVEL_W, HALF_WINDOW_WIDTH, HEIGHT};
+use euclid::vec2;
+
+// how long to say well done before moving on to the next lesson, in steps
+const PAUSE_FRAMES: u32 = 90;
+// how far across the pitch to move the ball before asking the player to switch
+const SWITCH_BALL_OFFSET: f32 = 250.0;
+
+// the lessons, in the order they're taught
+#[derive(Copy, Clone, PartialEq)]
+enum Lesson {
+    Move,
+    Switch,
+    Pass,
+    Shoot,
+    Finished,
+}
+
+impl Lesson {
+    // names the lesson's prompt in the language files
+    fn key(self) -> &'static str {
+        match self {
+            Self::Move => "tutorial.move",
+            Self::Switch => "tutorial.switch",
+            Self::Pass => "tutorial.pass",
+            Self::Shoot => "tutorial.shoot",
+            Self::Finished => "tutorial.finished",
+        }
+    }
+
+    // true if event shows the player has done what the lesson asks
+    fn done_by(self, event: &GameEvent) -> bool {
+        matches!(
+            (self, event),
+            (Self::Move, GameEvent::Possession { team: 0 })
+                | (Self::Switch, GameEvent::Switch { team: 0 })
+                | (
+                    Self::Pass,
+                    GameEvent::Kick {
+                        team: 0,
+                        shot: false
+                    }
+                )
+                | (Self::Shoot, GameEvent::Goal { team: 0 })
+        )
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Move => Self::Switch,
+            Self::Switch => Self::Pass,
+            Self::Pass => Self::Shoot,
+            Self::Shoot | Self::Finished => Self::Finished,
+        }
+    }
+}
+
+// walks a new player through the controls in a training session, one prompt at a time
+pub struct Tutorial {
+    lesson: Lesson,
+    // steps left showing the well done message after finishing a lesson
+    pause: u32,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            lesson: Lesson::Move,
+            pause: 0,
+        }
+    }
+
+    // called once per simulation step, after the game has been updated
+    pub fn update(&mut self, game: &mut Game) {
+        if self.pause > 0 {
+            self.pause -= 1;
+            if self.pause == 0 {
+                self.lesson = self.lesson.next();
+                if self.lesson == Lesson::Switch {
+                    // switching only works when nobody has the ball, so leave it lying loose away
+                    // from the player
+                    let dropped = game.ball_pos(game.balls[0]).and_then(|ball_pos| {
+                        let x = if ball_pos.x < HALF_LEVEL_W {
+                            HALF_LEVEL_W + SWITCH_BALL_OFFSET
+                        } else {
+                            HALF_LEVEL_W - SWITCH_BALL_OFFSET
+                        };
+                        game.drop_ball(vec2(x, ball_pos.y))
+                    });
+                    if let Err(e) = dropped {
+                        game.recover(e);
+                    }
+                }
+            }
+        } else if self.lesson != Lesson::Finished
+            && game.events.iter().any(|e| self.lesson.done_by(e))
+        {
+            self.pause = PAUSE_FRAMES;
+        }
+    }
+
+    // draw in screen coordinates
+    pub fn draw(&self, strings: &Strings) {
+        let key = if self.pause > 0 {
+            "tutorial.well_done"
+        } else {
+            self.lesson.key()
+        };
+        draw_text_centred(strings.get(key), HALF_WINDOW_WIDTH, 40.0, 28.0);
+        draw_text_centred(
+            strings.get("tutorial.keys"),
+            HALF_WINDOW_WIDTH,
+            HEIGHT - 10.0,
+            16.0,
+        );
+    }
+}
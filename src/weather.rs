@@ -0,0 +1,135 @@
+use crate::{HEIGHT, WIDTH};
+use macroquad::prelude::*;
+
+const RAIN_DROPS: usize = 150;
+const SNOW_FLAKES: usize = 100;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Weather {
+    Clear,
+    // wet pitch: players slide about and the ball runs further
+    Rain,
+    // heavy pitch: the ball slows down more quickly and footing is a little uncertain
+    Snow,
+}
+
+impl Weather {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Clear => Self::Rain,
+            Self::Rain => Self::Snow,
+            Self::Snow => Self::Clear,
+        }
+    }
+
+    // names the weather in the language files
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Clear => "clear",
+            Self::Rain => "rain",
+            Self::Snow => "snow",
+        }
+    }
+
+    // fraction of the ball's speed kept from one step to the next
+    pub fn ball_drag(self) -> f32 {
+        match self {
+            Self::Clear => 0.98,
+            Self::Rain => 0.985,
+            Self::Snow => 0.975,
+        }
+    }
+
+    // how quickly players can change their velocity, where 1 means instantly
+    pub fn player_grip(self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 0.35,
+            Self::Snow => 0.6,
+        }
+    }
+}
+
+struct Particle {
+    pos: Vec2,
+    speed: f32,
+}
+
+// falling rain or snow drawn over the game in screen coordinates; this is purely cosmetic, so it
+// uses its own random numbers and leaves the simulation's alone
+pub struct Precipitation {
+    particles: Vec<Particle>,
+    weather: Weather,
+    seed: u32,
+    time: f32,
+}
+
+impl Precipitation {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            weather: Weather::Clear,
+            seed: 0x2545f491,
+            time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, weather: Weather, dt: f32) {
+        if weather != self.weather {
+            self.weather = weather;
+            let count = match weather {
+                Weather::Clear => 0,
+                Weather::Rain => RAIN_DROPS,
+                Weather::Snow => SNOW_FLAKES,
+            };
+            self.particles.clear();
+            for _ in 0..count {
+                let pos = vec2(self.random() * WIDTH, self.random() * HEIGHT);
+                let speed = 0.5 + self.random();
+                self.particles.push(Particle { pos, speed });
+            }
+        }
+        self.time += dt;
+        let (fall, drift) = match weather {
+            Weather::Rain => (vec2(-60.0, 600.0), 0.0),
+            _ => (vec2(0.0, 50.0), 20.0),
+        };
+        for i in 0..self.particles.len() {
+            let sway = (self.time * 2.0 + i as f32).sin() * drift;
+            let p = &mut self.particles[i];
+            p.pos += (fall + vec2(sway, 0.0)) * p.speed * dt;
+            if p.pos.y > HEIGHT || p.pos.x < 0.0 || p.pos.x > WIDTH {
+                // start again from a random point along the top of the screen
+                let x = self.random() * WIDTH;
+                self.particles[i].pos = vec2(x, 0.0);
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        match self.weather {
+            Weather::Clear => (),
+            Weather::Rain => {
+                draw_rectangle(0.0, 0.0, WIDTH, HEIGHT, Color::new(0.1, 0.1, 0.2, 0.2));
+                for p in &self.particles {
+                    let tail = p.pos - vec2(-3.0, 30.0) * p.speed * 0.4;
+                    let colour = Color::new(0.7, 0.75, 0.9, 0.5);
+                    draw_line(p.pos.x, p.pos.y, tail.x, tail.y, 1.0, colour);
+                }
+            }
+            Weather::Snow => {
+                for p in &self.particles {
+                    draw_circle(p.pos.x, p.pos.y, 1.0 + p.speed, WHITE);
+                }
+            }
+        }
+    }
+
+    // xorshift, returning a number in [0, 1)
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1 << 24) as f32
+    }
+}
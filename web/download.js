@@ -0,0 +1,17 @@
+// lets the game hand the player a file, such as a screenshot (see src/screenshot.rs), by having the
+// browser download it; load this after mq_js_bundle.js and before the game itself
+miniquad_add_plugin({
+    name: "soccer_download",
+    version: 1,
+    register_plugin: function (importObject) {
+        const bytes = (ptr, len) => new Uint8Array(wasm_memory.buffer, ptr, len);
+        importObject.env.soccer_download = function (name, name_len, data, data_len) {
+            const link = document.createElement("a");
+            link.download = new TextDecoder().decode(bytes(name, name_len));
+            link.href = URL.createObjectURL(new Blob([bytes(data, data_len).slice()]));
+            link.click();
+            // give the download a moment to start before letting go of the data
+            setTimeout(() => URL.revokeObjectURL(link.href), 1000);
+        };
+    },
+});
@@ -0,0 +1,11 @@
+// tells the game whether the page has focus, so a match can pause while it's in the background
+// (see src/focus.rs); load this after mq_js_bundle.js and before the game itself
+miniquad_add_plugin({
+    name: "soccer_focus",
+    version: 1,
+    register_plugin: function (importObject) {
+        importObject.env.soccer_has_focus = function () {
+            return !document.hidden && document.hasFocus() ? 1 : 0;
+        };
+    },
+});
@@ -0,0 +1,32 @@
+// gives the game somewhere to save its files in the browser (see src/storage.rs), by keeping them in
+// localStorage; load this after mq_js_bundle.js and before the game itself
+miniquad_add_plugin({
+    name: "soccer_storage",
+    version: 1,
+    register_plugin: function (importObject) {
+        const bytes = (ptr, len) => new Uint8Array(wasm_memory.buffer, ptr, len);
+        const text = (ptr, len) => new TextDecoder().decode(bytes(ptr, len));
+        const stored = (key, key_len) => {
+            const value = window.localStorage.getItem(text(key, key_len));
+            return value === null ? null : new TextEncoder().encode(value);
+        };
+        importObject.env.soccer_storage_len = function (key, key_len) {
+            const value = stored(key, key_len);
+            return value === null ? -1 : value.length;
+        };
+        importObject.env.soccer_storage_read = function (key, key_len, buf, buf_len) {
+            const value = stored(key, key_len);
+            if (value !== null) {
+                bytes(buf, buf_len).set(value.subarray(0, buf_len));
+            }
+        };
+        importObject.env.soccer_storage_write = function (key, key_len, value, value_len) {
+            try {
+                window.localStorage.setItem(text(key, key_len), text(value, value_len));
+                return 0;
+            } catch (e) {
+                return 1;
+            }
+        };
+    },
+});